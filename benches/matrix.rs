@@ -0,0 +1,61 @@
+//! Benchmarks of parsing and sorting on matrices that are generated at bench time,
+//! such that they run without downloading any files.
+//!
+//! Run with `cargo bench`, or `cargo bench -- sort/counting` for a single strategy.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use matrix_market_transform::generators::{erdos_renyi, laplacian_2d};
+use matrix_market_transform::*;
+
+/// Generated matrices, with their entries in random order.
+fn fixtures() -> Vec<(String, Matrix)> {
+    let mut fixtures = Vec::new();
+    for n in [100, 500] {
+        let mut m = laplacian_2d(n, n);
+        m.shuffle(1);
+        fixtures.push((format!("laplacian-{n}x{n}-real"), m));
+    }
+    for (name, data_type) in [("real", DataType::Real), ("complex", DataType::Complex), ("integer", DataType::Integer), ("pattern", DataType::Bool)] {
+        let m = erdos_renyi(20_000, 20_000, 1e-3, data_type, 2);
+        fixtures.push((format!("random-20000-{name}"), m));
+    }
+    fixtures
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, m) in fixtures() {
+        let bytes = m.to_string().into_bytes();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &bytes, |b, bytes| {
+            b.iter(|| Matrix::from_bytes(bytes, m.data_type()));
+        });
+    }
+    group.finish();
+}
+
+fn sort(c: &mut Criterion) {
+    let strategies = [
+        ("zip", SortStrategy::Zip),
+        ("permute", SortStrategy::Permute),
+        ("counting", SortStrategy::Counting),
+        ("external", SortStrategy::External),
+    ];
+
+    let mut group = c.benchmark_group("sort");
+    group.sample_size(20);
+    for (name, m) in fixtures() {
+        group.throughput(Throughput::Elements(m.nvals() as u64));
+        for (strategy_name, strategy) in strategies {
+            let options = SortOptions::new().strategy(strategy);
+            group.bench_with_input(BenchmarkId::new(strategy_name, &name), &m, |b, m| {
+                // Every iteration sorts a fresh copy, as sorting sorted entries does nothing
+                b.iter_batched(|| m.clone(), |mut m| m.sort_with(SortOrder::RowMajor, &options).unwrap(), BatchSize::LargeInput);
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, sort);
+criterion_main!(benches);
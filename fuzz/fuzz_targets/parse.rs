@@ -0,0 +1,36 @@
+//! Parses arbitrary bytes with [`Matrix::try_parse_bytes`], which must return an error rather than panic.
+//!
+//! Run with `cargo +nightly fuzz run parse` from the root of the repository.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use matrix_market_transform::*;
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte picks the data type and options, and the rest is the file
+    let Some((&choice, bytes)) = data.split_first() else {
+        return;
+    };
+    let data_type = match choice % 4 {
+        0 => DataType::Real,
+        1 => DataType::Complex,
+        2 => DataType::Integer,
+        _ => DataType::Bool,
+    };
+    let nonfinite_policy = match (choice >> 2) % 4 {
+        0 => NonFinitePolicy::Keep,
+        1 => NonFinitePolicy::Error,
+        2 => NonFinitePolicy::Drop,
+        _ => NonFinitePolicy::Zero,
+    };
+    let options = ReadOptions::new()
+        .nonfinite_policy(nonfinite_policy)
+        .lenient_numbers(choice & 16 != 0)
+        .count_policy(if choice & 32 != 0 { CountPolicy::Strict } else { CountPolicy::Lenient });
+
+    if let Ok(m) = Matrix::try_parse_bytes(bytes, data_type, &options) {
+        // Whatever parses must also be written without panicking
+        let _ = m.to_string();
+    }
+});
@@ -0,0 +1,55 @@
+/* C interface to matrix-market-transform, built with `cargo build --release --features capi`. */
+
+#ifndef MMT_H
+#define MMT_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Value types of `mmt_real` and `mmt_int` follow the `x64` feature of the library. */
+#ifdef MMT_X64
+typedef double mmt_real;
+typedef long long mmt_int;
+#else
+typedef float mmt_real;
+typedef int mmt_int;
+#endif
+
+/* Value types, as passed to `mmt_read_file`. */
+#define MMT_REAL 0
+#define MMT_COMPLEX 1
+#define MMT_INTEGER 2
+#define MMT_PATTERN 3
+
+/* Sort orders, as passed to `mmt_sort`. */
+#define MMT_ROW_MAJOR 0
+#define MMT_COL_MAJOR 1
+
+typedef struct MmtMatrix mmt_matrix;
+
+/* Reads a Matrix-Market file, returning NULL if the file cannot be read or parsed. */
+mmt_matrix *mmt_read_file(const char *path, int data_type);
+
+/* Sorts the entries of a matrix, returning 0 on success and -1 on invalid arguments. */
+int mmt_sort(mmt_matrix *m, int order);
+
+/* Exposes a matrix in compressed sparse row format with 0-based indices,
+ * sorting it in row-major order if needed. Returns 0 on success and -1 on invalid arguments.
+ *
+ * `values` points to `mmt_real`, interleaved `mmt_real` pairs, or `mmt_int` values
+ * for real, complex, and integer matrices respectively, and is NULL for pattern matrices.
+ * Any output argument may be NULL. The arrays remain valid until the matrix is sorted or freed. */
+int mmt_get_csr_pointers(mmt_matrix *m, size_t *nrows, size_t *ncols, size_t *nvals,
+                         const size_t **row_ptr, const size_t **col_idx, const void **values);
+
+/* Frees a matrix returned by `mmt_read_file`. */
+void mmt_free(mmt_matrix *m);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MMT_H */
@@ -0,0 +1,99 @@
+//! Reading a matrix from a tar archive, optionally gzip-compressed, or a zip archive, as SuiteSparse distributes them,
+//! without extracting it to disk: the member is parsed while it is decompressed.
+//!
+//! SuiteSparse tarballs hold the matrix in `name/name.mtx`, next to files such as the right-hand side `name/name_b.mtx`.
+//! The primary matrix is the `.mtx` file named after its directory, or otherwise the only `.mtx` file
+//! that is not a right-hand side. Any other member is read by naming it.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+
+use crate::{DataType, Error, Matrix, ReadOptions};
+
+/// Whether the path is that of an archive, following its extension:
+/// `.tar`, `.tar.gz`, `.tgz`, or `.zip`.
+pub fn is_archive(path: &Path) -> bool {
+    archive_stem(path).is_some()
+}
+
+/// File name of an archive without the extension that makes it one, such as `bcsstk01` for `bcsstk01.tar.gz`.
+pub fn archive_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    [".tar.gz", ".tgz", ".tar", ".zip"].into_iter().find_map(|ext| name.strip_suffix(ext))
+}
+
+impl Matrix {
+    /// Reads a matrix from a member of an archive, which is the primary matrix unless a member is named,
+    /// by its path within the archive or by its file name.
+    /// Fails if the archive has no such member, or if it has no single primary matrix.
+    pub fn from_archive(path: &Path, member: Option<&str>, data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if name.ends_with(".zip") {
+            let mut archive = zip::ZipArchive::new(BufReader::new(File::open(path)?)).map_err(io::Error::other)?;
+            let names: Vec<_> = archive.file_names().map(str::to_string).collect();
+            let selected = select(&names, member)?;
+            let file = archive.by_name(&selected).map_err(io::Error::other)?;
+            return Matrix::from_reader_with(BufReader::new(file), data_type, options);
+        }
+
+        // Tar archives can only be read in order, so the primary matrix is parsed as soon as it is found,
+        // and the archive is only read again if the member can only be chosen after seeing all of them
+        let mut names = Vec::new();
+        for entry in open_tar(path)?.entries()? {
+            let entry = entry?;
+            let entry_name = entry.path()?.to_string_lossy().into_owned();
+            if matches(&entry_name, member) {
+                return Matrix::from_reader_with(BufReader::new(entry), data_type, options);
+            }
+            names.push(entry_name);
+        }
+        let selected = select(&names, member)?;
+        for entry in open_tar(path)?.entries()? {
+            let entry = entry?;
+            if entry.path()?.to_string_lossy() == selected {
+                return Matrix::from_reader_with(BufReader::new(entry), data_type, options);
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("{selected} disappeared from the archive")).into())
+    }
+}
+
+fn open_tar(path: &Path) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    let rdr = BufReader::new(File::open(path)?);
+    let rdr: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz" || ext == "tgz") {
+        Box::new(GzDecoder::new(rdr))
+    } else {
+        Box::new(rdr)
+    };
+    Ok(tar::Archive::new(rdr))
+}
+
+/// Whether a member is the named one, or the primary matrix if none is named.
+fn matches(name: &str, member: Option<&str>) -> bool {
+    let path = Path::new(name);
+    match member {
+        Some(member) => name == member || path.file_name().is_some_and(|file_name| file_name == member),
+        None => path.extension().is_some_and(|ext| ext == "mtx")
+            && path.file_stem().zip(path.parent().and_then(Path::file_name)).is_some_and(|(stem, dir)| stem == dir),
+    }
+}
+
+/// Chooses the member to read from the names of all members.
+fn select(names: &[String], member: Option<&str>) -> io::Result<String> {
+    if let Some(name) = names.iter().find(|name| matches(name, member)) {
+        return Ok(name.clone());
+    }
+    let candidates: Vec<_> = names.iter().filter(|name| name.ends_with(".mtx")).map(String::as_str).collect();
+    if let Some(member) = member {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("the archive has no member {member}; it has {}", candidates.join(", "))));
+    }
+    match candidates.iter().filter(|name| !name.ends_with("_b.mtx")).collect::<Vec<_>>().as_slice() {
+        [name] => Ok(name.to_string()),
+        [] if candidates.is_empty() => Err(io::Error::new(io::ErrorKind::NotFound, "the archive has no .mtx file")),
+        [] => Err(io::Error::new(io::ErrorKind::NotFound, format!("the archive only has right-hand sides; pass `--member` with one of {}", candidates.join(", ")))),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("the archive has several matrices; pass `--member` with one of {}", candidates.join(", ")))),
+    }
+}
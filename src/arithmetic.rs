@@ -0,0 +1,99 @@
+use crate::{Error, Matrix, MatrixData, Symmetry};
+
+impl Matrix {
+    /// Element-wise sum, on the union of the patterns of both matrices.
+    /// Both matrices must have the same dimensions, data type, and symmetry.
+    ///
+    /// The result is sorted in row-major order without duplicate entries;
+    /// entries that cancel out are kept as explicit zeros.
+    pub fn add(&self, other: &Matrix) -> Result<Matrix, Error> {
+        self.check_compatible(other)?;
+
+        let mut res = self.clone();
+        res.append(other);
+        res.sum_duplicates()?;
+        Ok(res)
+    }
+
+    /// Element-wise difference, on the union of the patterns of both matrices; see [`Matrix::add`].
+    /// For pattern matrices, this is the same as the sum.
+    pub fn sub(&self, other: &Matrix) -> Result<Matrix, Error> {
+        self.check_compatible(other)?;
+
+        let mut negated = other.clone();
+        negated.negate();
+        self.add(&negated)
+    }
+
+    /// Element-wise product, on the intersection of the patterns of both matrices.
+    /// Both matrices must have the same dimensions, data type, and symmetry,
+    /// and duplicate entries are summed before multiplying.
+    ///
+    /// The result is sorted in row-major order. The product of two skew-symmetric matrices is symmetric.
+    /// Fails if the product of two integer entries does not fit in [`Int`](crate::Int).
+    pub fn hadamard(&self, other: &Matrix) -> Result<Matrix, Error> {
+        self.check_compatible(other)?;
+
+        let a = self.coalesced(self.is_row_major(), Matrix::row_major_permutation)?;
+        let b = other.coalesced(other.is_row_major(), Matrix::row_major_permutation)?;
+
+        // Pairs of indices of the entries in both matrices
+        let (mut i, mut j) = (0, 0);
+        let mut pairs = Vec::new();
+        while i < a.nvals && j < b.nvals {
+            match (a.rows[i], a.cols[i]).cmp(&(b.rows[j], b.cols[j])) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    pairs.push((i, j));
+                    i += 1;
+                    j += 1;
+                },
+            }
+        }
+
+        let (left, right): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        let mut res = a.gather(&left);
+        if let Some(k) = res.vals.multiply(&b.vals.gather(&right)) {
+            return Err(Error::ProductOverflow { row: res.rows[k], col: res.cols[k] });
+        }
+        if res.symmetry == Symmetry::SkewSymmetric {
+            res.symmetry = Symmetry::Symmetric;
+        }
+
+        Ok(res)
+    }
+}
+
+impl MatrixData {
+    /// Multiplies every value by the value at the same index in `other`.
+    /// Returns the index of the first integer product that overflows, leaving the values partially multiplied.
+    fn multiply(&mut self, other: &MatrixData) -> Option<usize> {
+        match (self, other) {
+            (MatrixData::Real(xs), MatrixData::Real(other_xs)) => {
+                xs.iter_mut().zip(other_xs).for_each(|(x, y)| *x *= y);
+            },
+            (MatrixData::Complex(xs, ys), MatrixData::Complex(other_xs, other_ys)) => {
+                for k in 0..xs.len() {
+                    let (x, y) = (xs[k], ys[k]);
+                    xs[k] = x * other_xs[k] - y * other_ys[k];
+                    ys[k] = x * other_ys[k] + y * other_xs[k];
+                }
+            },
+            (MatrixData::Integer(xs), MatrixData::Integer(other_xs)) => {
+                for (k, (x, y)) in xs.iter_mut().zip(other_xs).enumerate() {
+                    let Some(product) = x.checked_mul(*y) else {
+                        return Some(k);
+                    };
+                    *x = product;
+                }
+            },
+            (MatrixData::Bool(), MatrixData::Bool()) => {
+                /* nothing to do */
+            },
+            _ => unreachable!("data types must match"),
+        }
+
+        None
+    }
+}
@@ -0,0 +1,81 @@
+//! Export of the triplets as Arrow record batches, with 1-based `row` and `col` columns as `u64`,
+//! and the values in a `value` column as `f64` or `i64`, or in `re` and `im` columns for complex values.
+//! Pattern matrices have no value columns.
+//!
+//! The dimensions, data type, and symmetry are kept in the schema metadata,
+//! under `nrows`, `ncols`, `data_type`, and `symmetry`.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+use crate::{Error, Matrix, MatrixData};
+
+impl Matrix {
+    /// Converts the triplets into a single record batch, keeping the order of the entries.
+    pub fn to_record_batch(&self) -> RecordBatch {
+        let index = |indices: &[usize]| -> ArrayRef {
+            Arc::new(UInt64Array::from_iter_values(indices.iter().map(|&idx| idx as u64)))
+        };
+        let real = |xs: &[crate::Real]| -> ArrayRef {
+            Arc::new(Float64Array::from_iter_values(xs.iter().map(|&x| x as f64)))
+        };
+
+        let mut fields = vec![Field::new("row", DataType::UInt64, false), Field::new("col", DataType::UInt64, false)];
+        let mut columns = vec![index(&self.rows), index(&self.cols)];
+        match &self.vals {
+            MatrixData::Real(xs) => {
+                fields.push(Field::new("value", DataType::Float64, false));
+                columns.push(real(xs));
+            },
+            MatrixData::Complex(xs, ys) => {
+                fields.push(Field::new("re", DataType::Float64, false));
+                fields.push(Field::new("im", DataType::Float64, false));
+                columns.push(real(xs));
+                columns.push(real(ys));
+            },
+            MatrixData::Integer(xs) => {
+                fields.push(Field::new("value", DataType::Int64, false));
+                columns.push(Arc::new(Int64Array::from_iter_values(xs.iter().map(|&x| x as i64))));
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+
+        let metadata = HashMap::from([
+            ("nrows".to_string(), self.nrows.to_string()),
+            ("ncols".to_string(), self.ncols.to_string()),
+            ("data_type".to_string(), self.vals.field().to_string()),
+            ("symmetry".to_string(), self.symmetry.to_string()),
+        ]);
+
+        let schema = Arc::new(Schema::new_with_metadata(fields, metadata));
+        RecordBatch::try_new(schema, columns).expect("columns match the schema")
+    }
+
+    /// Writes the triplets as an Arrow IPC file.
+    pub fn write_arrow_ipc<W: Write>(&self, wtr: W) -> Result<(), Error> {
+        let batch = self.to_record_batch();
+        let mut wtr = arrow_ipc::writer::FileWriter::try_new(wtr, &batch.schema()).map_err(io::Error::other)?;
+        wtr.write(&batch).map_err(io::Error::other)?;
+        wtr.finish().map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    /// Writes the triplets as a Snappy-compressed Parquet file.
+    pub fn write_parquet<W: Write + Send>(&self, wtr: W) -> Result<(), Error> {
+        let batch = self.to_record_batch();
+        let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let mut wtr = ArrowWriter::try_new(wtr, batch.schema(), Some(props)).map_err(io::Error::other)?;
+        wtr.write(&batch).map_err(io::Error::other)?;
+        wtr.close().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{Matrix, Symmetry};
+
+impl Matrix {
+    /// Largest distance of an entry from the diagonal.
+    pub fn bandwidth(&self) -> usize {
+        (0..self.nvals).map(|i| self.rows[i].abs_diff(self.cols[i])).max().unwrap_or(0)
+    }
+
+    /// Fraction of the entries within each of the given distances `k` of the diagonal, where `|row - col| <= k`.
+    /// Compact symmetric forms count their mirrored entries as well.
+    pub fn band_fractions(&self, widths: &[usize]) -> Vec<f64> {
+        let mirrored = self.symmetry != Symmetry::General;
+        let copies = |i: usize| if mirrored && self.rows[i] != self.cols[i] { 2 } else { 1 };
+        let total: usize = (0..self.nvals).map(copies).sum();
+        widths.iter().map(|&k| {
+            let within: usize = (0..self.nvals).filter(|&i| self.rows[i].abs_diff(self.cols[i]) <= k).map(copies).sum();
+            within as f64 / total.max(1) as f64
+        }).collect()
+    }
+
+    /// Size of the envelope of the structure of the matrix plus its transpose:
+    /// the sum over all rows of the distance from the first entry in that row to the diagonal.
+    pub fn profile(&self) -> usize {
+        self.envelope_starts().iter().enumerate().map(|(k, &first)| k - first).sum()
+    }
+
+    /// Root mean square of the wavefront, where the wavefront at step `i` is the number of rows
+    /// whose envelope contains column `i`, in the structure of the matrix plus its transpose.
+    /// This is the number of rows a frontal solver keeps active at step `i`.
+    pub fn rms_wavefront(&self) -> f64 {
+        let starts = self.envelope_starts();
+        let n = starts.len();
+        if n == 0 {
+            return 0.0;
+        }
+
+        // Row `k` is active from step `starts[k]` up to and including step `k`
+        let mut changes = vec![0isize; n + 1];
+        for (k, &first) in starts.iter().enumerate() {
+            changes[first] += 1;
+            changes[k + 1] -= 1;
+        }
+
+        let mut wavefront = 0;
+        let mut sum = 0.0;
+        for change in &changes[..n] {
+            wavefront += change;
+            sum += (wavefront * wavefront) as f64;
+        }
+        (sum / n as f64).sqrt()
+    }
+
+    /// Smallest (0-based) column in each row of the lower triangle of the matrix plus its transpose,
+    /// including the diagonal.
+    fn envelope_starts(&self) -> Vec<usize> {
+        let mut starts: Vec<_> = (0..self.nrows.max(self.ncols)).collect();
+        for i in 0..self.nvals {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            let (hi, lo) = (row.max(col), row.min(col));
+            starts[hi] = starts[hi].min(lo);
+        }
+        starts
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::{self, Write};
+
+use crate::{Error, Matrix, MatrixData, Symmetry};
+
+/// Block sparse row matrix with square blocks, see [`Matrix::to_bsr`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bsr {
+    pub nrows: usize,
+    pub ncols: usize,
+    pub block_size: usize,
+    /// The blocks of block row `i` are found in the range `row_offsets[i]..row_offsets[i + 1]`.
+    pub row_offsets: Vec<usize>,
+    /// (0-based) block column of every block.
+    pub block_cols: Vec<usize>,
+    /// `block_size * block_size` values of every block in row-major order, with zeros where the matrix has no entry.
+    pub values: Vec<f64>,
+}
+
+impl Matrix {
+    /// Fraction of the positions in the nonzero blocks of the given size that hold an entry,
+    /// counting duplicate entries once and including mirrored entries of compact symmetric forms.
+    /// Blocks at the bottom and right edges are counted as full blocks.
+    pub fn block_fill(&self, block_size: usize) -> f64 {
+        assert!(block_size > 0, "block size must be positive");
+        let positions = self.positions();
+        let mut blocks: Vec<_> = positions.iter().map(|&(row, col)| (row / block_size, col / block_size)).collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+
+        positions.len() as f64 / (blocks.len() * block_size * block_size).max(1) as f64
+    }
+
+    /// Largest block size up to `max_block_size` that divides both dimensions,
+    /// and whose blocks are filled at least `min_fill`; see [`Matrix::block_fill`].
+    /// For example, finite-element matrices with three unknowns per node have a block size of three.
+    pub fn detect_block_size(&self, max_block_size: usize, min_fill: f64) -> usize {
+        (2..=max_block_size).rev()
+            .filter(|&b| self.nrows.is_multiple_of(b) && self.ncols.is_multiple_of(b))
+            .find(|&b| self.block_fill(b) >= min_fill)
+            .unwrap_or(1)
+    }
+
+    /// Converts a general real, integer, or pattern matrix into square blocks of the given size,
+    /// summing duplicate entries. The last block row and column are padded with zeros if needed.
+    ///
+    /// Panics if the block size is zero.
+    pub fn to_bsr(&self, block_size: usize) -> Result<Bsr, Error> {
+        assert!(block_size > 0, "block size must be positive");
+        self.check_general()?;
+        if let MatrixData::Complex(..) = self.vals {
+            return Err(Error::TypeMismatch { expected: "real", found: self.vals.field() });
+        }
+
+        let b = block_size;
+        let mut order: Vec<_> = (0..self.nvals).collect();
+        order.sort_unstable_by_key(|&i| ((self.rows[i] - 1) / b, (self.cols[i] - 1) / b));
+
+        let mut row_offsets = vec![0; self.nrows.div_ceil(b) + 1];
+        let mut block_cols = Vec::new();
+        let mut values = Vec::new();
+        let mut last = None;
+        for i in order {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            let block = (row / b, col / b);
+            if last != Some(block) {
+                last = Some(block);
+                row_offsets[block.0 + 1] += 1;
+                block_cols.push(block.1);
+                values.resize(values.len() + b * b, 0.0);
+            }
+
+            let start = values.len() - b * b;
+            values[start + row % b * b + col % b] += self.value_at(i).0;
+        }
+
+        for i in 1..row_offsets.len() {
+            row_offsets[i] += row_offsets[i - 1];
+        }
+
+        Ok(Bsr { nrows: self.nrows, ncols: self.ncols, block_size, row_offsets, block_cols, values })
+    }
+
+    /// Distinct (0-based) coordinates of the entries in row-major order, including mirrored entries.
+    fn positions(&self) -> Vec<(usize, usize)> {
+        let mut positions = Vec::with_capacity(self.nvals);
+        for i in 0..self.nvals {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            positions.push((row, col));
+            if self.symmetry != Symmetry::General && row != col {
+                positions.push((col, row));
+            }
+        }
+
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+}
+
+impl Bsr {
+    /// Number of nonzero blocks.
+    pub fn nblocks(&self) -> usize {
+        self.block_cols.len()
+    }
+
+    /// Writes the blocks in a simple binary format, with every number in little-endian byte order:
+    ///
+    /// - the magic bytes `BSR1`,
+    /// - `nrows`, `ncols`, `block_size`, and the number of blocks, as `u64`,
+    /// - the block row offsets, as `u64`,
+    /// - the block columns, as `u64`,
+    /// - the values, as `f64`.
+    pub fn write<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        wtr.write_all(b"BSR1")?;
+        for n in [self.nrows, self.ncols, self.block_size, self.nblocks()] {
+            wtr.write_all(&(n as u64).to_le_bytes())?;
+        }
+        for &n in self.row_offsets.iter().chain(&self.block_cols) {
+            wtr.write_all(&(n as u64).to_le_bytes())?;
+        }
+        for &x in &self.values {
+            wtr.write_all(&x.to_le_bytes())?;
+        }
+        wtr.flush()
+    }
+}
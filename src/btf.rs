@@ -0,0 +1,251 @@
+use crate::{Matrix, Symmetry};
+
+/// Dulmage-Mendelsohn decomposition of a matrix, see [`Matrix::btf`].
+///
+/// Permuting the matrix by `row_permutation` and `col_permutation` gives a block upper triangular matrix,
+/// with on its diagonal an underdetermined block (fewer rows than columns), a square block, and an overdetermined block
+/// (more rows than columns). The square block is itself block upper triangular, with irreducible diagonal blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockTriangularForm {
+    /// Entry `k` is the (0-based) row that moves to position `k`.
+    pub row_permutation: Vec<usize>,
+    /// Entry `k` is the (0-based) column that moves to position `k`.
+    pub col_permutation: Vec<usize>,
+    /// Size of a maximum matching, which is an upper bound for the rank of the matrix with any values.
+    pub structural_rank: usize,
+    /// Number of rows and columns of the underdetermined, square, and overdetermined blocks.
+    pub coarse: [(usize, usize); 3],
+    /// Sizes of the diagonal blocks of the square block, in order.
+    pub blocks: Vec<usize>,
+}
+
+impl Matrix {
+    /// Column matched to every (0-based) row in a maximum matching of the rows and columns,
+    /// where a row and column may only be matched if the matrix has an entry at their intersection.
+    /// Mirrored entries of matrices stored in a compact symmetric form are included.
+    pub fn maximum_matching(&self) -> Vec<Option<usize>> {
+        maximum_matching(&self.row_adjacency(), self.ncols).0
+    }
+
+    /// Computes the Dulmage-Mendelsohn decomposition, which permutes the matrix to block triangular form.
+    /// Mirrored entries of matrices stored in a compact symmetric form are included.
+    pub fn btf(&self) -> BlockTriangularForm {
+        let row_adjacency = self.row_adjacency();
+        let (row_match, col_match) = maximum_matching(&row_adjacency, self.ncols);
+
+        let mut col_adjacency = vec![Vec::new(); self.ncols];
+        for (row, cols) in row_adjacency.iter().enumerate() {
+            cols.iter().for_each(|&col| col_adjacency[col].push(row));
+        }
+
+        // Columns reachable from unmatched columns through alternating paths form the underdetermined block,
+        // and rows reachable from unmatched rows form the overdetermined block
+        let (under_cols, under_rows) = alternating_reach(&col_adjacency, &col_match, &row_match);
+        let (over_rows, over_cols) = alternating_reach(&row_adjacency, &row_match, &col_match);
+
+        // Rows of the square block, connected from a row to the rows matched to the columns of its entries
+        let square: Vec<_> = (0..self.nrows).filter(|&row| !under_rows[row] && !over_rows[row]).collect();
+        let mut local = vec![usize::MAX; self.nrows];
+        square.iter().enumerate().for_each(|(k, &row)| local[row] = k);
+        let graph: Vec<Vec<usize>> = square.iter().map(|&row| {
+            row_adjacency[row].iter()
+                .filter_map(|&col| col_match[col])
+                .filter(|&other| other != row && local[other] != usize::MAX)
+                .map(|other| local[other])
+                .collect()
+        }).collect();
+
+        // Tarjan finds the components in reverse topological order
+        let mut components = strongly_connected_components(&graph);
+        components.reverse();
+
+        let mut row_permutation: Vec<_> = (0..self.nrows).filter(|&row| under_rows[row]).collect();
+        let mut col_permutation: Vec<_> = (0..self.ncols).filter(|&col| under_cols[col]).collect();
+        let under = (row_permutation.len(), col_permutation.len());
+
+        for component in &components {
+            for &k in component {
+                row_permutation.push(square[k]);
+                col_permutation.push(row_match[square[k]].unwrap());
+            }
+        }
+
+        row_permutation.extend((0..self.nrows).filter(|&row| over_rows[row]));
+        col_permutation.extend((0..self.ncols).filter(|&col| over_cols[col]));
+        let over = (self.nrows - under.0 - square.len(), self.ncols - under.1 - square.len());
+
+        BlockTriangularForm {
+            row_permutation,
+            col_permutation,
+            structural_rank: row_match.iter().flatten().count(),
+            coarse: [under, (square.len(), square.len()), over],
+            blocks: components.iter().map(Vec::len).collect(),
+        }
+    }
+
+    /// Columns of the entries in every (0-based) row, including mirrored entries.
+    pub(crate) fn row_adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.nrows];
+        for i in 0..self.nvals {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            adjacency[row].push(col);
+            if self.symmetry != Symmetry::General && row != col {
+                adjacency[col].push(row);
+            }
+        }
+        adjacency
+    }
+}
+
+/// Maximum matching through depth-first searches for augmenting paths, with a cheap assignment first, after Duff.
+/// Returns the column matched to every row, and the row matched to every column.
+pub(crate) fn maximum_matching(adjacency: &[Vec<usize>], ncols: usize) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let mut row_match = vec![None; adjacency.len()];
+    let mut col_match = vec![None; ncols];
+    // Position of the next candidate for a cheap assignment in every row; matched columns stay matched
+    let mut cheap = vec![0; adjacency.len()];
+    // Last search in which every column was visited
+    let mut visited = vec![usize::MAX; ncols];
+
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    let mut path = Vec::new();
+    for root in 0..adjacency.len() {
+        stack.clear();
+        path.clear();
+        stack.push((root, 0));
+
+        while let Some(&mut (row, ref mut pos)) = stack.last_mut() {
+            let cols = &adjacency[row];
+            while cheap[row] < cols.len() && col_match[cols[cheap[row]]].is_some() {
+                cheap[row] += 1;
+            }
+
+            if let Some(&free) = cols.get(cheap[row]) {
+                // Augment: the last row takes the free column, and every other row the column leading to the next row
+                row_match[row] = Some(free);
+                col_match[free] = Some(row);
+                for (&(row, _), &col) in stack.iter().zip(&path) {
+                    row_match[row] = Some(col);
+                    col_match[col] = Some(row);
+                }
+                break;
+            }
+
+            match cols[*pos..].iter().position(|&col| visited[col] != root) {
+                Some(offset) => {
+                    let col = cols[*pos + offset];
+                    *pos += offset + 1;
+                    visited[col] = root;
+                    path.push(col);
+                    stack.push((col_match[col].unwrap(), 0));
+                },
+                None => {
+                    stack.pop();
+                    path.pop();
+                },
+            }
+        }
+    }
+
+    (row_match, col_match)
+}
+
+/// Vertices reachable from the unmatched vertices on one side through alternating paths,
+/// which go from one side to the other through an entry, and back through the matching.
+/// Returns the reached vertices on the starting side and on the other side.
+fn alternating_reach(adjacency: &[Vec<usize>], matching: &[Option<usize>], back: &[Option<usize>]) -> (Vec<bool>, Vec<bool>) {
+    let mut reached = vec![false; adjacency.len()];
+    let mut reached_other = vec![false; back.len()];
+
+    let mut queue: Vec<_> = (0..adjacency.len()).filter(|&v| matching[v].is_none()).collect();
+    queue.iter().for_each(|&v| reached[v] = true);
+    while let Some(v) = queue.pop() {
+        for &u in &adjacency[v] {
+            if !reached_other[u] {
+                reached_other[u] = true;
+                // In a maximum matching, every vertex reached this way is matched
+                if let Some(w) = back[u] && !reached[w] {
+                    reached[w] = true;
+                    queue.push(w);
+                }
+            }
+        }
+    }
+
+    (reached, reached_other)
+}
+
+/// Strongly connected components in reverse topological order, following Tarjan.
+fn strongly_connected_components(graph: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan {
+        index: vec![usize::MAX; graph.len()],
+        low: vec![0; graph.len()],
+        on_stack: vec![false; graph.len()],
+        stack: Vec::new(),
+        calls: Vec::new(),
+        next: 0,
+    };
+
+    let mut components = Vec::new();
+    for root in 0..graph.len() {
+        if tarjan.index[root] != usize::MAX {
+            continue;
+        }
+
+        tarjan.visit(root);
+        while let Some(&mut (v, ref mut pos)) = tarjan.calls.last_mut() {
+            if let Some(&w) = graph[v].get(*pos) {
+                *pos += 1;
+                if tarjan.index[w] == usize::MAX {
+                    tarjan.visit(w);
+                } else if tarjan.on_stack[w] {
+                    tarjan.low[v] = tarjan.low[v].min(tarjan.index[w]);
+                }
+                continue;
+            }
+
+            tarjan.calls.pop();
+            if let Some(&(u, _)) = tarjan.calls.last() {
+                tarjan.low[u] = tarjan.low[u].min(tarjan.low[v]);
+            }
+
+            if tarjan.low[v] == tarjan.index[v] {
+                let mut component = Vec::new();
+                loop {
+                    let w = tarjan.stack.pop().unwrap();
+                    tarjan.on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.reverse();
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// State of an iterative depth-first search for strongly connected components.
+struct Tarjan {
+    index: Vec<usize>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    /// Vertices being visited, with the position of the next edge to follow.
+    calls: Vec<(usize, usize)>,
+    next: usize,
+}
+
+impl Tarjan {
+    fn visit(&mut self, v: usize) {
+        self.index[v] = self.next;
+        self.low[v] = self.next;
+        self.next += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+        self.calls.push((v, 0));
+    }
+}
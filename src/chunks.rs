@@ -0,0 +1,55 @@
+//! Splitting the contents of a file into chunks of whole lines, to parse them in parallel
+//! without collecting a vector of lines: a first pass counts the entries of every chunk,
+//! and a second pass parses every chunk into its own part of the arrays.
+
+use crate::is_data;
+
+/// Number of bytes per chunk of lines.
+const CHUNK_SIZE: usize = 1 << 18;
+
+/// The size line, and the bytes after it, or `None` if the file is empty or contains only comments.
+pub(crate) fn split_header(mut bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    while !bytes.is_empty() {
+        let (line, rest) = match bytes.iter().position(|&b| b == b'\n') {
+            Some(end) => (&bytes[..end], &bytes[end + 1..]),
+            None => (bytes, &[][..]),
+        };
+        if is_data(line) {
+            return Some((line, rest));
+        }
+        bytes = rest;
+    }
+    None
+}
+
+/// Splits the bytes into chunks of about [`CHUNK_SIZE`] bytes that end at the end of a line.
+pub(crate) fn line_chunks(mut bytes: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::with_capacity(bytes.len() / CHUNK_SIZE + 1);
+    while !bytes.is_empty() {
+        let end = match bytes.iter().skip(CHUNK_SIZE).position(|&b| b == b'\n') {
+            Some(end) => CHUNK_SIZE + end + 1,
+            None => bytes.len(),
+        };
+        let (chunk, rest) = bytes.split_at(end);
+        chunks.push(chunk);
+        bytes = rest;
+    }
+    chunks
+}
+
+/// The lines of a chunk that hold an entry.
+#[inline]
+pub(crate) fn data_lines(chunk: &[u8]) -> impl Iterator<Item = &[u8]> {
+    chunk.split(|&b| b == b'\n').filter(|line| is_data(line))
+}
+
+/// Splits the slice into consecutive parts of the given lengths.
+pub(crate) fn split_by_counts<'a, T>(mut xs: &'a mut [T], counts: &[usize]) -> Vec<&'a mut [T]> {
+    counts.iter()
+        .map(|&count| {
+            let (part, rest) = std::mem::take(&mut xs).split_at_mut(count);
+            xs = rest;
+            part
+        })
+        .collect()
+}
@@ -0,0 +1,190 @@
+//! Comparison of matrices up to a tolerance on their floating-point values,
+//! such as to verify that a written file reads back as the matrix it was written from.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use rayon::prelude::*;
+
+use crate::profile::json_number;
+use crate::{Error, Matrix, MatrixData, Real, Symmetry};
+
+/// Tolerance when comparing real and complex values, where two values are equal if they are within either bound.
+/// The default only accepts identical values.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Tolerance {
+    ulps: u64,
+    abs: f64,
+}
+
+impl Tolerance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest number of representable values between two equal values.
+    pub fn ulps(mut self, ulps: u64) -> Self {
+        self.ulps = ulps;
+        self
+    }
+
+    /// Largest absolute difference between two equal values.
+    pub fn abs(mut self, abs: f64) -> Self {
+        self.abs = abs;
+        self
+    }
+
+    /// Whether the values are equal within the tolerance, where NaNs equal each other.
+    pub fn eq(&self, a: Real, b: Real) -> bool {
+        a == b
+            || (a.is_nan() && b.is_nan())
+            || (a as f64 - b as f64).abs() <= self.abs
+            || ulps_between(a, b) <= self.ulps
+    }
+}
+
+/// Errors of a matrix with respect to a reference matrix, see [`Matrix::error_metrics`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ErrorMetrics {
+    /// Largest absolute difference of an entry.
+    pub max_abs: f64,
+    /// Largest relative difference `|a - g| / |g|` of an entry,
+    /// which is infinite if an entry differs where the reference is zero.
+    pub max_rel: f64,
+    /// Mean relative difference over the entries where the reference is not zero.
+    pub mean_rel: f64,
+    /// Frobenius norm of the difference.
+    pub fro: f64,
+    /// Frobenius norm of the difference, relative to that of the reference.
+    pub rel_fro: f64,
+    /// (1-based) row and column of the entry with the largest relative difference, if any entry differs.
+    pub worst: Option<(usize, usize)>,
+}
+
+impl ErrorMetrics {
+    /// Formats the metrics as a JSON object.
+    pub fn to_json(&self) -> String {
+        let worst = self.worst.map_or("null".to_string(), |(row, col)| format!("[{row},{col}]"));
+        format!(
+            "{{\"max_abs\":{},\"max_rel\":{},\"mean_rel\":{},\"fro\":{},\"rel_fro\":{},\"worst\":{worst}}}",
+            json_number(self.max_abs), json_number(self.max_rel), json_number(self.mean_rel),
+            json_number(self.fro), json_number(self.rel_fro),
+        )
+    }
+}
+
+impl fmt::Display for ErrorMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "max abs:    {:.6e}", self.max_abs)?;
+        writeln!(f, "max rel:    {:.6e}", self.max_rel)?;
+        writeln!(f, "mean rel:   {:.6e}", self.mean_rel)?;
+        writeln!(f, "frobenius:  {:.6e}", self.fro)?;
+        writeln!(f, "rel fro:    {:.6e}", self.rel_fro)?;
+        match self.worst {
+            Some((row, col)) => writeln!(f, "worst:      ({row}, {col})"),
+            None => writeln!(f, "worst:      none"),
+        }
+    }
+}
+
+impl Matrix {
+    /// Errors of this matrix with respect to a reference matrix with the same dimensions, data type, and symmetry,
+    /// on the union of their patterns, where duplicate entries are summed first and missing entries are zero.
+    /// Complex values compare by the absolute value of their difference,
+    /// and compact symmetric forms count their mirrored entries in the Frobenius norms.
+    pub fn error_metrics(&self, golden: &Matrix) -> Result<ErrorMetrics, Error> {
+        self.check_compatible(golden)?;
+
+        let a = self.coalesced(self.is_row_major(), Matrix::row_major_permutation)?;
+        let g = golden.coalesced(golden.is_row_major(), Matrix::row_major_permutation)?;
+
+        let mut metrics = ErrorMetrics { max_abs: 0.0, max_rel: 0.0, mean_rel: 0.0, fro: 0.0, rel_fro: 0.0, worst: None };
+        let (mut golden_squares, mut rel_sum, mut rel_count) = (0.0, 0.0, 0);
+        let (mut i, mut j) = (0, 0);
+        while i < a.nvals || j < g.nvals {
+            let order = match (i < a.nvals, j < g.nvals) {
+                (true, true) => (a.rows[i], a.cols[i]).cmp(&(g.rows[j], g.cols[j])),
+                (true, false) => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+            let ((x, y), (u, v), (row, col)) = match order {
+                Ordering::Less => (a.value_at(i), (0.0, 0.0), (a.rows[i], a.cols[i])),
+                Ordering::Greater => ((0.0, 0.0), g.value_at(j), (g.rows[j], g.cols[j])),
+                Ordering::Equal => (a.value_at(i), g.value_at(j), (a.rows[i], a.cols[i])),
+            };
+            if order != Ordering::Greater {
+                i += 1;
+            }
+            if order != Ordering::Less {
+                j += 1;
+            }
+
+            let copies = if a.symmetry != Symmetry::General && row != col { 2.0 } else { 1.0 };
+            let abs = (x - u).hypot(y - v);
+            let reference = u.hypot(v);
+            let rel = if reference != 0.0 { abs / reference } else if abs != 0.0 { f64::INFINITY } else { 0.0 };
+            metrics.fro += copies * abs * abs;
+            golden_squares += copies * reference * reference;
+            metrics.max_abs = metrics.max_abs.max(abs);
+            if reference != 0.0 {
+                rel_sum += rel;
+                rel_count += 1;
+            }
+            if abs != 0.0 && (metrics.worst.is_none() || rel > metrics.max_rel) {
+                metrics.max_rel = rel;
+                metrics.worst = Some((row, col));
+            }
+        }
+
+        metrics.mean_rel = rel_sum / rel_count.max(1) as f64;
+        metrics.fro = metrics.fro.sqrt();
+        metrics.rel_fro = if golden_squares > 0.0 { metrics.fro / golden_squares.sqrt() } else if metrics.fro > 0.0 { f64::INFINITY } else { 0.0 };
+        Ok(metrics)
+    }
+
+    /// Checks that the other matrix has the same dimensions, data type, symmetry, and entries in the same order,
+    /// where integer values must be equal, and real and complex values equal within the tolerance.
+    pub fn check_equal(&self, other: &Matrix, tol: Tolerance) -> Result<(), Error> {
+        if (self.nrows, self.ncols) != (other.nrows, other.ncols) {
+            return Err(Error::DimensionMismatch { expected: (self.nrows, self.ncols), found: (other.nrows, other.ncols) });
+        }
+        if self.vals.field() != other.vals.field() {
+            return Err(Error::TypeMismatch { expected: self.vals.field(), found: other.vals.field() });
+        }
+        if self.symmetry != other.symmetry {
+            return Err(Error::SymmetryMismatch { expected: self.symmetry, found: other.symmetry });
+        }
+        if self.nvals != other.nvals {
+            return Err(Error::EntryCount { expected: self.nvals, found: other.nvals });
+        }
+
+        let differs = (0..self.nvals).into_par_iter().find_first(|&i| {
+            !self.same_coords_as(other, i) || match (&self.vals, &other.vals) {
+                (MatrixData::Real(xs), MatrixData::Real(ys)) => !tol.eq(xs[i], ys[i]),
+                (MatrixData::Complex(xs, ys), MatrixData::Complex(us, vs)) => !tol.eq(xs[i], us[i]) || !tol.eq(ys[i], vs[i]),
+                (MatrixData::Integer(xs), MatrixData::Integer(ys)) => xs[i] != ys[i],
+                _ => false,
+            }
+        });
+
+        match differs {
+            Some(i) => Err(Error::ValueMismatch { row: self.rows[i], col: self.cols[i] }),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn same_coords_as(&self, other: &Matrix, i: usize) -> bool {
+        self.rows[i] == other.rows[i] && self.cols[i] == other.cols[i]
+    }
+}
+
+/// Number of representable values between two finite values, such that neighbouring values are one apart.
+fn ulps_between(a: Real, b: Real) -> u64 {
+    // Maps the values to integers that are ordered like them, with both zeros at zero
+    let ordered = |x: Real| {
+        let magnitude = x.abs().to_bits() as i128;
+        if x.is_sign_negative() { -magnitude } else { magnitude }
+    };
+    u64::try_from((ordered(a) - ordered(b)).unsigned_abs()).unwrap_or(u64::MAX)
+}
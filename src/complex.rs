@@ -0,0 +1,150 @@
+use rayon::prelude::*;
+
+use crate::{DataType, Error, Matrix, MatrixData, Real, SortOrder, Symmetry};
+
+impl Matrix {
+    /// Swaps the rows and columns of the matrix.
+    ///
+    /// Matrices stored in a compact symmetric form keep their stored triangle,
+    /// and instead have their values negated or conjugated as the symmetry requires.
+    pub fn transpose(&mut self) {
+        match self.symmetry {
+            Symmetry::General => {
+                std::mem::swap(&mut self.rows, &mut self.cols);
+                std::mem::swap(&mut self.nrows, &mut self.ncols);
+                self.sortedness = match self.sortedness {
+                    Some(SortOrder::RowMajor) => Some(SortOrder::ColMajor),
+                    Some(SortOrder::ColMajor) => Some(SortOrder::RowMajor),
+                    _ => None,
+                };
+            },
+            Symmetry::Symmetric => {
+                /* nothing to do */
+            },
+            Symmetry::SkewSymmetric => self.negate(),
+            Symmetry::Hermitian => self.conjugate(),
+        }
+    }
+
+    /// Negates the imaginary parts of a complex matrix; other matrices are left unchanged.
+    pub fn conjugate(&mut self) {
+        if let MatrixData::Complex(_, ys) = &mut self.vals {
+            ys.par_iter_mut().for_each(|y| *y = -*y);
+        }
+    }
+
+    /// Transposes and conjugates the matrix.
+    pub fn conjugate_transpose(&mut self) {
+        self.conjugate();
+        self.transpose();
+    }
+
+    /// Real matrix of the absolute values, or magnitudes of complex values.
+    /// Pattern entries become ones.
+    pub fn abs(&self) -> Matrix {
+        let xs = (0..self.nvals).into_par_iter()
+            .map(|i| self.abs_at(i) as Real)
+            .collect();
+
+        let symmetry = match self.symmetry {
+            Symmetry::General => Symmetry::General,
+            _ => Symmetry::Symmetric,
+        };
+
+        self.with_real_values(xs, symmetry)
+    }
+
+    /// Real matrix of the real parts of a complex matrix.
+    pub fn real_part(&self) -> Result<Matrix, Error> {
+        let (xs, _) = self.complex_parts()?;
+        let symmetry = match self.symmetry {
+            Symmetry::Hermitian => Symmetry::Symmetric,
+            symmetry => symmetry,
+        };
+        Ok(self.with_real_values(xs.clone(), symmetry))
+    }
+
+    /// Real matrix of the imaginary parts of a complex matrix.
+    pub fn imag_part(&self) -> Result<Matrix, Error> {
+        let (_, ys) = self.complex_parts()?;
+        let symmetry = match self.symmetry {
+            Symmetry::Hermitian => Symmetry::SkewSymmetric,
+            symmetry => symmetry,
+        };
+        Ok(self.with_real_values(ys.clone(), symmetry))
+    }
+
+    /// Matrix with the values converted to the given data type, where pattern entries become ones
+    /// and converting to a pattern drops the values.
+    /// Fails for conversions that would lose information, from real to integer values and from complex to real or integer values;
+    /// see [`Matrix::real_part`] and [`Matrix::abs`] for those.
+    pub fn cast(&self, data_type: DataType) -> Result<Matrix, Error> {
+        let ones = || vec![1.0; self.nvals];
+        let vals = match (&self.vals, data_type) {
+            (_, DataType::Bool) => MatrixData::Bool(),
+            (MatrixData::Bool(), DataType::Real) => MatrixData::Real(ones()),
+            (MatrixData::Bool(), DataType::Complex) => MatrixData::Complex(ones(), vec![0.0; self.nvals]),
+            (MatrixData::Bool(), DataType::Integer) => MatrixData::Integer(vec![1; self.nvals]),
+            (MatrixData::Integer(xs), DataType::Real) => MatrixData::Real(xs.par_iter().map(|&x| x as Real).collect()),
+            (MatrixData::Integer(xs), DataType::Complex) => {
+                MatrixData::Complex(xs.par_iter().map(|&x| x as Real).collect(), vec![0.0; self.nvals])
+            },
+            (MatrixData::Real(xs), DataType::Complex) => MatrixData::Complex(xs.clone(), vec![0.0; self.nvals]),
+            (MatrixData::Real(_), DataType::Real)
+            | (MatrixData::Complex(..), DataType::Complex)
+            | (MatrixData::Integer(_), DataType::Integer) => self.vals.clone(),
+            (vals, DataType::Integer) => return Err(Error::TypeMismatch { expected: "integer", found: vals.field() }),
+            (vals, _) => return Err(Error::TypeMismatch { expected: "real", found: vals.field() }),
+        };
+
+        // The pattern of a skew-symmetric or hermitian matrix is symmetric
+        let symmetry = match (data_type, self.symmetry) {
+            (DataType::Bool, Symmetry::SkewSymmetric | Symmetry::Hermitian) => Symmetry::Symmetric,
+            (_, symmetry) => symmetry,
+        };
+
+        Ok(Matrix {
+            rows: self.rows.clone(),
+            cols: self.cols.clone(),
+            vals,
+            symmetry,
+            ..*self
+        })
+    }
+
+    fn complex_parts(&self) -> Result<(&Vec<Real>, &Vec<Real>), Error> {
+        match &self.vals {
+            MatrixData::Complex(xs, ys) => Ok((xs, ys)),
+            vals => Err(Error::TypeMismatch { expected: "complex", found: vals.field() }),
+        }
+    }
+
+    fn with_real_values(&self, xs: Vec<Real>, symmetry: Symmetry) -> Matrix {
+        Matrix {
+            rows: self.rows.clone(),
+            cols: self.cols.clone(),
+            vals: MatrixData::Real(xs),
+            symmetry,
+            ..*self
+        }
+    }
+
+    /// Negates every value; pattern matrices are left unchanged.
+    pub(crate) fn negate(&mut self) {
+        match &mut self.vals {
+            MatrixData::Real(xs) => {
+                xs.par_iter_mut().for_each(|x| *x = -*x);
+            },
+            MatrixData::Complex(xs, ys) => {
+                xs.par_iter_mut().for_each(|x| *x = -*x);
+                ys.par_iter_mut().for_each(|y| *y = -*y);
+            },
+            MatrixData::Integer(xs) => {
+                xs.par_iter_mut().for_each(|x| *x = -*x);
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+    }
+}
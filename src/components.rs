@@ -0,0 +1,119 @@
+use rayon::prelude::*;
+
+use crate::Matrix;
+
+/// Connected components of the graph of a matrix, see [`Matrix::connected_components`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Components {
+    /// Component of every (0-based) row.
+    pub rows: Vec<usize>,
+    /// Component of every (0-based) column.
+    pub cols: Vec<usize>,
+    /// Number of components, which are numbered in order of their first row, and then of their first column.
+    pub count: usize,
+}
+
+impl Components {
+    /// Number of rows and columns in each component.
+    pub fn sizes(&self) -> Vec<(usize, usize)> {
+        let mut sizes = vec![(0, 0); self.count];
+        self.rows.iter().for_each(|&k| sizes[k].0 += 1);
+        self.cols.iter().for_each(|&k| sizes[k].1 += 1);
+        sizes
+    }
+}
+
+impl Matrix {
+    /// Connected components of the graph of the matrix, in which every entry connects its row and column.
+    ///
+    /// For square matrices row `i` and column `i` are the same vertex, such that this is the graph of the matrix plus
+    /// its transpose, and every component is a diagonal block after a symmetric permutation.
+    /// For rectangular matrices the graph is bipartite, with separate vertices for rows and columns.
+    /// Rows and columns without entries form components of their own.
+    pub fn connected_components(&self) -> Components {
+        let square = self.nrows == self.ncols;
+        let col_offset = if square { 0 } else { self.nrows };
+        let mut sets = DisjointSets::new(col_offset + self.ncols);
+        for i in 0..self.nvals {
+            sets.union(self.rows[i] - 1, col_offset + self.cols[i] - 1);
+        }
+
+        let mut labels = vec![usize::MAX; sets.len()];
+        let mut count = 0;
+        let mut label = |v: usize| {
+            let root = sets.find(v);
+            if labels[root] == usize::MAX {
+                labels[root] = count;
+                count += 1;
+            }
+            labels[root]
+        };
+
+        let rows: Vec<_> = (0..self.nrows).map(&mut label).collect();
+        let cols: Vec<_> = (0..self.ncols).map(|j| label(col_offset + j)).collect();
+        Components { rows, cols, count }
+    }
+
+    /// Splits the matrix into one matrix per connected component, in the order of [`Matrix::connected_components`].
+    /// The rows and columns of each part are renumbered, keeping their relative order.
+    pub fn split_components(&self) -> Vec<Matrix> {
+        let components = self.connected_components();
+        let (row_local, row_sizes) = local_indices(&components.rows, components.count);
+        let (col_local, col_sizes) = local_indices(&components.cols, components.count);
+
+        let mut indices = vec![Vec::new(); components.count];
+        for i in 0..self.nvals {
+            indices[components.rows[self.rows[i] - 1]].push(i);
+        }
+
+        indices.par_iter().enumerate().map(|(k, indices)| {
+            let mut part = self.gather(indices);
+            part.rows.iter_mut().for_each(|row| *row = row_local[*row - 1] + 1);
+            part.cols.iter_mut().for_each(|col| *col = col_local[*col - 1] + 1);
+            part.nrows = row_sizes[k];
+            part.ncols = col_sizes[k];
+            part
+        }).collect()
+    }
+}
+
+/// Position of every index among the indices with the same label, and the number of indices with each label.
+fn local_indices(labels: &[usize], count: usize) -> (Vec<usize>, Vec<usize>) {
+    let mut sizes = vec![0; count];
+    let local = labels.iter().map(|&k| {
+        sizes[k] += 1;
+        sizes[k] - 1
+    }).collect();
+    (local, sizes)
+}
+
+/// Union-find over the vertices `0..n`.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(n: usize) -> Self {
+        Self { parents: (0..n).collect() }
+    }
+
+    fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    fn find(&mut self, mut v: usize) -> usize {
+        while self.parents[v] != v {
+            // Path halving
+            self.parents[v] = self.parents[self.parents[v]];
+            v = self.parents[v];
+        }
+        v
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use rayon::prelude::*;
+
+use crate::{Error, Matrix, Symmetry};
+
+impl Matrix {
+    /// Places the given general matrices next to each other.
+    /// All matrices must have the same number of rows and the same data type.
+    ///
+    /// Panics if `matrices` is empty.
+    pub fn hstack(matrices: &[Matrix]) -> Result<Matrix, Error> {
+        let (first, rest) = matrices.split_first().expect("expected at least one matrix");
+        first.check_general()?;
+
+        let mut res = first.clone();
+        for m in rest {
+            res.check_same_kind(m)?;
+            if m.nrows != res.nrows {
+                return Err(Error::DimensionMismatch {
+                    expected: (res.nrows, m.ncols),
+                    found: (m.nrows, m.ncols),
+                });
+            }
+
+            res.append_shifted(m, 0, res.ncols);
+            res.ncols += m.ncols;
+        }
+
+        Ok(res)
+    }
+
+    /// Places the given general matrices on top of each other.
+    /// All matrices must have the same number of columns and the same data type.
+    ///
+    /// Panics if `matrices` is empty.
+    pub fn vstack(matrices: &[Matrix]) -> Result<Matrix, Error> {
+        let (first, rest) = matrices.split_first().expect("expected at least one matrix");
+        first.check_general()?;
+
+        let mut res = first.clone();
+        for m in rest {
+            res.check_same_kind(m)?;
+            if m.ncols != res.ncols {
+                return Err(Error::DimensionMismatch {
+                    expected: (m.nrows, res.ncols),
+                    found: (m.nrows, m.ncols),
+                });
+            }
+
+            res.append_shifted(m, res.nrows, 0);
+            res.nrows += m.nrows;
+        }
+
+        Ok(res)
+    }
+
+    /// Places the given matrices along the diagonal of a larger matrix.
+    /// All matrices must have the same data type and symmetry,
+    /// and the result has that same symmetry.
+    ///
+    /// Panics if `matrices` is empty.
+    pub fn block_diag(matrices: &[Matrix]) -> Result<Matrix, Error> {
+        let (first, rest) = matrices.split_first().expect("expected at least one matrix");
+
+        let mut res = first.clone();
+        for m in rest {
+            res.check_same_kind(m)?;
+            res.append_shifted(m, res.nrows, res.ncols);
+            res.nrows += m.nrows;
+            res.ncols += m.ncols;
+        }
+
+        Ok(res)
+    }
+
+    /// Appends the entries of `other`, with their indices shifted by the given offsets.
+    fn append_shifted(&mut self, other: &Matrix, row_offset: usize, col_offset: usize) {
+        let start = self.nvals;
+        self.append(other);
+        self.rows[start..].par_iter_mut().for_each(|row| *row += row_offset);
+        self.cols[start..].par_iter_mut().for_each(|col| *col += col_offset);
+    }
+
+    pub(crate) fn check_general(&self) -> Result<(), Error> {
+        if self.symmetry != Symmetry::General {
+            return Err(Error::SymmetryMismatch {
+                expected: Symmetry::General,
+                found: self.symmetry,
+            });
+        }
+
+        Ok(())
+    }
+}
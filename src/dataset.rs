@@ -0,0 +1,168 @@
+//! Bundles of a matrix with the files that collections ship next to it, such as SuiteSparse's
+//! right-hand side `name_b.mtx` and node coordinates `name_coord.mtx`,
+//! which are permuted and scaled together with the matrix, such that they keep describing the same problem.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::vector::parse_array;
+use crate::{DataType, Error, Matrix, MatrixData, ReadOptions, Real, Reordering, Vector};
+
+/// Coordinates of the nodes of a matrix, such as the vertices of a mesh, with a point per row of the matrix,
+/// stored in the array format with a column per dimension.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coordinates {
+    len: usize,
+    dim: usize,
+    /// Values of the points, stored dimension by dimension as in the file.
+    vals: Vec<Real>,
+}
+
+impl Coordinates {
+    /// Number of points.
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    /// Number of dimensions of every point.
+    pub fn dim(&self) -> usize { self.dim }
+
+    /// Coordinate `d` of point `i`.
+    pub fn get(&self, i: usize, d: usize) -> Real {
+        self.vals[d * self.len + i]
+    }
+
+    /// Parses the contents of a Matrix-Market file in the real array format, with a row per point.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (dim, vals) = parse_array(bytes, DataType::Real, None)?;
+        let MatrixData::Real(vals) = vals else { unreachable!() };
+        Ok(Self { len: vals.len().checked_div(dim).unwrap_or(0), dim, vals })
+    }
+
+    /// Moves point `permutation[k]` to position `k`, for every `k`, as [`Vector::permute`] does.
+    ///
+    /// Panics if `permutation` is not a permutation of the points.
+    pub fn permute(&mut self, permutation: &[usize]) {
+        assert_eq!(permutation.len(), self.len, "expected one index per point");
+        let (len, vals) = (self.len, &self.vals);
+        self.vals = (0..self.dim)
+            .flat_map(|d| permutation.iter().map(move |&i| vals[d * len + i]))
+            .collect();
+    }
+}
+
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "%%MatrixMarket matrix array real general")?;
+        writeln!(f, "{} {}", self.len, self.dim)?;
+        self.vals.iter().try_for_each(|x| writeln!(f, "{x}"))
+    }
+}
+
+/// A matrix with its right-hand side and node coordinates, if any,
+/// which have an entry and a point per row of the matrix.
+#[derive(Clone, Debug)]
+pub struct Dataset {
+    pub matrix: Matrix,
+    pub rhs: Option<Vector>,
+    pub coords: Option<Coordinates>,
+}
+
+impl Dataset {
+    /// Bundles a matrix with its members, failing if they do not have an entry or point per row of the matrix.
+    pub fn new(matrix: Matrix, rhs: Option<Vector>, coords: Option<Coordinates>) -> Result<Self, Error> {
+        let nrows = matrix.nrows();
+        if let Some(rhs) = &rhs && rhs.len() != nrows {
+            return Err(Error::DimensionMismatch { expected: (nrows, 1), found: (rhs.len(), 1) });
+        }
+        if let Some(coords) = &coords && coords.len() != nrows {
+            return Err(Error::DimensionMismatch { expected: (nrows, coords.dim()), found: (coords.len(), coords.dim()) });
+        }
+        Ok(Self { matrix, rhs, coords })
+    }
+
+    /// Path of a member of the bundle of the matrix at `path`, such as `A_b.mtx` for the suffix `b` of `A.mtx`.
+    pub fn member_path(path: &Path, suffix: &str) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{stem}_{suffix}.mtx"))
+    }
+
+    /// Reads the matrix at `path`, along with its members, see [`Dataset::with_members`].
+    pub fn load(path: &Path, data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        let matrix = Matrix::from_reader_with(BufReader::new(File::open(path)?), data_type, options)?;
+        Self::with_members(matrix, path)
+    }
+
+    /// Bundles the matrix read from `path` with the right-hand side `_b.mtx` and the coordinates `_coord.mtx`
+    /// next to it, if they exist. The right-hand side is read as complex for complex matrices, and as real otherwise.
+    pub fn with_members(matrix: Matrix, path: &Path) -> Result<Self, Error> {
+        let rhs_type = if matches!(matrix.data_type(), DataType::Complex) { DataType::Complex } else { DataType::Real };
+        let read = |suffix: &str| -> io::Result<Option<Vec<u8>>> {
+            match fs::read(Self::member_path(path, suffix)) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        };
+        let rhs = read("b")?.map(|bytes| Vector::from_bytes(&bytes, rhs_type)).transpose()?;
+        let coords = read("coord")?.map(|bytes| Coordinates::from_bytes(&bytes)).transpose()?;
+        Self::new(matrix, rhs, coords)
+    }
+
+    /// Writes the matrix to `path`, and its members next to it, see [`Dataset::write_members`].
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut wtr = BufWriter::new(File::create(path)?);
+        write!(wtr, "{}", self.matrix)?;
+        wtr.flush()?;
+        self.write_members(path)
+    }
+
+    /// Writes the right-hand side and the coordinates, if any, next to the matrix at `path`.
+    pub fn write_members(&self, path: &Path) -> io::Result<()> {
+        if let Some(rhs) = &self.rhs {
+            fs::write(Self::member_path(path, "b"), rhs.to_string())?;
+        }
+        if let Some(coords) = &self.coords {
+            fs::write(Self::member_path(path, "coord"), coords.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Reorders the matrix symmetrically, see [`Matrix::reorder`], and the members along with its rows.
+    pub fn reorder(&mut self, method: Reordering) -> Result<Vec<usize>, Error> {
+        let permutation = self.matrix.reordering(method)?;
+        self.permute_symmetric(&permutation);
+        Ok(permutation)
+    }
+
+    /// Permutes the matrix symmetrically, see [`Matrix::permute_symmetric`], and the members along with its rows,
+    /// such that the solution is permuted the same way.
+    pub fn permute_symmetric(&mut self, permutation: &[usize]) {
+        self.matrix.permute_symmetric(permutation);
+        if let Some(rhs) = &mut self.rhs {
+            rhs.permute(permutation);
+        }
+        if let Some(coords) = &mut self.coords {
+            coords.permute(permutation);
+        }
+    }
+
+    /// Scales the rows of the matrix, see [`Matrix::scale_rows`], and the right-hand side by the same factors,
+    /// such that the solution stays the same.
+    pub fn scale_rows(&mut self, factors: &[f64]) {
+        self.matrix.scale_rows(factors);
+        if let Some(rhs) = &mut self.rhs {
+            rhs.scale_entries(factors);
+        }
+    }
+
+    /// Equilibrates the matrix, see [`Matrix::equilibrate`], and scales the right-hand side by the row factors.
+    /// The solution of the result is that of the original divided by the column factors.
+    pub fn equilibrate(&mut self) -> (Vec<f64>, Vec<f64>) {
+        let (row_factors, col_factors) = self.matrix.equilibrate();
+        if let Some(rhs) = &mut self.rhs {
+            rhs.scale_entries(&row_factors);
+        }
+        (row_factors, col_factors)
+    }
+}
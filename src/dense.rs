@@ -0,0 +1,45 @@
+//! Dense copies of small matrices, such as to compare them with reference results.
+
+use std::io::Write;
+
+use crate::{Error, Matrix, MatrixData, Symmetry};
+
+impl Matrix {
+    /// Copies the matrix into a dense row-major vector of `nrows * ncols` values, where duplicate entries are summed,
+    /// compact symmetric forms have their mirrored entries included, and pattern entries are one.
+    /// Fails for complex matrices, and for matrices with more than `max_elements` elements.
+    pub fn to_dense_vec(&self, max_elements: usize) -> Result<Vec<f64>, Error> {
+        let mut dense = vec![0.0; self.dense_len(max_elements)?];
+        for i in 0..self.nvals {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            let (x, _) = self.value_at(i);
+            dense[row * self.ncols + col] += x;
+            if self.symmetry != Symmetry::General && row != col {
+                dense[col * self.ncols + row] += if self.symmetry == Symmetry::SkewSymmetric { -x } else { x };
+            }
+        }
+        Ok(dense)
+    }
+
+    /// Number of elements of a dense copy of the matrix, which fails as [`Matrix::to_dense_vec`] does,
+    /// such as to check the limit before creating an output file.
+    pub fn dense_len(&self, max_elements: usize) -> Result<usize, Error> {
+        if let MatrixData::Complex(..) = self.vals {
+            return Err(Error::TypeMismatch { expected: "real", found: self.vals.field() });
+        }
+        self.nrows.checked_mul(self.ncols)
+            .filter(|&len| len <= max_elements)
+            .ok_or(Error::DenseLimit { nrows: self.nrows, ncols: self.ncols, max_elements })
+    }
+
+    /// Writes the matrix as dense text, with a line of space-separated values per row, see [`Matrix::to_dense_vec`].
+    pub fn write_dense<W: Write>(&self, mut wtr: W, max_elements: usize) -> Result<(), Error> {
+        let dense = self.to_dense_vec(max_elements)?;
+        for row in dense.chunks(self.ncols.max(1)) {
+            let line: Vec<_> = row.iter().map(f64::to_string).collect();
+            writeln!(wtr, "{}", line.join(" "))?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{Error, Int, Matrix, MatrixData, Real, Symmetry};
+
+impl Matrix {
+    /// (1-based) rows up to the smaller dimension that have no entry on the main diagonal.
+    pub fn missing_diagonal(&self) -> Vec<usize> {
+        let present = self.diagonal_present();
+        (1..=present.len()).filter(|&i| !present[i - 1]).collect()
+    }
+
+    /// (1-based) rows that have entries on the main diagonal, which sum to zero.
+    pub fn zero_diagonal(&self) -> Vec<usize> {
+        let n = self.nrows.min(self.ncols);
+        let mut sums = vec![None; n];
+        for i in 0..self.nvals {
+            if self.rows[i] == self.cols[i] {
+                let (x, y) = self.value_at(i);
+                let (re, im) = sums[self.rows[i] - 1].unwrap_or((0.0, 0.0));
+                sums[self.rows[i] - 1] = Some((re + x, im + y));
+            }
+        }
+
+        (1..=n).filter(|&i| sums[i - 1] == Some((0.0, 0.0))).collect()
+    }
+
+    /// Appends an entry with the given value on the main diagonal of every row that lacks one,
+    /// up to the smaller dimension, and returns the number of inserted entries.
+    /// Integer matrices get the value rounded towards zero, and complex matrices get a zero imaginary part.
+    ///
+    /// Fails if a skew-symmetric matrix would get a nonzero value, as its diagonal must be zero.
+    pub fn ensure_diagonal(&mut self, value: f64) -> Result<usize, Error> {
+        let missing = self.missing_diagonal();
+        let n = missing.len();
+        if self.symmetry == Symmetry::SkewSymmetric && value != 0.0 && let Some(&i) = missing.first() {
+            return Err(Error::SymmetryViolation { row: i, col: i, symmetry: Symmetry::SkewSymmetric });
+        }
+        let vals = match &self.vals {
+            MatrixData::Real(_) => MatrixData::Real(vec![value as Real; n]),
+            MatrixData::Complex(..) => MatrixData::Complex(vec![value as Real; n], vec![0.0; n]),
+            MatrixData::Integer(_) => MatrixData::Integer(vec![value as Int; n]),
+            MatrixData::Bool() => MatrixData::Bool(),
+        };
+
+        self.append(&Matrix::new(self.nrows, self.ncols, missing.clone(), missing, vals));
+        Ok(n)
+    }
+
+    /// Whether each row up to the smaller dimension has an entry on the main diagonal.
+    fn diagonal_present(&self) -> Vec<bool> {
+        let mut present = vec![false; self.nrows.min(self.ncols)];
+        for i in 0..self.nvals {
+            if self.rows[i] == self.cols[i] {
+                present[self.rows[i] - 1] = true;
+            }
+        }
+        present
+    }
+}
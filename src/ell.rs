@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+
+use crate::{Error, Matrix, MatrixData};
+
+/// ELLPACK matrix, with every row padded to the length of the longest row, see [`Matrix::to_ell`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ell {
+    pub nrows: usize,
+    pub ncols: usize,
+    /// Number of entries in the longest row.
+    pub width: usize,
+    /// (0-based) column of entry `k` of row `i` at `k * nrows + i`, such that consecutive rows are adjacent.
+    pub cols: Vec<usize>,
+    /// Value of entry `k` of row `i` at `k * nrows + i`.
+    pub values: Vec<f64>,
+}
+
+/// SELL-C-σ matrix, see [`Matrix::to_sell`]: rows are sorted by length within windows of `sigma` rows,
+/// and every chunk of `chunk_size` consecutive rows is stored as a small ELLPACK matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sell {
+    pub nrows: usize,
+    pub ncols: usize,
+    pub chunk_size: usize,
+    pub sigma: usize,
+    /// Entry `k` is the (0-based) row stored at position `k`.
+    pub permutation: Vec<usize>,
+    /// The entries of chunk `c` are found in the range `chunk_offsets[c]..chunk_offsets[c + 1]`,
+    /// with entry `k` of the row at position `r` of the chunk at `chunk_offsets[c] + k * chunk_size + r`.
+    pub chunk_offsets: Vec<usize>,
+    pub cols: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+impl Matrix {
+    /// Converts a general real, integer, or pattern matrix into the ELLPACK format.
+    /// Entries keep their order within rows, and padding has column zero and value zero,
+    /// such that kernels need not skip it.
+    pub fn to_ell(&self) -> Result<Ell, Error> {
+        let rows = self.entries_per_row()?;
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut cols = vec![0; self.nrows * width];
+        let mut values = vec![0.0; self.nrows * width];
+        for (i, entries) in rows.iter().enumerate() {
+            for (k, &e) in entries.iter().enumerate() {
+                cols[k * self.nrows + i] = self.cols[e] - 1;
+                values[k * self.nrows + i] = self.value_at(e).0;
+            }
+        }
+
+        Ok(Ell { nrows: self.nrows, ncols: self.ncols, width, cols, values })
+    }
+
+    /// Converts a general real, integer, or pattern matrix into the SELL-C-σ format, with `C = chunk_size`.
+    /// The number of rows is padded to a multiple of the chunk size with empty rows,
+    /// which are not part of the permutation. A `sigma` of one keeps the rows in their order.
+    ///
+    /// Panics if the chunk size or `sigma` is zero.
+    pub fn to_sell(&self, chunk_size: usize, sigma: usize) -> Result<Sell, Error> {
+        assert!(chunk_size > 0 && sigma > 0, "chunk size and sigma must be positive");
+        let rows = self.entries_per_row()?;
+
+        let mut permutation: Vec<_> = (0..self.nrows).collect();
+        for window in permutation.chunks_mut(sigma) {
+            window.sort_by_key(|&i| std::cmp::Reverse(rows[i].len()));
+        }
+
+        let mut chunk_offsets = vec![0];
+        let mut cols = Vec::new();
+        let mut values = Vec::new();
+        for chunk in permutation.chunks(chunk_size) {
+            let width = chunk.iter().map(|&i| rows[i].len()).max().unwrap_or(0);
+            let start = cols.len();
+            cols.resize(start + width * chunk_size, 0);
+            values.resize(start + width * chunk_size, 0.0);
+
+            for (r, &i) in chunk.iter().enumerate() {
+                for (k, &e) in rows[i].iter().enumerate() {
+                    cols[start + k * chunk_size + r] = self.cols[e] - 1;
+                    values[start + k * chunk_size + r] = self.value_at(e).0;
+                }
+            }
+            chunk_offsets.push(cols.len());
+        }
+
+        Ok(Sell { nrows: self.nrows, ncols: self.ncols, chunk_size, sigma, permutation, chunk_offsets, cols, values })
+    }
+
+    /// Indices of the entries of every (0-based) row, in column order.
+    fn entries_per_row(&self) -> Result<Vec<Vec<usize>>, Error> {
+        self.check_general()?;
+        if let MatrixData::Complex(..) = self.vals {
+            return Err(Error::TypeMismatch { expected: "real", found: self.vals.field() });
+        }
+
+        let mut rows = vec![Vec::new(); self.nrows];
+        for i in self.row_major_permutation() {
+            rows[self.rows[i] - 1].push(i);
+        }
+        Ok(rows)
+    }
+}
+
+impl Ell {
+    /// Writes the matrix in a simple binary format, with every number in little-endian byte order:
+    ///
+    /// - the magic bytes `ELL1`,
+    /// - `nrows`, `ncols`, and `width`, as `u64`,
+    /// - the columns, as `u64`,
+    /// - the values, as `f64`.
+    pub fn write<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        wtr.write_all(b"ELL1")?;
+        write_u64s(&mut wtr, &[self.nrows, self.ncols, self.width])?;
+        write_u64s(&mut wtr, &self.cols)?;
+        write_f64s(&mut wtr, &self.values)?;
+        wtr.flush()
+    }
+}
+
+impl Sell {
+    /// Number of chunks, including a partial last chunk.
+    pub fn nchunks(&self) -> usize {
+        self.chunk_offsets.len() - 1
+    }
+
+    /// Writes the matrix in a simple binary format, with every number in little-endian byte order:
+    ///
+    /// - the magic bytes `SELL`,
+    /// - `nrows`, `ncols`, `chunk_size`, `sigma`, the number of chunks, and the number of stored entries, as `u64`,
+    /// - the permutation, as `u64`,
+    /// - the chunk offsets, as `u64`,
+    /// - the columns, as `u64`,
+    /// - the values, as `f64`.
+    pub fn write<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        wtr.write_all(b"SELL")?;
+        write_u64s(&mut wtr, &[self.nrows, self.ncols, self.chunk_size, self.sigma, self.nchunks(), self.cols.len()])?;
+        write_u64s(&mut wtr, &self.permutation)?;
+        write_u64s(&mut wtr, &self.chunk_offsets)?;
+        write_u64s(&mut wtr, &self.cols)?;
+        write_f64s(&mut wtr, &self.values)?;
+        wtr.flush()
+    }
+}
+
+fn write_u64s<W: Write>(wtr: &mut W, xs: &[usize]) -> io::Result<()> {
+    xs.iter().try_for_each(|&x| wtr.write_all(&(x as u64).to_le_bytes()))
+}
+
+fn write_f64s<W: Write>(wtr: &mut W, xs: &[f64]) -> io::Result<()> {
+    xs.iter().try_for_each(|&x| wtr.write_all(&x.to_le_bytes()))
+}
@@ -0,0 +1,175 @@
+use std::{fmt, io};
+
+use crate::{DataType, Int, Symmetry};
+
+/// Errors that can occur while reading or transforming a matrix.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The dimensions of two matrices that are combined do not match.
+    DimensionMismatch { expected: (usize, usize), found: (usize, usize) },
+    /// The data types of two matrices that are combined do not match.
+    TypeMismatch { expected: &'static str, found: &'static str },
+    /// The symmetry qualifiers of two matrices that are combined do not match.
+    SymmetryMismatch { expected: Symmetry, found: Symmetry },
+    /// An entry of a matrix that only stores its lower triangle lies above the diagonal.
+    UpperEntry { row: usize, col: usize, symmetry: Symmetry },
+    /// An entry does not have the structure that the symmetry requires, such as a diagonal entry of a hermitian matrix
+    /// with an imaginary part.
+    SymmetryViolation { row: usize, col: usize, symmetry: Symmetry },
+    /// An entry occurs more than once, which the chosen policy does not allow.
+    DuplicateEntry { row: usize, col: usize },
+    /// The number of entries differs from the size line.
+    EntryCount { expected: usize, found: usize },
+    /// A value parsed to NaN or an infinity, which the chosen policy does not allow.
+    NonFinite { line: usize },
+    /// The size line does not hold the number of rows, columns, and entries.
+    SizeLine { line: usize },
+    /// An integer value does not fit in the integers of the given number of bits.
+    IntegerOverflow { line: usize, bits: u32 },
+    /// The sum of the integer values of duplicate entries does not fit in [`Int`].
+    SumOverflow { row: usize, col: usize },
+    /// The product of the integer values of two entries does not fit in [`Int`].
+    ProductOverflow { row: usize, col: usize },
+    /// A matrix of this size does not fit in memory, or its indices do not fit in `usize`.
+    TooLarge { nrows: u64, ncols: u64, nvals: u64 },
+    /// A dense copy of the matrix would have more elements than allowed.
+    DenseLimit { nrows: usize, ncols: usize, max_elements: usize },
+    /// A field of an entry is not a valid index or value.
+    InvalidField { line: usize, field: String },
+    /// An entry lies outside the dimensions of the matrix, such as at index zero.
+    OutOfBounds { line: usize, row: usize, col: usize, nrows: usize, ncols: usize },
+    /// An entry has fewer fields than the data type needs, such as a pattern matrix read as real.
+    FieldCount { line: usize, expected: usize, found: usize, data_type: DataType },
+    /// Two matrices that should be equal differ in an entry, such as a matrix and the file it was written to.
+    ValueMismatch { row: usize, col: usize },
+    /// An index of a permutation is out of range or occurs more than once.
+    InvalidPermutation { index: usize, len: usize },
+    /// An index of an index map is zero, or occurs more than once among the old or the new indices.
+    InvalidIndexMap { index: usize },
+    /// An entry of a tensor has a different number of fields than the first entry.
+    RaggedEntry { line: usize, expected: usize, found: usize },
+    /// The modes to sort a tensor by are not a permutation of its modes.
+    InvalidModes { modes: Vec<usize>, ndims: usize },
+    /// A plan is not valid YAML, or has an unknown key, step, or value.
+    InvalidPlan { message: String },
+    /// A thread pool with the requested number of threads could not be created.
+    ThreadPool(rayon::ThreadPoolBuildError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Error::*;
+        match self {
+            Io(e) => write!(f, "{e}"),
+            DimensionMismatch { expected, found } => {
+                write!(f, "expected a {}x{} matrix, found {}x{}", expected.0, expected.1, found.0, found.1)
+            },
+            TypeMismatch { expected, found } => {
+                write!(f, "expected a {expected} matrix, found {found}")
+            },
+            SymmetryMismatch { expected, found } => {
+                write!(f, "expected a {expected} matrix, found {found}")
+            },
+            UpperEntry { row, col, symmetry } => {
+                write!(f, "entry at ({row}, {col}) lies above the diagonal of a {symmetry} matrix, which only stores its lower triangle")
+            },
+            SymmetryViolation { row, col, symmetry } => {
+                write!(f, "entry at ({row}, {col}) violates the structure of a {symmetry} matrix")
+            },
+            DuplicateEntry { row, col } => {
+                write!(f, "duplicate entry at ({row}, {col})")
+            },
+            EntryCount { expected, found } => {
+                write!(f, "size line declares {expected} entries, found {found}")
+            },
+            NonFinite { line } => {
+                write!(f, "non-finite value on line {line}")
+            },
+            SizeLine { line } => {
+                write!(f, "expected the number of rows, columns, and entries on the size line, line {line}")
+            },
+            IntegerOverflow { line, bits } => {
+                write!(f, "integer value on line {line} does not fit in {bits} bits")
+            },
+            SumOverflow { row, col } => {
+                write!(f, "sum of the duplicate entries at ({row}, {col}) does not fit in {} bits", Int::BITS)
+            },
+            ProductOverflow { row, col } => {
+                write!(f, "product of the entries at ({row}, {col}) does not fit in {} bits", Int::BITS)
+            },
+            TooLarge { nrows, ncols, nvals } => {
+                write!(f, "a {nrows}x{ncols} matrix with {nvals} entries does not fit in memory")
+            },
+            DenseLimit { nrows, ncols, max_elements } => {
+                write!(f, "a dense {nrows}x{ncols} matrix has more than {max_elements} elements")
+            },
+            InvalidField { line, field } => {
+                write!(f, "invalid field `{field}` on line {line}")
+            },
+            OutOfBounds { line, row, col, nrows, ncols } => {
+                write!(f, "entry at ({row}, {col}) on line {line} lies outside the {nrows}x{ncols} matrix")
+            },
+            FieldCount { line, expected, found, data_type } => {
+                write!(f, "expected {expected} fields for {data_type}, found {found} on line {line}")?;
+                match self.suggested_type() {
+                    Some(DataType::Bool) => write!(f, "; is this a pattern matrix?"),
+                    Some(suggested) => write!(f, "; is this a {suggested} matrix?"),
+                    None => Ok(()),
+                }
+            },
+            ValueMismatch { row, col } => {
+                write!(f, "entries at ({row}, {col}) differ")
+            },
+            InvalidPermutation { index, len } => {
+                write!(f, "index {index} is out of range or repeated in a permutation of length {len}")
+            },
+            InvalidIndexMap { index } => {
+                write!(f, "index {index} is zero or repeated in an index map")
+            },
+            RaggedEntry { line, expected, found } => {
+                write!(f, "expected {expected} fields like the first entry, found {found} on line {line}")
+            },
+            InvalidModes { modes, ndims } => {
+                write!(f, "modes {modes:?} are not a permutation of the {ndims} modes of the tensor")
+            },
+            InvalidPlan { message } => {
+                write!(f, "invalid plan: {message}")
+            },
+            ThreadPool(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error {
+    /// Data type to read the file as instead, if the entries have the fields of another data type.
+    pub fn suggested_type(&self) -> Option<DataType> {
+        match self {
+            Error::FieldCount { found: 2, .. } => Some(DataType::Bool),
+            Error::FieldCount { found: 3, expected: 4, .. } => Some(DataType::Real),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::ThreadPool(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<rayon::ThreadPoolBuildError> for Error {
+    fn from(e: rayon::ThreadPoolBuildError) -> Self {
+        Error::ThreadPool(e)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{Error, Matrix};
+
+impl Matrix {
+    /// Parent of every (0-based) column in the elimination tree of the structure of the matrix plus its transpose,
+    /// or `None` for the roots, after Liu. The parent of column `j` is the row of the first entry below the diagonal
+    /// in column `j` of the Cholesky factor, for the current ordering.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn elimination_tree(&self) -> Result<Vec<Option<usize>>, Error> {
+        self.check_square()?;
+        Ok(elimination_tree(&self.adjacency()))
+    }
+
+    /// Number of entries in every column of the Cholesky factor of the structure of the matrix plus its transpose,
+    /// including the diagonal, for the current ordering and assuming that no values cancel.
+    /// Their sum predicts the size of the factor, and comparing it between orderings predicts which one fills in least.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn cholesky_counts(&self) -> Result<Vec<usize>, Error> {
+        self.check_square()?;
+        let adjacency = self.adjacency();
+        let parent = elimination_tree(&adjacency);
+
+        // Row `i` of the factor has entries in the columns of the subtree from the entries of row `i` up to `i`
+        let mut counts = vec![1; adjacency.len()];
+        let mut mark = vec![usize::MAX; adjacency.len()];
+        for (i, neighbours) in adjacency.iter().enumerate() {
+            mark[i] = i;
+            for &k in neighbours.iter().take_while(|&&k| k < i) {
+                let mut j = k;
+                while mark[j] != i {
+                    counts[j] += 1;
+                    mark[j] = i;
+                    j = parent[j].expect("row is an ancestor of the columns of its entries");
+                }
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/// Elimination tree of a symmetric structure given by its sorted adjacency lists,
+/// with path compression through the ancestors of the columns.
+fn elimination_tree(adjacency: &[Vec<usize>]) -> Vec<Option<usize>> {
+    let mut parent = vec![None; adjacency.len()];
+    let mut ancestor: Vec<Option<usize>> = vec![None; adjacency.len()];
+    for (k, neighbours) in adjacency.iter().enumerate() {
+        for &i in neighbours.iter().take_while(|&&i| i < k) {
+            // Follow the path from `i` to the root of its subtree, which becomes a child of `k`
+            let mut next = Some(i);
+            while let Some(j) = next && j != k {
+                next = ancestor[j].replace(k);
+                if next.is_none() {
+                    parent[j] = Some(k);
+                }
+            }
+        }
+    }
+    parent
+}
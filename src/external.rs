@@ -0,0 +1,323 @@
+//! External sorting: chunks of entries are sorted and spilled to temporary files,
+//! after which the entries are freed and merged back from the files.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::{debug, warn};
+use rayon::prelude::*;
+
+use crate::{Int, Matrix, MatrixData, Real, SortOptions, SortOrder, TieBreak};
+
+/// Default number of entries per chunk.
+pub(crate) const CHUNK_SIZE: usize = 1 << 22;
+
+/// Number of external sorts so far, to give concurrent sorts distinct directories.
+static SORTS: AtomicUsize = AtomicUsize::new(0);
+
+impl Matrix {
+    /// Writes the entries, sorted into row- or column-major order, to temporary runs of `chunk_size` entries each,
+    /// which [`SpilledRuns::merge_into`] merges back. The matrix is unchanged, whether this fails or not.
+    ///
+    /// Panics if the chunk size is zero.
+    pub(crate) fn spill_runs(&self, col_major: bool, chunk_size: usize) -> io::Result<SpilledRuns> {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        // There are neither temporary directories nor process identifiers in the browser
+        if cfg!(target_family = "wasm") {
+            return Err(io::ErrorKind::Unsupported.into());
+        }
+
+        let dir = PrivateDir::new()?;
+        let mut runs = Vec::new();
+        for (k, start) in (0..self.nvals).step_by(chunk_size).enumerate() {
+            let path = dir.0.join(format!("{k}.run"));
+            let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+            runs.push(path);
+            self.write_run(file, start..(start + chunk_size).min(self.nvals), col_major)?;
+        }
+        Ok(SpilledRuns { _dir: dir, runs, col_major })
+    }
+
+    /// Sorts the entries into row- or column-major order externally, like [`SortStrategy::External`](crate::SortStrategy::External),
+    /// but keeps the runs in `dir` with a checkpoint of the completed ones, such that a sort of the same entries
+    /// that is interrupted, such as by killing the process, can continue from there.
+    /// With `resume`, runs of a previous sort in `dir` are reused if they match their chunk of the entries,
+    /// and otherwise they are written again. The runs and the checkpoint are removed once the sort succeeds.
+    ///
+    /// The merge of the runs is repeated when resuming, as it builds the entries in memory.
+    /// Other orders, and tie-breaking by value, sort in memory as with [`Matrix::sort_with`].
+    ///
+    /// Panics if the chunk size is zero.
+    pub fn sort_checkpointed(&mut self, order: SortOrder, options: &SortOptions, dir: &Path, resume: bool) -> io::Result<()> {
+        let col_major = match order {
+            SortOrder::RowMajor | SortOrder::ColMajor if options.tie_break == TieBreak::None && !self.is_sorted(order) => {
+                order == SortOrder::ColMajor
+            },
+            _ => {
+                return self.sort_with(order, options);
+            },
+        };
+        let chunk_size = options.chunk_size.unwrap_or(CHUNK_SIZE);
+        assert!(chunk_size > 0, "chunk size must be positive");
+
+        fs::create_dir_all(dir)?;
+        let checkpoint = dir.join("checkpoint");
+        let header = format!("%%mmt-checkpoint {} {} {} {} {order} {chunk_size}", self.nrows, self.ncols, self.nvals, self.data_type());
+        let size = record_size(&self.vals);
+
+        // Runs that the previous sort completed, with the checksums of their chunks
+        let mut done = HashMap::new();
+        if resume && let Ok(contents) = fs::read_to_string(&checkpoint) {
+            let mut lines = contents.lines();
+            if lines.next() == Some(header.as_str()) {
+                for line in lines {
+                    if let ["run", k, checksum] = line.split_whitespace().collect::<Vec<_>>()[..]
+                        && let (Ok(k), Ok(checksum)) = (k.parse::<usize>(), u64::from_str_radix(checksum, 16)) {
+                        done.insert(k, checksum);
+                    }
+                }
+            } else {
+                warn!("the checkpoint in {} is of other entries, starting over", dir.display());
+            }
+        }
+
+        let runs: Vec<_> = (0..self.nvals).step_by(chunk_size)
+            .map(|start| start..(start + chunk_size).min(self.nvals))
+            .enumerate()
+            .map(|(k, range)| (dir.join(format!("{k}.run")), self.checksum(range.clone()), range))
+            .collect();
+
+        // The completed runs are recorded again first, such that they survive another interruption
+        let mut log = File::create(&checkpoint)?;
+        writeln!(log, "{header}")?;
+        let mut missing = Vec::new();
+        for (k, (path, checksum, range)) in runs.iter().enumerate() {
+            let complete = done.get(&k) == Some(checksum)
+                && fs::metadata(path).is_ok_and(|meta| meta.len() == (range.len() * size) as u64);
+            if complete {
+                writeln!(log, "run {k} {checksum:016x}")?;
+            } else {
+                missing.push(k);
+            }
+        }
+        log.sync_data()?;
+        debug!("reusing {} of {} runs", runs.len() - missing.len(), runs.len());
+
+        for k in missing {
+            let (path, checksum, range) = &runs[k];
+            // A run only gets its name once it is complete
+            let partial = path.with_extension("partial");
+            self.write_run(File::create(&partial)?, range.clone(), col_major)?;
+            File::open(&partial)?.sync_all()?;
+            fs::rename(&partial, path)?;
+            writeln!(log, "run {k} {checksum:016x}")?;
+            log.sync_data()?;
+        }
+
+        let runs: Vec<_> = runs.into_iter().map(|(path, ..)| path).collect();
+        self.merge_runs(&runs, col_major)?;
+        self.sortedness = Some(order);
+        drop(Runs(runs));
+        fs::remove_file(&checkpoint)
+    }
+
+    /// Writes the entries in `range`, sorted by their coordinates, as a run to a new file.
+    fn write_run(&self, file: File, range: Range<usize>, col_major: bool) -> io::Result<()> {
+        let key = |i: usize| if col_major { (self.cols[i], self.rows[i]) } else { (self.rows[i], self.cols[i]) };
+        let mut wtr = BufWriter::new(file);
+        let mut chunk: Vec<_> = range.collect();
+        chunk.par_sort_by_key(|&i| key(i));
+        let mut record = Vec::new();
+        for i in chunk {
+            record.clear();
+            self.encode(i, &mut record);
+            wtr.write_all(&record)?;
+        }
+        wtr.flush()
+    }
+
+    /// Merges the runs into the entries, which are freed first.
+    /// The entries are left empty if reading a run fails.
+    fn merge_runs(&mut self, runs: &[PathBuf], col_major: bool) -> io::Result<()> {
+        let mut readers = runs.iter()
+            .map(|path| Ok(BufReader::new(File::open(path)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let data_type = self.vals.data_type();
+        let size = record_size(&self.vals);
+        self.rows = Vec::new();
+        self.cols = Vec::new();
+        self.vals = MatrixData::new(data_type);
+
+        let mut rows = Vec::with_capacity(self.nvals);
+        let mut cols = Vec::with_capacity(self.nvals);
+        let mut vals = MatrixData::with_capacity(data_type, self.nvals);
+
+        // Ties are broken by the index of the run, which keeps the merge stable
+        let mut heads = Vec::with_capacity(readers.len());
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+        for (k, rdr) in readers.iter_mut().enumerate() {
+            let record = next_record(rdr, size)?.expect("runs are never empty");
+            heap.push(Reverse((record_key(&record, col_major), k)));
+            heads.push(record);
+        }
+
+        while let Some(Reverse((_, k))) = heap.pop() {
+            decode(&heads[k], &mut rows, &mut cols, &mut vals);
+            if let Some(record) = next_record(&mut readers[k], size)? {
+                heap.push(Reverse((record_key(&record, col_major), k)));
+                heads[k] = record;
+            }
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+        self.vals = vals;
+        Ok(())
+    }
+
+    /// Checksum of the entries in `range` and their positions, to recognize the chunk of a run.
+    fn checksum(&self, range: Range<usize>) -> u64 {
+        range.into_par_iter()
+            .map(|i| {
+                let mut record = (i as u64).to_le_bytes().to_vec();
+                self.encode(i, &mut record);
+                // FNV-1a
+                record.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+            })
+            .reduce(|| 0, u64::wrapping_add)
+    }
+
+    /// Appends entry `i` as a record of its row, column, and value in little-endian bytes.
+    fn encode(&self, i: usize, record: &mut Vec<u8>) {
+        record.extend_from_slice(&(self.rows[i] as u64).to_le_bytes());
+        record.extend_from_slice(&(self.cols[i] as u64).to_le_bytes());
+        match &self.vals {
+            MatrixData::Real(xs) => {
+                record.extend_from_slice(&xs[i].to_le_bytes());
+            },
+            MatrixData::Complex(xs, ys) => {
+                record.extend_from_slice(&xs[i].to_le_bytes());
+                record.extend_from_slice(&ys[i].to_le_bytes());
+            },
+            MatrixData::Integer(xs) => {
+                record.extend_from_slice(&xs[i].to_le_bytes());
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+    }
+}
+
+/// Runs of an external sort in their private directory, see [`Matrix::spill_runs`].
+pub(crate) struct SpilledRuns {
+    _dir: PrivateDir,
+    runs: Vec<PathBuf>,
+    col_major: bool,
+}
+
+impl SpilledRuns {
+    /// Replaces the entries of the matrix that the runs were written from by the merged runs,
+    /// keeping entries with the same coordinates in their original order.
+    /// The entries are freed first, and are left empty if reading a run fails.
+    pub(crate) fn merge_into(self, m: &mut Matrix) -> io::Result<()> {
+        m.merge_runs(&self.runs, self.col_major)
+    }
+}
+
+/// Files of the runs of a checkpointed sort, removed when dropped.
+struct Runs(Vec<PathBuf>);
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Directory for the runs of an external sort that only the current user can access, removed with its runs when dropped.
+/// It is created anew in the temporary directory, such that no one else can have placed files or links there.
+struct PrivateDir(PathBuf);
+
+impl PrivateDir {
+    fn new() -> io::Result<Self> {
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        loop {
+            let sort = SORTS.fetch_add(1, Ordering::Relaxed);
+            let path = std::env::temp_dir().join(format!("mmt-{}-{sort}", process::id()));
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self(path)),
+                // Someone else took the name, so we try the next one
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for PrivateDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Number of bytes of a record with values of the given kind.
+fn record_size(vals: &MatrixData) -> usize {
+    2 * size_of::<u64>() + match vals {
+        MatrixData::Real(_) => size_of::<Real>(),
+        MatrixData::Complex(..) => 2 * size_of::<Real>(),
+        MatrixData::Integer(_) => size_of::<Int>(),
+        MatrixData::Bool() => 0,
+    }
+}
+
+/// Reads the next record of a run, or `None` at the end of the run.
+fn next_record(rdr: &mut impl Read, size: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut record = vec![0; size];
+    match rdr.read_exact(&mut record) {
+        Ok(()) => Ok(Some(record)),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The (major, minor) key of a record.
+fn record_key(record: &[u8], col_major: bool) -> (u64, u64) {
+    let row = u64::from_le_bytes(record[0..8].try_into().unwrap());
+    let col = u64::from_le_bytes(record[8..16].try_into().unwrap());
+    if col_major { (col, row) } else { (row, col) }
+}
+
+/// Pushes the entry of a record.
+fn decode(record: &[u8], rows: &mut Vec<usize>, cols: &mut Vec<usize>, vals: &mut MatrixData) {
+    rows.push(u64::from_le_bytes(record[0..8].try_into().unwrap()) as usize);
+    cols.push(u64::from_le_bytes(record[8..16].try_into().unwrap()) as usize);
+    let value = &record[16..];
+    match vals {
+        MatrixData::Real(xs) => {
+            xs.push(Real::from_le_bytes(value.try_into().unwrap()));
+        },
+        MatrixData::Complex(xs, ys) => {
+            let (x, y) = value.split_at(size_of::<Real>());
+            xs.push(Real::from_le_bytes(x.try_into().unwrap()));
+            ys.push(Real::from_le_bytes(y.try_into().unwrap()));
+        },
+        MatrixData::Integer(xs) => {
+            xs.push(Int::from_le_bytes(value.try_into().unwrap()));
+        },
+        MatrixData::Bool() => {
+            /* nothing to do */
+        },
+    }
+}
@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+
+use rayon::prelude::*;
+
+use crate::{Error, Matrix, MatrixData, Symmetry, Value};
+
+impl Matrix {
+    /// Removes the rows and columns without entries, renumbering the remaining ones in their original order,
+    /// and returns the old (1-based) index of every new row and of every new column.
+    ///
+    /// Compact symmetric forms keep a row and column with the same index if either has entries,
+    /// such that they stay square and share one map.
+    pub fn compress_empty(&mut self) -> (Vec<usize>, Vec<usize>) {
+        let mut used_rows = vec![false; self.nrows];
+        let mut used_cols = vec![false; self.ncols];
+        for i in 0..self.nvals {
+            used_rows[self.rows[i] - 1] = true;
+            used_cols[self.cols[i] - 1] = true;
+        }
+        if self.symmetry != Symmetry::General {
+            used_rows.iter_mut().zip(&mut used_cols).for_each(|(r, c)| {
+                *r |= *c;
+                *c = *r;
+            });
+        }
+
+        let (row_map, new_rows) = renumbering(&used_rows);
+        let (col_map, new_cols) = renumbering(&used_cols);
+        // Renumbering keeps the order of the indices, and thereby the sortedness of the entries
+        self.rows.par_iter_mut().for_each(|row| *row = new_rows[*row - 1]);
+        self.cols.par_iter_mut().for_each(|col| *col = new_cols[*col - 1]);
+        self.nrows = row_map.len();
+        self.ncols = col_map.len();
+        (row_map, col_map)
+    }
+
+    /// Keeps only the entries for which `keep` returns true given their (1-based) row, column, and value,
+    /// in their original order. Compact symmetric forms are expanded first, such that `keep` sees the implied entries
+    /// as well, and the result is general.
+    ///
+    /// Fails if the data type does not match `N`, where pattern matrices match any value type,
+    /// with every entry being one, as for [`Matrix::values`].
+    pub fn retain<N, F>(&mut self, keep: F) -> Result<(), Error>
+    where
+        N: Value,
+        F: Fn(usize, usize, N) -> bool + Sync,
+    {
+        if !matches!(self.vals, MatrixData::Bool()) && self.vals.field() != N::FIELD {
+            return Err(Error::TypeMismatch { expected: N::FIELD, found: self.vals.field() });
+        }
+        self.expand_symmetric();
+
+        let indices: Vec<_> = (0..self.nvals).into_par_iter()
+            .filter(|&i| keep(self.rows[i], self.cols[i], N::value_at(self, i).unwrap()))
+            .collect();
+        if indices.len() < self.nvals {
+            let sortedness = self.sortedness;
+            *self = self.gather(&indices);
+            self.sortedness = sortedness;
+        }
+        Ok(())
+    }
+
+    /// Returns a new matrix containing only the entries on the main diagonal.
+    pub fn diagonal(&self) -> Self {
+        self.select(|row, col| row == col)
+    }
+
+    /// Returns a new matrix containing only the entries on or below the main diagonal.
+    /// If `strict` is set, the diagonal itself is excluded as well.
+    pub fn lower_triangle(&self, strict: bool) -> Self {
+        if strict {
+            self.select(|row, col| row > col)
+        } else {
+            self.select(|row, col| row >= col)
+        }
+    }
+
+    /// Returns a new matrix containing only the entries on or above the main diagonal.
+    /// If `strict` is set, the diagonal itself is excluded as well.
+    pub fn upper_triangle(&self, strict: bool) -> Self {
+        if strict {
+            self.select(|row, col| row < col)
+        } else {
+            self.select(|row, col| row <= col)
+        }
+    }
+
+    /// Returns a new matrix of the same dimensions, containing only the entries
+    /// whose coordinates satisfy `keep`, in their original order.
+    /// Compact symmetric forms are expanded first, such that the implied entries are selected as well,
+    /// and the result is general.
+    fn select<F>(&self, keep: F) -> Self
+    where
+        F: Fn(usize, usize) -> bool + Sync,
+    {
+        let m = if self.symmetry == Symmetry::General {
+            Cow::Borrowed(self)
+        } else {
+            let mut expanded = self.clone();
+            expanded.expand_symmetric();
+            Cow::Owned(expanded)
+        };
+
+        let indices: Vec<_> = (0..m.nvals).into_par_iter()
+            .filter(|&i| keep(m.rows[i], m.cols[i]))
+            .collect();
+        let mut selected = m.gather(&indices);
+        selected.sortedness = m.sortedness;
+        selected
+    }
+
+    /// Returns a new matrix of the same dimensions, containing the entries at the given indices.
+    /// It keeps the symmetry, which is only right if the indices keep every entry or if the matrix is general.
+    pub(crate) fn gather(&self, indices: &[usize]) -> Self {
+        Self {
+            rows: indices.par_iter().map(|&i| self.rows[i]).collect(),
+            cols: indices.par_iter().map(|&i| self.cols[i]).collect(),
+            vals: self.vals.gather(indices),
+            nrows: self.nrows,
+            ncols: self.ncols,
+            nvals: indices.len(),
+            symmetry: self.symmetry,
+            sortedness: None,
+        }
+    }
+}
+
+impl MatrixData {
+    pub(crate) fn gather(&self, indices: &[usize]) -> Self {
+        match self {
+            MatrixData::Real(xs) => {
+                MatrixData::Real(indices.par_iter().map(|&i| xs[i]).collect())
+            },
+            MatrixData::Complex(xs, ys) => {
+                MatrixData::Complex(indices.par_iter().map(|&i| xs[i]).collect(),
+                                    indices.par_iter().map(|&i| ys[i]).collect())
+            },
+            MatrixData::Integer(xs) => {
+                MatrixData::Integer(indices.par_iter().map(|&i| xs[i]).collect())
+            },
+            MatrixData::Bool() => MatrixData::Bool(),
+        }
+    }
+}
+
+/// Old (1-based) indices of the used indices, and the new (1-based) index of every old index,
+/// which is meaningless for unused ones.
+fn renumbering(used: &[bool]) -> (Vec<usize>, Vec<usize>) {
+    let mut map = Vec::new();
+    let new = used.iter().enumerate().map(|(i, &used)| {
+        if used {
+            map.push(i + 1);
+        }
+        map.len()
+    }).collect();
+    (map, new)
+}
@@ -0,0 +1,41 @@
+//! Conversions into [`faer`] sparse matrices, which use 0-based indices.
+
+use faer::sparse::{SparseColMat, SparseRowMat, SymbolicSparseColMat, SymbolicSparseRowMat};
+
+use crate::{offsets, Error, Matrix, Value};
+
+impl Matrix {
+    /// Converts a general matrix into a `faer` compressed sparse column matrix.
+    ///
+    /// Matrices that are already sorted in column-major order are converted directly;
+    /// otherwise the entries are gathered through a column-major permutation, leaving this matrix unchanged.
+    /// Duplicate entries are summed.
+    pub fn to_sparse_col_mat<T: Value>(&self) -> Result<SparseColMat<usize, T>, Error> {
+        self.check_general()?;
+
+        let m = self.coalesced(self.is_col_major(), Matrix::col_major_permutation)?;
+        let values = m.values_as::<T>()?;
+        let col_ptr = offsets(&m.cols, m.ncols);
+        let row_idx = m.rows.iter().map(|&row| row - 1).collect();
+
+        let symbolic = SymbolicSparseColMat::new_checked(m.nrows, m.ncols, col_ptr, None, row_idx);
+        Ok(SparseColMat::new(symbolic, values))
+    }
+
+    /// Converts a general matrix into a `faer` compressed sparse row matrix.
+    ///
+    /// Matrices that are already sorted in row-major order are converted directly;
+    /// otherwise the entries are gathered through a row-major permutation, leaving this matrix unchanged.
+    /// Duplicate entries are summed.
+    pub fn to_sparse_row_mat<T: Value>(&self) -> Result<SparseRowMat<usize, T>, Error> {
+        self.check_general()?;
+
+        let m = self.coalesced(self.is_row_major(), Matrix::row_major_permutation)?;
+        let values = m.values_as::<T>()?;
+        let row_ptr = offsets(&m.rows, m.nrows);
+        let col_idx = m.cols.iter().map(|&col| col - 1).collect();
+
+        let symbolic = SymbolicSparseRowMat::new_checked(m.nrows, m.ncols, row_ptr, None, col_idx);
+        Ok(SparseRowMat::new(symbolic, values))
+    }
+}
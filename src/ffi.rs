@@ -0,0 +1,171 @@
+//! C interface, declared in `include/mmt.h`.
+//!
+//! Matrices are passed around as opaque pointers created by [`mmt_read_file`] and released by [`mmt_free`].
+//! Compressed row pointers and column indices handed out by [`mmt_get_csr_pointers`] are 0-based,
+//! and remain valid until the matrix is sorted again or freed.
+
+use std::ffi::{CStr, c_char, c_int, c_void};
+use std::{fs, ptr};
+
+use memmap2::Mmap;
+
+use crate::{DataType, Matrix, MatrixData, ReadOptions, Real, offsets};
+
+/// Value types, as passed to [`mmt_read_file`].
+pub const MMT_REAL: c_int = 0;
+pub const MMT_COMPLEX: c_int = 1;
+pub const MMT_INTEGER: c_int = 2;
+pub const MMT_PATTERN: c_int = 3;
+
+/// Sort orders, as passed to [`mmt_sort`].
+pub const MMT_ROW_MAJOR: c_int = 0;
+pub const MMT_COL_MAJOR: c_int = 1;
+
+/// Opaque matrix handle, holding the compressed row layout once it has been requested.
+pub struct MmtMatrix {
+    matrix: Matrix,
+    csr: Option<Csr>,
+}
+
+struct Csr {
+    row_ptr: Vec<usize>,
+    col_idx: Vec<usize>,
+    /// Complex values interleaved as real and imaginary parts.
+    values: Vec<Real>,
+}
+
+/// Reads a Matrix-Market file, returning null if the arguments are invalid or the file cannot be read or parsed.
+///
+/// # Safety
+///
+/// `path` must be null or point to a nul-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mmt_read_file(path: *const c_char, data_type: c_int) -> *mut MmtMatrix {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+
+    let data_type = match data_type {
+        MMT_REAL => DataType::Real,
+        MMT_COMPLEX => DataType::Complex,
+        MMT_INTEGER => DataType::Integer,
+        MMT_PATTERN => DataType::Bool,
+        _ => return ptr::null_mut(),
+    };
+
+    let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+        return ptr::null_mut();
+    };
+    let Ok(file) = fs::File::open(path) else {
+        return ptr::null_mut();
+    };
+
+    let Ok(mmap) = (unsafe { Mmap::map(&file) }) else {
+        return ptr::null_mut();
+    };
+
+    // Panics cannot unwind into C, and abort release builds, so the file is parsed without panicking
+    match Matrix::try_parse_bytes(&mmap, data_type, &ReadOptions::default()) {
+        Ok(matrix) => Box::into_raw(Box::new(MmtMatrix { matrix, csr: None })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Sorts the entries of a matrix, returning 0 on success and -1 if the arguments are invalid.
+///
+/// # Safety
+///
+/// `m` must be null or a pointer returned by [`mmt_read_file`] that has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mmt_sort(m: *mut MmtMatrix, order: c_int) -> c_int {
+    let Some(m) = (unsafe { m.as_mut() }) else {
+        return -1;
+    };
+
+    match order {
+        MMT_ROW_MAJOR => m.matrix.sort_row_major(),
+        MMT_COL_MAJOR => m.matrix.sort_col_major(),
+        _ => return -1,
+    }
+
+    m.csr = None;
+    0
+}
+
+/// Exposes a matrix in compressed sparse row format, sorting it in row-major order if needed.
+/// Returns 0 on success and -1 if the arguments are invalid.
+///
+/// `row_ptr` receives `nrows + 1` offsets and `col_idx` receives `nvals` column indices.
+/// `values` receives `nvals` values of the matrix type, with complex values interleaved as
+/// real and imaginary parts, or null for pattern matrices.
+/// Symmetric matrices only expose their stored entries.
+///
+/// # Safety
+///
+/// `m` must be null or a pointer returned by [`mmt_read_file`] that has not been freed,
+/// and each output pointer must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mmt_get_csr_pointers(
+    m: *mut MmtMatrix,
+    nrows: *mut usize,
+    ncols: *mut usize,
+    nvals: *mut usize,
+    row_ptr: *mut *const usize,
+    col_idx: *mut *const usize,
+    values: *mut *const c_void,
+) -> c_int {
+    let Some(m) = (unsafe { m.as_mut() }) else {
+        return -1;
+    };
+
+    if !m.matrix.is_row_major() {
+        m.matrix.sort_row_major();
+        m.csr = None;
+    }
+
+    let matrix = &m.matrix;
+    let csr = m.csr.get_or_insert_with(|| Csr {
+        row_ptr: offsets(&matrix.rows, matrix.nrows),
+        col_idx: matrix.cols.iter().map(|&col| col - 1).collect(),
+        values: match &matrix.vals {
+            MatrixData::Complex(xs, ys) => xs.iter().zip(ys).flat_map(|(&x, &y)| [x, y]).collect(),
+            _ => Vec::new(),
+        },
+    });
+
+    let data = match &matrix.vals {
+        MatrixData::Real(xs) => xs.as_ptr() as *const c_void,
+        MatrixData::Complex(_, _) => csr.values.as_ptr() as *const c_void,
+        MatrixData::Integer(xs) => xs.as_ptr() as *const c_void,
+        MatrixData::Bool() => ptr::null(),
+    };
+
+    unsafe {
+        write(nrows, matrix.nrows);
+        write(ncols, matrix.ncols);
+        write(nvals, matrix.nvals);
+        write(row_ptr, csr.row_ptr.as_ptr());
+        write(col_idx, csr.col_idx.as_ptr());
+        write(values, data);
+    }
+    0
+}
+
+/// Frees a matrix returned by [`mmt_read_file`].
+///
+/// # Safety
+///
+/// `m` must be null or a pointer returned by [`mmt_read_file`] that has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mmt_free(m: *mut MmtMatrix) {
+    if !m.is_null() {
+        drop(unsafe { Box::from_raw(m) });
+    }
+}
+
+/// Writes to an optional output pointer.
+unsafe fn write<T>(dst: *mut T, value: T) {
+    if let Some(dst) = unsafe { dst.as_mut() } {
+        *dst = value;
+    }
+}
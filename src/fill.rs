@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use crate::{Error, Matrix};
+
+/// Symbolic fill of a level-based incomplete factorization, see [`Matrix::ilu_fill`] and [`Matrix::ic_fill`].
+///
+/// Entries of the matrix, and the diagonal, have level 0, and eliminating an entry at level `a`
+/// against one of level `b` creates fill at level `a + b + 1`. ILU(k) and IC(k) keep the entries up to level `k`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolicFill {
+    /// Number of entries of the factors at each level, from 0 up to the requested level.
+    pub levels: Vec<usize>,
+}
+
+impl SymbolicFill {
+    /// Number of entries of the factors of ILU(k) or IC(k), up to the requested level.
+    pub fn nnz(&self, k: usize) -> usize {
+        self.levels.iter().take(k + 1).sum()
+    }
+
+    /// Ratio of the entries of the factors at level `k` to those at level 0, which have the structure of the matrix.
+    pub fn growth(&self, k: usize) -> f64 {
+        self.nnz(k) as f64 / self.levels[0].max(1) as f64
+    }
+}
+
+impl Matrix {
+    /// Symbolic fill of ILU(k) for every level up to `max_level`, counting the entries of `L + U`,
+    /// where the unit diagonal of `L` is not stored. ILU(0) has the structure of the matrix,
+    /// with any missing diagonal entries added. Mirrored entries of compact symmetric forms are included.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn ilu_fill(&self, max_level: usize) -> Result<SymbolicFill, Error> {
+        self.check_square()?;
+        Ok(symbolic_fill(self.row_adjacency(), max_level, false))
+    }
+
+    /// Symbolic fill of IC(k) for every level up to `max_level`, counting the entries of the lower triangular factor,
+    /// including its diagonal. The structure of the matrix plus its transpose is factorized,
+    /// which is that of the matrix itself if it is symmetric.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn ic_fill(&self, max_level: usize) -> Result<SymbolicFill, Error> {
+        self.check_square()?;
+        Ok(symbolic_fill(self.adjacency(), max_level, true))
+    }
+}
+
+/// Eliminates the rows in order, keeping the level of every entry of the factors up to `max_level`,
+/// and counts the entries of each level of every row, or of its lower triangle only.
+fn symbolic_fill(adjacency: Vec<Vec<usize>>, max_level: usize, lower: bool) -> SymbolicFill {
+    let mut levels = vec![0; max_level + 1];
+    // Entries of the upper factor in every eliminated row, with their levels
+    let mut upper: Vec<Vec<(usize, usize)>> = Vec::with_capacity(adjacency.len());
+    for (i, cols) in adjacency.into_iter().enumerate() {
+        let mut row: BTreeMap<usize, usize> = cols.into_iter().map(|col| (col, 0)).collect();
+        row.insert(i, 0);
+
+        // Columns of the lower factor are eliminated in increasing order, including the fill they create
+        let mut next = 0;
+        while let Some((&k, &level_ik)) = row.range(next..i).next() {
+            next = k + 1;
+            for &(j, level_kj) in &upper[k] {
+                let level = level_ik + level_kj + 1;
+                if level <= max_level {
+                    row.entry(j).and_modify(|l| *l = (*l).min(level)).or_insert(level);
+                }
+            }
+        }
+
+        let counted = if lower { row.range(..=i) } else { row.range(..) };
+        counted.for_each(|(_, &level)| levels[level] += 1);
+        upper.push(row.range(i + 1..).map(|(&j, &level)| (j, level)).collect());
+    }
+    SymbolicFill { levels }
+}
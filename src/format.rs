@@ -0,0 +1,136 @@
+//! Options for the text of written entries: hexadecimal values, and fixed-width fields that line up in columns,
+//! like many Fortran writers produce.
+//!
+//! Aligned values are written in scientific notation with enough digits to read back as the same value,
+//! such as `-1.50000000e-7` without the `x64` feature, and every entry line has the same length.
+
+use std::fmt::{self, Write};
+
+use rayon::prelude::*;
+
+use crate::hexfloat::format_hex;
+use crate::{Matrix, MatrixData, Real};
+
+/// Number of digits after the decimal point of aligned values, enough for them to read back as the same value.
+const DIGITS: usize = if size_of::<Real>() == 4 { 8 } else { 16 };
+
+/// Width of an aligned value: a sign, the digits, a decimal point, and an exponent such as `e-45` or `e-324`.
+const REAL_WIDTH: usize = 3 + DIGITS + if size_of::<Real>() == 4 { 4 } else { 5 };
+
+/// Width of an aligned hexadecimal value, such as `-0x1.fffffffffffffp+1023`.
+const HEX_WIDTH: usize = 23;
+
+/// How to write the entries of a matrix as text, see [`Matrix::formatted`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextFormat {
+    hex_floats: bool,
+    align: bool,
+}
+
+impl TextFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write real and complex values in hexadecimal, see [`Matrix::hex_floats`].
+    pub fn hex_floats(mut self, hex_floats: bool) -> Self {
+        self.hex_floats = hex_floats;
+        self
+    }
+
+    /// Right-align every field to a fixed width, such that the fields line up in columns
+    /// and every entry line has the same length.
+    pub fn align(mut self, align: bool) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+/// Widths of the fields of aligned entries.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Widths {
+    row: usize,
+    col: usize,
+    value: usize,
+}
+
+/// Displays a matrix with the given text format, see [`Matrix::formatted`].
+pub struct Formatted<'a> {
+    m: &'a Matrix,
+    format: TextFormat,
+}
+
+impl Matrix {
+    /// Formats the matrix like its [`Display`](fmt::Display) implementation, but with the given text format.
+    pub fn formatted(&self, format: TextFormat) -> Formatted<'_> {
+        Formatted { m: self, format }
+    }
+
+    /// Banner and size line, as written before the entries.
+    pub(crate) fn header(&self) -> String {
+        format!("%%MatrixMarket matrix coordinate {} {}\n{} {} {}\n", self.vals.field(), self.symmetry, self.nrows, self.ncols, self.nvals)
+    }
+
+    /// Widths of the fields if they are aligned.
+    pub(crate) fn widths(&self, format: &TextFormat) -> Option<Widths> {
+        if !format.align {
+            return None;
+        }
+        let value = match &self.vals {
+            MatrixData::Real(_) | MatrixData::Complex(..) if format.hex_floats => HEX_WIDTH,
+            MatrixData::Real(_) | MatrixData::Complex(..) => REAL_WIDTH,
+            MatrixData::Integer(xs) => xs.par_iter().map(|x| x.to_string().len()).max().unwrap_or(0),
+            MatrixData::Bool() => 0,
+        };
+        Some(Widths { row: digits(self.nrows), col: digits(self.ncols), value })
+    }
+
+    /// Length of every aligned entry line, including its newline.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn line_len(&self, widths: &Widths) -> usize {
+        let values = match self.vals {
+            MatrixData::Real(_) | MatrixData::Integer(_) => 1,
+            MatrixData::Complex(..) => 2,
+            MatrixData::Bool() => 0,
+        };
+        widths.row + 1 + widths.col + values * (1 + widths.value) + 1
+    }
+
+    /// Writes entry `i` as a line, with the fields aligned to `widths` if given.
+    pub(crate) fn write_entry<W: Write>(&self, wtr: &mut W, i: usize, format: &TextFormat, widths: Option<&Widths>) -> fmt::Result {
+        let (row, col) = (self.rows[i], self.cols[i]);
+        let Some(&Widths { row: wr, col: wc, value: w }) = widths else {
+            return match &self.vals {
+                MatrixData::Real(xs) if format.hex_floats => writeln!(wtr, "{row} {col} {}", format_hex(xs[i])),
+                MatrixData::Real(xs) => writeln!(wtr, "{row} {col} {}", xs[i]),
+                MatrixData::Complex(xs, ys) if format.hex_floats => writeln!(wtr, "{row} {col} {} {}", format_hex(xs[i]), format_hex(ys[i])),
+                MatrixData::Complex(xs, ys) => writeln!(wtr, "{row} {col} {} {}", xs[i], ys[i]),
+                MatrixData::Integer(xs) => writeln!(wtr, "{row} {col} {}", xs[i]),
+                MatrixData::Bool() => writeln!(wtr, "{row} {col}"),
+            };
+        };
+        write!(wtr, "{row:>wr$} {col:>wc$}")?;
+        match &self.vals {
+            MatrixData::Real(xs) if format.hex_floats => writeln!(wtr, " {:>w$}", format_hex(xs[i])),
+            MatrixData::Real(xs) => writeln!(wtr, " {:>w$.DIGITS$e}", xs[i]),
+            MatrixData::Complex(xs, ys) if format.hex_floats => writeln!(wtr, " {:>w$} {:>w$}", format_hex(xs[i]), format_hex(ys[i])),
+            MatrixData::Complex(xs, ys) => writeln!(wtr, " {:>w$.DIGITS$e} {:>w$.DIGITS$e}", xs[i], ys[i]),
+            MatrixData::Integer(xs) => writeln!(wtr, " {:>w$}", xs[i]),
+            MatrixData::Bool() => writeln!(wtr),
+        }
+    }
+}
+
+impl fmt::Display for Formatted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.m;
+        let widths = m.widths(&self.format);
+        f.write_str(&m.header())?;
+        (0..m.nvals).try_for_each(|i| m.write_entry(f, i, &self.format, widths.as_ref()))
+    }
+}
+
+/// Number of decimal digits of an index.
+fn digits(n: usize) -> usize {
+    n.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
@@ -0,0 +1,215 @@
+//! Synthetic matrices for testing and benchmarking.
+//!
+//! All generators are deterministic for a given seed, on every platform,
+//! and produce their entries in row-major order; use [`Matrix::shuffle`] to randomize that order.
+
+use crate::{DataType, Int, Matrix, MatrixData, Real};
+
+/// Random pattern where every entry is present with probability `density`,
+/// with uniformly random values of the given data type.
+pub fn erdos_renyi(nrows: usize, ncols: usize, density: f64, data_type: DataType, seed: u64) -> Matrix {
+    assert!((0.0..=1.0).contains(&density), "density must be between 0 and 1");
+    let mut rng = Rng::new(seed);
+
+    let mut rows = Vec::new();
+    let mut cols = Vec::new();
+    for row in 1..=nrows {
+        rng.sample(ncols, density, |col| {
+            rows.push(row);
+            cols.push(col + 1);
+        });
+    }
+
+    let vals = rng.values(data_type, rows.len());
+    Matrix::new(nrows, ncols, rows, cols, vals)
+}
+
+/// Square matrix with all entries within `lower` diagonals below and `upper` diagonals above the main diagonal,
+/// with uniformly random values of the given data type.
+pub fn banded(n: usize, lower: usize, upper: usize, data_type: DataType, seed: u64) -> Matrix {
+    let mut rng = Rng::new(seed);
+
+    let mut rows = Vec::new();
+    let mut cols = Vec::new();
+    for row in 1..=n {
+        for col in row.saturating_sub(lower).max(1)..=(row + upper).min(n) {
+            rows.push(row);
+            cols.push(col);
+        }
+    }
+
+    let vals = rng.values(data_type, rows.len());
+    Matrix::new(n, n, rows, cols, vals)
+}
+
+/// Symmetric positive definite real matrix, where every off-diagonal entry is present with probability `density`.
+/// Off-diagonal values are negative, and each diagonal value exceeds the absolute row sum of the off-diagonal values,
+/// making the matrix strictly diagonally dominant.
+pub fn diagonally_dominant(n: usize, density: f64, seed: u64) -> Matrix {
+    assert!((0.0..=1.0).contains(&density), "density must be between 0 and 1");
+    let mut rng = Rng::new(seed);
+
+    // Sample the strict lower triangle, and mirror it into an adjacency list
+    let mut adj = vec![Vec::new(); n];
+    let mut sampled = Vec::new();
+    for row in 0..n {
+        sampled.clear();
+        rng.sample(row, density, |col| sampled.push(col));
+
+        for &col in &sampled {
+            let x = -(1.0 - rng.next_f64());
+            adj[row].push((col, x));
+            adj[col].push((row, x));
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut cols = Vec::new();
+    let mut xs = Vec::new();
+    for (row, entries) in adj.iter_mut().enumerate() {
+        let diag = 1.0 + entries.iter().map(|&(_, x)| f64::abs(x)).sum::<f64>();
+        entries.push((row, diag));
+        entries.sort_unstable_by_key(|&(col, _)| col);
+
+        for &(col, x) in entries.iter() {
+            rows.push(row + 1);
+            cols.push(col + 1);
+            xs.push(x as Real);
+        }
+    }
+
+    Matrix::new(n, n, rows, cols, MatrixData::Real(xs))
+}
+
+/// Five-point finite-difference Laplacian of an `nx` by `ny` grid.
+pub fn laplacian_2d(nx: usize, ny: usize) -> Matrix {
+    laplacian(&[nx, ny])
+}
+
+/// Seven-point finite-difference Laplacian of an `nx` by `ny` by `nz` grid.
+pub fn laplacian_3d(nx: usize, ny: usize, nz: usize) -> Matrix {
+    laplacian(&[nx, ny, nz])
+}
+
+/// Finite-difference Laplacian of a grid with the given dimensions,
+/// where the first dimension varies fastest.
+fn laplacian(dims: &[usize]) -> Matrix {
+    let n: usize = dims.iter().product();
+    let strides: Vec<usize> = dims.iter()
+        .scan(1, |stride, &dim| {
+            let res = *stride;
+            *stride *= dim;
+            Some(res)
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut cols = Vec::new();
+    let mut xs = Vec::new();
+    for node in 0..n {
+        // Neighbours are visited in increasing order: backwards along the
+        // slowest dimension first, then the node itself, then forwards.
+        for (d, &stride) in strides.iter().enumerate().rev() {
+            if (node / stride) % dims[d] > 0 {
+                rows.push(node + 1);
+                cols.push(node - stride + 1);
+                xs.push(-1.0);
+            }
+        }
+
+        rows.push(node + 1);
+        cols.push(node + 1);
+        xs.push(2.0 * dims.len() as Real);
+
+        for (d, &stride) in strides.iter().enumerate() {
+            if (node / stride) % dims[d] + 1 < dims[d] {
+                rows.push(node + 1);
+                cols.push(node + stride + 1);
+                xs.push(-1.0);
+            }
+        }
+    }
+
+    Matrix::new(n, n, rows, cols, MatrixData::Real(xs))
+}
+
+impl Matrix {
+    /// Randomizes the order of the entries, for example to benchmark sorting on generated matrices.
+    pub fn shuffle(&mut self, seed: u64) {
+        let mut rng = Rng::new(seed);
+        for i in (1..self.nvals).rev() {
+            let j = rng.below(i + 1);
+            self.swap(i, j);
+        }
+        self.sortedness = None;
+    }
+}
+
+/// SplitMix64 generator, implemented here such that generated matrices never change between versions.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly random value in [0, 1).
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniformly random value in [0, n).
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Calls `f` for every index in [0, len) with probability `density`,
+    /// in increasing order and in time proportional to the number of selected indices.
+    fn sample(&mut self, len: usize, density: f64, mut f: impl FnMut(usize)) {
+        if density <= 0.0 {
+            return;
+        }
+
+        let mut i = 0;
+        while i < len {
+            if density < 1.0 {
+                // Geometrically distributed gap until the next selected index
+                let gap = ((1.0 - self.next_f64()).ln() / (1.0 - density).ln()).floor();
+                i = i.saturating_add(gap as usize);
+                if i >= len {
+                    break;
+                }
+            }
+
+            f(i);
+            i += 1;
+        }
+    }
+
+    /// Uniformly random values in [0, 1) for reals, and [1, 10) for integers.
+    fn values(&mut self, data_type: DataType, n: usize) -> MatrixData {
+        match data_type {
+            DataType::Real => {
+                MatrixData::Real((0..n).map(|_| self.next_f64() as Real).collect())
+            },
+            DataType::Complex => {
+                let xs = (0..n).map(|_| self.next_f64() as Real).collect();
+                let ys = (0..n).map(|_| self.next_f64() as Real).collect();
+                MatrixData::Complex(xs, ys)
+            },
+            DataType::Integer => {
+                MatrixData::Integer((0..n).map(|_| self.below(9) as Int + 1).collect())
+            },
+            DataType::Bool => MatrixData::Bool(),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Graphs in the DIMACS shortest-path format and the SNAP edge-list format, as square matrices
+//! with an entry for every arc from the row to the column node.
+//!
+//! DIMACS files have comment lines starting with `c`, a problem line `p sp n m`,
+//! and an arc line `a u v w` for every arc, with 1-based nodes and integer weights.
+//! SNAP files have comment lines starting with `#`, and a line `u v` for every edge,
+//! with 0-based nodes that are not necessarily contiguous, and sometimes a weight.
+//! Node `u` of a SNAP file becomes row and column `u + 1`, such that missing nodes become empty rows.
+
+use std::io::{self, Write};
+
+use rayon::prelude::*;
+
+use crate::{split_fields, DataType, Error, Int, Matrix, MatrixData, Real, Symmetry};
+
+/// Edge-list formats of graphs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum GraphFormat {
+    /// DIMACS shortest-path format, with `p sp n m` and `a u v w` lines.
+    Dimacs,
+    /// SNAP edge list, with a `u v` line for every edge.
+    Snap,
+}
+
+impl Matrix {
+    /// Parses a graph in the given format in parallel, as an integer, real, or pattern matrix.
+    /// Weights default to one if a SNAP file has none.
+    pub fn from_graph(bytes: &[u8], format: GraphFormat, data_type: DataType) -> Result<Self, Error> {
+        if let DataType::Complex = data_type {
+            return Err(Error::TypeMismatch { expected: "real", found: "complex" });
+        }
+
+        let comment = match format {
+            GraphFormat::Dimacs => b'c',
+            GraphFormat::Snap => b'#',
+        };
+        let lines: Vec<_> = bytes.split(|&b| b == b'\n')
+            .map(|line| line.trim_ascii())
+            .filter(|line| line.first().is_some_and(|&b| b != comment))
+            .collect();
+
+        let (nodes, expected, lines) = match format {
+            GraphFormat::Dimacs => {
+                let (problem, arcs) = lines.split_first().ok_or_else(|| invalid_data("expected a problem line"))?;
+                let [b"p", _, n, m] = fields(problem)[..] else {
+                    return Err(invalid_data(format!("expected a problem line, found `{}`", String::from_utf8_lossy(problem))).into());
+                };
+                (Some(parse(n, problem)?), parse(m, problem)?, arcs)
+            },
+            GraphFormat::Snap => (None, lines.len(), &lines[..]),
+        };
+
+        let entries = lines.par_iter()
+            .map(|line| {
+                let parts = fields(line);
+                let parts = match format {
+                    GraphFormat::Dimacs if parts.len() == 4 && parts[0] == b"a" => &parts[1..],
+                    GraphFormat::Snap if parts.len() >= 2 => &parts[..],
+                    _ => return Err(invalid_data(format!("expected an arc, found `{}`", String::from_utf8_lossy(line)))),
+                };
+
+                let (u, v): (usize, usize) = (parse(parts[0], line)?, parse(parts[1], line)?);
+                let (u, v) = match format {
+                    GraphFormat::Dimacs => (u, v),
+                    GraphFormat::Snap => (u + 1, v + 1),
+                };
+                let weight = parts.get(2).copied().unwrap_or(b"1");
+                Ok((u, v, weight))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if expected != entries.len() {
+            return Err(Error::EntryCount { expected, found: entries.len() });
+        }
+
+        let n = nodes.unwrap_or_else(|| entries.par_iter().map(|&(u, v, _)| u.max(v)).max().unwrap_or(0));
+        if let Some(&(u, v, _)) = entries.par_iter().find_any(|&&(u, v, _)| u == 0 || u > n || v == 0 || v > n) {
+            return Err(invalid_data(format!("arc ({u}, {v}) is out of bounds for {n} nodes")).into());
+        }
+
+        let rows = entries.par_iter().map(|&(u, _, _)| u).collect();
+        let cols = entries.par_iter().map(|&(_, v, _)| v).collect();
+        let vals = match data_type {
+            DataType::Integer => MatrixData::Integer(entries.par_iter().map(|&(_, _, w)| parse::<Int>(w, w)).collect::<Result<_, _>>()?),
+            DataType::Real => MatrixData::Real(entries.par_iter().map(|&(_, _, w)| parse::<Real>(w, w)).collect::<Result<_, _>>()?),
+            _ => MatrixData::Bool(),
+        };
+
+        Ok(Matrix::new(n, n, rows, cols, vals))
+    }
+
+    /// Writes a square real, integer, or pattern matrix as a graph in the given format,
+    /// with an arc from the row to the column node of every entry, and weights of one for patterns.
+    /// Compact symmetric forms have their mirrored arcs written as well.
+    pub fn write_graph<W: Write>(&self, mut wtr: W, format: GraphFormat) -> Result<(), Error> {
+        self.check_square()?;
+        if let MatrixData::Complex(..) = self.vals {
+            return Err(Error::TypeMismatch { expected: "real", found: "complex" });
+        }
+
+        let mirrored = |i: usize| self.symmetry != Symmetry::General && self.rows[i] != self.cols[i];
+        let narcs = self.nvals + (0..self.nvals).into_par_iter().filter(|&i| mirrored(i)).count();
+
+        match format {
+            GraphFormat::Dimacs => writeln!(wtr, "p sp {} {}", self.nrows, narcs)?,
+            GraphFormat::Snap => {
+                writeln!(wtr, "# Nodes: {} Edges: {}", self.nrows, narcs)?;
+                writeln!(wtr, "# FromNodeId\tToNodeId")?;
+            },
+        }
+
+        for i in 0..self.nvals {
+            let (u, v) = (self.rows[i], self.cols[i]);
+            self.write_arc(&mut wtr, format, u, v, i, false)?;
+            if mirrored(i) {
+                self.write_arc(&mut wtr, format, v, u, i, true)?;
+            }
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Writes the arc from `u` to `v` with the value of entry `i`, negated for the mirror of a skew-symmetric entry.
+    fn write_arc<W: Write>(&self, wtr: &mut W, format: GraphFormat, u: usize, v: usize, i: usize, mirror: bool) -> io::Result<()> {
+        let sign = if mirror && self.symmetry == Symmetry::SkewSymmetric { -1 } else { 1 };
+        match format {
+            GraphFormat::Dimacs => write!(wtr, "a {u} {v}")?,
+            GraphFormat::Snap => write!(wtr, "{}\t{}", u - 1, v - 1)?,
+        }
+
+        match (&self.vals, format) {
+            (MatrixData::Real(xs), GraphFormat::Dimacs) => writeln!(wtr, " {}", sign as Real * xs[i]),
+            (MatrixData::Real(xs), GraphFormat::Snap) => writeln!(wtr, "\t{}", sign as Real * xs[i]),
+            (MatrixData::Integer(xs), GraphFormat::Dimacs) => writeln!(wtr, " {}", sign as Int * xs[i]),
+            (MatrixData::Integer(xs), GraphFormat::Snap) => writeln!(wtr, "\t{}", sign as Int * xs[i]),
+            (_, GraphFormat::Dimacs) => writeln!(wtr, " 1"),
+            (_, GraphFormat::Snap) => writeln!(wtr),
+        }
+    }
+}
+
+fn fields(line: &[u8]) -> Vec<&[u8]> {
+    split_fields(line).collect()
+}
+
+fn parse<T: std::str::FromStr>(part: &[u8], line: &[u8]) -> io::Result<T> {
+    str::from_utf8(part).ok()
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(|| invalid_data(format!("invalid number in `{}`", String::from_utf8_lossy(line))))
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(msg: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
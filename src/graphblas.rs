@@ -0,0 +1,86 @@
+//! Export of the arrays that `GrB_Matrix_import` consumes.
+//!
+//! The serialized blobs of `GrB_Matrix_serialize` are internal to SuiteSparse:GraphBLAS and change between versions,
+//! so instead this writes the arrays of the import formats from the GraphBLAS C API specification,
+//! which a few lines of C read back into `GrB_Matrix_import`.
+
+use std::io::Write;
+
+use crate::{offsets, Error, Matrix, MatrixData};
+
+/// Formats of `GrB_Matrix_import`, with the same numbering as `GrB_Format`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum GraphBlasFormat {
+    /// Row offsets, column indices, and values, by row.
+    Csr = 0,
+    /// Column offsets, row indices, and values, by column.
+    Csc = 1,
+    /// Row indices, column indices, and values.
+    Coo = 2,
+}
+
+impl Matrix {
+    /// Writes a general matrix as the three arrays of `GrB_Matrix_import` in the given format,
+    /// with duplicate entries summed. Every number is in little-endian byte order:
+    ///
+    /// - the magic bytes `GrBI`,
+    /// - the format, the type code, `nrows`, `ncols`, and the lengths of the three arrays, as `u64`,
+    /// - the first two arrays (`Ap` and `Ai`), as `u64`,
+    /// - the values (`Ax`), as `u8` for patterns, `i64` for integers, `f64` for reals,
+    ///   and pairs of `f64` for complex values.
+    ///
+    /// The type codes are 0 for `GrB_BOOL`, 1 for `GrB_INT64`, 2 for `GrB_FP64`, and 3 for `GxB_FC64`.
+    /// Pattern matrices have every value `true`.
+    pub fn write_graphblas<W: Write>(&self, mut wtr: W, format: GraphBlasFormat) -> Result<(), Error> {
+        self.check_general()?;
+
+        let m = match format {
+            GraphBlasFormat::Csr | GraphBlasFormat::Coo => self.coalesced(self.is_row_major(), Matrix::row_major_permutation)?,
+            GraphBlasFormat::Csc => self.coalesced(self.is_col_major(), Matrix::col_major_permutation)?,
+        };
+
+        let zero_based = |indices: &[usize]| indices.iter().map(|&idx| idx - 1).collect::<Vec<_>>();
+        let (ap, ai) = match format {
+            GraphBlasFormat::Csr => (offsets(&m.rows, m.nrows), zero_based(&m.cols)),
+            GraphBlasFormat::Csc => (offsets(&m.cols, m.ncols), zero_based(&m.rows)),
+            GraphBlasFormat::Coo => (zero_based(&m.rows), zero_based(&m.cols)),
+        };
+
+        let type_code = match &m.vals {
+            MatrixData::Bool() => 0,
+            MatrixData::Integer(_) => 1,
+            MatrixData::Real(_) => 2,
+            MatrixData::Complex(..) => 3,
+        };
+
+        wtr.write_all(b"GrBI")?;
+        for n in [format as usize, type_code, m.nrows, m.ncols, ap.len(), ai.len(), m.nvals] {
+            wtr.write_all(&(n as u64).to_le_bytes())?;
+        }
+        for &n in ap.iter().chain(&ai) {
+            wtr.write_all(&(n as u64).to_le_bytes())?;
+        }
+
+        match &m.vals {
+            MatrixData::Bool() => {
+                wtr.write_all(&vec![1u8; m.nvals])?;
+            },
+            MatrixData::Integer(xs) => {
+                xs.iter().try_for_each(|&x| wtr.write_all(&(x as i64).to_le_bytes()))?;
+            },
+            MatrixData::Real(xs) => {
+                xs.iter().try_for_each(|&x| wtr.write_all(&(x as f64).to_le_bytes()))?;
+            },
+            MatrixData::Complex(xs, ys) => {
+                xs.iter().zip(ys).try_for_each(|(&x, &y)| {
+                    wtr.write_all(&(x as f64).to_le_bytes())?;
+                    wtr.write_all(&(y as f64).to_le_bytes())
+                })?;
+            },
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+}
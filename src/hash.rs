@@ -0,0 +1,65 @@
+use std::cmp::Ordering;
+
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Matrix, MatrixData};
+
+impl Matrix {
+    /// SHA-256 digest of the canonical form of this matrix: its dimensions, data type, and symmetry,
+    /// followed by its entries in row-major order with duplicate entries summed.
+    /// Two matrices with the same entries have the same digest, regardless of the order of their entries.
+    ///
+    /// With a `quantum`, values are rounded to the nearest multiple of it first,
+    /// such that values that differ by rounding errors usually hash the same.
+    /// Fails if the sum of duplicate integer entries overflows.
+    pub fn canonical_hash(&self, quantum: Option<f64>) -> Result<[u8; 32], Error> {
+        // Floating-point sums depend on their order, so duplicates are summed in order of their values
+        let sorted = self.is_row_major() && !(1..self.nvals).any(|i| self.same_coords(i - 1, i));
+        let m = self.coalesced(sorted, Matrix::canonical_permutation)?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{} {} {} {}\n", m.nrows, m.ncols, m.vals.field(), m.symmetry));
+
+        let value = |x: f64| match quantum {
+            Some(quantum) => ((x / quantum).round() as i64).to_le_bytes(),
+            // Adding zero turns negative zero into positive zero
+            None => (x + 0.0).to_bits().to_le_bytes(),
+        };
+
+        for i in 0..m.nvals {
+            hasher.update((m.rows[i] as u64).to_le_bytes());
+            hasher.update((m.cols[i] as u64).to_le_bytes());
+            match &m.vals {
+                MatrixData::Real(xs) => {
+                    hasher.update(value(xs[i] as f64));
+                },
+                MatrixData::Complex(xs, ys) => {
+                    hasher.update(value(xs[i] as f64));
+                    hasher.update(value(ys[i] as f64));
+                },
+                MatrixData::Integer(xs) => {
+                    hasher.update((xs[i] as i64).to_le_bytes());
+                },
+                MatrixData::Bool() => {
+                    /* nothing to do */
+                },
+            }
+        }
+
+        Ok(hasher.finalize().into())
+    }
+
+    /// Indices of the entries in row-major order, with the entries at the same coordinates ordered by value.
+    fn canonical_permutation(&self) -> Vec<usize> {
+        let mut permutation: Vec<_> = (0..self.nvals).collect();
+        permutation.sort_unstable_by(|&a, &b| {
+            (self.rows[a], self.cols[a]).cmp(&(self.rows[b], self.cols[b])).then_with(|| match &self.vals {
+                MatrixData::Real(xs) => xs[a].total_cmp(&xs[b]),
+                MatrixData::Complex(xs, ys) => xs[a].total_cmp(&xs[b]).then(ys[a].total_cmp(&ys[b])),
+                MatrixData::Integer(xs) => xs[a].cmp(&xs[b]),
+                MatrixData::Bool() => Ordering::Equal,
+            })
+        });
+        permutation
+    }
+}
@@ -0,0 +1,207 @@
+//! Reading and writing HDF5 files with the datasets `shape`, `rows`, `cols`, and `values`,
+//! the way h5py users commonly store COO matrices. Indices are 0-based `int64`,
+//! and complex values are compounds of `r` and `i`, as h5py stores them.
+//! Pattern matrices have no `values` dataset.
+//!
+//! The datasets are chunked and compressed with shuffling and deflate, if the HDF5 library supports it.
+
+use std::ffi::{c_void, CString};
+use std::io;
+use std::path::Path;
+
+use hdf5_metno_sys::h5::{H5open, hsize_t};
+use hdf5_metno_sys::h5d::{H5Dclose, H5Dcreate2, H5Dget_space, H5Dopen2, H5Dread, H5Dwrite};
+use hdf5_metno_sys::h5f::{H5Fclose, H5Fcreate, H5Fopen, H5F_ACC_RDONLY, H5F_ACC_TRUNC};
+use hdf5_metno_sys::h5i::hid_t;
+use hdf5_metno_sys::h5l::H5Lexists;
+use hdf5_metno_sys::h5p::{H5Pclose, H5Pcreate, H5Pset_chunk, H5Pset_deflate, H5Pset_shuffle, H5P_CLS_DATASET_CREATE, H5P_DEFAULT};
+use hdf5_metno_sys::h5s::{H5Sclose, H5Screate_simple, H5Sget_simple_extent_npoints, H5S_ALL};
+use hdf5_metno_sys::h5t::{H5Tclose, H5Tcreate, H5Tinsert, H5T_class_t, H5T_NATIVE_DOUBLE, H5T_NATIVE_INT64};
+use hdf5_metno_sys::h5z::{H5Zfilter_avail, H5Z_FILTER_DEFLATE, H5Z_FILTER_SHUFFLE};
+
+use crate::{DataType, Error, Int, Matrix, MatrixData, Real};
+
+/// Number of elements per chunk of a dataset.
+const CHUNK_SIZE: usize = 1 << 16;
+
+impl Matrix {
+    /// Writes a general matrix as an HDF5 file, keeping the order of the entries.
+    pub fn write_hdf5(&self, path: &Path) -> Result<(), Error> {
+        self.check_general()?;
+
+        let file = Handle::new(unsafe { H5Fcreate(c_path(path)?.as_ptr(), H5F_ACC_TRUNC, H5P_DEFAULT, H5P_DEFAULT) }, H5Fclose)?;
+        let int64 = *H5T_NATIVE_INT64;
+        let zero_based = |indices: &[usize]| indices.iter().map(|&idx| idx as i64 - 1).collect::<Vec<_>>();
+
+        write_dataset(&file, "shape", int64, &[self.nrows as i64, self.ncols as i64])?;
+        write_dataset(&file, "rows", int64, &zero_based(&self.rows))?;
+        write_dataset(&file, "cols", int64, &zero_based(&self.cols))?;
+
+        match &self.vals {
+            MatrixData::Real(xs) => {
+                let xs: Vec<_> = xs.iter().map(|&x| x as f64).collect();
+                write_dataset(&file, "values", *H5T_NATIVE_DOUBLE, &xs)?;
+            },
+            MatrixData::Complex(xs, ys) => {
+                let zs: Vec<_> = xs.iter().zip(ys).map(|(&x, &y)| [x as f64, y as f64]).collect();
+                write_dataset(&file, "values", complex_type()?.0, &zs)?;
+            },
+            MatrixData::Integer(xs) => {
+                let xs: Vec<_> = xs.iter().map(|&x| x as i64).collect();
+                write_dataset(&file, "values", int64, &xs)?;
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Reads a general matrix from an HDF5 file, with the values converted to the given data type.
+    /// Any integer type works for the indices, and any numeric type for the values.
+    pub fn from_hdf5(path: &Path, data_type: DataType) -> Result<Self, Error> {
+        let file = Handle::new(unsafe { H5Fopen(c_path(path)?.as_ptr(), H5F_ACC_RDONLY, H5P_DEFAULT) }, H5Fclose)?;
+        let int64 = *H5T_NATIVE_INT64;
+
+        let shape: Vec<i64> = read_dataset(&file, "shape", int64)?;
+        let [nrows, ncols] = shape[..] else {
+            return Err(invalid_data(format!("expected a shape of two dimensions, found {}", shape.len())).into());
+        };
+
+        let one_based = |indices: Vec<i64>, n: i64| {
+            indices.into_iter()
+                .map(|idx| if (0..n).contains(&idx) { Ok(idx as usize + 1) } else { Err(invalid_data(format!("index {idx} out of bounds"))) })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let rows = one_based(read_dataset(&file, "rows", int64)?, nrows)?;
+        let cols = one_based(read_dataset(&file, "cols", int64)?, ncols)?;
+        if rows.len() != cols.len() {
+            return Err(Error::EntryCount { expected: rows.len(), found: cols.len() });
+        }
+
+        let vals = match data_type {
+            DataType::Bool => MatrixData::Bool(),
+            _ if !has_dataset(&file, "values")? => {
+                return Err(Error::TypeMismatch { expected: MatrixData::new(data_type).field(), found: "pattern" });
+            },
+            DataType::Real => {
+                let xs: Vec<f64> = read_values(&file, *H5T_NATIVE_DOUBLE, rows.len())?;
+                MatrixData::Real(xs.into_iter().map(|x| x as Real).collect())
+            },
+            DataType::Complex => {
+                let zs: Vec<[f64; 2]> = read_values(&file, complex_type()?.0, rows.len())?;
+                MatrixData::Complex(zs.iter().map(|z| z[0] as Real).collect(), zs.iter().map(|z| z[1] as Real).collect())
+            },
+            DataType::Integer => {
+                let xs: Vec<i64> = read_values(&file, int64, rows.len())?;
+                MatrixData::Integer(xs.into_iter().map(|x| x as Int).collect())
+            },
+        };
+
+        Ok(Matrix::new(nrows as usize, ncols as usize, rows, cols, vals))
+    }
+}
+
+/// Identifier of an HDF5 object, closed when dropped.
+pub(crate) struct Handle(pub(crate) hid_t, unsafe extern "C" fn(hid_t) -> i32);
+
+impl Handle {
+    /// Wraps the identifier returned by an HDF5 call, which is negative if the call failed.
+    pub(crate) fn new(id: hid_t, close: unsafe extern "C" fn(hid_t) -> i32) -> io::Result<Self> {
+        if id < 0 {
+            return Err(io::Error::other("HDF5 call failed"));
+        }
+        Ok(Handle(id, close))
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe { (self.1)(self.0) };
+    }
+}
+
+/// Fails if an HDF5 call returned a negative status.
+pub(crate) fn check(status: i32) -> io::Result<()> {
+    if status < 0 {
+        return Err(io::Error::other("HDF5 call failed"));
+    }
+    Ok(())
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+pub(crate) fn c_path(path: &Path) -> io::Result<CString> {
+    let path = path.to_str().ok_or_else(|| io::Error::other("expected a UTF-8 path"))?;
+    CString::new(path).map_err(io::Error::other)
+}
+
+/// Compound type of two doubles named `r` and `i`, as h5py stores complex values.
+fn complex_type() -> io::Result<Handle> {
+    check(unsafe { H5open() })?;
+    let ty = Handle::new(unsafe { H5Tcreate(H5T_class_t::H5T_COMPOUND, 16) }, H5Tclose)?;
+    check(unsafe { H5Tinsert(ty.0, c"r".as_ptr(), 0, *H5T_NATIVE_DOUBLE) })?;
+    check(unsafe { H5Tinsert(ty.0, c"i".as_ptr(), 8, *H5T_NATIVE_DOUBLE) })?;
+    Ok(ty)
+}
+
+fn has_dataset(file: &Handle, name: &str) -> io::Result<bool> {
+    let name = CString::new(name).map_err(io::Error::other)?;
+    let exists = unsafe { H5Lexists(file.0, name.as_ptr(), H5P_DEFAULT) };
+    check(exists)?;
+    Ok(exists > 0)
+}
+
+/// Writes a one-dimensional dataset of elements of type `T` into a file or group,
+/// where `T` must match the memory type `ty`.
+pub(crate) fn write_dataset<T>(loc: &Handle, name: &str, ty: hid_t, xs: &[T]) -> io::Result<()> {
+    let name = CString::new(name).map_err(io::Error::other)?;
+    let dims = [xs.len() as hsize_t];
+    let space = Handle::new(unsafe { H5Screate_simple(1, dims.as_ptr(), std::ptr::null()) }, H5Sclose)?;
+
+    let props = Handle::new(unsafe { H5Pcreate(*H5P_CLS_DATASET_CREATE) }, H5Pclose)?;
+    if !xs.is_empty() {
+        // Chunk dimensions must be positive, so empty datasets stay contiguous
+        let chunk = [xs.len().min(CHUNK_SIZE) as hsize_t];
+        check(unsafe { H5Pset_chunk(props.0, 1, chunk.as_ptr()) })?;
+        if unsafe { H5Zfilter_avail(H5Z_FILTER_SHUFFLE) } > 0 && unsafe { H5Zfilter_avail(H5Z_FILTER_DEFLATE) } > 0 {
+            check(unsafe { H5Pset_shuffle(props.0) })?;
+            check(unsafe { H5Pset_deflate(props.0, 4) })?;
+        }
+    }
+
+    let dataset = Handle::new(unsafe {
+        H5Dcreate2(loc.0, name.as_ptr(), ty, space.0, H5P_DEFAULT, props.0, H5P_DEFAULT)
+    }, H5Dclose)?;
+    check(unsafe { H5Dwrite(dataset.0, ty, H5S_ALL, H5S_ALL, H5P_DEFAULT, xs.as_ptr() as *const c_void) })
+}
+
+/// Reads a dataset of any shape as a flat vector of elements of type `T`,
+/// converted by HDF5 to the memory type `ty`, which must match `T`.
+fn read_dataset<T: Copy + Default>(file: &Handle, name: &str, ty: hid_t) -> io::Result<Vec<T>> {
+    let c_name = CString::new(name).map_err(io::Error::other)?;
+    let dataset = Handle::new(unsafe { H5Dopen2(file.0, c_name.as_ptr(), H5P_DEFAULT) }, H5Dclose)
+        .map_err(|_| invalid_data(format!("expected a `{name}` dataset")))?;
+    let space = Handle::new(unsafe { H5Dget_space(dataset.0) }, H5Sclose)?;
+
+    let len = unsafe { H5Sget_simple_extent_npoints(space.0) };
+    if len < 0 {
+        return Err(io::Error::other("HDF5 call failed"));
+    }
+
+    let mut xs = vec![T::default(); len as usize];
+    check(unsafe { H5Dread(dataset.0, ty, H5S_ALL, H5S_ALL, H5P_DEFAULT, xs.as_mut_ptr() as *mut c_void) })?;
+    Ok(xs)
+}
+
+/// Reads the `values` dataset, which must have an element for each of the `nvals` entries.
+fn read_values<T: Copy + Default>(file: &Handle, ty: hid_t, nvals: usize) -> Result<Vec<T>, Error> {
+    let xs = read_dataset(file, "values", ty)?;
+    if xs.len() != nvals {
+        return Err(Error::EntryCount { expected: nvals, found: xs.len() });
+    }
+    Ok(xs)
+}
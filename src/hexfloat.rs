@@ -0,0 +1,118 @@
+//! Hexadecimal floating-point values, such as `-0x1.8p+3`, as written by C's `%a`.
+//! They represent every value exactly, and are accepted wherever a real value is read.
+//!
+//! The default output already reads back as the same values, since it is the shortest decimal
+//! representation that does so; hexadecimal values are for tools that parse decimals less carefully.
+
+use std::fmt;
+
+use crate::{Matrix, MatrixData, Real};
+
+/// Displays a matrix with hexadecimal real and complex values, see [`Matrix::hex_floats`].
+pub struct HexFloats<'a>(&'a Matrix);
+
+impl Matrix {
+    /// Formats the matrix like its [`Display`](fmt::Display) implementation, but with hexadecimal floating-point values.
+    pub fn hex_floats(&self) -> HexFloats<'_> {
+        HexFloats(self)
+    }
+}
+
+impl fmt::Display for HexFloats<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.0;
+        match &m.vals {
+            MatrixData::Real(xs) => {
+                writeln!(f, "%%MatrixMarket matrix coordinate {} {}", m.vals.field(), m.symmetry)?;
+                writeln!(f, "{} {} {}", m.nrows, m.ncols, m.nvals)?;
+                (0..m.nvals).try_for_each(|i| writeln!(f, "{} {} {}", m.rows[i], m.cols[i], format_hex(xs[i])))
+            },
+            MatrixData::Complex(xs, ys) => {
+                writeln!(f, "%%MatrixMarket matrix coordinate {} {}", m.vals.field(), m.symmetry)?;
+                writeln!(f, "{} {} {}", m.nrows, m.ncols, m.nvals)?;
+                (0..m.nvals).try_for_each(|i| writeln!(f, "{} {} {} {}", m.rows[i], m.cols[i], format_hex(xs[i]), format_hex(ys[i])))
+            },
+            MatrixData::Integer(_) | MatrixData::Bool() => write!(f, "{m}"),
+        }
+    }
+}
+
+/// Formats a value in hexadecimal, with a normalized mantissa and trailing zeros removed.
+pub(crate) fn format_hex(x: Real) -> String {
+    // Widening to `f64` is exact
+    let x = x as f64;
+    if !x.is_finite() {
+        return x.to_string();
+    }
+
+    let sign = if x.is_sign_negative() { "-" } else { "" };
+    let bits = x.to_bits();
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    let mantissa = bits & ((1 << 52) - 1);
+    if exp == 0 && mantissa == 0 {
+        return format!("{sign}0x0p+0");
+    }
+
+    // Subnormal values have no implicit leading one
+    let (lead, exp) = if exp == 0 { (0, -1022) } else { (1, exp - 1023) };
+    let digits = format!("{mantissa:013x}");
+    let digits = digits.trim_end_matches('0');
+    if digits.is_empty() {
+        format!("{sign}0x{lead}p{exp:+}")
+    } else {
+        format!("{sign}0x{lead}.{digits}p{exp:+}")
+    }
+}
+
+/// Parses a hexadecimal value with an optional sign, fraction, and binary exponent,
+/// or returns `None` if it is not one. Values written by [`format_hex`] are read back exactly.
+pub(crate) fn parse_hex(part: &[u8]) -> Option<f64> {
+    let (negative, part) = match part.first()? {
+        b'-' => (true, &part[1..]),
+        b'+' => (false, &part[1..]),
+        _ => (false, part),
+    };
+    let part = part.strip_prefix(b"0x").or_else(|| part.strip_prefix(b"0X"))?;
+
+    let (digits, exp) = match part.iter().position(|&b| b == b'p' || b == b'P') {
+        Some(p) => (&part[..p], str::from_utf8(&part[p + 1..]).ok()?.parse::<i32>().ok()?),
+        None => (part, 0),
+    };
+
+    let (mut mantissa, mut exp, mut any, mut fraction) = (0u64, exp, false, false);
+    for &b in digits {
+        if b == b'.' && !fraction {
+            fraction = true;
+            continue;
+        }
+        let digit = (b as char).to_digit(16)? as u64;
+        any = true;
+        if mantissa >> 60 == 0 {
+            mantissa = mantissa << 4 | digit;
+            exp = exp.saturating_sub(if fraction { 4 } else { 0 });
+        } else {
+            // Digits beyond the precision only scale the value
+            exp = exp.saturating_add(if fraction { 0 } else { 4 });
+        }
+    }
+    if !any {
+        return None;
+    }
+
+    let x = scale(mantissa as f64, exp);
+    Some(if negative { -x } else { x })
+}
+
+/// Multiplies by a power of two, exactly unless the result overflows or underflows.
+fn scale(mut x: f64, mut exp: i32) -> f64 {
+    let pow2 = |e: i32| f64::from_bits(((e + 1023) as u64) << 52);
+    while exp > 1023 && x.is_finite() {
+        x *= pow2(1023);
+        exp -= 1023;
+    }
+    while exp < -1022 && x != 0.0 {
+        x *= pow2(-1022);
+        exp += 1022;
+    }
+    x * pow2(exp.clamp(-1022, 1023))
+}
@@ -0,0 +1,70 @@
+//! JSON exports of the triplets, with 1-based indices as in the file: one object per entry and line,
+//! for line-oriented tools such as `jq` and bulk ingestion, or a single object with the layout of the `serde` feature.
+//! Non-finite values, which JSON cannot represent, are written as `null`.
+
+use std::io::{self, Write};
+
+use crate::{Matrix, MatrixData, Real};
+
+impl Matrix {
+    /// Writes every stored entry as a JSON object on a line of its own, such as `{"r":1,"c":2,"v":1.5}`,
+    /// where complex values are `[re, im]` pairs and entries of pattern matrices have no value.
+    pub fn write_jsonl<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        for i in 0..self.nvals {
+            write!(wtr, "{{\"r\":{},\"c\":{}", self.rows[i], self.cols[i])?;
+            if !matches!(self.vals, MatrixData::Bool()) {
+                wtr.write_all(b",\"v\":")?;
+                self.write_json_value(&mut wtr, i)?;
+            }
+            wtr.write_all(b"}\n")?;
+        }
+        wtr.flush()
+    }
+
+    /// Writes the matrix as a single JSON object `{nrows, ncols, data_type, symmetry, rows, cols, values}`,
+    /// as the `serde` feature serializes it, where pattern matrices have no values.
+    pub fn write_json<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        write!(wtr, "{{\"nrows\":{},\"ncols\":{},\"data_type\":\"{}\",\"symmetry\":\"{}\"", self.nrows, self.ncols, self.vals.field(), self.symmetry)?;
+        for (name, indices) in [("rows", &self.rows), ("cols", &self.cols)] {
+            write!(wtr, ",\"{name}\":[")?;
+            for (i, idx) in indices.iter().enumerate() {
+                write!(wtr, "{}{idx}", if i == 0 { "" } else { "," })?;
+            }
+            wtr.write_all(b"]")?;
+        }
+        if !matches!(self.vals, MatrixData::Bool()) {
+            wtr.write_all(b",\"values\":[")?;
+            for i in 0..self.nvals {
+                if i > 0 {
+                    wtr.write_all(b",")?;
+                }
+                self.write_json_value(&mut wtr, i)?;
+            }
+            wtr.write_all(b"]")?;
+        }
+        wtr.write_all(b"}\n")?;
+        wtr.flush()
+    }
+
+    /// Writes the value of entry `i` as a JSON number, or a pair of them for complex values.
+    fn write_json_value<W: Write>(&self, wtr: &mut W, i: usize) -> io::Result<()> {
+        match &self.vals {
+            MatrixData::Real(xs) => write_number(wtr, xs[i]),
+            MatrixData::Complex(xs, ys) => {
+                wtr.write_all(b"[")?;
+                write_number(wtr, xs[i])?;
+                wtr.write_all(b",")?;
+                write_number(wtr, ys[i])?;
+                wtr.write_all(b"]")
+            },
+            MatrixData::Integer(xs) => write!(wtr, "{}", xs[i]),
+            MatrixData::Bool() => Ok(()),
+        }
+    }
+}
+
+/// Writes a real value such that it reads back as the same value, and with a fraction or exponent,
+/// such as `2.0` and `1e-7`, as for other JSON writers.
+fn write_number<W: Write>(wtr: &mut W, x: Real) -> io::Result<()> {
+    if x.is_finite() { write!(wtr, "{x:?}") } else { wtr.write_all(b"null") }
+}
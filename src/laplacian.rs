@@ -0,0 +1,75 @@
+//! Adjacency and Laplacian matrices of undirected graphs that are stored as square matrices,
+//! with an edge between the row and column node of every off-diagonal entry.
+
+use rayon::prelude::*;
+
+use crate::{Error, Matrix, MatrixData, Real, SortOrder, Symmetry};
+
+impl Matrix {
+    /// Adjacency matrix of the undirected graph of the matrix: a symmetric pattern matrix with an entry in the lower triangle
+    /// for every pair of nodes that an entry connects in either direction, without self-loops, in row-major order.
+    pub fn to_adjacency(&self) -> Result<Matrix, Error> {
+        self.check_square()?;
+
+        // Every edge once, from its larger to its smaller node
+        let mut edges: Vec<_> = (0..self.nvals).into_par_iter()
+            .filter(|&i| self.rows[i] != self.cols[i])
+            .map(|i| (self.rows[i].max(self.cols[i]), self.rows[i].min(self.cols[i])))
+            .collect();
+        edges.par_sort_unstable();
+        edges.dedup();
+
+        let (rows, cols) = edges.into_iter().unzip();
+        let mut m = Matrix::new(self.nrows, self.ncols, rows, cols, MatrixData::Bool());
+        m.symmetry = Symmetry::Symmetric;
+        m.sortedness = Some(SortOrder::RowMajor);
+        Ok(m)
+    }
+
+    /// Laplacian `D - A` of the undirected graph of the matrix, where `A` is its [adjacency matrix](Matrix::to_adjacency)
+    /// and `D` the diagonal matrix of the degrees, as a symmetric real matrix in row-major order.
+    /// Isolated nodes have no diagonal entry.
+    pub fn to_laplacian(&self) -> Result<Matrix, Error> {
+        self.laplacian(false)
+    }
+
+    /// Normalized Laplacian `I - D^(-1/2) A D^(-1/2)` of the undirected graph of the matrix, see [`Matrix::to_laplacian`].
+    /// Isolated nodes have no diagonal entry.
+    pub fn to_normalized_laplacian(&self) -> Result<Matrix, Error> {
+        self.laplacian(true)
+    }
+
+    fn laplacian(&self, normalized: bool) -> Result<Matrix, Error> {
+        let a = self.to_adjacency()?;
+        let mut degrees = vec![0usize; a.nrows];
+        for i in 0..a.nvals {
+            degrees[a.rows[i] - 1] += 1;
+            degrees[a.cols[i] - 1] += 1;
+        }
+
+        let n = a.nvals + degrees.iter().filter(|&&d| d > 0).count();
+        let (mut rows, mut cols, mut xs) = (Vec::with_capacity(n), Vec::with_capacity(n), Vec::with_capacity(n));
+        let mut k = 0;
+        for row in 1..=a.nrows {
+            // The edges to smaller nodes come before the diagonal
+            while k < a.nvals && a.rows[k] == row {
+                let col = a.cols[k];
+                let x = if normalized { -1.0 / ((degrees[row - 1] * degrees[col - 1]) as f64).sqrt() } else { -1.0 };
+                rows.push(row);
+                cols.push(col);
+                xs.push(x as Real);
+                k += 1;
+            }
+            if degrees[row - 1] > 0 {
+                rows.push(row);
+                cols.push(row);
+                xs.push(if normalized { 1.0 } else { degrees[row - 1] as Real });
+            }
+        }
+
+        let mut m = Matrix::new(a.nrows, a.ncols, rows, cols, MatrixData::Real(xs));
+        m.symmetry = Symmetry::Symmetric;
+        m.sortedness = Some(SortOrder::RowMajor);
+        Ok(m)
+    }
+}
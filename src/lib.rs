@@ -1,10 +1,134 @@
-use std::{fmt, fs, io::{BufRead, BufReader, Read}, str::{self, FromStr}};
+// With `x64`, casts between `Real` and `f64` are no-ops.
+#![cfg_attr(feature = "x64", allow(clippy::unnecessary_cast))]
 
+#[cfg(feature = "mmap")]
+use std::fs;
+use std::{collections::TryReserveError, fmt, io::{BufRead, BufReader, Read}, str::{self, FromStr}};
+
+#[cfg(feature = "mmap")]
 use memmap2::MmapOptions;
 use rayon::prelude::*;
 
+use chunks::{data_lines, line_chunks, split_by_counts, split_header};
+use read::{check_index, check_shape};
+
+mod arithmetic;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod bandwidth;
+mod btf;
+mod bsr;
+mod chunks;
+mod compare;
+mod complex;
+mod components;
+mod concat;
+mod dataset;
+mod dense;
+mod diagonal;
+mod ell;
+mod error;
+mod etree;
+mod external;
+mod extract;
+mod fill;
+mod format;
+#[cfg(feature = "capi")]
+pub mod ffi;
+#[cfg(feature = "faer")]
+mod faer;
+pub mod generators;
+mod graph;
+mod graphblas;
+mod hash;
+mod hexfloat;
+#[cfg(feature = "hdf5")]
+mod hdf5;
+mod json;
+mod laplacian;
+#[cfg(feature = "hdf5")]
+mod mat;
+mod matching;
+mod memory;
+mod merge;
+#[cfg(feature = "mmap")]
+mod mmap_writer;
+mod norm;
+mod npy;
+mod parallel;
+mod partition;
+mod permutation;
+pub mod pipeline;
+mod pattern;
+mod profile;
+mod raw;
+mod read;
+mod relabel;
+#[cfg(feature = "object-store")]
+mod remote;
+mod reorder;
+mod row;
+mod scale;
+#[cfg(feature = "serde")]
+mod serde;
+mod sort;
+mod spmv;
+mod spy;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "sprs")]
+mod sprs;
+mod stats;
+mod symmetry;
+mod tensor;
+mod value;
+mod vector;
+mod writer;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "archive")]
+pub use archive::{archive_stem, is_archive};
+pub use bsr::Bsr;
+pub use compare::{ErrorMetrics, Tolerance};
+pub use btf::BlockTriangularForm;
+pub use components::Components;
+pub use dataset::{Coordinates, Dataset};
+pub use ell::{Ell, Sell};
+pub use error::Error;
+pub use fill::SymbolicFill;
+pub use format::{Formatted, TextFormat};
+pub use graph::GraphFormat;
+pub use graphblas::GraphBlasFormat;
+pub use hexfloat::HexFloats;
+pub use num_complex::Complex;
+pub use merge::DuplicatePolicy;
+pub use norm::Norms;
+pub use parallel::ParallelConfig;
+pub use partition::Imbalance;
+pub use permutation::{Permutation, PermutationFormat};
+pub use pattern::Pattern;
+pub use profile::{Histogram, ValueProfile};
+pub use relabel::IndexMap;
+pub use raw::{IndexType, RawOptions};
+pub use read::{CountPolicy, IntWidth, NonFinitePolicy, Precision, ReadOptions};
+pub use reorder::Reordering;
+pub use row::{RowView, ValueSlice};
+pub use sort::{SortOptions, SortOrder, SortStrategy, TieBreak};
+pub use spy::Spy;
+pub use stats::{Gershgorin, LineMetrics, Stats};
+pub use symmetry::{Symmetry, SymmetryReport};
+pub use tensor::Tensor;
+pub use value::Value;
+pub use vector::Vector;
+pub use writer::MatrixWriter;
+
 #[repr(align(64))]
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub struct Matrix {
     rows: Vec<usize>,
     cols: Vec<usize>,
@@ -12,25 +136,31 @@ pub struct Matrix {
     nrows: usize,
     ncols: usize,
     nvals: usize,
+    symmetry: Symmetry,
+    /// Order that the entries are known to be in, set by the sorts and reset by anything that moves entries.
+    sortedness: Option<SortOrder>,
 }
 
+/// Precision of real and complex values.
 #[cfg(not(feature = "x64"))]
-#[repr(align(64))]
-#[derive(Clone, PartialEq)]
-enum MatrixData {
-    Real(Vec<f32>),
-    Complex(Vec<f32>, Vec<f32>),
-    Integer(Vec<i32>),
-    Bool(),
-}
+pub type Real = f32;
+/// Precision of real and complex values.
+#[cfg(feature = "x64")]
+pub type Real = f64;
 
+/// Precision of integer values.
+#[cfg(not(feature = "x64"))]
+pub type Int = i32;
+/// Precision of integer values.
 #[cfg(feature = "x64")]
+pub type Int = i64;
+
 #[repr(align(64))]
 #[derive(Clone, PartialEq)]
 enum MatrixData {
-    Real(Vec<f64>),
-    Complex(Vec<f64>, Vec<f64>),
-    Integer(Vec<i64>),
+    Real(Vec<Real>),
+    Complex(Vec<Real>, Vec<Real>),
+    Integer(Vec<Int>),
     Bool(),
 }
 
@@ -47,136 +177,247 @@ impl Matrix {
     pub fn nrows(&self) -> usize { self.nrows }
     pub fn ncols(&self) -> usize { self.ncols }
     pub fn nvals(&self) -> usize { self.nvals }
+    pub fn symmetry(&self) -> Symmetry { self.symmetry }
+    pub fn data_type(&self) -> DataType { self.vals.data_type() }
+
+    /// Row indices of the entries, starting at one.
+    pub fn rows(&self) -> &[usize] { &self.rows }
+    /// Column indices of the entries, starting at one.
+    pub fn cols(&self) -> &[usize] { &self.cols }
 
+    /// Creates a general matrix from its (1-based) coordinates and values.
+    #[inline]
+    fn new(nrows: usize, ncols: usize, rows: Vec<usize>, cols: Vec<usize>, vals: MatrixData) -> Self {
+        let nvals = rows.len();
+        Self { rows, cols, vals, nrows, ncols, nvals, symmetry: Symmetry::General, sortedness: None }
+    }
+
+    /// Memory-maps a Matrix-Market file and parses it in parallel.
+    #[cfg(feature = "mmap")]
     pub fn from_mmap(file: fs::File, data_type: DataType) -> Self {
-        let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
-        let mut lines = mmap.split(|&b| b == b'\n')
-            // We deliberately do not `map` yet because we are still in sequential mode
-            .skip_while(|b| b.trim_ascii()[0] == b'%');
+        Self::from_mmap_with(file, data_type, &ReadOptions::default()).unwrap()
+    }
 
-        if let Some(header) = lines.next() {
-            let parts: Vec<_> = header.split(|&b| b.is_ascii_whitespace()).collect();
-            let nrows = parse_utf8(parts[0]);
-            let ncols = parse_utf8(parts[1]);
-            let nvals = parse_utf8(parts[2]);
+    /// Like [`Matrix::from_mmap`], with the given options.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap_with(file: fs::File, data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        let mmap = unsafe { MmapOptions::new().map(&file)? };
+        Self::from_bytes_with(&mmap, data_type, options)
+    }
+
+    /// Parses the contents of a Matrix-Market file in parallel.
+    pub fn from_bytes(bytes: &[u8], data_type: DataType) -> Self {
+        Self::from_bytes_with(bytes, data_type, &ReadOptions::default()).unwrap()
+    }
+
+    /// Like [`Matrix::from_bytes`], with the given options.
+    #[tracing::instrument(name = "parse", skip_all, fields(%data_type, bytes = bytes.len()))]
+    pub fn from_bytes_with(bytes: &[u8], data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        let banner = bytes.split(|&b| b == b'\n').next().unwrap_or_default();
+        let symmetry = Symmetry::from_banner(&String::from_utf8_lossy(banner));
+
+        if let Some((header, body)) = split_header(bytes) {
+            let (nrows, ncols, expected) = parse_size(header, || line_of(bytes, header))?;
+
+            // Instead of collecting the lines, we count the entries of every chunk of lines first,
+            // such that every chunk can then be parsed into its own part of the arrays
+            let chunks = line_chunks(body);
+            let counts: Vec<usize> = chunks.par_iter().map(|chunk| data_lines(chunk).count()).collect();
+            let nvals = counts.iter().sum();
+            options.check_count(expected, nvals)?;
+            let lenient = options.is_lenient();
 
             let mut rows = vec![0usize; nvals];
             let mut cols = vec![0usize; nvals];
 
-            let tail = lines.collect::<Vec<_>>()
-                .into_par_iter()
-                .zip(rows.par_iter_mut())
-                .zip(cols.par_iter_mut());
+            let tail = chunks.into_par_iter()
+                .zip(split_by_counts(&mut rows, &counts))
+                .zip(split_by_counts(&mut cols, &counts));
 
             let vals = match data_type {
                 DataType::Real => {
                     let mut xs = vec![0.0; nvals];
-                    tail.zip(xs.par_iter_mut())
-                        .for_each(|(((line, row), col), x)| {
-                            let parts: Vec<_> = line.trim_ascii().split(|&b| b.is_ascii_whitespace()).collect();
-                            *row = parse_utf8(parts[0]);
-                            *col = parse_utf8(parts[1]);
-                            *x = parse_utf8(parts[2]);
-                        });
+                    tail.zip(split_by_counts(&mut xs, &counts))
+                        .try_for_each(|(((chunk, rows), cols), xs)| {
+                            for (((line, row), col), x) in data_lines(chunk).zip(rows).zip(cols).zip(xs) {
+                                let line_number = || line_of(bytes, line);
+                                let [r, c, v] = entry_fields(line, data_type, line_number)?;
+                                *row = parse_field(r, parse_decimal, line_number)?;
+                                *col = parse_field(c, parse_decimal, line_number)?;
+                                *x = parse_field(v, |v| parse_real(v, lenient), line_number)?;
+                            }
+                            Ok::<_, Error>(())
+                        })?;
                     MatrixData::Real(xs)
                 },
                 DataType::Complex => {
                     let mut xs = vec![0.0; nvals];
                     let mut ys = vec![0.0; nvals];
-                    tail.zip(xs.par_iter_mut())
-                        .zip(ys.par_iter_mut())
-                        .for_each(|((((line, row), col), x), y)| {
-                            let parts: Vec<_> = line.split(|&b| b.is_ascii_whitespace()).collect();
-                            *row = parse_utf8(parts[0]);
-                            *col = parse_utf8(parts[1]);
-                            *x = parse_utf8(parts[2]);
-                            *y = parse_utf8(parts[3]);
-                        });
+                    tail.zip(split_by_counts(&mut xs, &counts))
+                        .zip(split_by_counts(&mut ys, &counts))
+                        .try_for_each(|((((chunk, rows), cols), xs), ys)| {
+                            for ((((line, row), col), x), y) in data_lines(chunk).zip(rows).zip(cols).zip(xs).zip(ys) {
+                                let line_number = || line_of(bytes, line);
+                                let [r, c, re, im] = entry_fields(line, data_type, line_number)?;
+                                *row = parse_field(r, parse_decimal, line_number)?;
+                                *col = parse_field(c, parse_decimal, line_number)?;
+                                *x = parse_field(re, |re| parse_real(re, lenient), line_number)?;
+                                *y = parse_field(im, |im| parse_real(im, lenient), line_number)?;
+                            }
+                            Ok::<_, Error>(())
+                        })?;
                     MatrixData::Complex(xs, ys)
                 },
                 DataType::Integer => {
                     let mut xs = vec![0; nvals];
-                    tail.zip(xs.par_iter_mut())
-                        .for_each(|(((line, row), col), x)| {
-                            let parts: Vec<_> = line.split(|&b| b.is_ascii_whitespace()).collect();
-                            *row = parse_utf8(parts[0]);
-                            *col = parse_utf8(parts[1]);
-                            *x = parse_utf8(parts[2]);
-                        });
+                    tail.zip(split_by_counts(&mut xs, &counts))
+                        .try_for_each(|(((chunk, rows), cols), xs)| {
+                            for (((line, row), col), x) in data_lines(chunk).zip(rows).zip(cols).zip(xs) {
+                                let line_number = || line_of(bytes, line);
+                                let [r, c, v] = entry_fields(line, data_type, line_number)?;
+                                *row = parse_field(r, parse_decimal, line_number)?;
+                                *col = parse_field(c, parse_decimal, line_number)?;
+                                *x = parse_int(v, line_number)?;
+                            }
+                            Ok::<_, Error>(())
+                        })?;
                     MatrixData::Integer(xs)
                 },
                 DataType::Bool => {
-                    tail.for_each(|((line, row), col)| {
-                            let parts: Vec<_> = line.split(|&b| b.is_ascii_whitespace()).collect();
-                            *row = parse_utf8(parts[0]);
-                            *col = parse_utf8(parts[1]);
-                        });
+                    tail.try_for_each(|((chunk, rows), cols)| {
+                            for ((line, row), col) in data_lines(chunk).zip(rows).zip(cols) {
+                                let line_number = || line_of(bytes, line);
+                                let [r, c] = entry_fields(line, data_type, line_number)?;
+                                *row = parse_field(r, parse_decimal, line_number)?;
+                                *col = parse_field(c, parse_decimal, line_number)?;
+                            }
+                            Ok::<_, Error>(())
+                        })?;
                     MatrixData::Bool()
                 },
             };
 
-            Self { rows, cols, vals, nrows, ncols, nvals }
+            let mut m = Self { rows, cols, vals, nrows, ncols, nvals, symmetry, sortedness: None };
+            m.check_bounds(|i| entry_line(bytes, i))?;
+            m.round_values(options);
+            m.check_int_width(options, |i| entry_line(bytes, i))?;
+            m.scrub_nonfinite(options, |i| entry_line(bytes, i))?;
+            m.check_skew_diagonal()?;
+            m.detect_sortedness();
+            Ok(m)
         } else {
             // File is empty or contains only comments, return empty matrix
-            Self {
+            Ok(Self {
                 rows: Vec::new(),
                 cols: Vec::new(),
                 vals: MatrixData::new(data_type),
                 nrows: 0, ncols: 0, nvals: 0,
-            }
+                symmetry,
+                sortedness: None,
+            })
         }
     }
 
+    /// Parses bytes that cannot be trusted, such as uploads, which never panics whatever the bytes are.
+    /// This is [`Matrix::from_bytes_with`], which like every reader fails on malformed lines,
+    /// on entries outside the dimensions, and on symmetric forms that are not square, which would make later transformations panic.
+    pub fn try_parse_bytes(bytes: &[u8], data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        Self::from_bytes_with(bytes, data_type, options)
+    }
+
     pub fn from_reader<R: Read>(rdr: BufReader<R>, data_type: DataType) -> Self {
+        Self::from_reader_with(rdr, data_type, &ReadOptions::default()).unwrap()
+    }
+
+    /// Like [`Matrix::from_reader`], with the given options.
+    #[tracing::instrument(name = "parse", skip_all, fields(%data_type))]
+    pub fn from_reader_with<R: Read>(rdr: BufReader<R>, data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
         let mut lines = rdr.lines()
             .map_while(Result::ok)
-            // We assume comments can only appear at the start of the file
-            .skip_while(|line| line.starts_with('%'));
-
-        if let Some(header) = lines.next() {
-            let parts: Vec<_> = header.split_ascii_whitespace().collect();
-            let nrows = parts[0].parse().unwrap();
-            let ncols = parts[1].parse().unwrap();
-            let nvals = parts[2].parse().unwrap();
-
-            let mut rows = Vec::with_capacity(nvals);
-            let mut cols = Vec::with_capacity(nvals);
-            let mut vals = MatrixData::with_capacity(data_type, nvals);
-
-            for line in lines {
-                let parts: Vec<_> = line.split_ascii_whitespace().collect();
-                rows.push(parts[0].parse().unwrap());
-                cols.push(parts[1].parse().unwrap());
-                match &mut vals {
+            .enumerate()
+            .peekable();
+        let symmetry = lines.peek()
+            .map_or(Symmetry::General, |(_, banner)| Symmetry::from_banner(banner));
+        let mut lines = lines
+            .filter(|(_, line)| is_data(line.as_bytes()));
+
+        if let Some((n, header)) = lines.next() {
+            let (nrows, ncols, nvals) = parse_size(header.as_bytes(), || n + 1)?;
+            check_shape(symmetry, nrows, ncols)?;
+            let lenient = options.is_lenient();
+
+            // The entries are allocated up front from the size line, which may declare more than fit in memory
+            Self::check_addressable(nrows, ncols, nvals, data_type, None)?;
+            let too_large = |_| Error::TooLarge { nrows: nrows as u64, ncols: ncols as u64, nvals: nvals as u64 };
+            let mut rows = Vec::new();
+            let mut cols = Vec::new();
+            rows.try_reserve_exact(nvals).map_err(too_large)?;
+            cols.try_reserve_exact(nvals).map_err(too_large)?;
+            let mut vals = MatrixData::try_with_capacity(data_type, nvals).map_err(too_large)?;
+
+            let mut line_numbers = Vec::new();
+            for (n, line) in lines {
+                if options.needs_lines() {
+                    line_numbers.push(n + 1);
+                }
+                let line = line.as_bytes();
+                let line_number = || n + 1;
+                let (r, c) = match &mut vals {
                     MatrixData::Real(xs) => {
-                        xs.push(parts[2].parse().unwrap())
+                        let [r, c, v] = entry_fields(line, data_type, line_number)?;
+                        xs.push(parse_field(v, |v| parse_real(v, lenient), line_number)?);
+                        (r, c)
                     },
                     MatrixData::Complex(xs, ys) => {
-                        xs.push(parts[2].parse().unwrap());
-                        ys.push(parts[3].parse().unwrap());
+                        let [r, c, re, im] = entry_fields(line, data_type, line_number)?;
+                        xs.push(parse_field(re, |re| parse_real(re, lenient), line_number)?);
+                        ys.push(parse_field(im, |im| parse_real(im, lenient), line_number)?);
+                        (r, c)
                     },
                     MatrixData::Integer(xs) => {
-                        xs.push(parts[2].parse().unwrap())
+                        let [r, c, v] = entry_fields(line, data_type, line_number)?;
+                        xs.push(parse_int(v, line_number)?);
+                        (r, c)
                     },
                     MatrixData::Bool() => {
-                        /* nothing to do */
+                        let [r, c] = entry_fields(line, data_type, line_number)?;
+                        (r, c)
                     },
-                }
+                };
+                let (row, col) = (parse_field(r, parse_decimal, line_number)?, parse_field(c, parse_decimal, line_number)?);
+                check_index(row, col, nrows, ncols, line_number)?;
+                rows.push(row);
+                cols.push(col);
             }
 
-            Self { rows, cols, vals, nrows, ncols, nvals }
+            options.check_count(nvals, rows.len())?;
+            let nvals = rows.len();
+
+            let mut m = Self { rows, cols, vals, nrows, ncols, nvals, symmetry, sortedness: None };
+            m.round_values(options);
+            m.check_int_width(options, |i| line_numbers[i])?;
+            m.scrub_nonfinite(options, |i| line_numbers[i])?;
+            m.check_skew_diagonal()?;
+            m.detect_sortedness();
+            Ok(m)
         } else {
             // File is empty or contains only comments, return empty matrix
-            Self {
+            Ok(Self {
                 rows: Vec::new(),
                 cols: Vec::new(),
                 vals: MatrixData::new(data_type),
                 nrows: 0, ncols: 0, nvals: 0,
-            }
+                symmetry,
+                sortedness: None,
+            })
         }
     }
 
+    #[tracing::instrument(name = "sort", skip_all, fields(order = "row-major"))]
     pub fn sort_row_major(&mut self) {
+        if self.is_sorted(SortOrder::RowMajor) {
+            return;
+        }
         match &mut self.vals {
             MatrixData::Real(xs) => {
                 let mut zipped: Vec<_> = (0..self.nvals)
@@ -247,9 +488,14 @@ impl Matrix {
                     });
             },
         };
+        self.sortedness = Some(SortOrder::RowMajor);
     }
 
+    #[tracing::instrument(name = "sort", skip_all, fields(order = "col-major"))]
     pub fn sort_col_major(&mut self) {
+        if self.is_sorted(SortOrder::ColMajor) {
+            return;
+        }
         match &mut self.vals {
             MatrixData::Real(xs) => {
                 let mut zipped: Vec<_> = (0..self.nvals)
@@ -320,24 +566,57 @@ impl Matrix {
                     });
             },
         };
+        self.sortedness = Some(SortOrder::ColMajor);
+    }
+
+    /// Checks whether the entries are in row-major order.
+    pub fn is_row_major(&self) -> bool {
+        self.is_sorted(SortOrder::RowMajor) || (1..self.nvals).into_par_iter()
+            .all(|i| (self.rows[i - 1], self.cols[i - 1]) <= (self.rows[i], self.cols[i]))
+    }
+
+    /// Checks whether the entries are in column-major order.
+    pub fn is_col_major(&self) -> bool {
+        self.is_sorted(SortOrder::ColMajor) || (1..self.nvals).into_par_iter()
+            .all(|i| (self.cols[i - 1], self.rows[i - 1]) <= (self.cols[i], self.rows[i]))
     }
 
     /// Slightly more memory-friendly approach to sorting.
-    /// Only allocates one additional array of length `nvals`.
+    /// Only allocates one additional array of length `nvals`, see [`SortStrategy::Permute`].
     pub fn permute_row_major(&mut self) {
-        let mut permutation: Vec<_> = (0..self.nvals).collect();
-        permutation.sort_unstable_by(|&a, &b|
-            (self.rows[a], self.cols[a]).cmp(&(self.rows[b], self.cols[b])));
+        if self.is_sorted(SortOrder::RowMajor) {
+            return;
+        }
+        let permutation = self.row_major_permutation();
         self.apply_permutation(permutation);
+        self.sortedness = Some(SortOrder::RowMajor);
     }
 
     /// Slightly more memory-friendly approach to sorting.
-    /// Only allocates one additional array of length `nvals`.
+    /// Only allocates one additional array of length `nvals`, see [`SortStrategy::Permute`].
     pub fn permute_col_major(&mut self) {
+        if self.is_sorted(SortOrder::ColMajor) {
+            return;
+        }
+        let permutation = self.col_major_permutation();
+        self.apply_permutation(permutation);
+        self.sortedness = Some(SortOrder::ColMajor);
+    }
+
+    /// Indices of the entries in row-major order, without moving the entries themselves.
+    fn row_major_permutation(&self) -> Vec<usize> {
+        let mut permutation: Vec<_> = (0..self.nvals).collect();
+        permutation.sort_unstable_by(|&a, &b|
+            (self.rows[a], self.cols[a]).cmp(&(self.rows[b], self.cols[b])));
+        permutation
+    }
+
+    /// Indices of the entries in column-major order, without moving the entries themselves.
+    fn col_major_permutation(&self) -> Vec<usize> {
         let mut permutation: Vec<_> = (0..self.nvals).collect();
         permutation.sort_unstable_by(|&a, &b|
             (self.cols[a], self.rows[a]).cmp(&(self.cols[b], self.rows[b])));
-        self.apply_permutation(permutation);
+        permutation
     }
 
     fn apply_permutation(&mut self, mut permutation: Vec<usize>) {
@@ -361,6 +640,28 @@ impl Matrix {
         }
     }
 
+    /// Value of the entry at index `i` as a (real, imaginary) pair, where pattern entries are one.
+    #[inline]
+    fn value_at(&self, i: usize) -> (f64, f64) {
+        match &self.vals {
+            MatrixData::Real(xs) => (xs[i] as f64, 0.0),
+            MatrixData::Complex(xs, ys) => (xs[i] as f64, ys[i] as f64),
+            MatrixData::Integer(xs) => (xs[i] as f64, 0.0),
+            MatrixData::Bool() => (1.0, 0.0),
+        }
+    }
+
+    /// Absolute value of the entry at index `i`, where pattern entries are one.
+    #[inline]
+    fn abs_at(&self, i: usize) -> f64 {
+        match &self.vals {
+            MatrixData::Real(xs) => (xs[i] as f64).abs(),
+            MatrixData::Complex(xs, ys) => (xs[i] as f64).hypot(ys[i] as f64),
+            MatrixData::Integer(xs) => (xs[i] as f64).abs(),
+            MatrixData::Bool() => 1.0,
+        }
+    }
+
     #[inline]
     fn swap(&mut self, a: usize, b: usize) {
         self.rows.swap(a, b);
@@ -405,18 +706,55 @@ impl MatrixData {
             Bool => MatrixData::Bool(),
         }
     }
+
+    /// Like [`MatrixData::with_capacity`], or an error if the values do not fit in memory.
+    fn try_with_capacity(data_type: DataType, nvals: usize) -> Result<Self, TryReserveError> {
+        let mut vals = Self::new(data_type);
+        match &mut vals {
+            MatrixData::Real(xs) => xs.try_reserve_exact(nvals)?,
+            MatrixData::Complex(xs, ys) => {
+                xs.try_reserve_exact(nvals)?;
+                ys.try_reserve_exact(nvals)?;
+            },
+            MatrixData::Integer(xs) => xs.try_reserve_exact(nvals)?,
+            MatrixData::Bool() => {},
+        }
+        Ok(vals)
+    }
+
+    #[inline]
+    fn data_type(&self) -> DataType {
+        match self {
+            MatrixData::Real(_) => DataType::Real,
+            MatrixData::Complex(_, _) => DataType::Complex,
+            MatrixData::Integer(_) => DataType::Integer,
+            MatrixData::Bool() => DataType::Bool,
+        }
+    }
+
+    /// Name of this data type in the Matrix-Market banner.
+    #[inline]
+    fn field(&self) -> &'static str {
+        match self {
+            MatrixData::Real(_) => "real",
+            MatrixData::Complex(_, _) => "complex",
+            MatrixData::Integer(_) => "integer",
+            MatrixData::Bool() => "pattern",
+        }
+    }
 }
 
 impl fmt::Debug for Matrix {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let n = f.width().unwrap_or(5);
+        let n = f.width().unwrap_or(5).min(self.nvals);
         let p = f.precision().unwrap_or(2);
 
-        let name = if n >= self.nvals { "Matrix" } else { &format!("Matrix (head={n})") };
+        let name = if n == self.nvals { "Matrix" } else { &format!("Matrix (head={n})") };
         let mut wtr = f.debug_struct(name);
         wtr.field("nrows", &self.nrows)
             .field("ncols", &self.ncols)
             .field("nvals", &self.nvals)
+            .field("symmetry", &self.symmetry)
             .field("rows", &format_args!("{:?}", &self.rows[..n]))
             .field("cols", &format_args!("{:?}", &self.cols[..n]));
 
@@ -440,8 +778,19 @@ impl fmt::Debug for Matrix {
     }
 }
 
+impl PartialEq for Matrix {
+    /// Compares the dimensions, symmetry, and entries in their order, regardless of which order they are known to be in.
+    fn eq(&self, other: &Self) -> bool {
+        (self.nrows, self.ncols, self.nvals, self.symmetry) == (other.nrows, other.ncols, other.nvals, other.symmetry)
+            && self.rows == other.rows
+            && self.cols == other.cols
+            && self.vals == other.vals
+    }
+}
+
 impl fmt::Display for Matrix {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "%%MatrixMarket matrix coordinate {} {}", self.vals.field(), self.symmetry)?;
         writeln!(f, "{} {} {}", self.nrows, self.ncols, self.nvals)?;
         (0..self.nvals).try_for_each(|i| {
             use MatrixData::*;
@@ -467,6 +816,64 @@ impl fmt::Display for DataType {
     }
 }
 
+/// (1-based) line number of the entry at index `i`, counting from the start of the file.
+fn entry_line(bytes: &[u8], i: usize) -> usize {
+    bytes.split(|&b| b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| is_data(line))
+        .nth(i + 1)
+        .map_or(0, |(n, _)| n + 1)
+}
+
+/// Whether a line contains an entry or the size line, rather than being blank or a comment.
+/// Lines may end in `\r\n`, and comments may appear anywhere.
+#[inline]
+fn is_data(line: &[u8]) -> bool {
+    line.iter().find(|&&b| !is_space(b)).is_some_and(|&b| b != b'%')
+}
+
+/// Whether a byte separates fields, which like C's `isspace` includes vertical tabs and form feeds.
+#[inline(always)]
+fn is_space(b: u8) -> bool {
+    b.is_ascii_whitespace() || b == b'\x0b'
+}
+
+/// Whitespace-separated fields of a line, ignoring leading, trailing, and repeated spaces, tabs, and carriage returns.
+#[inline(always)]
+pub(crate) fn split_fields(line: &[u8]) -> impl Iterator<Item = &[u8]> {
+    line.split(|&b| is_space(b)).filter(|part| !part.is_empty())
+}
+
+/// The first `N` fields of a line, ignoring any further fields, such as trailing comments.
+///
+/// Panics if the line has fewer fields.
+#[inline(always)]
+fn fields<const N: usize>(line: &[u8]) -> [&[u8]; N] {
+    let mut parts = split_fields(line);
+    std::array::from_fn(|_| parts.next().expect("expected more fields on a line"))
+}
+
+/// The first `N` fields of an entry, like [`fields`], or an error naming the line if it has fewer,
+/// where `line_number` is only computed in that case.
+#[inline(always)]
+fn entry_fields<const N: usize>(line: &[u8], data_type: DataType, line_number: impl FnOnce() -> usize) -> Result<[&[u8]; N], Error> {
+    let mut parts = split_fields(line);
+    let mut fields = [&line[..0]; N];
+    for (found, field) in fields.iter_mut().enumerate() {
+        *field = match parts.next() {
+            Some(part) => part,
+            None => return Err(Error::FieldCount { line: line_number(), expected: N, found, data_type }),
+        };
+    }
+    Ok(fields)
+}
+
+/// (1-based) line number of a line that is a slice of `bytes`.
+fn line_of(bytes: &[u8], line: &[u8]) -> usize {
+    let offset = line.as_ptr() as usize - bytes.as_ptr() as usize;
+    bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1
+}
+
 #[inline(always)]
 fn parse_utf8<T>(part: &[u8]) -> T
 where
@@ -476,6 +883,134 @@ where
     str::from_utf8(part).unwrap().parse().unwrap()
 }
 
+/// Like [`parse_utf8`], or `None` if the field is not valid.
+#[inline(always)]
+fn try_parse_utf8<T: FromStr>(part: &[u8]) -> Option<T> {
+    str::from_utf8(part).ok()?.parse().ok()
+}
+
+/// Parses a field with `parse`, or returns an error naming the field and its line,
+/// where `line_number` is only computed in that case.
+#[inline(always)]
+fn parse_field<T>(part: &[u8], parse: impl FnOnce(&[u8]) -> Option<T>, line_number: impl FnOnce() -> usize) -> Result<T, Error> {
+    parse(part).ok_or_else(|| Error::InvalidField { line: line_number(), field: String::from_utf8_lossy(part).into_owned() })
+}
+
+/// Parses an integer value, or returns an error naming its line, which tells values that overflow [`Int`] apart.
+#[inline(always)]
+fn parse_int(part: &[u8], line_number: impl FnOnce() -> usize) -> Result<Int, Error> {
+    match try_parse_utf8(part) {
+        Some(x) => Ok(x),
+        None if try_parse_utf8::<i128>(part).is_some() => Err(Error::IntegerOverflow { line: line_number(), bits: Int::BITS }),
+        None => Err(Error::InvalidField { line: line_number(), field: String::from_utf8_lossy(part).into_owned() }),
+    }
+}
+
+/// Parses the number of rows, columns, and entries of a size line, ignoring any further fields.
+/// The numbers are read as 64-bit integers, such that numbers that do not fit in `usize` are reported as such.
+pub(crate) fn parse_size(line: &[u8], line_number: impl FnOnce() -> usize) -> Result<(usize, usize, usize), Error> {
+    let mut parts = split_fields(line).map(try_parse_utf8::<u64>);
+    let (Some(nrows), Some(ncols), Some(nvals)) = (parts.next().flatten(), parts.next().flatten(), parts.next().flatten()) else {
+        return Err(Error::SizeLine { line: line_number() });
+    };
+
+    match (usize::try_from(nrows), usize::try_from(ncols), usize::try_from(nvals)) {
+        (Ok(nrows), Ok(ncols), Ok(nvals)) => Ok((nrows, ncols, nvals)),
+        _ => Err(Error::TooLarge { nrows, ncols, nvals }),
+    }
+}
+
+/// Parses a (row or column) index.
+#[cfg(not(feature = "simd"))]
+#[inline(always)]
+fn parse_decimal(part: &[u8]) -> Option<usize> {
+    try_parse_utf8(part)
+}
+
+/// Parses a (row or column) index of up to sixteen digits in at most two 64-bit words,
+/// falling back to `str::parse` for longer or invalid indices.
+#[cfg(feature = "simd")]
+#[inline(always)]
+fn parse_decimal(part: &[u8]) -> Option<usize> {
+    let x = match part.len() {
+        1..=8 => parse_eight(part),
+        9..=16 => {
+            let (hi, lo) = part.split_at(part.len() - 8);
+            parse_eight(hi).zip(parse_eight(lo)).map(|(hi, lo)| hi * 100_000_000 + lo)
+        },
+        _ => None,
+    };
+
+    x.and_then(|x| usize::try_from(x).ok()).or_else(|| try_parse_utf8(part))
+}
+
+/// Parses up to eight decimal digits at once, by padding them with leading zeros into a little-endian word,
+/// and combining pairs of digits, then pairs of those, and then pairs of those.
+#[cfg(feature = "simd")]
+#[inline(always)]
+fn parse_eight(part: &[u8]) -> Option<u64> {
+    let mut bytes = [b'0'; 8];
+    bytes[8 - part.len()..].copy_from_slice(part);
+    let x = u64::from_le_bytes(bytes);
+
+    // Every byte must be in `0x30..=0x39`
+    let digits = (x & 0xF0F0_F0F0_F0F0_F0F0) == 0x3030_3030_3030_3030
+        && (x.wrapping_add(0x0606_0606_0606_0606) & 0xF0F0_F0F0_F0F0_F0F0) == 0x3030_3030_3030_3030;
+    if !digits {
+        return None;
+    }
+
+    let x = x & 0x0F0F_0F0F_0F0F_0F0F;
+    let x = x.wrapping_mul(10 << 8 | 1) >> 8 & 0x00FF_00FF_00FF_00FF;
+    let x = x.wrapping_mul(100 << 16 | 1) >> 16 & 0x0000_FFFF_0000_FFFF;
+    let x = x.wrapping_mul(10000 << 32 | 1) >> 32;
+    Some(x)
+}
+
+/// Parses a real value, which may be hexadecimal, also accepting the forms of [`normalize_number`] if `lenient` is set.
+#[inline(always)]
+fn parse_real(part: &[u8], lenient: bool) -> Option<Real> {
+    match try_parse_utf8(part) {
+        Some(x) => Some(x),
+        None => match hexfloat::parse_hex(part) {
+            Some(x) => Some(x as Real),
+            None if lenient => try_parse_utf8(&normalize_number(part)),
+            None => None,
+        },
+    }
+}
+
+/// Rewrites decimal commas such as `1,5` into `1.5`, Fortran exponents such as `1.0D+03` into `1.0E+03`,
+/// and Fortran exponents without a letter, such as `1.0-300`, into `1.0E-300`.
+fn normalize_number(part: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(part.len() + 1);
+    for (i, &b) in part.iter().enumerate() {
+        match b {
+            b',' => res.push(b'.'),
+            b'D' | b'd' | b'Q' | b'q' => res.push(b'E'),
+            b'+' | b'-' if i > 0 && (part[i - 1].is_ascii_digit() || part[i - 1] == b'.') => {
+                res.push(b'E');
+                res.push(b);
+            },
+            _ => res.push(b),
+        }
+    }
+    res
+}
+
+/// Compressed offsets of sorted (1-based) indices: the entries with index `i`
+/// are found in the range `offsets[i - 1]..offsets[i]`.
+fn offsets(indices: &[usize], n: usize) -> Vec<usize> {
+    let mut offsets = vec![0; n + 1];
+    for &idx in indices {
+        offsets[idx] += 1;
+    }
+    for i in 0..n {
+        offsets[i + 1] += offsets[i];
+    }
+    offsets
+}
+
 /// Mark the element at this index as visited by toggling the most-significant bit.
 #[inline(always)]
 fn mark_visited(idx: usize) -> usize {
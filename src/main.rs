@@ -1,79 +1,2298 @@
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fmt,
-    fs::File,
+    fs::{self, File},
     io::{self, BufWriter, Write},
-    path::PathBuf,
-    time::Instant,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+    sync::{Mutex, OnceLock, atomic::{AtomicUsize, Ordering}},
+    time::{Duration, Instant},
 };
 
-use clap::Parser;
+use clap::{builder::RangedU64ValueParser, ArgGroup, Parser, Subcommand, ValueEnum};
+use log::{debug, error, info, warn, LevelFilter};
 use matrix_market_transform::*;
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
-    pub input_file: PathBuf,
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Only print errors.
+    #[arg(short('q'), long("quiet"), global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Increase logging verbosity; `-v` also dumps the matrix head.
+    #[arg(short('v'), long("verbose"), global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Print phase timings as a single JSON object on stdout.
+    #[arg(long("timing-json"), global = true)]
+    pub timing_json: bool,
+
+    /// Record the phases, and the parsing, sorting, and writing within them, as Chrome-trace JSON,
+    /// which `chrome://tracing` and Perfetto show.
+    #[arg(long("trace-out"), global = true)]
+    pub trace_out: Option<PathBuf>,
+
+    /// What to do when the number of entries differs from the size line.
+    #[arg(long("count"), global = true, value_enum, default_value_t = CountPolicy::Lenient)]
+    pub count: CountPolicy,
+
+    /// What to do with values that parse to NaN or an infinity.
+    #[arg(long("nonfinite"), global = true, value_enum, default_value_t = NonFinitePolicy::Keep)]
+    pub nonfinite: NonFinitePolicy,
+
+    /// Also accept values with decimal commas, such as `1,5`, and Fortran exponents, such as `1.0D+03`.
+    #[arg(long("lenient-numbers"), global = true)]
+    pub lenient_numbers: bool,
+
+    /// Round real and complex values to the nearest `f32` as they are read, as a build without the `x64` feature does;
+    /// the rounded values are still stored at the width of the build.
+    #[arg(long("round-to-f32"), global = true)]
+    pub round_to_f32: bool,
+
+    /// Width to read integer values at, failing on values that do not fit; `64` needs a build with the `x64` feature.
+    #[arg(long("int-width"), global = true, value_enum, default_value_t = IntWidth::default())]
+    pub int_width: IntWidth,
+
+    /// Write real and complex values in hexadecimal, such as `0x1.8p+1`, which C's `strtod` reads exactly.
+    #[arg(long("hex-floats"), global = true)]
+    pub hex_floats: bool,
+
+    /// Right-align the fields of every entry to a fixed width, with values in scientific notation,
+    /// such that they line up in columns; this also saves `--mmap-write` from measuring the entries.
+    #[arg(long("align"), global = true)]
+    pub align: bool,
+
+    /// Write local files through a memory map, formatting the entries on all threads.
+    #[arg(long("mmap-write"), global = true)]
+    pub mmap_write: bool,
+
+    /// Write matrices in a compact symmetric form as general matrices, with their upper triangle stored explicitly.
+    #[arg(long("expand-symmetric"), global = true)]
+    pub expand_symmetric: bool,
+
+    /// After writing a local file, read it back and check that it equals the written matrix.
+    #[arg(long("verify"), global = true)]
+    pub verify: bool,
+
+    /// Number of representable values that real and complex values may be off by when verifying.
+    #[arg(long("verify-ulps"), global = true, default_value_t = 0, requires = "verify")]
+    pub verify_ulps: u64,
+
+    /// Absolute difference that real and complex values may be off by when verifying.
+    #[arg(long("verify-abs"), global = true, default_value_t = 0.0, requires = "verify")]
+    pub verify_abs: f64,
+
+    /// Sort stably, such that entries with the same coordinates keep their order,
+    /// and written files are byte-identical across runs, thread counts, and machines.
+    #[arg(long("deterministic"), global = true)]
+    pub deterministic: bool,
+
+    /// Member of `.tar`, `.tar.gz`, `.tgz`, or `.zip` inputs to read, by its path or file name,
+    /// instead of the primary matrix, such as the right-hand side `name_b.mtx`.
+    #[cfg(feature = "archive")]
+    #[arg(long("member"), global = true)]
+    pub member: Option<String>,
+
+    /// Number of threads to use; defaults to one per logical CPU.
+    #[arg(long("threads"), global = true, default_value_t = 0, hide_default_value = true)]
+    pub threads: usize,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Sort the entries into row-major or column-major order.
+    Sort {
+        #[command(flatten)]
+        io: IoArgs,
+
+        #[arg(short('s'), long("sort"), default_value_t = SortOrder::RowMajor)]
+        sort_order: SortOrder,
+
+        /// Keep entries that compare equal in their original order.
+        #[arg(long("stable"))]
+        stable: bool,
+
+        /// How to order entries with the same coordinates.
+        #[arg(long("tie-break"), value_enum, default_value_t = TieBreak::None)]
+        tie_break: TieBreak,
+
+        /// Sort by tiles with this many rows, instead of in the given order.
+        #[arg(long("block-rows"), requires = "block_cols", conflicts_with = "sort_order")]
+        block_rows: Option<usize>,
+
+        /// Sort by tiles with this many columns, instead of in the given order.
+        #[arg(long("block-cols"), requires = "block_rows")]
+        block_cols: Option<usize>,
+
+        /// How to sort into row- or column-major order, trading speed for memory.
+        #[arg(long("strategy"), value_enum, default_value_t = SortStrategy::Auto)]
+        strategy: SortStrategy,
+
+        /// Estimate the peak memory from the size lines, and pick the fastest strategy
+        /// that stays within this many bytes, such as `512M` or `8G`.
+        #[arg(long("mem-limit"), value_parser = parse_bytes, conflicts_with = "strategy")]
+        mem_limit: Option<usize>,
+
+        /// Sort externally, keeping the sorted runs and a checkpoint of the completed ones in this directory.
+        #[arg(long("checkpoint-dir"), conflicts_with_all = ["block_rows", "strategy", "mem_limit"])]
+        checkpoint_dir: Option<PathBuf>,
+
+        /// Continue an interrupted sort from the checkpoint in `--checkpoint-dir`, reusing its completed runs.
+        #[arg(long("resume"), requires = "checkpoint_dir")]
+        resume: bool,
+    },
+    /// Scale the rows and columns of the matrix.
+    #[command(group(ArgGroup::new("factors").required(true).multiple(true)))]
+    Scale {
+        #[command(flatten)]
+        io: IoArgs,
+
+        /// Scale the rows, and then the columns, by the inverse of their infinity-norm.
+        #[arg(long("equilibrate"), group = "factors", conflicts_with_all = ["row_factors", "col_factors"])]
+        equilibrate: bool,
+
+        /// Multiply every row by its entry in this vector in the array format, such as to undo an equilibration.
+        #[arg(long("row-factors"), group = "factors")]
+        row_factors: Option<PathBuf>,
+
+        /// Multiply every column by its entry in this vector in the array format, after scaling the rows.
+        #[arg(long("col-factors"), group = "factors")]
+        col_factors: Option<PathBuf>,
+    },
+    /// Extract the diagonal, or the lower or upper triangle.
+    Extract {
+        #[command(flatten)]
+        io: IoArgs,
+
+        #[arg(short('p'), long("part"))]
+        part: Part,
+
+        /// Exclude the diagonal from the lower or upper triangle.
+        #[arg(long("strict"))]
+        strict: bool,
+    },
+    /// Keep only the entries in the given rows, columns, band, and range of values.
+    Filter {
+        #[command(flatten)]
+        io: IoArgs,
+
+        /// Range of (1-based) rows to keep, such as `1..100` or `..=99`, where the end is excluded unless after `..=`.
+        #[arg(long("keep-rows"), value_parser = parse_index_range)]
+        keep_rows: Option<RangeInclusive<usize>>,
+
+        /// Range of (1-based) columns to keep, like `--keep-rows`.
+        #[arg(long("keep-cols"), value_parser = parse_index_range)]
+        keep_cols: Option<RangeInclusive<usize>>,
+
+        /// Keep only the entries within `k` of the diagonal, where `|row - col| <= k`.
+        #[arg(long("band"))]
+        band: Option<usize>,
+
+        /// Keep only the entries with at least this value; needs a real, integer, or pattern matrix.
+        #[arg(long("min-value"), allow_negative_numbers = true)]
+        min_value: Option<Real>,
+
+        /// Keep only the entries with at most this value; needs a real, integer, or pattern matrix.
+        #[arg(long("max-value"), allow_negative_numbers = true)]
+        max_value: Option<Real>,
+    },
+    /// Apply a complex-specific operation; read the input with `-t complex`.
+    Complex {
+        #[command(flatten)]
+        io: IoArgs,
+
+        #[arg(long("op"))]
+        op: ComplexOp,
+    },
+    /// Symmetrically permute the rows and columns of a square matrix,
+    /// and compare its bandwidth, profile, and wavefront before and after.
+    Reorder {
+        #[command(flatten)]
+        io: IoArgs,
+
+        #[arg(short('m'), long("method"), visible_alias("algorithm"), default_value = "rcm")]
+        method: ReorderMethod,
+
+        /// Seed of the random permutation.
+        #[arg(long("seed"), default_value_t = 0)]
+        seed: u64,
+
+        /// Apply the permutation in this file, rather than computing one.
+        #[arg(long("permutation"), conflicts_with_all = ["method", "save_permutation"])]
+        permutation: Option<PathBuf>,
+
+        /// Write the computed permutation to this file.
+        #[arg(long("save-permutation"))]
+        save_permutation: Option<PathBuf>,
+
+        #[arg(long("perm-format"), default_value = "text")]
+        perm_format: PermutationFormat,
+
+        /// Also print the predicted number of entries of the Cholesky factor for the natural ordering
+        /// and every deterministic method, side by side.
+        #[arg(long("compare-fill"))]
+        compare_fill: bool,
+
+        /// Right-hand side in the array format, which is permuted with the rows of the matrix.
+        #[arg(long("rhs"))]
+        rhs: Option<PathBuf>,
+
+        /// Where to write the permuted right-hand side, which defaults to the destination of the matrix
+        /// for `--in-place` and `--out-dir`.
+        #[arg(long("rhs-out"), requires = "rhs")]
+        rhs_out: Option<PathBuf>,
+    },
+    /// Transform a matrix together with the right-hand side `_b.mtx` and the coordinates `_coord.mtx` next to it,
+    /// and write them as a bundle next to the output file.
+    Dataset {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        /// Reorder the rows and columns symmetrically, and the right-hand side and coordinates with the rows.
+        #[arg(short('m'), long("reorder"))]
+        reorder: Option<ReorderMethod>,
+
+        /// Seed of the random permutation.
+        #[arg(long("seed"), default_value_t = 0)]
+        seed: u64,
+
+        /// Equilibrate the matrix after reordering it, and scale the right-hand side by the row factors.
+        #[arg(long("equilibrate"))]
+        equilibrate: bool,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Report the rows without a diagonal entry, or with a zero one, which factorizations cannot pivot on.
+    Diagonal {
+        #[command(flatten)]
+        io: IoArgs,
+
+        /// Insert the missing diagonal entries with this value.
+        #[arg(long("ensure-diagonal"), allow_negative_numbers = true)]
+        ensure_diagonal: Option<f64>,
+    },
+    /// Store a symmetric matrix in the compact form, keeping only its lower triangle.
+    Compress {
+        #[command(flatten)]
+        io: IoArgs,
+
+        /// Relative tolerance when comparing mirrored values.
+        #[arg(long("tol"), default_value_t = 0.0)]
+        tol: f64,
+
+        /// Compact form to store the matrix in.
+        #[arg(long("as"), value_enum, default_value_t = CompactForm::Symmetric)]
+        form: CompactForm,
+    },
+    /// Union the entries of several matrices with the same dimensions.
+    Merge {
+        #[command(flatten)]
+        io: MultiIoArgs,
+
+        /// What to do with entries that occur in more than one input.
+        #[arg(long("duplicates"), value_enum, default_value_t = DuplicatePolicy::Sum)]
+        duplicates: DuplicatePolicy,
+    },
+    /// Add, subtract, or multiply several matrices with the same dimensions element-wise, from left to right.
+    Elementwise {
+        #[command(flatten)]
+        io: MultiIoArgs,
+
+        #[arg(long("op"))]
+        op: ElementwiseOp,
+    },
+    /// Concatenate several matrices horizontally, vertically, or block-diagonally.
+    Concat {
+        #[command(flatten)]
+        io: MultiIoArgs,
+
+        #[arg(short('m'), long("mode"))]
+        mode: ConcatMode,
+    },
+    /// Apply the steps of a YAML plan to its inputs, and write the results where it says.
+    Run {
+        plan_file: PathBuf,
+    },
+    /// Generate a synthetic matrix.
+    Generate {
+        #[command(subcommand)]
+        generator: Generator,
+
+        #[arg(short('o'), global = true)]
+        output_file: Option<PathBuf>,
+
+        /// Seed of the random number generator.
+        #[arg(long("seed"), global = true, default_value_t = 0)]
+        seed: u64,
+
+        /// Randomize the order of the generated entries.
+        #[arg(long("shuffle"), global = true)]
+        shuffle: bool,
+    },
+    /// Render the nonzero pattern as an image.
+    Spy {
+        input_file: PathBuf,
+
+        /// Output image; its format (png, pgm, or ppm) follows from the extension.
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        #[arg(long("width"), default_value_t = 512, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        width: usize,
+
+        /// Defaults to preserving the aspect ratio of the matrix.
+        #[arg(long("height"), value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        height: Option<usize>,
+    },
+    /// Print a structural summary of the matrix.
+    Stats {
+        input_file: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+
+        /// Also print the nonzero pattern, this many characters wide.
+        #[arg(long("spy"), num_args(0..=1), default_missing_value = "64", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        spy: Option<usize>,
+
+        /// Also print a numeric profile of the values, with histograms of this many bins.
+        #[arg(long("histogram"), num_args(0..=1), default_missing_value = "10", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        histogram: Option<usize>,
+
+        /// Print the summary and profile as a single JSON object instead.
+        #[arg(long("json"))]
+        json: bool,
+
+        /// Relative tolerance when comparing mirrored values for the symmetry score.
+        #[arg(long("tol"), default_value_t = 0.0)]
+        tol: f64,
+
+        /// Also print the bounds of the Gershgorin discs of a square matrix, and its diagonally dominant rows.
+        #[arg(long("gershgorin"))]
+        gershgorin: bool,
+
+        /// Write the Gershgorin disc of every row to this CSV file.
+        #[arg(long("gershgorin-csv"))]
+        gershgorin_csv: Option<PathBuf>,
+
+        /// Also print the Frobenius, infinity, and one norms, and the largest absolute value.
+        #[arg(long("norms"))]
+        norms: bool,
+
+        /// Also print the percentage of entries within each of these distances of the diagonal, such as `1,10,100`.
+        #[arg(long("bands"), value_delimiter(','))]
+        bands: Vec<usize>,
+
+        /// Also print this many entries of largest absolute value, with their coordinates.
+        #[arg(long("top-k"))]
+        top_k: Option<usize>,
+
+        /// Write the number of entries, smallest and largest value, and bandwidth of every row to this CSV file.
+        #[arg(long("per-row"))]
+        per_row: Option<PathBuf>,
+
+        /// Write the same metrics as `--per-row` for every column to this CSV file.
+        #[arg(long("per-col"))]
+        per_col: Option<PathBuf>,
+    },
+    /// Predict the fill of the level-based incomplete factorizations ILU(k), or IC(k) with `--ic`,
+    /// without computing them, such as to compare orderings.
+    Fill {
+        input_file: PathBuf,
+
+        /// Highest level of fill to report.
+        #[arg(short('k'), long("levels"), default_value_t = 2)]
+        levels: usize,
+
+        /// Count the lower triangular factor of an incomplete Cholesky factorization instead.
+        #[arg(long("ic"))]
+        ic: bool,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Print the Dulmage-Mendelsohn decomposition: the structural rank and the blocks of the block triangular form.
+    Btf {
+        input_file: PathBuf,
+
+        /// Also write the matrix, permuted to block triangular form.
+        #[arg(short('o'))]
+        output_file: Option<PathBuf>,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Convert the matrix to another text format.
+    Convert {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: Option<PathBuf>,
+
+        #[arg(long("to"), value_enum)]
+        to: ConvertFormat,
+
+        /// Refuse matrices with more elements than this when converting to a dense format.
+        #[arg(long("max-elements"), default_value_t = 1 << 24)]
+        max_elements: usize,
+
+        /// Width of the indices of a raw dump.
+        #[arg(long("index-type"), value_enum, default_value_t = IndexType::Int32)]
+        index_type: IndexType,
+
+        /// Keep the 1-based indices in a raw dump, instead of making them 0-based.
+        #[arg(long("one-based"))]
+        one_based: bool,
+
+        /// Precision of the real and complex values of a raw dump; defaults to that of the build.
+        #[arg(long("value-precision"), value_enum)]
+        value_precision: Option<Precision>,
+
+        /// Write NumPy arrays as a single array of records with the fields `row`, `col`, and `val` to the file given by `-o`.
+        #[arg(long("records"))]
+        records: bool,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Convert a general matrix to block sparse row format, written in the binary layout of `Bsr::write`.
+    Bsr {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        /// Defaults to the largest block size whose blocks are filled at least `--min-fill`.
+        #[arg(short('b'), long("block-size"))]
+        block_size: Option<usize>,
+
+        #[arg(long("max-block-size"), default_value_t = 8)]
+        max_block_size: usize,
+
+        #[arg(long("min-fill"), default_value_t = 1.0)]
+        min_fill: f64,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Convert a general matrix to ELLPACK, or SELL-C-sigma with `--chunk-size`, written in the binary layout of
+    /// `Ell::write` or `Sell::write`.
+    Ell {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        /// Number of rows per chunk, C.
+        #[arg(short('c'), long("chunk-size"))]
+        chunk_size: Option<usize>,
+
+        /// Number of rows in every window that is sorted by row length.
+        #[arg(long("sigma"), default_value_t = 1, requires = "chunk_size")]
+        sigma: usize,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Write a general matrix as the arrays of `GrB_Matrix_import`, in the layout of `Matrix::write_graphblas`.
+    Graphblas {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        #[arg(long("format"), value_enum, default_value_t = GraphBlasFormat::Csr)]
+        format: GraphBlasFormat,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Write the triplets as an Arrow IPC (`.arrow`) or Parquet (`.parquet`) file, following the extension.
+    #[cfg(feature = "arrow")]
+    Arrow {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Write the triplets to a table of a SQLite database, replacing any table of the same name.
+    #[cfg(feature = "sqlite")]
+    Sqlite {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        /// Name of the table of the triplets.
+        #[arg(long("table"), default_value = "triplets")]
+        table: String,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Convert between Matrix-Market and HDF5 (`.h5` or `.hdf5`) files, following the extension of the input file.
+    #[cfg(feature = "hdf5")]
+    Hdf5 {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Write the matrix as a sparse variable of a MATLAB v7.3 (`.mat`) file.
+    #[cfg(feature = "hdf5")]
+    Mat {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        /// Name of the MATLAB variable.
+        #[arg(long("name"), default_value = "A")]
+        name: String,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Sort the entries of a FROSTT sparse tensor (`.tns`) lexicographically by its modes.
+    Tensor {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: Option<PathBuf>,
+
+        /// (0-based) modes in the order to compare them, such as `2,0,1`; by default in their natural order.
+        #[arg(long("modes"), value_delimiter(','))]
+        modes: Option<Vec<usize>>,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Convert between Matrix-Market files and DIMACS or SNAP graphs;
+    /// `.mtx` inputs are written as graphs, and other inputs are read as graphs.
+    /// With `--transform`, the adjacency or Laplacian matrix of the graph is written instead of the graph itself.
+    Graph {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+
+        /// Format of the graph; `.mtx` inputs are only written as a graph if it is given.
+        #[arg(long("format"), required_unless_present = "transform")]
+        format: Option<GraphFormat>,
+
+        #[arg(long("transform"), value_enum)]
+        transform: Option<GraphTransform>,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Integer)]
+        data_type: DataType,
+    },
+    /// Partition the matrix into contiguous blocks of rows, or a grid of blocks of rows and columns,
+    /// written to a directory as `<name>.<index>.mtx`, with a manifest of the blocks in `<name>.json`.
+    Partition {
+        input_file: PathBuf,
+
+        /// Number of blocks of rows.
+        #[arg(short('p'), long("parts"))]
+        parts: usize,
+
+        /// Also split the columns into this many blocks, for a grid of parts ordered by block of rows.
+        #[arg(long("col-parts"), default_value_t = 1)]
+        col_parts: usize,
+
+        #[arg(long("by"), default_value = "rows")]
+        by: PartitionBy,
+
+        /// Renumber the rows and columns of each block from one, rather than keeping the global indices.
+        #[arg(long("local"))]
+        local: bool,
+
+        #[arg(long("out-dir"))]
+        out_dir: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Scale or permute a dense vector in the array format, such as a right-hand side or a solution.
+    Vector {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: Option<PathBuf>,
+
+        /// Multiply every entry by this factor.
+        #[arg(long("scale"), allow_negative_numbers = true)]
+        scale: Option<f64>,
+
+        /// Move the entries with the permutation in this file, as `reorder --permutation` moves the rows.
+        #[arg(long("permutation"))]
+        permutation: Option<PathBuf>,
+
+        /// Apply the inverse of the permutation, such as to map the solution of a reordered system back.
+        #[arg(long("inverse"), requires = "permutation")]
+        inverse: bool,
+
+        #[arg(long("perm-format"), default_value = "text")]
+        perm_format: PermutationFormat,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Invert, compose, or verify permutations, as written by `reorder --save-permutation`.
+    Perm {
+        #[command(subcommand)]
+        action: PermAction,
+
+        #[arg(long("format"), global = true, default_value = "text")]
+        format: PermutationFormat,
+
+        /// Format of the output, if it differs from that of the inputs.
+        #[arg(long("out-format"), global = true)]
+        out_format: Option<PermutationFormat>,
+    },
+    /// Split the matrix into several matrices, written to a directory as `<name>.<index>.mtx`,
+    /// with a manifest of the parts in `<name>.json`.
+    Split {
+        input_file: PathBuf,
+
+        #[arg(long("by"))]
+        by: SplitBy,
+
+        #[arg(long("out-dir"))]
+        out_dir: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Time sparse matrix-vector products with the entries in every sort order.
+    BenchSpmv {
+        input_file: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+
+        #[arg(short('n'), long("iterations"), default_value_t = 100, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        iterations: usize,
+    },
+    /// Print a digest of the canonical form of each matrix, which does not depend on the order of the entries.
+    Hash {
+        #[arg(required = true)]
+        input_files: Vec<PathBuf>,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+
+        /// Round values to the nearest multiple of this before hashing.
+        #[arg(long("quantum"))]
+        quantum: Option<f64>,
+    },
+    /// Compare a matrix with a golden reference: the largest and mean relative error,
+    /// the Frobenius norm of the difference, and the coordinates of the worst mismatch.
+    Diff {
+        golden_file: PathBuf,
+
+        input_file: PathBuf,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+
+        #[arg(long("report"), value_enum, default_value_t = ReportFormat::Text)]
+        report: ReportFormat,
+
+        /// Fail if the largest relative error exceeds this, such as in regression tests.
+        #[arg(long("max-rel"))]
+        max_rel: Option<f64>,
+    },
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum ReportFormat {
+    /// One metric per line.
+    Text,
+    /// A single JSON object.
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+enum PermAction {
+    /// Write the permutation that undoes the given one.
+    Invert {
+        input_file: PathBuf,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+    },
+    /// Write the permutation that applies the given ones in order.
+    Compose {
+        #[arg(required = true)]
+        input_files: Vec<PathBuf>,
+
+        #[arg(short('o'))]
+        output_file: PathBuf,
+    },
+    /// Check that the file holds a permutation, optionally of the given length.
+    Verify {
+        input_file: PathBuf,
+
+        #[arg(short('n'), long("len"))]
+        len: Option<usize>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum Generator {
+    /// Random pattern where every entry is present with a given probability.
+    ErdosRenyi {
+        #[arg(long("rows"))]
+        nrows: usize,
+
+        #[arg(long("cols"))]
+        ncols: usize,
+
+        #[arg(short('d'), long("density"), value_parser = parse_probability)]
+        density: f64,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Square matrix with all entries within a band around the diagonal.
+    Banded {
+        #[arg(short('n'))]
+        n: usize,
+
+        /// Number of diagonals below the main diagonal.
+        #[arg(long("lower"))]
+        lower: usize,
+
+        /// Number of diagonals above the main diagonal.
+        #[arg(long("upper"))]
+        upper: usize,
+
+        #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+        data_type: DataType,
+    },
+    /// Symmetric positive definite, strictly diagonally dominant matrix.
+    Spd {
+        #[arg(short('n'))]
+        n: usize,
+
+        #[arg(short('d'), long("density"), value_parser = parse_probability)]
+        density: f64,
+    },
+    /// Finite-difference Laplacian of a 2D grid.
+    Laplacian2d {
+        #[arg(long("nx"))]
+        nx: usize,
+
+        #[arg(long("ny"))]
+        ny: usize,
+    },
+    /// Finite-difference Laplacian of a 3D grid.
+    Laplacian3d {
+        #[arg(long("nx"))]
+        nx: usize,
+
+        #[arg(long("ny"))]
+        ny: usize,
+
+        #[arg(long("nz"))]
+        nz: usize,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+struct IoArgs {
+    /// Input files, or glob patterns such as `data/*.mtx`.
+    #[arg(required = true)]
+    pub input_files: Vec<PathBuf>,
 
     #[arg(short('o'))]
     pub output_file: Option<PathBuf>,
 
+    /// Replace the input file with the result, through a temporary file in the same directory.
+    #[arg(long("in-place"), conflicts_with = "output_file")]
+    pub in_place: bool,
+
+    /// Write each result to this directory, under the name of its input file; inputs with the same name are refused.
+    #[arg(long("out-dir"), conflicts_with_all = ["output_file", "in_place"])]
+    pub out_dir: Option<PathBuf>,
+
+    /// Number of files to process at the same time.
+    #[arg(short('j'), long("jobs"), default_value_t = 1)]
+    pub jobs: usize,
+
     #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
     pub data_type: DataType,
 
-    #[arg(short('s'), long("sort"), default_value_t = SortOrder::RowMajor)]
-    pub sort_order: SortOrder,
+    /// Relabel the rows with `old new` pairs of indices, or one new index per old row, from this file;
+    /// entries in rows without a new index are dropped.
+    #[arg(long("row-map"))]
+    pub row_map: Option<PathBuf>,
+
+    /// Relabel the columns like `--row-map`; symmetric matrices need the same file for both.
+    #[arg(long("col-map"))]
+    pub col_map: Option<PathBuf>,
+
+    /// Remove the rows and columns without entries before transforming, renumbering the others.
+    #[arg(long("drop-empty"))]
+    pub drop_empty: bool,
+
+    /// Write a JSON manifest of the written files to this path, with their dimensions, number of entries,
+    /// data type, symmetry, order, and digest, such that loaders need not scan them.
+    #[arg(long("manifest"))]
+    pub manifest: Option<PathBuf>,
+}
+
+impl IoArgs {
+    /// Reads, transforms, and writes every input file.
+    /// Several files are processed by up to `jobs` workers, which share the thread pool,
+    /// followed by a summary table on stdout.
+    fn run<F>(&self, options: &ReadOptions, timings: &mut Timings, transform: F) -> Result<(), Error>
+    where
+        F: Fn(Matrix, &mut Timings) -> Result<Matrix, Error> + Sync,
+    {
+        let inputs = expand_globs(&self.input_files)?;
+        if let [input] = inputs.as_slice() && self.out_dir.is_none() {
+            let (_, entry) = self.process(input, options, timings, &transform)?;
+            return self.write_manifest(entry.into_iter().collect());
+        }
+
+        if self.output_file.is_some() {
+            return Err(io::Error::other("`-o` takes a single input; use `--out-dir` for several").into());
+        }
+        if let Some(out_dir) = &self.out_dir {
+            // Object stores have no directories to create
+            if !is_url(out_dir) {
+                fs::create_dir_all(out_dir)?;
+            }
+        } else if !self.in_place {
+            return Err(io::Error::other("several inputs require `--out-dir` or `--in-place`").into());
+        }
+
+        // Inputs with the same file name would overwrite each other, or race with several jobs
+        let mut targets = HashMap::new();
+        for input in &inputs {
+            let target = self.output_path(input)?.unwrap_or_default();
+            if let Some(other) = targets.insert(target.clone(), input) {
+                let message = format!("{} and {} would both be written to {}", other.display(), input.display(), target.display());
+                return Err(io::Error::other(message).into());
+            }
+        }
+
+        let next = AtomicUsize::new(0);
+        let outcomes = Mutex::new(Vec::with_capacity(inputs.len()));
+        rayon::scope(|s| {
+            for _ in 0..self.jobs.clamp(1, inputs.len()) {
+                s.spawn(|_| {
+                    while let Some(input) = inputs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let mut file_timings = Timings::default();
+                        let now = Instant::now();
+                        let res = self.process(input, options, &mut file_timings, &transform);
+                        let elapsed = now.elapsed();
+                        if let Err(e) = &res {
+                            error!("{}: {e}", input.display());
+                        }
+                        outcomes.lock().unwrap().push((input, res, elapsed, file_timings));
+                    }
+                });
+            }
+        });
+
+        let mut outcomes = outcomes.into_inner().unwrap();
+        outcomes.sort_by_key(|&(input, ..)| input);
+
+        let width = inputs.iter().map(|input| input.display().to_string().len()).max().unwrap_or(0).max(4);
+        println!("{:width$}  {:>12}  {:>10}  status", "file", "entries", "time");
+        let mut failed = 0;
+        let mut entries = Vec::new();
+        for (input, res, elapsed, file_timings) in outcomes {
+            timings.add(file_timings);
+            let (nvals, status) = match res {
+                Ok((nvals, entry)) => {
+                    entries.extend(entry);
+                    (nvals.to_string(), "ok".to_string())
+                },
+                Err(e) => {
+                    failed += 1;
+                    ("-".to_string(), format!("error: {e}"))
+                },
+            };
+            println!("{:width$}  {nvals:>12}  {:>9.3}s  {status}", input.display().to_string(), elapsed.as_secs_f64());
+        }
+
+        // Files that failed are left out of the manifest
+        self.write_manifest(entries)?;
+        if failed > 0 {
+            return Err(io::Error::other(format!("{failed} of {} files failed", inputs.len())).into());
+        }
+        Ok(())
+    }
+
+    /// Writes the manifest of the written files, if requested.
+    fn write_manifest(&self, entries: Vec<String>) -> Result<(), Error> {
+        if let Some(path) = &self.manifest {
+            fs::write(path, format!("{{\"files\":[{}]}}\n", entries.join(",")))?;
+        }
+        Ok(())
+    }
+
+    /// Path that the result of an input is written to, or `None` for stdout.
+    fn output_path(&self, input: &Path) -> io::Result<Option<PathBuf>> {
+        if self.in_place {
+            return Ok(Some(input.to_path_buf()));
+        }
+        let Some(out_dir) = &self.out_dir else {
+            return Ok(self.output_file.clone());
+        };
+
+        let name = input.file_name().ok_or_else(|| io::Error::other("expected a file name"))?;
+        // Matrices read from archives are written as `.mtx` files named after the archive
+        #[cfg(feature = "archive")]
+        let name = &match archive_stem(input) {
+            Some(stem) => format!("{stem}.mtx").into(),
+            None => name.to_os_string(),
+        };
+        Ok(Some(out_dir.join(name)))
+    }
+
+    /// Transforms a single file, returning the number of entries in the result, and its manifest entry if requested.
+    fn process<F>(&self, input: &Path, options: &ReadOptions, timings: &mut Timings, transform: &F) -> Result<(usize, Option<String>), Error>
+    where
+        F: Fn(Matrix, &mut Timings) -> Result<Matrix, Error>,
+    {
+        #[cfg(feature = "archive")]
+        if self.in_place && is_archive(input) {
+            return Err(io::Error::other("archives cannot be rewritten in place").into());
+        }
+        let mut m = read(input, self.data_type, options, timings)?;
+        if self.row_map.is_some() || self.col_map.is_some() {
+            let load = |path: &Option<PathBuf>| path.as_ref().map(|path| IndexMap::load(File::open(path)?)).transpose();
+            let (row_map, col_map) = (load(&self.row_map)?, load(&self.col_map)?);
+            let nvals = m.nvals();
+            timings.time("relabel", || m.relabel(row_map.as_ref(), col_map.as_ref()))?;
+            info!("relabeled to a {}x{} matrix, dropping {} entries", m.nrows(), m.ncols(), nvals - m.nvals());
+        }
+        if self.drop_empty {
+            let (nrows, ncols) = (m.nrows(), m.ncols());
+            let (rows, cols) = timings.time("compress", || m.compress_empty());
+            info!("dropped {} empty rows and {} empty columns", nrows - rows.len(), ncols - cols.len());
+        }
+        let m = transform(m, timings)?;
+        debug!("{:#?}", m);
+
+        let path = self.output_path(input)?;
+
+        // Uploads only replace the object once they complete, so need no temporary file to write in place
+        if self.in_place && !is_url(input) {
+            write_in_place(input, &m, timings)?;
+        } else {
+            write(path.as_deref(), &m, timings)?;
+        }
+
+        let entry = self.manifest.is_some().then(|| timings.time("manifest", || manifest_entry(path.as_deref(), &m))).transpose()?;
+        Ok((m.nvals(), entry))
+    }
+}
+
+#[derive(Debug, clap::Args)]
+struct MultiIoArgs {
+    #[arg(num_args(2..), required = true)]
+    pub input_files: Vec<PathBuf>,
+
+    #[arg(short('o'))]
+    pub output_file: Option<PathBuf>,
+
+    #[arg(short('t'), long("type"), default_value_t = DataType::Real)]
+    pub data_type: DataType,
 }
 
 #[derive(Copy, Clone, Debug)]
 #[derive(clap::ValueEnum)]
-pub enum SortOrder {
-    RowMajor,
-    ColMajor,
+pub enum Part {
+    Diagonal,
+    Lower,
+    Upper,
 }
 
-impl fmt::Display for SortOrder {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use SortOrder::*;
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum CompactForm {
+    /// Lower triangle of a matrix that equals its transpose.
+    Symmetric,
+    /// Lower triangle of a complex matrix that equals its conjugate transpose, with a real diagonal.
+    Hermitian,
+    /// Strict lower triangle of a matrix that equals its negated transpose, with a zero diagonal.
+    Skew,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum ComplexOp {
+    /// Negate the imaginary parts.
+    Conj,
+    /// Transpose and negate the imaginary parts.
+    ConjTranspose,
+    /// Magnitudes, as a real matrix.
+    Abs,
+    /// Real parts, as a real matrix.
+    Real,
+    /// Imaginary parts, as a real matrix.
+    Imag,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum ReorderMethod {
+    /// Reverse Cuthill-McKee, which reduces the bandwidth and profile.
+    Rcm,
+    /// Rows of smallest degree first.
+    DegreeAsc,
+    /// Rows of largest degree first.
+    DegreeDesc,
+    /// Approximate minimum degree, which reduces the fill of factorizations.
+    Amd,
+    /// Nested dissection, which orders separators last, such that the parts they split can be factorized in parallel.
+    Nd,
+    /// Rows only, such that a maximum transversal makes the diagonal zero-free.
+    Matching,
+    /// Rows only, such that the product of the absolute values on the diagonal is largest, like MC64.
+    WeightedMatching,
+    /// Seeded random permutation.
+    Random,
+}
+
+/// Prints the predicted size of the Cholesky factor for the natural ordering and every deterministic reordering,
+/// with its growth over the lower triangle of the matrix.
+fn print_fill_comparison(m: &Matrix) -> Result<(), Error> {
+    let lower = m.ic_fill(0)?.nnz(0);
+    println!("{:14}  {:>14}  {:>8}", "ordering", "cholesky nnz", "growth");
+    let print = |name: &str, m: &Matrix| -> Result<(), Error> {
+        let nnz: usize = m.cholesky_counts()?.iter().sum();
+        println!("{name:14}  {nnz:>14}  {:>7.2}x", nnz as f64 / lower.max(1) as f64);
+        Ok(())
+    };
+    print("natural", m)?;
+    for method in ReorderMethod::value_variants().iter().filter(|method| !matches!(method, ReorderMethod::Random)) {
+        let Some(reordering) = method.reordering(0) else { continue };
+        let mut reordered = m.clone();
+        reordered.reorder(reordering)?;
+        print(method.to_possible_value().expect("no variant is skipped").get_name(), &reordered)?;
+    }
+    println!();
+    Ok(())
+}
+
+impl ReorderMethod {
+    /// Symmetric reordering of the method, or `None` for the matchings, which permute the rows only.
+    fn reordering(self, seed: u64) -> Option<Reordering> {
         match self {
-            RowMajor => write!(f, "row-major"),
-            ColMajor => write!(f, "col-major"),
+            ReorderMethod::Rcm => Some(Reordering::Rcm),
+            ReorderMethod::DegreeAsc => Some(Reordering::DegreeAscending),
+            ReorderMethod::DegreeDesc => Some(Reordering::DegreeDescending),
+            ReorderMethod::Amd => Some(Reordering::Amd),
+            ReorderMethod::Nd => Some(Reordering::NestedDissection),
+            ReorderMethod::Random => Some(Reordering::Random { seed }),
+            ReorderMethod::Matching | ReorderMethod::WeightedMatching => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum GraphTransform {
+    /// Pattern of the edges in either direction, without self-loops, in the lower triangle of a symmetric matrix.
+    Adjacency,
+    /// Degrees minus the adjacency matrix.
+    Laplacian,
+    /// Identity minus the adjacency matrix scaled by the inverse square roots of the degrees.
+    NormalizedLaplacian,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum PartitionBy {
+    /// Blocks with the same number of rows and columns.
+    Rows,
+    /// Blocks with about the same number of entries.
+    Nonzeros,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum SplitBy {
+    /// One matrix per connected component.
+    Component,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum ElementwiseOp {
+    /// Sum, on the union of the patterns.
+    Add,
+    /// Difference, on the union of the patterns.
+    Sub,
+    /// Product, on the intersection of the patterns.
+    Hadamard,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum ConvertFormat {
+    /// A line of space-separated values per row, including the zeros.
+    Dense,
+    /// Flat little-endian arrays `rows.bin`, `cols.bin`, and `vals.bin` with a descriptor `matrix.json`,
+    /// written to the directory given by `-o`.
+    Raw,
+    /// NumPy arrays `rows.npy`, `cols.npy`, `vals.npy`, and `shape.npy`, written to the directory given by `-o`.
+    Npy,
+    /// A JSON object `{"r":..,"c":..,"v":..}` per entry and line.
+    Jsonl,
+    /// A single JSON object with the dimensions and the arrays of rows, columns, and values.
+    Json,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(clap::ValueEnum)]
+pub enum ConcatMode {
+    Hstack,
+    Vstack,
+    Blockdiag,
+}
+
+/// Wall-clock time spent in each phase of a run.
+#[derive(Debug, Default)]
+struct Timings(Vec<(&'static str, Duration)>);
+
+impl Timings {
+    /// Runs `f` as the given phase, logging and recording how long it took.
+    fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let _span = tracing::info_span!("phase", phase = %phase).entered();
+        let now = Instant::now();
+        let res = f();
+        let elapsed = now.elapsed();
+        info!("{phase}: {elapsed:?}");
+        self.0.push((phase, elapsed));
+        res
+    }
+}
+
+impl Timings {
+    /// Adds the timings of another run, summing phases with the same name.
+    fn add(&mut self, other: Timings) {
+        for (phase, elapsed) in other.0 {
+            match self.0.iter_mut().find(|(p, _)| *p == phase) {
+                Some((_, total)) => *total += elapsed,
+                None => self.0.push((phase, elapsed)),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Timings {
+    /// Formats the timings as a JSON object, in seconds.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (phase, elapsed)) in self.0.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{sep}\"{phase}\":{}", elapsed.as_secs_f64())?;
         }
+        write!(f, "}}")
     }
 }
 
-fn main() -> io::Result<()> {
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let level = match (args.quiet, args.verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+
+    env_logger::Builder::new()
+        .filter_level(level)
+        .format_timestamp(None)
+        .format_target(false)
+        .parse_default_env()
+        .init();
+
+    // The guard writes the end of the trace when it is dropped, after the command has run
+    let _trace = match args.trace_out.as_deref().map(trace_to).transpose() {
+        Ok(guard) => guard,
+        Err(e) => {
+            error!("could not create the trace file: {e}");
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let config = ParallelConfig::new().threads(args.threads);
+    match config.install(|| run(args)).and_then(|res| res) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{e}");
+            if let Some(data_type) = e.suggested_type() {
+                info!("rerun with `--type {data_type}` to read it that way");
+            }
+            if let Error::IntegerOverflow { bits: 32, .. } = e {
+                match IntWidth::default() {
+                    IntWidth::I32 => info!("integers are 64-bit in a build with the `x64` feature"),
+                    IntWidth::I64 => info!("rerun with `--int-width 64` to read 64-bit integers"),
+                }
+            }
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// Records the spans of the library and of the phases in a Chrome-trace file.
+fn trace_to(path: &Path) -> io::Result<tracing_chrome::FlushGuard> {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+        .writer(File::create(path)?)
+        .include_args(true)
+        .build();
+    // Only the spans go to the trace, as the log messages already go to `env_logger`
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer)).map_err(io::Error::other)?;
+    Ok(guard)
+}
+
+fn run(args: Args) -> Result<(), Error> {
     let Args {
-        input_file,
-        output_file,
-        data_type,
-        sort_order,
-    } = Args::parse();
+        command,
+        timing_json,
+        count,
+        nonfinite,
+        lenient_numbers,
+        round_to_f32,
+        int_width,
+        hex_floats,
+        align,
+        mmap_write,
+        expand_symmetric,
+        verify,
+        verify_ulps,
+        verify_abs,
+        deterministic,
+        #[cfg(feature = "archive")]
+        member,
+        ..
+    } = args;
+
+    #[cfg(feature = "archive")]
+    let _ = MEMBER.set(member);
+    let _ = OUTPUT.set(Output {
+        format: TextFormat::new().hex_floats(hex_floats).align(align),
+        mmap_write,
+        expand_symmetric,
+        verify: verify.then(|| Tolerance::new().ulps(verify_ulps).abs(verify_abs)),
+    });
+
+    if int_width == IntWidth::I64 && IntWidth::default() == IntWidth::I32 {
+        return Err(io::Error::other("`--int-width 64` needs a build with the `x64` feature").into());
+    }
+    let options = ReadOptions::new()
+        .count_policy(count)
+        .nonfinite_policy(nonfinite)
+        .lenient_numbers(lenient_numbers)
+        .round_to_f32(round_to_f32)
+        .int_width(int_width);
+
+    let mut timings = Timings::default();
+
+    match command {
+        Command::Sort { io, sort_order, stable, tie_break, block_rows, block_cols, strategy, mem_limit, checkpoint_dir, resume } => {
+            if block_rows == Some(0) || block_cols == Some(0) {
+                return Err(io::Error::other("block dimensions must be positive").into());
+            }
+            if checkpoint_dir.is_some() && expand_globs(&io.input_files)?.len() > 1 {
+                return Err(io::Error::other("`--checkpoint-dir` takes a single input").into());
+            }
+
+            let strategy = match mem_limit {
+                Some(limit) => strategy_within(&io, limit)?,
+                None => strategy,
+            };
+            let sort_options = SortOptions::new().stable(stable || deterministic).tie_break(tie_break).strategy(strategy);
+
+            io.run(&options, &mut timings, |mut m, timings| {
+                Matrix::check_addressable(m.nrows(), m.ncols(), m.nvals(), m.data_type(), Some(strategy))?;
+                timings.time("sort", || match (block_rows, block_cols, &checkpoint_dir) {
+                    (Some(block_rows), Some(block_cols), _) => {
+                        m.sort_blocked_with(block_rows, block_cols, &sort_options);
+                        Ok(())
+                    },
+                    (_, _, Some(dir)) => m.sort_checkpointed(sort_order, &sort_options, dir, resume),
+                    _ => {
+                        if m.is_sorted(sort_order) {
+                            debug!("entries are already in {sort_order} order");
+                        }
+                        m.sort_with(sort_order, &sort_options)
+                    },
+                })?;
+                Ok(m)
+            })?;
+        },
+        Command::Scale { io, equilibrate, row_factors, col_factors } => {
+            let load = |path: Option<PathBuf>| -> Result<Option<Vec<f64>>, Error> {
+                path.map(|path| Vector::from_reader(io::BufReader::new(File::open(path)?), DataType::Real)?.to_vec()).transpose()
+            };
+            let (row_factors, col_factors) = (load(row_factors)?, load(col_factors)?);
+
+            io.run(&options, &mut timings, |mut m, timings| {
+                if equilibrate {
+                    let (row_factors, col_factors) = timings.time("scale", || m.equilibrate());
+                    debug!("row factors: {:?}", &row_factors[..row_factors.len().min(5)]);
+                    debug!("col factors: {:?}", &col_factors[..col_factors.len().min(5)]);
+                    return Ok(m);
+                }
+
+                for (factors, n) in [(&row_factors, m.nrows()), (&col_factors, m.ncols())] {
+                    if let Some(factors) = factors && factors.len() != n {
+                        return Err(Error::DimensionMismatch { expected: (n, 1), found: (factors.len(), 1) });
+                    }
+                }
+                timings.time("scale", || {
+                    if let Some(factors) = &row_factors {
+                        m.scale_rows(factors);
+                    }
+                    if let Some(factors) = &col_factors {
+                        m.scale_cols(factors);
+                    }
+                });
+                Ok(m)
+            })?;
+        },
+        Command::Extract { io, part, strict } => {
+            io.run(&options, &mut timings, |m, timings| {
+                Ok(timings.time("extract", || match part {
+                    Part::Diagonal => m.diagonal(),
+                    Part::Lower => m.lower_triangle(strict),
+                    Part::Upper => m.upper_triangle(strict),
+                }))
+            })?;
+        },
+        Command::Filter { io, keep_rows, keep_cols, band, min_value, max_value } => {
+            let rows = keep_rows.unwrap_or(1..=usize::MAX);
+            let cols = keep_cols.unwrap_or(1..=usize::MAX);
+            let keep = |row, col| rows.contains(&row) && cols.contains(&col) && band.is_none_or(|k| row.abs_diff(col) <= k);
+            let in_range = |x: Real| min_value.is_none_or(|min| x >= min) && max_value.is_none_or(|max| x <= max);
+            io.run(&options, &mut timings, |mut m, timings| {
+                let nvals = m.nvals();
+                timings.time("filter", || match m.data_type() {
+                    DataType::Complex if min_value.is_some() || max_value.is_some() => {
+                        Err(Error::TypeMismatch { expected: "real", found: "complex" })
+                    },
+                    DataType::Complex => m.retain(|row, col, _: Complex<Real>| keep(row, col)),
+                    DataType::Integer => m.retain(|row, col, x: Int| keep(row, col) && in_range(x as Real)),
+                    _ => m.retain(|row, col, x: Real| keep(row, col) && in_range(x)),
+                })?;
+                info!("kept {} of {nvals} entries", m.nvals());
+                Ok(m)
+            })?;
+        },
+        Command::Complex { io, op } => {
+            io.run(&options, &mut timings, |mut m, timings| {
+                timings.time("complex", || match op {
+                    ComplexOp::Conj => {
+                        m.conjugate();
+                        Ok(m)
+                    },
+                    ComplexOp::ConjTranspose => {
+                        m.conjugate_transpose();
+                        Ok(m)
+                    },
+                    ComplexOp::Abs => Ok(m.abs()),
+                    ComplexOp::Real => m.real_part(),
+                    ComplexOp::Imag => m.imag_part(),
+                })
+            })?;
+        },
+        Command::Reorder { io, method, seed, permutation, save_permutation, perm_format, compare_fill, rhs, rhs_out } => {
+            let reordering = method.reordering(seed);
+            if (save_permutation.is_some() || rhs.is_some()) && io.input_files.len() > 1 {
+                return Err(io::Error::other("`--save-permutation` and `--rhs` take a single input").into());
+            }
+            let permutation = permutation.map(|path| Permutation::load(File::open(path)?, perm_format)).transpose()?;
+
+            let rhs = match rhs {
+                Some(path) => {
+                    let out = match (rhs_out, &io.out_dir) {
+                        (Some(out), _) => Some(out),
+                        (None, _) if io.in_place => Some(path.clone()),
+                        (None, Some(out_dir)) => Some(out_dir.join(path.file_name().ok_or_else(|| io::Error::other("expected a file name"))?)),
+                        (None, None) if io.output_file.is_some() => {
+                            return Err(io::Error::other("`--rhs` with `-o` requires `--rhs-out`").into());
+                        },
+                        (None, None) => None,
+                    };
+                    let rhs = timings.time("read", || Vector::from_bytes(&fs::read(&path)?, io.data_type))?;
+                    Some((rhs, out))
+                },
+                None => None,
+            };
+
+            io.run(&options, &mut timings, |mut m, timings| {
+                if compare_fill {
+                    timings.time("fill", || print_fill_comparison(&m))?;
+                }
+                let missing_before = reordering.is_none().then(|| m.missing_diagonal().len());
+                let before = (m.bandwidth(), m.profile(), m.rms_wavefront());
+                let permutation = match &permutation {
+                    Some(permutation) => {
+                        let n = permutation.len();
+                        if (m.nrows(), m.ncols()) != (n, n) {
+                            return Err(Error::DimensionMismatch { expected: (n, n), found: (m.nrows(), m.ncols()) });
+                        }
+                        timings.time("reorder", || m.permute_symmetric(permutation.indices()));
+                        permutation.clone()
+                    },
+                    None => {
+                        let permutation = match reordering {
+                            Some(reordering) => Permutation::new(timings.time("reorder", || m.reorder(reordering))?)?,
+                            None => {
+                                // Permuting only the rows breaks the symmetry of a compact symmetric form
+                                m.expand_symmetric();
+                                let rows = timings.time("match", || if matches!(method, ReorderMethod::WeightedMatching) {
+                                    m.weighted_transversal()
+                                } else {
+                                    m.transversal()
+                                })?;
+                                let cols: Vec<_> = (0..m.ncols()).collect();
+                                timings.time("reorder", || m.permute(&rows, &cols))?;
+                                Permutation::new(rows)?
+                            },
+                        };
+                        if let Some(path) = &save_permutation {
+                            permutation.save(BufWriter::new(File::create(path)?), perm_format)?;
+                        }
+                        permutation
+                    },
+                };
+                let after = (m.bandwidth(), m.profile(), m.rms_wavefront());
+
+                if let Some((rhs, out)) = &rhs {
+                    if rhs.len() != m.nrows() {
+                        return Err(Error::DimensionMismatch { expected: (m.nrows(), 1), found: (rhs.len(), 1) });
+                    }
+                    let mut rhs = rhs.clone();
+                    rhs.permute(permutation.indices());
+                    if let Some(out) = out {
+                        timings.time("write", || fs::write(out, rhs.to_string()))?;
+                    }
+                }
+
+                println!("{:14}  {:>12}  {:>12}", "", "before", "after");
+                println!("{:14}  {:>12}  {:>12}", "bandwidth", before.0, after.0);
+                println!("{:14}  {:>12}  {:>12}", "profile", before.1, after.1);
+                println!("{:14}  {:>12.2}  {:>12.2}", "rms wavefront", before.2, after.2);
+                if let Some(missing_before) = missing_before {
+                    println!("{:14}  {:>12}  {:>12}", "missing diag", missing_before, m.missing_diagonal().len());
+                }
+                Ok(m)
+            })?;
+        },
+        Command::Dataset { input_file, output_file, reorder, seed, equilibrate, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let mut dataset = Dataset::with_members(m, &input_file)?;
+            info!("right-hand side: {}, coordinates: {}", dataset.rhs.is_some(), dataset.coords.is_some());
+            if let Some(method) = reorder {
+                let reordering = method.reordering(seed).ok_or_else(|| io::Error::other("`dataset` only reorders symmetrically"))?;
+                timings.time("reorder", || dataset.reorder(reordering))?;
+            }
+            if equilibrate {
+                timings.time("scale", || dataset.equilibrate());
+            }
+            write(Some(&output_file), &dataset.matrix, &mut timings)?;
+            timings.time("write", || dataset.write_members(&output_file))?;
+        },
+        Command::Diagonal { io, ensure_diagonal } => {
+            io.run(&options, &mut timings, |mut m, timings| {
+                let (missing, zero) = timings.time("diagonal", || (m.missing_diagonal(), m.zero_diagonal()));
+                println!("missing diagonal: {} rows{}", missing.len(), rows_list(&missing));
+                println!("zero diagonal:    {} rows{}", zero.len(), rows_list(&zero));
+
+                if let Some(value) = ensure_diagonal {
+                    let inserted = timings.time("ensure", || m.ensure_diagonal(value))?;
+                    info!("inserted {inserted} diagonal entries");
+                }
+                Ok(m)
+            })?;
+        },
+        Command::Compress { io, tol, form } => {
+            io.run(&options, &mut timings, |mut m, timings| {
+                let report = timings.time("detect", || m.detect_symmetry(tol))?;
+                info!("{:?}", report);
+                let (holds, name) = match form {
+                    CompactForm::Symmetric => (report.symmetric, "symmetric"),
+                    CompactForm::Hermitian => (report.hermitian, "hermitian"),
+                    CompactForm::Skew => (report.skew, "skew-symmetric"),
+                };
+                if !holds {
+                    return Err(io::Error::other(format!("matrix is not {name} within tolerance {tol}")).into());
+                }
+
+                timings.time("compress", || match form {
+                    CompactForm::Symmetric => m.compress_symmetric(),
+                    CompactForm::Hermitian => m.compress_hermitian(tol),
+                    CompactForm::Skew => m.compress_skew(),
+                })?;
+                Ok(m)
+            })?;
+        },
+        Command::Merge { io, duplicates } => {
+            let (first, rest) = io.input_files.split_first().unwrap();
+            let mut m = read(first, io.data_type, &options, &mut timings)?;
+
+            for path in rest {
+                let other = read(path, io.data_type, &options, &mut timings)?;
+                timings.time("merge", || m.merge(&other, duplicates))?;
+            }
+            debug!("{:#?}", m);
+
+            write(io.output_file.as_deref(), &m, &mut timings)?;
+        },
+        Command::Elementwise { io, op } => {
+            let (first, rest) = io.input_files.split_first().unwrap();
+            let mut m = read(first, io.data_type, &options, &mut timings)?;
 
-    let file = File::open(input_file)?;
+            for path in rest {
+                let other = read(path, io.data_type, &options, &mut timings)?;
+                m = timings.time("elementwise", || match op {
+                    ElementwiseOp::Add => m.add(&other),
+                    ElementwiseOp::Sub => m.sub(&other),
+                    ElementwiseOp::Hadamard => m.hadamard(&other),
+                })?;
+            }
+            debug!("{:#?}", m);
+
+            write(io.output_file.as_deref(), &m, &mut timings)?;
+        },
+        Command::Concat { io, mode } => {
+            let ms = io.input_files.iter()
+                .map(|path| read(path, io.data_type, &options, &mut timings))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let m = timings.time("concat", || match mode {
+                ConcatMode::Hstack => Matrix::hstack(&ms),
+                ConcatMode::Vstack => Matrix::vstack(&ms),
+                ConcatMode::Blockdiag => Matrix::block_diag(&ms),
+            })?;
+            debug!("{:#?}", m);
+
+            write(io.output_file.as_deref(), &m, &mut timings)?;
+        },
+        Command::Run { plan_file } => {
+            let plan = pipeline::Plan::from_yaml(&fs::read_to_string(plan_file)?)?;
+            debug!("steps: {:?}", plan.pipeline.names().collect::<Vec<_>>());
+
+            let io = IoArgs {
+                input_files: plan.inputs,
+                output_file: plan.output,
+                in_place: plan.in_place,
+                out_dir: plan.out_dir,
+                jobs: plan.jobs,
+                data_type: plan.data_type,
+                row_map: None,
+                col_map: None,
+                drop_empty: false,
+                manifest: plan.manifest,
+            };
+            io.run(&options, &mut timings, |m, timings| {
+                plan.pipeline.run_with(m, |step, m| timings.time(step.name(), || step.apply(m)))
+            })?;
+        },
+        Command::Generate { generator, output_file, seed, shuffle } => {
+            let mut m = timings.time("generate", || match generator {
+                Generator::ErdosRenyi { nrows, ncols, density, data_type } => {
+                    generators::erdos_renyi(nrows, ncols, density, data_type, seed)
+                },
+                Generator::Banded { n, lower, upper, data_type } => {
+                    generators::banded(n, lower, upper, data_type, seed)
+                },
+                Generator::Spd { n, density } => generators::diagonally_dominant(n, density, seed),
+                Generator::Laplacian2d { nx, ny } => generators::laplacian_2d(nx, ny),
+                Generator::Laplacian3d { nx, ny, nz } => generators::laplacian_3d(nx, ny, nz),
+            });
+
+            if shuffle {
+                timings.time("shuffle", || m.shuffle(seed));
+            }
+            debug!("{:#?}", m);
+
+            write(output_file.as_deref(), &m, &mut timings)?;
+        },
+        Command::Spy { input_file, output_file, width, height } => {
+            // Only the pattern is needed, so we skip parsing the values
+            let m = read(&input_file, DataType::Bool, &options, &mut timings)?;
+
+            let height = height.unwrap_or_else(|| (width * m.nrows()).div_ceil(m.ncols().max(1)).max(1));
+            let spy = timings.time("spy", || m.spy(width, height));
+
+            let file = File::create(&output_file)?;
+            let wtr = BufWriter::new(file);
+            let extension = output_file.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+            timings.time("write", || match extension.to_ascii_lowercase().as_str() {
+                "pgm" => spy.write_pgm(wtr),
+                "ppm" => spy.write_ppm(wtr),
+                _ => spy.write_png(wtr),
+            })?;
+        },
+        Command::Stats { input_file, data_type, spy, histogram, json, tol, gershgorin, gershgorin_csv, norms, bands, top_k, per_row, per_col } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+
+            let mut stats = timings.time("stats", || m.stats());
+            if tol > 0.0 {
+                stats.symmetry_score = timings.time("symmetry", || m.symmetry_score(tol));
+            }
+            stats.bands = bands.iter().copied().zip(m.band_fractions(&bands)).collect();
+            if let Some(k) = top_k {
+                stats.largest = timings.time("largest", || m.largest_entries(k));
+            }
+            let profile = histogram.map(|bins| timings.time("profile", || m.value_profile(bins)));
+            let discs = (gershgorin || gershgorin_csv.is_some())
+                .then(|| timings.time("gershgorin", || m.gershgorin()))
+                .transpose()?;
+
+            if let (Some(discs), Some(path)) = (&discs, &gershgorin_csv) {
+                discs.write_csv(BufWriter::new(File::create(path)?))?;
+            }
+            if let Some(path) = &per_row {
+                timings.time("per-row", || m.row_metrics()).write_csv(BufWriter::new(File::create(path)?))?;
+            }
+            if let Some(path) = &per_col {
+                timings.time("per-col", || m.col_metrics()).write_csv(BufWriter::new(File::create(path)?))?;
+            }
+            let discs = discs.filter(|_| gershgorin);
+            let norms = norms.then(|| timings.time("norms", || m.norms())).transpose()?;
+
+            if json {
+                let mut fields = vec![format!("\"stats\":{}", stats.to_json())];
+                if let Some(profile) = &profile {
+                    fields.push(format!("\"profile\":{}", profile.to_json()));
+                }
+                if let Some(discs) = &discs {
+                    fields.push(format!("\"gershgorin\":{}", discs.to_json()));
+                }
+                if let Some(norms) = &norms {
+                    fields.push(format!("\"norms\":{}", norms.to_json()));
+                }
+                println!("{{{}}}", fields.join(","));
+            } else {
+                print!("{}", stats);
+                if let Some(profile) = &profile {
+                    print!("{}", profile);
+                }
+                if let Some(discs) = &discs {
+                    print!("{}", discs);
+                }
+                if let Some(norms) = &norms {
+                    print!("{}", norms);
+                }
+            }
+
+            if let Some(cols) = spy {
+                print!("{}", m.spy_ascii(cols));
+            }
+        },
+        Command::Fill { input_file, levels, ic, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let fill = timings.time("fill", || if ic { m.ic_fill(levels) } else { m.ilu_fill(levels) })?;
+            let name = if ic { "IC" } else { "ILU" };
+            println!("{:8}  {:>12}  {:>12}  {:>8}", "level", "new entries", "entries", "growth");
+            for k in 0..=levels {
+                println!("{:8}  {:>12}  {:>12}  {:>7.2}x", format!("{name}({k})"), fill.levels[k], fill.nnz(k), fill.growth(k));
+            }
+        },
+        Command::Btf { input_file, output_file, data_type } => {
+            let mut m = read(&input_file, data_type, &options, &mut timings)?;
+
+            let btf = timings.time("btf", || m.btf());
+            let [under, square, over] = btf.coarse;
+            println!("structural rank: {} of {}", btf.structural_rank, m.nrows().min(m.ncols()));
+            println!("underdetermined: {} x {}", under.0, under.1);
+            println!("square:          {} x {} in {} blocks, the largest of size {}",
+                square.0, square.1, btf.blocks.len(), btf.blocks.iter().max().unwrap_or(&0));
+            println!("overdetermined:  {} x {}", over.0, over.1);
+
+            if let Some(output_file) = output_file {
+                timings.time("permute", || m.permute(&btf.row_permutation, &btf.col_permutation))?;
+                write(Some(&output_file), &m, &mut timings)?;
+            }
+        },
+        Command::Bsr { input_file, output_file, block_size, max_block_size, min_fill, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+
+            let block_size = match block_size {
+                Some(0) => return Err(io::Error::other("block size must be positive").into()),
+                Some(block_size) => block_size,
+                None => timings.time("detect", || m.detect_block_size(max_block_size, min_fill)),
+            };
+            info!("block size {block_size}, with {:.1}% fill", 100.0 * m.block_fill(block_size));
+
+            let bsr = timings.time("convert", || m.to_bsr(block_size))?;
+            let wtr = BufWriter::new(File::create(output_file)?);
+            timings.time("write", || bsr.write(wtr))?;
+        },
+        Command::Ell { input_file, output_file, chunk_size, sigma, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let wtr = BufWriter::new(File::create(output_file)?);
+
+            match chunk_size {
+                Some(0) => return Err(io::Error::other("chunk size must be positive").into()),
+                _ if sigma == 0 => return Err(io::Error::other("sigma must be positive").into()),
+                Some(chunk_size) => {
+                    let sell = timings.time("convert", || m.to_sell(chunk_size, sigma))?;
+                    info!("{} chunks, {:.1}% padding", sell.nchunks(), padding(sell.cols.len(), m.nvals()));
+                    timings.time("write", || sell.write(wtr))?;
+                },
+                None => {
+                    let ell = timings.time("convert", || m.to_ell())?;
+                    info!("width {}, {:.1}% padding", ell.width, padding(ell.cols.len(), m.nvals()));
+                    timings.time("write", || ell.write(wtr))?;
+                },
+            }
+        },
+        Command::Convert { input_file, output_file, to, max_elements, index_type, one_based, value_precision, records, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let raw_options = RawOptions::new().index_type(index_type).one_based(one_based).precision(value_precision.unwrap_or_default());
+            match to {
+                ConvertFormat::Raw => {
+                    let dir = output_file.ok_or_else(|| io::Error::other("`--to raw` needs an output directory, given by `-o`"))?;
+                    timings.time("write", || as_written(&m).write_raw(&dir, &raw_options))?;
+                },
+                ConvertFormat::Jsonl | ConvertFormat::Json => {
+                    let m = as_written(&m);
+                    let wtr: Box<dyn Write> = match output_file {
+                        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                        None => Box::new(io::stdout().lock()),
+                    };
+                    timings.time("write", || if matches!(to, ConvertFormat::Jsonl) { m.write_jsonl(wtr) } else { m.write_json(wtr) })?;
+                },
+                ConvertFormat::Npy => {
+                    let path = output_file.ok_or_else(|| io::Error::other("`--to npy` needs an output path, given by `-o`"))?;
+                    timings.time("write", || if records {
+                        as_written(&m).write_npy_records(BufWriter::new(File::create(&path)?), &raw_options)
+                    } else {
+                        as_written(&m).write_npy(&path, &raw_options)
+                    })?;
+                },
+                ConvertFormat::Dense => match output_file {
+                    Some(path) => {
+                        // Checked before creating the file, which would otherwise be left empty
+                        m.dense_len(max_elements)?;
+                        let wtr = BufWriter::new(File::create(path)?);
+                        timings.time("write", || m.write_dense(wtr, max_elements))?;
+                    },
+                    None => m.write_dense(io::stdout().lock(), max_elements)?,
+                },
+            }
+        },
+        Command::Graphblas { input_file, output_file, format, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let wtr = BufWriter::new(File::create(output_file)?);
+            timings.time("write", || m.write_graphblas(wtr, format))?;
+        },
+        #[cfg(feature = "arrow")]
+        Command::Arrow { input_file, output_file, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let parquet = output_file.extension().is_some_and(|ext| ext == "parquet");
+            let wtr = BufWriter::new(File::create(output_file)?);
+            if parquet {
+                timings.time("write", || m.write_parquet(wtr))?;
+            } else {
+                timings.time("write", || m.write_arrow_ipc(wtr))?;
+            }
+        },
+        #[cfg(feature = "sqlite")]
+        Command::Sqlite { input_file, output_file, table, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            timings.time("write", || m.write_sqlite(&output_file, &table))?;
+        },
+        #[cfg(feature = "hdf5")]
+        Command::Hdf5 { input_file, output_file, data_type } => {
+            if input_file.extension().is_some_and(|ext| ext == "h5" || ext == "hdf5") {
+                let m = timings.time("read", || Matrix::from_hdf5(&input_file, data_type))?;
+                write(Some(&output_file), &m, &mut timings)?;
+            } else {
+                let m = read(&input_file, data_type, &options, &mut timings)?;
+                timings.time("write", || m.write_hdf5(&output_file))?;
+            }
+        },
+        #[cfg(feature = "hdf5")]
+        Command::Mat { input_file, output_file, name, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            timings.time("write", || m.write_mat(&output_file, &name))?;
+        },
+        Command::Tensor { input_file, output_file, modes, data_type } => {
+            let file = File::open(&input_file)?;
+            let mut t = timings.time("read", || Tensor::from_mmap(file, data_type))?;
+            info!("{} modes of {:?}, {} entries", t.ndims(), t.dims(), t.nvals());
+
+            let modes = modes.unwrap_or_else(|| (0..t.ndims()).collect());
+            timings.time("sort", || t.sort(&modes))?;
+
+            if let Some(path) = output_file {
+                let mut wtr = BufWriter::new(File::create(path)?);
+                timings.time("write", || {
+                    write!(wtr, "{t}")?;
+                    wtr.flush()
+                })?;
+            }
+        },
+        Command::Graph { input_file, output_file, format, transform, data_type } => {
+            let mtx = input_file.extension().is_some_and(|ext| ext == "mtx");
+            let m = match format {
+                Some(format) if !mtx => {
+                    let bytes = fs::read(&input_file)?;
+                    timings.time("read", || Matrix::from_graph(&bytes, format, data_type))?
+                },
+                None if !mtx => return Err(io::Error::other("expected `--format` to read a graph").into()),
+                _ => read(&input_file, data_type, &options, &mut timings)?,
+            };
+
+            let m = match transform {
+                Some(transform) => timings.time("transform", || match transform {
+                    GraphTransform::Adjacency => m.to_adjacency(),
+                    GraphTransform::Laplacian => m.to_laplacian(),
+                    GraphTransform::NormalizedLaplacian => m.to_normalized_laplacian(),
+                })?,
+                None => m,
+            };
+
+            match format {
+                Some(format) if mtx => {
+                    let wtr = BufWriter::new(File::create(output_file)?);
+                    timings.time("write", || m.write_graph(wtr, format))?;
+                },
+                _ => write(Some(&output_file), &m, &mut timings)?,
+            }
+        },
+        Command::Partition { input_file, parts, col_parts, by, local, out_dir, data_type } => {
+            if parts == 0 || col_parts == 0 {
+                return Err(io::Error::other("expected at least one part").into());
+            }
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+
+            let (row_blocks, col_blocks) = match by {
+                PartitionBy::Rows => (m.row_blocks(parts), m.col_blocks(col_parts)),
+                PartitionBy::Nonzeros => (m.balanced_row_blocks(parts), m.balanced_col_blocks(col_parts)),
+            };
+            let ms = timings.time("partition", || m.partition_blocks(&row_blocks, &col_blocks, local))?;
+
+            let counts: Vec<_> = ms.iter().map(|part| part.nvals()).collect();
+            let imbalance = Imbalance::new(&counts);
+            println!("{imbalance}");
+
+            fs::create_dir_all(&out_dir)?;
+            let stem = input_file.file_stem().ok_or_else(|| io::Error::other("expected a file name"))?.to_string_lossy();
+            let mut entries = Vec::with_capacity(ms.len());
+            for (k, part) in ms.iter().enumerate() {
+                let name = format!("{stem}.{k}.mtx");
+                write(Some(&out_dir.join(&name)), part, &mut timings)?;
+                let (rows, cols) = (&row_blocks[k / col_parts], &col_blocks[k % col_parts]);
+                entries.push(format!(
+                    "{{\"file\":{name:?},\"first_row\":{},\"nrows\":{},\"first_col\":{},\"ncols\":{},{}}}",
+                    rows.start + 1, rows.len(), cols.start + 1, cols.len(), manifest_fields(&as_written(part))?,
+                ));
+            }
+
+            let manifest = format!(
+                "{{\"nrows\":{},\"ncols\":{},\"nvals\":{},\"grid\":[{parts},{col_parts}],\"indices\":\"{}\",\"imbalance\":{},\"parts\":[{}]}}\n",
+                m.nrows(), m.ncols(), m.nvals(), if local { "local" } else { "global" }, imbalance.to_json(), entries.join(","),
+            );
+            fs::write(out_dir.join(format!("{stem}.json")), manifest)?;
+        },
+        Command::Vector { input_file, output_file, scale, permutation, inverse, perm_format, data_type } => {
+            let mut v = timings.time("read", || Vector::from_reader(io::BufReader::new(File::open(&input_file)?), data_type))?;
+            debug!("{:?}", v);
+
+            if let Some(path) = permutation {
+                let mut permutation = Permutation::load(File::open(path)?, perm_format)?;
+                if permutation.len() != v.len() {
+                    return Err(Error::DimensionMismatch { expected: (v.len(), 1), found: (permutation.len(), 1) });
+                }
+                if inverse {
+                    permutation = permutation.inverse();
+                }
+                timings.time("permute", || v.permute(permutation.indices()));
+            }
+            if let Some(factor) = scale {
+                timings.time("scale", || v.scale(factor));
+            }
+
+            if let Some(path) = output_file {
+                timings.time("write", || fs::write(path, v.to_string()))?;
+            }
+        },
+        Command::Perm { action, format, out_format } => {
+            let load = |path: &Path| Permutation::load(File::open(path)?, format);
+            let save = |path: &Path, permutation: &Permutation| {
+                permutation.save(BufWriter::new(File::create(path)?), out_format.unwrap_or(format))
+            };
+
+            match action {
+                PermAction::Invert { input_file, output_file } => {
+                    save(&output_file, &load(&input_file)?.inverse())?;
+                },
+                PermAction::Compose { input_files, output_file } => {
+                    let mut permutation = load(&input_files[0])?;
+                    for path in &input_files[1..] {
+                        permutation = permutation.then(&load(path)?)?;
+                    }
+                    save(&output_file, &permutation)?;
+                },
+                PermAction::Verify { input_file, len } => {
+                    let permutation = load(&input_file)?;
+                    if let Some(len) = len && len != permutation.len() {
+                        return Err(io::Error::other(format!("expected a permutation of length {len}, found {}", permutation.len())).into());
+                    }
+                    println!("valid permutation of length {}{}", permutation.len(), if permutation.is_identity() { " (identity)" } else { "" });
+                },
+            }
+        },
+        Command::Split { input_file, by, out_dir, data_type } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+
+            let parts = match by {
+                SplitBy::Component => timings.time("components", || m.split_components()),
+            };
+            info!("{} parts", parts.len());
+
+            fs::create_dir_all(&out_dir)?;
+            let stem = input_file.file_stem().ok_or_else(|| io::Error::other("expected a file name"))?.to_string_lossy();
+            let mut entries = Vec::with_capacity(parts.len());
+            for (k, part) in parts.iter().enumerate() {
+                let name = format!("{stem}.{k}.mtx");
+                write(Some(&out_dir.join(&name)), part, &mut timings)?;
+                entries.push(manifest_entry(Some(Path::new(&name)), part)?);
+            }
+
+            let manifest = format!("{{\"nrows\":{},\"ncols\":{},\"nvals\":{},\"parts\":[{}]}}\n", m.nrows(), m.ncols(), m.nvals(), entries.join(","));
+            fs::write(out_dir.join(format!("{stem}.json")), manifest)?;
+        },
+        Command::BenchSpmv { input_file, data_type, iterations } => {
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+            let complex = matches!(data_type, DataType::Complex);
+
+            println!("{:10}  {:>12}  {:>10}", "layout", "per product", "GFLOP/s");
+            for sort_order in [SortOrder::RowMajor, SortOrder::ColMajor, SortOrder::Morton, SortOrder::Hilbert] {
+                let mut m = m.clone();
+                m.sort(sort_order)?;
+
+                let elapsed = timings.time("spmv", || {
+                    let (x, z) = (vec![1.0; m.ncols()], vec![Complex::new(1.0, 0.0); m.ncols()]);
+                    // One product up front, to warm the caches
+                    let mut elapsed = Duration::ZERO;
+                    for k in 0..=iterations {
+                        let now = Instant::now();
+                        if complex {
+                            std::hint::black_box(m.spmv_complex(std::hint::black_box(&z)));
+                        } else {
+                            std::hint::black_box(m.spmv(std::hint::black_box(&x)));
+                        }
+                        if k > 0 {
+                            elapsed += now.elapsed();
+                        }
+                    }
+                    elapsed / iterations.max(1) as u32
+                });
+
+                // Two floating-point operations per entry, and four times as many for complex values
+                let flops = 2.0 * m.nvals() as f64 * if complex { 4.0 } else { 1.0 };
+                println!("{:10}  {:>10.3}ms  {:>10.3}", sort_order.to_string(),
+                    elapsed.as_secs_f64() * 1e3, flops / elapsed.as_secs_f64() / 1e9);
+            }
+        },
+        Command::Hash { input_files, data_type, quantum } => {
+            for path in input_files {
+                let m = read(&path, data_type, &options, &mut timings)?;
+                let digest = timings.time("hash", || m.canonical_hash(quantum))?;
+                let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+                println!("{hex}  {}", path.display());
+            }
+        },
+        Command::Diff { golden_file, input_file, data_type, report, max_rel } => {
+            let golden = read(&golden_file, data_type, &options, &mut timings)?;
+            let m = read(&input_file, data_type, &options, &mut timings)?;
+
+            let metrics = timings.time("diff", || m.error_metrics(&golden))?;
+            match report {
+                ReportFormat::Text => print!("{metrics}"),
+                ReportFormat::Json => println!("{}", metrics.to_json()),
+            }
+            if let Some(max_rel) = max_rel && (metrics.max_rel > max_rel || metrics.max_rel.is_nan()) {
+                return Err(io::Error::other(format!("largest relative error {:.6e} exceeds {max_rel:e}", metrics.max_rel)).into());
+            }
+        },
+    }
+
+    if timing_json {
+        println!("{}", timings);
+    }
+
+    Ok(())
+}
+
+/// The first few (1-based) rows, such as `: 1, 4, 9, ...`, or nothing if there are none.
+fn rows_list(rows: &[usize]) -> String {
+    const SHOWN: usize = 8;
+    if rows.is_empty() {
+        return String::new();
+    }
+
+    let shown: Vec<_> = rows.iter().take(SHOWN).map(usize::to_string).collect();
+    let more = if rows.len() > SHOWN { ", ..." } else { "" };
+    format!(": {}{more}", shown.join(", "))
+}
+
+/// Percentage of stored entries that are padding.
+fn padding(stored: usize, nvals: usize) -> f64 {
+    100.0 * stored.saturating_sub(nvals) as f64 / stored.max(1) as f64
+}
+
+/// Expands arguments that contain glob patterns and do not name an existing file.
+fn expand_globs(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut res = Vec::with_capacity(paths.len());
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if path.exists() || !pattern.contains(['*', '?', '[']) {
+            res.push(path.clone());
+            continue;
+        }
+
+        let matches = glob::glob(&pattern).map_err(io::Error::other)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io::Error::other)?;
+        if matches.is_empty() {
+            return Err(io::Error::other(format!("no files match `{pattern}`")));
+        }
+        res.extend(matches);
+    }
+    Ok(res)
+}
+
+/// Estimates the peak memory of sorting the inputs from their size lines, with up to `jobs` files at the same time,
+/// and picks the fastest strategy that stays within `limit` bytes.
+fn strategy_within(io: &IoArgs, limit: usize) -> Result<SortStrategy, Error> {
+    let mut nvals = Vec::new();
+    for input in expand_globs(&io.input_files)? {
+        let rdr = io::BufReader::new(File::open(&input)?);
+        nvals.push(Matrix::read_size(rdr)?.map_or(0, |(_, _, nvals)| nvals));
+    }
+    nvals.sort_unstable_by(|a, b| b.cmp(a));
+    nvals.truncate(io.jobs.max(1));
+
+    let peak = |strategy| nvals.iter().fold(0, |sum: usize, &n| sum.saturating_add(Matrix::estimated_sort_bytes(n, io.data_type, strategy)));
+    for strategy in [SortStrategy::Zip, SortStrategy::Permute, SortStrategy::External] {
+        debug!("estimated peak memory with {strategy:?}: {} bytes", peak(strategy));
+        if peak(strategy) <= limit {
+            if strategy != SortStrategy::Zip {
+                warn!("sorting tuples would take about {} bytes, sorting with {strategy:?} instead", peak(SortStrategy::Zip));
+            }
+            return Ok(strategy);
+        }
+    }
+
+    warn!("sorting would take about {} bytes even externally, exceeding the limit of {limit} bytes", peak(SortStrategy::External));
+    Ok(SortStrategy::External)
+}
+
+/// Parses a number of bytes with an optional binary suffix `K`, `M`, `G`, or `T`.
+fn parse_bytes(s: &str) -> Result<usize, String> {
+    let shift = match s.as_bytes().last() {
+        Some(b'K' | b'k') => 10,
+        Some(b'M' | b'm') => 20,
+        Some(b'G' | b'g') => 30,
+        Some(b'T' | b't') => 40,
+        _ => 0,
+    };
+    let digits = if shift > 0 { &s[..s.len() - 1] } else { s };
+    digits.parse::<usize>().ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("expected a number of bytes such as `512M`, found `{s}`"))
+}
+
+/// Parses a probability between 0 and 1.
+fn parse_probability(s: &str) -> Result<f64, String> {
+    s.parse::<f64>().ok()
+        .filter(|p| (0.0..=1.0).contains(p))
+        .ok_or_else(|| format!("expected a number between 0 and 1, found `{s}`"))
+}
+
+/// Parses a range of indices `start..end` or `start..=end`, where either bound may be omitted.
+fn parse_index_range(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let err = || format!("expected a range such as `1..100` or `..=99`, found `{s}`");
+    let (start, end, inclusive) = match s.split_once("..=") {
+        Some((start, end)) => (start, end, true),
+        None => s.split_once("..").map(|(start, end)| (start, end, false)).ok_or_else(err)?,
+    };
+    let parse = |part: &str| part.parse::<usize>().map_err(|_| err());
+    let start = if start.is_empty() { 1 } else { parse(start)? };
+    let end = match (end, inclusive) {
+        ("", _) => usize::MAX,
+        (end, true) => parse(end)?,
+        // There is no index zero, so `..1` keeps nothing
+        (end, false) => parse(end)?.saturating_sub(1),
+    };
+    Ok(start..=end)
+}
+
+fn read(path: &Path, data_type: DataType, options: &ReadOptions, timings: &mut Timings) -> Result<Matrix, Error> {
+    #[cfg(feature = "object-store")]
+    if is_url(path) {
+        let m = timings.time("read", || block_on(Matrix::from_url(&path.to_string_lossy(), data_type, options)))??;
+        debug!("{:#?}", m);
+        return Ok(m);
+    }
+
+    #[cfg(feature = "archive")]
+    if is_archive(path) {
+        let member = MEMBER.get().and_then(Option::as_deref);
+        let m = timings.time("read", || Matrix::from_archive(path, member, data_type, options))?;
+        debug!("{:#?}", m);
+        return Ok(m);
+    }
+
+    let file = File::open(path)?;
     // let rdr = BufReader::new(file);
 
-    let now = Instant::now();
-    let mut m = Matrix::from_mmap(file, data_type);
-    //let mut m = Matrix::from_reader(rdr, data_type);
-    println!("Read: {:?}", now.elapsed());
-    println!("{:#?}", m);
+    // Patterns are parsed into 32-bit indices first, which takes less time and memory
+    let m = match data_type {
+        DataType::Bool => timings.time("read", || Pattern::from_mmap_with(file, options).map(Matrix::from))?,
+        _ => timings.time("read", || Matrix::from_mmap_with(file, data_type, options))?,
+    };
+    //let m = timings.time("read", || Matrix::from_reader_with(rdr, data_type, options))?;
+    debug!("{:#?}", m);
+
+    Ok(m)
+}
+
+/// Member of archives to read, as set by the global flag, or the primary matrix.
+#[cfg(feature = "archive")]
+static MEMBER: OnceLock<Option<String>> = OnceLock::new();
 
-    let now = Instant::now();
-    match sort_order {
-        SortOrder::RowMajor => m.sort_row_major(),
-        SortOrder::ColMajor => m.sort_col_major(),
+/// How to write files, as set by the global flags.
+#[derive(Debug, Default)]
+struct Output {
+    format: TextFormat,
+    mmap_write: bool,
+    expand_symmetric: bool,
+    /// Tolerance to verify written files with, if they should be read back.
+    verify: Option<Tolerance>,
+}
+
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+fn write(path: Option<&Path>, m: &Matrix, timings: &mut Timings) -> io::Result<()> {
+    let output = OUTPUT.get_or_init(Output::default);
+
+    // A compact symmetric form with entries above the diagonal would silently read back as a different matrix
+    let m = as_written(m);
+    m.check_lower_storage().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e}; pass --expand-symmetric to write a general matrix")))?;
+
+    #[cfg(feature = "object-store")]
+    if let Some(path) = path && is_url(path) {
+        return timings.time("write", || block_on(m.write_url_with(&path.to_string_lossy(), &output.format)))?;
     }
-    println!("Sort: {:?}", now.elapsed());
-    println!("{:#?}", m);
 
-    if let Some(path) = output_file {
-        let file = File::create(path)?;
-        let mut wtr = BufWriter::new(file);
+    if let Some(path) = path {
+        timings.time("write", || {
+            if output.mmap_write {
+                return m.write_mmap(path, &output.format);
+            }
 
-        let now = Instant::now();
-        write!(wtr, "{}", m)?;
-        println!("Write: {:?}", now.elapsed());
+            let mut wtr = BufWriter::new(File::create(path)?);
+            write!(wtr, "{}", m.formatted(output.format))?;
+            wtr.flush()
+        })?;
+
+        if let Some(tol) = output.verify {
+            timings.time("verify", || {
+                let written = Matrix::from_mmap_with(File::open(path)?, m.data_type(), &ReadOptions::default())?;
+                m.check_equal(&written, tol)
+            }).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{} does not read back as written: {e}", path.display())))?;
+        }
     }
 
     Ok(())
 }
+
+/// The matrix as it is written, with compact symmetric forms expanded if requested.
+fn as_written(m: &Matrix) -> Cow<'_, Matrix> {
+    let output = OUTPUT.get_or_init(Output::default);
+    if output.expand_symmetric && m.symmetry() != Symmetry::General {
+        let mut expanded = m.clone();
+        expanded.expand_symmetric();
+        Cow::Owned(expanded)
+    } else {
+        Cow::Borrowed(m)
+    }
+}
+
+/// Describes a written file for a dataset manifest, as a JSON object.
+fn manifest_entry(path: Option<&Path>, m: &Matrix) -> Result<String, Error> {
+    let file = path.map_or("null".to_string(), |path| format!("{:?}", path.display().to_string()));
+    let written = as_written(m);
+    Ok(format!("{{\"file\":{file},\"nrows\":{},\"ncols\":{},{}}}", written.nrows(), written.ncols(), manifest_fields(&written)?))
+}
+
+/// Number of entries, data type, symmetry, (1-based) index base, order, and the digest that `hash` prints,
+/// as the fields of a JSON object.
+fn manifest_fields(m: &Matrix) -> Result<String, Error> {
+    let ordering = [SortOrder::RowMajor, SortOrder::ColMajor, SortOrder::Morton, SortOrder::Hilbert].into_iter()
+        .find(|&order| m.is_sorted(order))
+        .or_else(|| m.is_row_major().then_some(SortOrder::RowMajor))
+        .or_else(|| m.is_col_major().then_some(SortOrder::ColMajor))
+        .map_or("null".to_string(), |order| format!("\"{order}\""));
+    let digest: String = m.canonical_hash(None)?.iter().map(|b| format!("{b:02x}")).collect();
+    Ok(format!(
+        "\"nvals\":{},\"data_type\":\"{}\",\"symmetry\":\"{}\",\"index_base\":1,\"ordering\":{ordering},\"sha256\":\"{digest}\"",
+        m.nvals(), m.data_type(), m.symmetry(),
+    ))
+}
+
+/// Whether the path is an object-store URL, rather than a local path, which requires the `object-store` feature.
+fn is_url(path: &Path) -> bool {
+    cfg!(feature = "object-store") && path.to_str().is_some_and(|path| ["s3://", "gs://", "az://"].iter().any(|scheme| path.starts_with(scheme)))
+}
+
+/// Runs a future of the object store on a runtime on the current thread.
+#[cfg(feature = "object-store")]
+fn block_on<F: Future>(f: F) -> io::Result<F::Output> {
+    Ok(tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(f))
+}
+
+/// Writes to a temporary file next to `path` and renames it over `path` once complete,
+/// such that `path` is never left half-written.
+fn write_in_place(path: &Path, m: &Matrix, timings: &mut Timings) -> io::Result<()> {
+    let name = path.file_name().ok_or_else(|| io::Error::other("expected a file name"))?;
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), process::id()));
+
+    let res = write(Some(&tmp), m, timings).and_then(|()| {
+        File::open(&tmp)?.sync_all()?;
+        fs::set_permissions(&tmp, fs::metadata(path)?.permissions())?;
+        fs::rename(&tmp, path)
+    });
+
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    res
+}
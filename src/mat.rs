@@ -0,0 +1,118 @@
+//! Export of sparse variables in MATLAB v7.3 files, which are HDF5 files behind a 512-byte MATLAB header.
+//! A sparse variable is a group with the attributes `MATLAB_class` and `MATLAB_sparse` (the number of rows),
+//! holding the compressed sparse column arrays `jc`, `ir`, and `data`.
+
+use std::ffi::{c_void, CString};
+use std::fs::OpenOptions;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use hdf5_metno_sys::h5::H5open;
+use hdf5_metno_sys::h5a::{H5Aclose, H5Acreate2, H5Awrite};
+use hdf5_metno_sys::h5f::{H5Fclose, H5Fcreate, H5F_ACC_TRUNC};
+use hdf5_metno_sys::h5g::{H5Gclose, H5Gcreate2};
+use hdf5_metno_sys::h5i::hid_t;
+use hdf5_metno_sys::h5p::{H5Pclose, H5Pcreate, H5Pset_userblock, H5P_CLS_FILE_CREATE, H5P_DEFAULT};
+use hdf5_metno_sys::h5s::{H5Sclose, H5Screate, H5S_class_t};
+use hdf5_metno_sys::h5t::{H5Tclose, H5Tcopy, H5Tcreate, H5Tinsert, H5Tset_size, H5T_class_t, H5T_C_S1};
+use hdf5_metno_sys::h5t::{H5T_NATIVE_DOUBLE, H5T_NATIVE_INT32, H5T_NATIVE_UINT64, H5T_NATIVE_UINT8};
+
+use crate::hdf5::{c_path, check, write_dataset, Handle};
+use crate::{offsets, Error, Matrix, MatrixData};
+
+/// Size of the userblock that holds the MATLAB header.
+const USERBLOCK_SIZE: u64 = 512;
+
+impl Matrix {
+    /// Writes a general matrix as the sparse variable `name` of a MATLAB v7.3 file, with duplicate entries summed.
+    /// MATLAB only has sparse doubles and logicals, so integer values are written as doubles,
+    /// and pattern matrices as logicals.
+    pub fn write_mat(&self, path: &Path, name: &str) -> Result<(), Error> {
+        self.check_general()?;
+        let m = self.coalesced(self.is_col_major(), Matrix::col_major_permutation)?;
+
+        {
+            check(unsafe { H5open() })?;
+            let props = Handle::new(unsafe { H5Pcreate(*H5P_CLS_FILE_CREATE) }, H5Pclose)?;
+            check(unsafe { H5Pset_userblock(props.0, USERBLOCK_SIZE) })?;
+            let file = Handle::new(unsafe { H5Fcreate(c_path(path)?.as_ptr(), H5F_ACC_TRUNC, props.0, H5P_DEFAULT) }, H5Fclose)?;
+
+            let c_name = CString::new(name).map_err(io::Error::other)?;
+            let group = Handle::new(unsafe { H5Gcreate2(file.0, c_name.as_ptr(), H5P_DEFAULT, H5P_DEFAULT, H5P_DEFAULT) }, H5Gclose)?;
+
+            let class = match m.vals {
+                MatrixData::Bool() => "logical",
+                _ => "double",
+            };
+            write_class(&group, class)?;
+            write_scalar(&group, "MATLAB_sparse", *H5T_NATIVE_UINT64, &(m.nrows as u64))?;
+
+            if class == "logical" {
+                write_scalar(&group, "MATLAB_int_decode", *H5T_NATIVE_INT32, &1i32)?;
+            }
+
+            let jc: Vec<_> = offsets(&m.cols, m.ncols).into_iter().map(|x| x as u64).collect();
+            write_dataset(&group, "jc", *H5T_NATIVE_UINT64, &jc)?;
+
+            // MATLAB leaves out the row indices and values of empty sparse matrices
+            if m.nvals > 0 {
+                let ir: Vec<_> = m.rows.iter().map(|&row| row as u64 - 1).collect();
+                write_dataset(&group, "ir", *H5T_NATIVE_UINT64, &ir)?;
+                match &m.vals {
+                    MatrixData::Complex(xs, ys) => {
+                        let zs: Vec<_> = xs.iter().zip(ys).map(|(&x, &y)| [x as f64, y as f64]).collect();
+                        write_dataset(&group, "data", complex_type()?.0, &zs)?;
+                    },
+                    MatrixData::Bool() => {
+                        write_dataset(&group, "data", *H5T_NATIVE_UINT8, &vec![1u8; m.nvals])?;
+                    },
+                    _ => {
+                        let xs: Vec<_> = (0..m.nvals).map(|i| m.value_at(i).0).collect();
+                        write_dataset(&group, "data", *H5T_NATIVE_DOUBLE, &xs)?;
+                    },
+                }
+            }
+        }
+
+        write_header(path)?;
+        Ok(())
+    }
+}
+
+/// Writes the MATLAB header over the start of the (zeroed) userblock:
+/// 116 bytes of text, 8 bytes of subsystem data offset, the version `0x0200`, and the endian indicator.
+fn write_header(path: &Path) -> io::Result<()> {
+    let text = format!("MATLAB 7.3 MAT-file, Platform: {}, Created by: matrix-market-transform HDF5 schema 1.00 .", std::env::consts::OS);
+    let mut header = [b' '; 128];
+    header[..text.len().min(116)].copy_from_slice(&text.as_bytes()[..text.len().min(116)]);
+    header[124..126].copy_from_slice(&0x0200u16.to_le_bytes());
+    header[126..128].copy_from_slice(b"IM");
+
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header)?;
+    file.sync_all()
+}
+
+/// Writes the `MATLAB_class` attribute as a fixed-length string.
+fn write_class(group: &Handle, class: &str) -> io::Result<()> {
+    let ty = Handle::new(unsafe { H5Tcopy(*H5T_C_S1) }, H5Tclose)?;
+    check(unsafe { H5Tset_size(ty.0, class.len()) })?;
+    write_scalar(group, "MATLAB_class", ty.0, class.as_bytes().as_ptr())
+}
+
+/// Writes a scalar attribute from the value at `x`, which must match the memory type `ty`.
+fn write_scalar<T>(group: &Handle, name: &str, ty: hid_t, x: *const T) -> io::Result<()> {
+    let name = CString::new(name).map_err(io::Error::other)?;
+    let space = Handle::new(unsafe { H5Screate(H5S_class_t::H5S_SCALAR) }, H5Sclose)?;
+    let attr = Handle::new(unsafe { H5Acreate2(group.0, name.as_ptr(), ty, space.0, H5P_DEFAULT, H5P_DEFAULT) }, H5Aclose)?;
+    check(unsafe { H5Awrite(attr.0, ty, x as *const c_void) })
+}
+
+/// Compound type of two doubles named `real` and `imag`, as MATLAB stores complex values.
+fn complex_type() -> io::Result<Handle> {
+    let ty = Handle::new(unsafe { H5Tcreate(H5T_class_t::H5T_COMPOUND, 16) }, H5Tclose)?;
+    check(unsafe { H5Tinsert(ty.0, c"real".as_ptr(), 0, *H5T_NATIVE_DOUBLE) })?;
+    check(unsafe { H5Tinsert(ty.0, c"imag".as_ptr(), 8, *H5T_NATIVE_DOUBLE) })?;
+    Ok(ty)
+}
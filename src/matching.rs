@@ -0,0 +1,148 @@
+//! Row permutations that put a matching of the rows and columns on the diagonal, as HSL's MC64 computes them
+//! before a factorization without pivoting: a maximum transversal makes the diagonal zero-free,
+//! and a matching of maximum weight also makes it large.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::btf::maximum_matching;
+use crate::{Error, Matrix, Symmetry};
+
+impl Matrix {
+    /// Row permutation that puts a maximum matching on the diagonal, which is zero-free if the matrix
+    /// is structurally nonsingular. Entry `k` is the (0-based) row that moves to position `k`,
+    /// for [`Matrix::permute`] with the identity as column permutation.
+    /// Rows that cannot be matched fill the remaining positions in order.
+    /// Mirrored entries of matrices stored in a compact symmetric form are included.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn transversal(&self) -> Result<Vec<usize>, Error> {
+        self.check_square()?;
+        let (row_match, col_match) = maximum_matching(&self.row_adjacency(), self.ncols);
+        Ok(fill_unmatched(col_match, &row_match))
+    }
+
+    /// Row permutation that puts a matching of maximum product of absolute values on the diagonal,
+    /// like MC64 with job 5, which keeps the diagonal zero-free and makes it large, as [`Matrix::transversal`] does.
+    /// Entries are weighted by the logarithm of their absolute value relative to the largest one in their column,
+    /// and the matching is found by shortest augmenting paths, one column at a time.
+    /// Entries that are zero cannot be matched.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn weighted_transversal(&self) -> Result<Vec<usize>, Error> {
+        self.check_square()?;
+
+        // Entries of every column with their cost, which is zero for the largest entry of the column
+        let mut columns: Vec<Vec<(usize, f64)>> = vec![Vec::new(); self.ncols];
+        for i in 0..self.nvals {
+            let (row, col, abs) = (self.rows[i] - 1, self.cols[i] - 1, self.abs_at(i));
+            if abs > 0.0 {
+                columns[col].push((row, abs));
+                if self.symmetry != Symmetry::General && row != col {
+                    columns[row].push((col, abs));
+                }
+            }
+        }
+        for entries in &mut columns {
+            let max = entries.iter().map(|&(_, abs)| abs).fold(0.0, f64::max);
+            entries.iter_mut().for_each(|(_, cost)| *cost = max.ln() - cost.ln());
+        }
+
+        // Potentials keep the reduced cost `cost - u[row] - v[col]` nonnegative, and zero on the matching
+        let (n, mut u, mut v) = (self.nrows, vec![0.0; self.nrows], vec![0.0; self.ncols]);
+        let mut row_match: Vec<Option<usize>> = vec![None; n];
+        let mut col_match: Vec<Option<usize>> = vec![None; n];
+        let (mut dist, mut pred, mut done) = (vec![f64::INFINITY; n], vec![0; n], vec![false; n]);
+        let mut visited = Vec::new();
+        for start in 0..n {
+            // Dijkstra from the column over the rows, continuing from matched rows through their columns
+            let mut heap = BinaryHeap::new();
+            // Rows that are done keep their path, even if rounding makes a reduced cost slightly negative
+            let mut relax = |col: usize, base: f64, dist: &mut [f64], done: &[bool], heap: &mut BinaryHeap<_>, visited: &mut Vec<usize>| {
+                for &(row, cost) in &columns[col] {
+                    let d = base + cost - u[row] - v[col];
+                    if !done[row] && d < dist[row] {
+                        if dist[row] == f64::INFINITY {
+                            visited.push(row);
+                        }
+                        dist[row] = d;
+                        pred[row] = col;
+                        heap.push(Reverse((Ordered(d), row)));
+                    }
+                }
+            };
+            relax(start, 0.0, &mut dist, &done, &mut heap, &mut visited);
+
+            let mut sink = None;
+            let mut finalized = Vec::new();
+            while let Some(Reverse((Ordered(d), row))) = heap.pop() {
+                if done[row] || d > dist[row] {
+                    continue;
+                }
+                done[row] = true;
+                finalized.push(row);
+                match row_match[row] {
+                    None => {
+                        sink = Some((row, d));
+                        break;
+                    },
+                    Some(col) => relax(col, d, &mut dist, &done, &mut heap, &mut visited),
+                }
+            }
+
+            if let Some((sink, length)) = sink {
+                v[start] += length;
+                for &row in &finalized {
+                    let delta = length - dist[row];
+                    u[row] -= delta;
+                    if let Some(col) = row_match[row] {
+                        v[col] += delta;
+                    }
+                }
+
+                // Augment along the path back to the starting column
+                let mut row = sink;
+                loop {
+                    let col = pred[row];
+                    let previous = col_match[col];
+                    row_match[row] = Some(col);
+                    col_match[col] = Some(row);
+                    match previous {
+                        Some(previous) if col != start => row = previous,
+                        _ => break,
+                    }
+                }
+            }
+
+            for row in visited.drain(..) {
+                dist[row] = f64::INFINITY;
+                done[row] = false;
+            }
+        }
+        Ok(fill_unmatched(col_match, &row_match))
+    }
+}
+
+/// Row permutation with the row matched to column `k` at position `k`, and the unmatched rows in the other positions.
+fn fill_unmatched(col_match: Vec<Option<usize>>, row_match: &[Option<usize>]) -> Vec<usize> {
+    let mut unmatched = (0..row_match.len()).filter(|&row| row_match[row].is_none());
+    col_match.into_iter().map(|row| row.or_else(|| unmatched.next()).unwrap()).collect()
+}
+
+/// Distance that orders totally, as distances are never NaN.
+#[derive(Copy, Clone, PartialEq)]
+struct Ordered(f64);
+
+impl Eq for Ordered {}
+
+impl PartialOrd for Ordered {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ordered {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
@@ -0,0 +1,80 @@
+//! Estimates of the memory that matrices take, to decide on a strategy before reading a file.
+
+use std::io::BufRead;
+use std::mem::size_of;
+
+use crate::external::CHUNK_SIZE;
+use crate::{is_data, parse_size, DataType, Error, Int, Matrix, Real, SortStrategy};
+
+impl Matrix {
+    /// Estimated number of bytes that a matrix with `nvals` entries of the given data type takes,
+    /// for its row and column indices and its values, saturating at `usize::MAX`.
+    pub fn estimated_bytes(nvals: usize, data_type: DataType) -> usize {
+        let value = match data_type {
+            DataType::Real => size_of::<Real>(),
+            DataType::Complex => 2 * size_of::<Real>(),
+            DataType::Integer => size_of::<Int>(),
+            DataType::Bool => 0,
+        };
+        nvals.saturating_mul(2 * size_of::<usize>() + value)
+    }
+
+    /// Estimated peak number of bytes while sorting a matrix with `nvals` entries into row- or column-major order
+    /// with the given strategy, where the automatic strategy is estimated as its worst case of sorting tuples.
+    /// Sorting tuples roughly doubles the memory, whereas sorting a permutation allocates one index per entry.
+    pub fn estimated_sort_bytes(nvals: usize, data_type: DataType, strategy: SortStrategy) -> usize {
+        let extra = match strategy {
+            SortStrategy::Auto | SortStrategy::Zip => nvals.saturating_mul(match data_type {
+                DataType::Real => size_of::<(usize, usize, Real)>(),
+                DataType::Complex => size_of::<(usize, usize, Real, Real)>(),
+                DataType::Integer => size_of::<(usize, usize, Int)>(),
+                DataType::Bool => size_of::<(usize, usize)>(),
+            }),
+            SortStrategy::Permute => nvals.saturating_mul(size_of::<usize>()),
+            SortStrategy::Counting => nvals.saturating_mul(2 * size_of::<usize>()),
+            SortStrategy::External => nvals.min(CHUNK_SIZE) * size_of::<usize>(),
+        };
+        Self::estimated_bytes(nvals, data_type).saturating_add(extra)
+    }
+
+    /// Checks that an `nrows` by `ncols` matrix with `nvals` entries of the given data type can be addressed
+    /// while sorting it with the given strategy, or only while reading it for `None`.
+    /// The automatic strategy is checked as its least case of sorting externally,
+    /// and the counting sort also allocates an offset for every row or column.
+    pub fn check_addressable(nrows: usize, ncols: usize, nvals: usize, data_type: DataType, strategy: Option<SortStrategy>) -> Result<(), Error> {
+        let bytes = match strategy {
+            None => Self::estimated_bytes(nvals, data_type),
+            Some(SortStrategy::Auto) => Self::estimated_sort_bytes(nvals, data_type, SortStrategy::External),
+            Some(SortStrategy::Counting) => {
+                let offsets = nrows.max(ncols).saturating_add(1).saturating_mul(size_of::<usize>());
+                Self::estimated_sort_bytes(nvals, data_type, SortStrategy::Counting).saturating_add(offsets)
+            },
+            Some(strategy) => Self::estimated_sort_bytes(nvals, data_type, strategy),
+        };
+
+        if bytes > isize::MAX as usize {
+            return Err(Error::TooLarge { nrows: nrows as u64, ncols: ncols as u64, nvals: nvals as u64 });
+        }
+        Ok(())
+    }
+
+    /// Reads the number of rows, columns, and entries from the size line of a Matrix-Market file,
+    /// without reading the entries, or returns `None` if the file has no size line.
+    pub fn read_size<R: BufRead>(rdr: R) -> Result<Option<(usize, usize, usize)>, Error> {
+        for (n, line) in rdr.split(b'\n').enumerate() {
+            let line = line?;
+            if is_data(&line) {
+                return parse_size(&line, || n + 1).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Memory that is available for new allocations, as reported by Linux, or `None` elsewhere.
+pub(crate) fn available_memory() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kib: usize = line.split_ascii_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
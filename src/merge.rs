@@ -0,0 +1,238 @@
+use std::borrow::Cow;
+
+use crate::{Error, Int, Matrix, MatrixData, SortOptions, SortOrder};
+
+/// What to do with entries that occur in more than one of the merged matrices.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum DuplicatePolicy {
+    /// Sum the values of duplicate entries.
+    #[default]
+    Sum,
+    /// Fail on the first entry that occurs in both matrices.
+    Error,
+}
+
+impl Matrix {
+    /// Unions the entries of `other` into this matrix.
+    /// Both matrices must have the same dimensions, data type, and symmetry.
+    ///
+    /// The result is sorted in row-major order, and entries that occur in both matrices are handled according to `policy`,
+    /// where the entries of this matrix come before those of `other`.
+    /// Duplicate entries within one of the matrices are always summed.
+    /// On error, this matrix is left unchanged.
+    pub fn merge(&mut self, other: &Matrix, policy: DuplicatePolicy) -> Result<(), Error> {
+        self.check_compatible(other)?;
+
+        let mut merged = self.clone();
+        merged.append(other);
+
+        if policy == DuplicatePolicy::Error {
+            // Within the entries at the same coordinates, those of this matrix come first
+            let mut order: Vec<_> = (0..merged.nvals).collect();
+            order.sort_unstable_by_key(|&i| (merged.rows[i], merged.cols[i], i >= self.nvals));
+            if let Some(pair) = order.windows(2)
+                .find(|pair| pair[0] < self.nvals && pair[1] >= self.nvals && merged.same_coords(pair[0], pair[1]))
+            {
+                return Err(Error::DuplicateEntry { row: merged.rows[pair[0]], col: merged.cols[pair[0]] });
+            }
+        }
+
+        merged.sort_with(SortOrder::RowMajor, &SortOptions::new().stable(true))?;
+        merged.coalesce_sorted()?;
+        *self = merged;
+        Ok(())
+    }
+
+    /// Sorts the entries in row-major order and sums the values of duplicate entries,
+    /// in the order that they are stored. Fails as [`Matrix::sort_with`] does,
+    /// or if the sum of integer values overflows, in which case the matrix is sorted but not summed.
+    pub fn sum_duplicates(&mut self) -> Result<(), Error> {
+        self.sort_with(SortOrder::RowMajor, &SortOptions::new().stable(true))?;
+        self.coalesce_sorted()
+    }
+
+    /// Checks that `other` has the same dimensions, data type, and symmetry as this matrix.
+    pub(crate) fn check_compatible(&self, other: &Matrix) -> Result<(), Error> {
+        if (self.nrows, self.ncols) != (other.nrows, other.ncols) {
+            return Err(Error::DimensionMismatch {
+                expected: (self.nrows, self.ncols),
+                found: (other.nrows, other.ncols),
+            });
+        }
+
+        self.check_same_kind(other)
+    }
+
+    /// Checks that `other` has the same data type and symmetry as this matrix.
+    pub(crate) fn check_same_kind(&self, other: &Matrix) -> Result<(), Error> {
+        if self.vals.field() != other.vals.field() {
+            return Err(Error::TypeMismatch {
+                expected: self.vals.field(),
+                found: other.vals.field(),
+            });
+        }
+
+        if self.symmetry != other.symmetry {
+            return Err(Error::SymmetryMismatch {
+                expected: self.symmetry,
+                found: other.symmetry,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Appends the entries of `other`, without any checks.
+    pub(crate) fn append(&mut self, other: &Matrix) {
+        self.rows.extend_from_slice(&other.rows);
+        self.cols.extend_from_slice(&other.cols);
+        self.vals.append(&other.vals);
+        self.nvals += other.nvals;
+        self.sortedness = None;
+    }
+
+    #[inline]
+    pub(crate) fn same_coords(&self, a: usize, b: usize) -> bool {
+        self.rows[a] == self.rows[b] && self.cols[a] == self.cols[b]
+    }
+
+    /// Returns this matrix in sorted order without duplicate entries, only copying it if needed.
+    /// Fails if the sum of integer values overflows.
+    pub(crate) fn coalesced(&self, sorted: bool, permutation: fn(&Matrix) -> Vec<usize>) -> Result<Cow<'_, Matrix>, Error> {
+        let mut m = if sorted {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.gather(&permutation(self)))
+        };
+
+        if (1..m.nvals).any(|i| m.same_coords(i - 1, i)) {
+            m.to_mut().coalesce_sorted()?;
+        }
+
+        Ok(m)
+    }
+
+    /// Sums adjacent entries with the same coordinates, assuming the matrix is sorted.
+    /// If the sum of integer values overflows, the matrix is left unchanged.
+    #[tracing::instrument(name = "coalesce", skip_all, fields(nvals = self.nvals))]
+    pub(crate) fn coalesce_sorted(&mut self) -> Result<(), Error> {
+        self.check_integer_sums()?;
+
+        let mut len = 0;
+        for i in 0..self.nvals {
+            if len > 0 && self.same_coords(len - 1, i) {
+                self.vals.accumulate(len - 1, i);
+            } else {
+                self.rows[len] = self.rows[i];
+                self.cols[len] = self.cols[i];
+                self.vals.copy(len, i);
+                len += 1;
+            }
+        }
+
+        self.rows.truncate(len);
+        self.cols.truncate(len);
+        self.vals.truncate(len);
+        self.nvals = len;
+        Ok(())
+    }
+
+    /// Checks that the sums of adjacent integer entries with the same coordinates fit in [`Int`].
+    fn check_integer_sums(&self) -> Result<(), Error> {
+        let MatrixData::Integer(xs) = &self.vals else {
+            return Ok(());
+        };
+
+        let mut sum: Int = 0;
+        for (i, &x) in xs.iter().enumerate() {
+            sum = if i > 0 && self.same_coords(i - 1, i) {
+                sum.checked_add(x).ok_or(Error::SumOverflow { row: self.rows[i], col: self.cols[i] })?
+            } else {
+                x
+            };
+        }
+
+        Ok(())
+    }
+}
+
+impl MatrixData {
+    fn append(&mut self, other: &MatrixData) {
+        match (self, other) {
+            (MatrixData::Real(xs), MatrixData::Real(other_xs)) => {
+                xs.extend_from_slice(other_xs);
+            },
+            (MatrixData::Complex(xs, ys), MatrixData::Complex(other_xs, other_ys)) => {
+                xs.extend_from_slice(other_xs);
+                ys.extend_from_slice(other_ys);
+            },
+            (MatrixData::Integer(xs), MatrixData::Integer(other_xs)) => {
+                xs.extend_from_slice(other_xs);
+            },
+            (MatrixData::Bool(), MatrixData::Bool()) => {
+                /* nothing to do */
+            },
+            _ => unreachable!("data types must match"),
+        }
+    }
+
+    /// Adds the value at `src` to the value at `dst`.
+    #[inline]
+    fn accumulate(&mut self, dst: usize, src: usize) {
+        match self {
+            MatrixData::Real(xs) => {
+                xs[dst] += xs[src];
+            },
+            MatrixData::Complex(xs, ys) => {
+                xs[dst] += xs[src];
+                ys[dst] += ys[src];
+            },
+            MatrixData::Integer(xs) => {
+                // Overflow is ruled out by `check_integer_sums` beforehand
+                xs[dst] = xs[dst].wrapping_add(xs[src]);
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+    }
+
+    /// Copies the value at `src` to `dst`.
+    #[inline]
+    fn copy(&mut self, dst: usize, src: usize) {
+        match self {
+            MatrixData::Real(xs) => {
+                xs[dst] = xs[src];
+            },
+            MatrixData::Complex(xs, ys) => {
+                xs[dst] = xs[src];
+                ys[dst] = ys[src];
+            },
+            MatrixData::Integer(xs) => {
+                xs[dst] = xs[src];
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        match self {
+            MatrixData::Real(xs) => {
+                xs.truncate(len);
+            },
+            MatrixData::Complex(xs, ys) => {
+                xs.truncate(len);
+                ys.truncate(len);
+            },
+            MatrixData::Integer(xs) => {
+                xs.truncate(len);
+            },
+            MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+    }
+}
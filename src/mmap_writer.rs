@@ -0,0 +1,62 @@
+//! Parallel writing through a memory-mapped output file: the exact length of every chunk of formatted entries
+//! is computed first, after which the file is mapped at its final size and the chunks are formatted into
+//! their own slices of it at the same time.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use memmap2::MmapOptions;
+use rayon::prelude::*;
+
+use crate::{Matrix, TextFormat};
+
+/// Number of entries per chunk that is formatted by a single task.
+const CHUNK_SIZE: usize = 1 << 16;
+
+impl Matrix {
+    /// Writes the matrix to a file like [`Matrix::formatted`], formatting the entries on the thread pool.
+    /// Unless the fields are aligned, which gives every entry line the same length,
+    /// the entries are formatted twice, once to measure them and once into the mapped file,
+    /// which still beats a serial write with enough threads.
+    pub fn write_mmap(&self, path: &Path, format: &TextFormat) -> io::Result<()> {
+        let header = self.header();
+        let widths = self.widths(format);
+        let chunks: Vec<_> = (0..self.nvals).step_by(CHUNK_SIZE).map(|start| start..(start + CHUNK_SIZE).min(self.nvals)).collect();
+        let lens: Vec<_> = match &widths {
+            Some(widths) => chunks.iter().map(|chunk| chunk.len() * self.line_len(widths)).collect(),
+            None => chunks.par_iter()
+                .map(|chunk| {
+                    let mut line = String::new();
+                    chunk.clone().map(|i| {
+                        line.clear();
+                        self.write_entry(&mut line, i, format, None).unwrap();
+                        line.len()
+                    }).sum()
+                })
+                .collect(),
+        };
+
+        let file = File::options().read(true).write(true).create(true).truncate(true).open(path)?;
+        file.set_len((header.len() + lens.iter().sum::<usize>()) as u64)?;
+        let mut mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+
+        let (head, mut rest) = mmap.split_at_mut(header.len());
+        head.copy_from_slice(header.as_bytes());
+        let mut slices = Vec::with_capacity(chunks.len());
+        for &len in &lens {
+            let (slice, tail) = rest.split_at_mut(len);
+            slices.push(slice);
+            rest = tail;
+        }
+        slices.into_par_iter().zip(chunks).try_for_each(|(mut slice, chunk)| {
+            let mut line = String::new();
+            chunk.into_iter().try_for_each(|i| {
+                line.clear();
+                self.write_entry(&mut line, i, format, widths.as_ref()).unwrap();
+                slice.write_all(line.as_bytes())
+            })
+        })?;
+        mmap.flush()
+    }
+}
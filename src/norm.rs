@@ -0,0 +1,93 @@
+//! Matrix norms, such as to scale residuals when comparing a transformed matrix with the original.
+
+use std::fmt;
+
+use crate::profile::json_number;
+use crate::{Error, Matrix, Symmetry};
+
+/// Norms of a matrix, see [`Matrix::norms`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Norms {
+    /// Square root of the sum of the squared absolute values.
+    pub fro: f64,
+    /// Largest sum of the absolute values in a row.
+    pub inf: f64,
+    /// Largest sum of the absolute values in a column.
+    pub one: f64,
+    /// Largest absolute value.
+    pub max_abs: f64,
+}
+
+impl Norms {
+    /// Formats the norms as a JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"fro\":{},\"inf\":{},\"one\":{},\"max_abs\":{}}}",
+            json_number(self.fro), json_number(self.inf), json_number(self.one), json_number(self.max_abs),
+        )
+    }
+}
+
+impl fmt::Display for Norms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "frobenius:  {:.6e}", self.fro)?;
+        writeln!(f, "inf norm:   {:.6e}", self.inf)?;
+        writeln!(f, "one norm:   {:.6e}", self.one)?;
+        writeln!(f, "max abs:    {:.6e}", self.max_abs)
+    }
+}
+
+impl Matrix {
+    /// Computes all norms at once, where duplicate entries are summed first,
+    /// and compact symmetric forms have their mirrored entries included.
+    /// Fails if the sum of duplicate integer entries overflows.
+    pub fn norms(&self) -> Result<Norms, Error> {
+        let m = self.coalesced(self.is_row_major(), Matrix::row_major_permutation)?;
+        let mirrored = m.symmetry != Symmetry::General;
+
+        let mut row_sums = vec![0.0; m.nrows];
+        let mut col_sums = vec![0.0; m.ncols];
+        let (mut squares, mut max_abs) = (0.0, 0.0f64);
+        for i in 0..m.nvals {
+            let (row, col) = (m.rows[i] - 1, m.cols[i] - 1);
+            let abs = m.abs_at(i);
+            let copies = if mirrored && row != col { 2.0 } else { 1.0 };
+
+            squares += copies * abs * abs;
+            max_abs = max_abs.max(abs);
+            row_sums[row] += abs;
+            col_sums[col] += abs;
+            if mirrored && row != col {
+                row_sums[col] += abs;
+                col_sums[row] += abs;
+            }
+        }
+
+        Ok(Norms {
+            fro: squares.sqrt(),
+            inf: row_sums.into_iter().fold(0.0, f64::max),
+            one: col_sums.into_iter().fold(0.0, f64::max),
+            max_abs,
+        })
+    }
+
+    /// Frobenius norm, see [`Matrix::norms`].
+    pub fn norm_fro(&self) -> Result<f64, Error> {
+        Ok(self.norms()?.fro)
+    }
+
+    /// Infinity norm, the largest sum of the absolute values in a row, see [`Matrix::norms`].
+    pub fn norm_inf(&self) -> Result<f64, Error> {
+        Ok(self.norms()?.inf)
+    }
+
+    /// One norm, the largest sum of the absolute values in a column, see [`Matrix::norms`].
+    pub fn norm_one(&self) -> Result<f64, Error> {
+        Ok(self.norms()?.one)
+    }
+
+    /// Largest absolute value, see [`Matrix::norms`].
+    pub fn max_abs(&self) -> Result<f64, Error> {
+        Ok(self.norms()?.max_abs)
+    }
+}
@@ -0,0 +1,90 @@
+//! NumPy `.npy` files of the triplets, which `numpy.load` reads without further dependencies,
+//! such as for `scipy.sparse.coo_array((vals, (rows, cols)), shape=shape)`.
+//!
+//! The arrays have the layout of a raw dump, see [`Matrix::write_raw`], behind a version 1.0 header.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{Error, Matrix, RawOptions};
+
+impl Matrix {
+    /// Writes the stored entries to the directory `dir` as the one-dimensional arrays `rows.npy`, `cols.npy`,
+    /// and `vals.npy`, where complex values are `complex64` or `complex128` and pattern matrices have no values,
+    /// and `shape.npy` with the number of rows and columns as `int64`.
+    /// Compact symmetric forms only have their lower triangle written.
+    pub fn write_npy(&self, dir: &Path, options: &RawOptions) -> Result<(), Error> {
+        options.check_indices(self)?;
+        fs::create_dir_all(dir)?;
+        let index = descr(options.index_type_name(), false);
+        for (name, indices) in [("rows.npy", &self.rows), ("cols.npy", &self.cols)] {
+            let mut wtr = BufWriter::new(File::create(dir.join(name))?);
+            write_header(&mut wtr, &format!("'{index}'"), self.nvals)?;
+            indices.iter().try_for_each(|&idx| options.write_index(&mut wtr, idx))?;
+            wtr.flush()?;
+        }
+        if let Some(value_type) = self.raw_value_type(options) {
+            let mut wtr = BufWriter::new(File::create(dir.join("vals.npy"))?);
+            write_header(&mut wtr, &format!("'{}'", descr(value_type, self.is_complex())), self.nvals)?;
+            (0..self.nvals).try_for_each(|i| self.write_raw_value(&mut wtr, i, options))?;
+            wtr.flush()?;
+        }
+
+        let mut wtr = BufWriter::new(File::create(dir.join("shape.npy"))?);
+        write_header(&mut wtr, "'<i8'", 2)?;
+        wtr.write_all(&(self.nrows as i64).to_le_bytes())?;
+        wtr.write_all(&(self.ncols as i64).to_le_bytes())?;
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Writes the stored entries as a single array of records with the fields `row`, `col`, and `val`,
+    /// of the same types as the arrays of [`Matrix::write_npy`], where pattern matrices have no `val` field.
+    /// The dimensions are not part of the array.
+    pub fn write_npy_records<W: Write>(&self, mut wtr: W, options: &RawOptions) -> Result<(), Error> {
+        options.check_indices(self)?;
+        let index = descr(options.index_type_name(), false);
+        let mut fields = format!("[('row', '{index}'), ('col', '{index}')");
+        if let Some(value_type) = self.raw_value_type(options) {
+            fields += &format!(", ('val', '{}')", descr(value_type, self.is_complex()));
+        }
+        fields += "]";
+
+        write_header(&mut wtr, &fields, self.nvals)?;
+        for i in 0..self.nvals {
+            options.write_index(&mut wtr, self.rows[i])?;
+            options.write_index(&mut wtr, self.cols[i])?;
+            self.write_raw_value(&mut wtr, i, options)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Little-endian NumPy type of a raw type, where complex values take two of them.
+fn descr(raw_type: &str, complex: bool) -> &'static str {
+    match (raw_type, complex) {
+        ("int32", _) => "<i4",
+        ("int64", _) => "<i8",
+        ("float32", false) => "<f4",
+        ("float64", false) => "<f8",
+        ("float32", true) => "<c8",
+        ("float64", true) => "<c16",
+        _ => unreachable!("unknown raw type {raw_type}"),
+    }
+}
+
+/// Writes the magic string, the version, and the header of a one-dimensional array of `len` elements of type `descr`.
+fn write_header<W: Write>(wtr: &mut W, descr: &str, len: usize) -> io::Result<()> {
+    let mut header = format!("{{'descr': {descr}, 'fortran_order': False, 'shape': ({len},), }}");
+    // The header ends in a newline, and is padded with spaces such that the data starts at a multiple of 64 bytes,
+    // after the 10 bytes of the magic string, the version, and the length of the header
+    let padding = 63 - (10 + header.len()) % 64;
+    header.extend(std::iter::repeat_n(' ', padding));
+    header.push('\n');
+
+    wtr.write_all(b"\x93NUMPY\x01\x00")?;
+    wtr.write_all(&(header.len() as u16).to_le_bytes())?;
+    wtr.write_all(header.as_bytes())
+}
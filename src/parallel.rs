@@ -0,0 +1,38 @@
+use rayon::ThreadPoolBuilder;
+
+use crate::Error;
+
+/// Number of threads used by the parallel operations of this crate.
+/// By default, rayon's global thread pool is used, which has one thread per logical CPU.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParallelConfig {
+    threads: Option<usize>,
+}
+
+impl ParallelConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the work to `threads` threads, or uses the global thread pool if zero.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = (threads > 0).then_some(threads);
+        self
+    }
+
+    /// Runs `op` with this configuration, building a scoped thread pool if a thread count was given.
+    /// Any parallel operations within `op` run in that pool.
+    pub fn install<R, F>(&self, op: F) -> Result<R, Error>
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        match self.threads {
+            Some(threads) => {
+                let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+                Ok(pool.install(op))
+            },
+            None => Ok(op()),
+        }
+    }
+}
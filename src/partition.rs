@@ -0,0 +1,141 @@
+//! Partitioning of a matrix into contiguous blocks of rows, or a grid of blocks of rows and columns,
+//! such as for distributed solvers.
+
+use std::fmt;
+use std::ops::Range;
+
+use rayon::prelude::*;
+
+use crate::profile::json_number;
+use crate::{offsets, Error, Matrix};
+
+/// Statistics of the number of entries per part of a partition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Imbalance {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// Largest number of entries relative to the mean, which is one if every part has the same number of entries.
+    pub ratio: f64,
+}
+
+impl Imbalance {
+    /// Statistics of the given numbers of entries per part.
+    pub fn new(counts: &[usize]) -> Self {
+        let min = counts.iter().copied().min().unwrap_or(0);
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let mean = counts.iter().sum::<usize>() as f64 / counts.len().max(1) as f64;
+        let ratio = if max == 0 { 1.0 } else { max as f64 / mean };
+        Self { min, max, mean, ratio }
+    }
+
+    /// Formats the statistics as a JSON object.
+    pub fn to_json(&self) -> String {
+        format!("{{\"min\":{},\"max\":{},\"mean\":{},\"ratio\":{}}}", self.min, self.max, json_number(self.mean), json_number(self.ratio))
+    }
+}
+
+impl fmt::Display for Imbalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entries per part: min {}, max {}, mean {:.1}, imbalance {:.3}", self.min, self.max, self.mean, self.ratio)
+    }
+}
+
+impl Matrix {
+    /// Boundaries of `parts` contiguous blocks of (0-based) rows, which differ in size by at most one row.
+    /// Panics if `parts` is zero.
+    pub fn row_blocks(&self, parts: usize) -> Vec<Range<usize>> {
+        equal_blocks(self.nrows, parts)
+    }
+
+    /// Boundaries of `parts` contiguous blocks of (0-based) columns, which differ in size by at most one column.
+    /// Panics if `parts` is zero.
+    pub fn col_blocks(&self, parts: usize) -> Vec<Range<usize>> {
+        equal_blocks(self.ncols, parts)
+    }
+
+    /// Boundaries of `parts` contiguous blocks of (0-based) rows with about the same number of entries,
+    /// where every boundary is the one closest to an equal share of the entries.
+    /// Panics if `parts` is zero.
+    pub fn balanced_row_blocks(&self, parts: usize) -> Vec<Range<usize>> {
+        balanced_blocks(&offsets(&self.rows, self.nrows), parts)
+    }
+
+    /// Boundaries of `parts` contiguous blocks of (0-based) columns with about the same number of entries.
+    /// Together with [`Matrix::balanced_row_blocks`] this balances the rows and columns of a grid of blocks separately,
+    /// which bounds the entries per block well for matrices whose entries are spread evenly, but not in general.
+    /// Panics if `parts` is zero.
+    pub fn balanced_col_blocks(&self, parts: usize) -> Vec<Range<usize>> {
+        balanced_blocks(&offsets(&self.cols, self.ncols), parts)
+    }
+
+    /// Splits the matrix into one matrix per block of (0-based) rows, in which the entries keep their relative order.
+    /// The blocks must be sorted and must not overlap, and entries outside of every block are dropped.
+    ///
+    /// If `local` is set, the rows of each part are renumbered from the first row of its block,
+    /// otherwise each part keeps the dimensions and row indices of the whole matrix.
+    /// The column indices are always those of the whole matrix.
+    pub fn partition_rows(&self, blocks: &[Range<usize>], local: bool) -> Result<Vec<Matrix>, Error> {
+        self.partition_blocks(blocks, std::slice::from_ref(&(0..self.ncols)), local)
+    }
+
+    /// Splits the matrix into one matrix per pair of a block of rows and a block of columns,
+    /// ordered by block of rows and then by block of columns, as [`Matrix::partition_rows`].
+    /// If `local` is set, the columns of each part are renumbered from the first column of its block as well.
+    pub fn partition_blocks(&self, row_blocks: &[Range<usize>], col_blocks: &[Range<usize>], local: bool) -> Result<Vec<Matrix>, Error> {
+        self.check_general()?;
+
+        let mut indices = vec![Vec::new(); row_blocks.len() * col_blocks.len()];
+        for i in 0..self.nvals {
+            if let Some(r) = find_block(row_blocks, self.rows[i] - 1)
+                && let Some(c) = find_block(col_blocks, self.cols[i] - 1)
+            {
+                indices[r * col_blocks.len() + c].push(i);
+            }
+        }
+
+        Ok(indices.par_iter().enumerate().map(|(k, indices)| {
+            let mut part = self.gather(indices);
+            if local {
+                let (rows, cols) = (&row_blocks[k / col_blocks.len()], &col_blocks[k % col_blocks.len()]);
+                part.rows.iter_mut().for_each(|row| *row -= rows.start);
+                part.cols.iter_mut().for_each(|col| *col -= cols.start);
+                part.nrows = rows.len();
+                part.ncols = cols.len();
+            }
+            part
+        }).collect())
+    }
+}
+
+/// Boundaries of `parts` blocks of `0..n`, which differ in size by at most one.
+fn equal_blocks(n: usize, parts: usize) -> Vec<Range<usize>> {
+    assert!(parts > 0, "expected at least one part");
+    (0..parts).map(|k| k * n / parts..(k + 1) * n / parts).collect()
+}
+
+/// Boundaries of `parts` blocks with about the same number of entries, given the number of entries before every index.
+fn balanced_blocks(offsets: &[usize], parts: usize) -> Vec<Range<usize>> {
+    assert!(parts > 0, "expected at least one part");
+    let n = offsets.len() - 1;
+    let nvals = offsets[n];
+
+    let mut bounds = vec![0; parts + 1];
+    bounds[parts] = n;
+    for k in 1..parts {
+        let target = k * nvals / parts;
+        let mut bound = offsets.partition_point(|&x| x < target).min(n);
+        if bound > 0 && target - offsets[bound - 1] < offsets[bound] - target {
+            bound -= 1;
+        }
+        bounds[k] = bound.max(bounds[k - 1]);
+    }
+
+    bounds.windows(2).map(|w| w[0]..w[1]).collect()
+}
+
+/// Position of the block that contains the index, in sorted blocks that do not overlap.
+fn find_block(blocks: &[Range<usize>], idx: usize) -> Option<usize> {
+    let k = blocks.partition_point(|block| block.end <= idx);
+    (k < blocks.len() && blocks[k].contains(&idx)).then_some(k)
+}
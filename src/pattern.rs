@@ -0,0 +1,104 @@
+//! Parsing of pattern matrices straight into 32-bit indices, for huge graphs.
+
+#[cfg(feature = "mmap")]
+use std::fs;
+use std::io;
+
+#[cfg(feature = "mmap")]
+use memmap2::MmapOptions;
+use rayon::prelude::*;
+
+use crate::chunks::{data_lines, line_chunks, split_by_counts, split_header};
+use crate::read::{check_index, check_shape};
+use crate::{entry_line, line_of, parse_size, split_fields, Error, Matrix, MatrixData, ReadOptions, Symmetry};
+
+/// Pattern matrix with 32-bit (1-based) indices, which takes half the memory of a [`Matrix`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    nrows: usize,
+    ncols: usize,
+    rows: Vec<u32>,
+    cols: Vec<u32>,
+    symmetry: Symmetry,
+}
+
+impl Pattern {
+    pub fn nrows(&self) -> usize { self.nrows }
+    pub fn ncols(&self) -> usize { self.ncols }
+    pub fn nvals(&self) -> usize { self.rows.len() }
+    pub fn symmetry(&self) -> Symmetry { self.symmetry }
+    pub fn rows(&self) -> &[u32] { &self.rows }
+    pub fn cols(&self) -> &[u32] { &self.cols }
+
+    /// Memory-maps a Matrix-Market file and parses the pattern of its entries in parallel.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap_with(file: fs::File, options: &ReadOptions) -> Result<Self, Error> {
+        let mmap = unsafe { MmapOptions::new().map(&file)? };
+        Self::from_bytes_with(&mmap, options)
+    }
+
+    /// Parses the pattern of the entries of a Matrix-Market file in parallel, ignoring any values.
+    /// Fails if an index does not fit in 32 bits, or lies outside the dimensions.
+    #[tracing::instrument(name = "parse", skip_all, fields(bytes = bytes.len()))]
+    pub fn from_bytes_with(bytes: &[u8], options: &ReadOptions) -> Result<Self, Error> {
+        let banner = bytes.split(|&b| b == b'\n').next().unwrap_or_default();
+        let symmetry = Symmetry::from_banner(&String::from_utf8_lossy(banner));
+
+        let Some((header, body)) = split_header(bytes) else {
+            // File is empty or contains only comments, return empty matrix
+            return Ok(Self { nrows: 0, ncols: 0, rows: Vec::new(), cols: Vec::new(), symmetry });
+        };
+
+        let (nrows, ncols, expected) = parse_size(header, || line_of(bytes, header))?;
+        check_shape(symmetry, nrows, ncols)?;
+
+        let chunks = line_chunks(body);
+        let counts: Vec<usize> = chunks.par_iter()
+            .map(|chunk| data_lines(chunk).count())
+            .collect();
+        let nvals = counts.iter().sum();
+        options.check_count(expected, nvals)?;
+
+        let mut rows = vec![0; nvals];
+        let mut cols = vec![0; nvals];
+        chunks.into_par_iter()
+            .zip(split_by_counts(&mut rows, &counts))
+            .zip(split_by_counts(&mut cols, &counts))
+            .try_for_each(|((chunk, rows), cols)| {
+                for ((line, row), col) in data_lines(chunk).zip(rows).zip(cols) {
+                    let mut parts = split_fields(line);
+                    *row = parse_index(parts.next(), line)?;
+                    *col = parse_index(parts.next(), line)?;
+                }
+                Ok::<_, io::Error>(())
+            })?;
+
+        let outside = (0..nvals).into_par_iter()
+            .find_first(|&i| !(1..=nrows).contains(&(rows[i] as usize)) || !(1..=ncols).contains(&(cols[i] as usize)));
+        if let Some(i) = outside {
+            check_index(rows[i] as usize, cols[i] as usize, nrows, ncols, || entry_line(bytes, i))?;
+        }
+        Ok(Self { nrows, ncols, rows, cols, symmetry })
+    }
+}
+
+impl From<Pattern> for Matrix {
+    /// Widens the indices, one array at a time.
+    fn from(pattern: Pattern) -> Self {
+        let Pattern { nrows, ncols, rows, cols, symmetry } = pattern;
+        let rows = rows.into_par_iter().map(|row| row as usize).collect();
+        let cols = cols.into_par_iter().map(|col| col as usize).collect();
+        let mut m = Matrix::new(nrows, ncols, rows, cols, MatrixData::Bool());
+        m.symmetry = symmetry;
+        m
+    }
+}
+
+/// Parses a (1-based) decimal index that fits in 32 bits.
+fn parse_index(part: Option<&[u8]>, line: &[u8]) -> io::Result<u32> {
+    part.filter(|part| !part.is_empty() && part.iter().all(u8::is_ascii_digit))
+        .and_then(|part| part.iter().try_fold(0u32, |x, &b| x.checked_mul(10)?.checked_add((b - b'0') as u32)))
+        .filter(|&x| x > 0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+            format!("expected a positive 32-bit index in `{}`", String::from_utf8_lossy(line).trim_end())))
+}
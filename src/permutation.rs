@@ -0,0 +1,126 @@
+//! Permutations of rows and columns, as computed by the reorderings, and how to store them,
+//! such that an ordering can be reused for matrices with the same structure.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::{is_data, Error};
+
+/// Formats to store a permutation in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum PermutationFormat {
+    /// One (1-based) index per line, where lines starting with `%` are comments.
+    Text,
+    /// One (0-based) index per 64-bit little-endian integer.
+    Binary,
+}
+
+/// Permutation of the indices `0..len`, in which entry `k` is the index that moves to position `k`,
+/// as returned by [`Matrix::reordering`](crate::Matrix::reordering).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Permutation {
+    indices: Vec<usize>,
+}
+
+impl Permutation {
+    /// Checks that every index in `0..indices.len()` occurs exactly once.
+    pub fn new(indices: Vec<usize>) -> Result<Self, Error> {
+        let len = indices.len();
+        let mut seen = vec![false; len];
+        for &index in &indices {
+            if index >= len || seen[index] {
+                return Err(Error::InvalidPermutation { index, len });
+            }
+            seen[index] = true;
+        }
+        Ok(Self { indices })
+    }
+
+    /// Permutation that moves nothing.
+    pub fn identity(len: usize) -> Self {
+        Self { indices: (0..len).collect() }
+    }
+
+    pub fn len(&self) -> usize { self.indices.len() }
+    pub fn is_empty(&self) -> bool { self.indices.is_empty() }
+    pub fn indices(&self) -> &[usize] { &self.indices }
+    pub fn into_indices(self) -> Vec<usize> { self.indices }
+
+    /// Permutation that undoes this one, with the position of every index.
+    pub fn inverse(&self) -> Self {
+        let mut indices = vec![0; self.len()];
+        for (new, &old) in self.indices.iter().enumerate() {
+            indices[old] = new;
+        }
+        Self { indices }
+    }
+
+    /// Permutation that applies this one and then `other`.
+    pub fn then(&self, other: &Permutation) -> Result<Self, Error> {
+        if other.len() != self.len() {
+            return Err(Error::DimensionMismatch { expected: (self.len(), self.len()), found: (other.len(), other.len()) });
+        }
+        Ok(Self { indices: other.indices.iter().map(|&k| self.indices[k]).collect() })
+    }
+
+    /// Whether the permutation moves nothing.
+    pub fn is_identity(&self) -> bool {
+        self.indices.iter().enumerate().all(|(k, &index)| k == index)
+    }
+
+    /// Writes the permutation in the given format.
+    pub fn save<W: Write>(&self, mut wtr: W, format: PermutationFormat) -> io::Result<()> {
+        match format {
+            PermutationFormat::Text => {
+                for &index in &self.indices {
+                    writeln!(wtr, "{}", index + 1)?;
+                }
+            },
+            PermutationFormat::Binary => {
+                for &index in &self.indices {
+                    wtr.write_all(&(index as u64).to_le_bytes())?;
+                }
+            },
+        }
+        wtr.flush()
+    }
+
+    /// Reads a permutation in the given format, and checks that it is one.
+    pub fn load<R: Read>(rdr: R, format: PermutationFormat) -> Result<Self, Error> {
+        let mut indices = Vec::new();
+        match format {
+            PermutationFormat::Text => {
+                for line in BufReader::new(rdr).split(b'\n') {
+                    let line = line?;
+                    if !is_data(&line) {
+                        continue;
+                    }
+                    let index = str::from_utf8(line.trim_ascii()).ok()
+                        .and_then(|line| line.parse::<usize>().ok())
+                        .filter(|&index| index > 0)
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
+                            format!("expected a positive index, found `{}`", String::from_utf8_lossy(&line).trim_end())))?;
+                    indices.push(index - 1);
+                }
+            },
+            PermutationFormat::Binary => {
+                let mut bytes = Vec::new();
+                BufReader::new(rdr).read_to_end(&mut bytes)?;
+                if bytes.len() % 8 != 0 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "expected a multiple of 8 bytes").into());
+                }
+                for chunk in bytes.chunks_exact(8) {
+                    let index = u64::from_le_bytes(chunk.try_into().unwrap());
+                    indices.push(usize::try_from(index).unwrap_or(usize::MAX));
+                }
+            },
+        }
+        Self::new(indices)
+    }
+}
+
+impl From<Permutation> for Vec<usize> {
+    fn from(permutation: Permutation) -> Self {
+        permutation.indices
+    }
+}
@@ -0,0 +1,371 @@
+//! Composition of transformations into a pipeline that applies them in turn,
+//! such that embedders can declare the steps instead of calling the methods one by one.
+//! The `run` subcommand applies a pipeline from a plan; the other subcommands call the methods directly,
+//! as they have options that the steps do not, such as the external sort and the permutation files.
+//!
+//! ```
+//! use matrix_market_transform::pipeline::{Pipeline, Sort, SumDuplicates, Threshold};
+//! use matrix_market_transform::{DataType, Matrix, SortOrder};
+//!
+//! let pipeline = Pipeline::new().then(SumDuplicates).then(Threshold(1e-12)).then(Sort::new(SortOrder::RowMajor));
+//! let m = pipeline.run(Matrix::from_bytes(b"2 2 2\n2 1 1\n1 1 2\n", DataType::Real)).unwrap();
+//! assert!(m.is_row_major());
+//! ```
+
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::{is_data, split_fields, DataType, Error, Matrix, Reordering, SortOptions, SortOrder};
+
+/// A step of a [`Pipeline`], which consumes a matrix and returns the transformed matrix.
+pub trait Transform: Send + Sync {
+    /// Short name of the step, such as for logs and timings.
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, m: Matrix) -> Result<Matrix, Error>;
+}
+
+/// Sorts the entries, see [`Matrix::sort_with`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sort {
+    pub order: SortOrder,
+    pub options: SortOptions,
+}
+
+impl Sort {
+    pub fn new(order: SortOrder) -> Self {
+        Self { order, options: SortOptions::new() }
+    }
+
+    pub fn options(mut self, options: SortOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl Transform for Sort {
+    fn name(&self) -> &'static str { "sort" }
+
+    fn apply(&self, mut m: Matrix) -> Result<Matrix, Error> {
+        m.sort_with(self.order, &self.options)?;
+        Ok(m)
+    }
+}
+
+/// Transposes the matrix, see [`Matrix::transpose`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Transpose;
+
+impl Transform for Transpose {
+    fn name(&self) -> &'static str { "transpose" }
+
+    fn apply(&self, mut m: Matrix) -> Result<Matrix, Error> {
+        m.transpose();
+        Ok(m)
+    }
+}
+
+/// Sums the entries with the same coordinates, see [`Matrix::sum_duplicates`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SumDuplicates;
+
+impl Transform for SumDuplicates {
+    fn name(&self) -> &'static str { "dedup" }
+
+    fn apply(&self, mut m: Matrix) -> Result<Matrix, Error> {
+        m.sum_duplicates()?;
+        Ok(m)
+    }
+}
+
+/// Drops the entries whose absolute value is below the threshold, where pattern entries are one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Threshold(pub f64);
+
+impl Transform for Threshold {
+    fn name(&self) -> &'static str { "threshold" }
+
+    fn apply(&self, m: Matrix) -> Result<Matrix, Error> {
+        let indices: Vec<_> = (0..m.nvals).filter(|&i| m.abs_at(i) >= self.0).collect();
+        let mut res = m.gather(&indices);
+        res.sortedness = m.sortedness;
+        Ok(res)
+    }
+}
+
+/// Symmetrically permutes the rows and columns of a square matrix, see [`Matrix::reorder`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Reorder(pub Reordering);
+
+impl Transform for Reorder {
+    fn name(&self) -> &'static str { "reorder" }
+
+    fn apply(&self, mut m: Matrix) -> Result<Matrix, Error> {
+        m.reorder(self.0)?;
+        Ok(m)
+    }
+}
+
+/// Removes the rows and columns without entries, see [`Matrix::compress_empty`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DropEmpty;
+
+impl Transform for DropEmpty {
+    fn name(&self) -> &'static str { "drop-empty" }
+
+    fn apply(&self, mut m: Matrix) -> Result<Matrix, Error> {
+        m.compress_empty();
+        Ok(m)
+    }
+}
+
+/// Stores the implied upper triangle of a compact symmetric form explicitly, see [`Matrix::expand_symmetric`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExpandSymmetric;
+
+impl Transform for ExpandSymmetric {
+    fn name(&self) -> &'static str { "expand-symmetric" }
+
+    fn apply(&self, mut m: Matrix) -> Result<Matrix, Error> {
+        m.expand_symmetric();
+        Ok(m)
+    }
+}
+
+/// Converts the values to another data type, see [`Matrix::cast`].
+#[derive(Copy, Clone, Debug)]
+pub struct Cast(pub DataType);
+
+impl Transform for Cast {
+    fn name(&self) -> &'static str { "cast" }
+
+    fn apply(&self, m: Matrix) -> Result<Matrix, Error> {
+        m.cast(self.0)
+    }
+}
+
+/// A step that calls a function, see [`from_fn`].
+pub struct FnTransform<F> {
+    name: &'static str,
+    f: F,
+}
+
+/// A step with the given name that calls `f`, for transformations without a step of their own.
+pub fn from_fn<F>(name: &'static str, f: F) -> FnTransform<F>
+where
+    F: Fn(Matrix) -> Result<Matrix, Error> + Send + Sync,
+{
+    FnTransform { name, f }
+}
+
+impl<F> Transform for FnTransform<F>
+where
+    F: Fn(Matrix) -> Result<Matrix, Error> + Send + Sync,
+{
+    fn name(&self) -> &'static str { self.name }
+
+    fn apply(&self, m: Matrix) -> Result<Matrix, Error> {
+        (self.f)(m)
+    }
+}
+
+/// Sequence of steps that are applied in turn, stopping at the first that fails.
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step.
+    pub fn then<T: Transform + 'static>(mut self, step: T) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    pub fn len(&self) -> usize { self.steps.len() }
+    pub fn is_empty(&self) -> bool { self.steps.is_empty() }
+
+    /// Names of the steps, in the order they are applied.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.steps.iter().map(|step| step.name())
+    }
+
+    /// Applies every step in turn.
+    pub fn run(&self, m: Matrix) -> Result<Matrix, Error> {
+        self.run_with(m, |step, m| step.apply(m))
+    }
+
+    /// Applies every step in turn through `apply`, which calls [`Transform::apply`] on the matrix,
+    /// such as to time or log the steps.
+    pub fn run_with<F>(&self, mut m: Matrix, mut apply: F) -> Result<Matrix, Error>
+    where
+        F: FnMut(&dyn Transform, Matrix) -> Result<Matrix, Error>,
+    {
+        for step in &self.steps {
+            let _span = tracing::info_span!("step", name = step.name()).entered();
+            m = apply(step.as_ref(), m)?;
+        }
+        Ok(m)
+    }
+
+    /// Reads a plan of one step per line, where lines starting with `%` are comments:
+    /// `sort <order>`, `transpose`, `dedup`, `threshold <value>`,
+    /// `reorder <rcm|degree-asc|degree-desc|amd|nd|random> [seed]`, `drop-empty`, `expand-symmetric`, and `cast <type>`.
+    /// Orders and data types are named as on the command line, such as `row-major` and `real`.
+    pub fn from_plan(plan: &str) -> Result<Self, Error> {
+        let mut pipeline = Self::new();
+        for (k, line) in plan.lines().enumerate() {
+            if !is_data(line.as_bytes()) {
+                continue;
+            }
+            let invalid = || Error::InvalidField { line: k + 1, field: line.trim().to_string() };
+            let fields: Vec<_> = split_fields(line.as_bytes()).map(|field| str::from_utf8(field).unwrap()).collect();
+            pipeline.steps.push(step(&fields).ok_or_else(invalid)?);
+        }
+        Ok(pipeline)
+    }
+}
+
+/// Batch of inputs, the steps to apply to each of them, and where to write the results,
+/// such that preprocessing recipes can be kept under version control, see [`Plan::from_yaml`].
+pub struct Plan {
+    /// Input files, or glob patterns such as `data/*.mtx`.
+    pub inputs: Vec<PathBuf>,
+    pub data_type: DataType,
+    pub pipeline: Pipeline,
+    /// Output file of a single input.
+    pub output: Option<PathBuf>,
+    /// Directory to write every result to, under the name of its input file.
+    pub out_dir: Option<PathBuf>,
+    /// Whether to replace every input file with its result.
+    pub in_place: bool,
+    /// Path of a JSON manifest of the written files.
+    pub manifest: Option<PathBuf>,
+    /// Number of files to process at the same time.
+    pub jobs: usize,
+}
+
+impl Plan {
+    /// Reads a plan from a YAML mapping, where `inputs` and `steps` are required:
+    ///
+    /// ```yaml
+    /// inputs: [data/*.mtx]    # or a single path
+    /// type: real              # real (the default), complex, integer, or bool
+    /// steps:
+    ///   - dedup
+    ///   - threshold: 1e-12    # or `threshold 1e-12`, as in `Pipeline::from_plan`
+    ///   - reorder: random 7
+    ///   - sort: row-major
+    /// out-dir: out/           # or `output: out.mtx` for a single input, or `in-place: true`
+    /// manifest: out/manifest.json
+    /// jobs: 4
+    /// ```
+    pub fn from_yaml(plan: &str) -> Result<Self, Error> {
+        let invalid = |message: String| Error::InvalidPlan { message };
+        let docs = YamlLoader::load_from_str(plan).map_err(|e| invalid(e.to_string()))?;
+        let Some(Yaml::Hash(keys)) = docs.first() else {
+            return Err(invalid("expected a mapping".to_string()));
+        };
+
+        let mut res = Self {
+            inputs: Vec::new(),
+            data_type: DataType::Real,
+            pipeline: Pipeline::new(),
+            output: None,
+            out_dir: None,
+            in_place: false,
+            manifest: None,
+            jobs: 1,
+        };
+        let mut has_steps = false;
+        for (key, value) in keys {
+            let key = key.as_str().unwrap_or_default();
+            let path = || value.as_str().map(PathBuf::from).ok_or_else(|| invalid(format!("`{key}` must be a path")));
+            match key {
+                "inputs" => res.inputs = match value {
+                    Yaml::Array(paths) => paths.iter().map(|path| path.as_str().map(PathBuf::from))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid("`inputs` must be a list of paths".to_string()))?,
+                    _ => vec![path()?],
+                },
+                "type" => res.data_type = value.as_str().and_then(|name| DataType::from_str(name, true).ok())
+                    .ok_or_else(|| invalid("`type` must be real, complex, integer, or bool".to_string()))?,
+                "steps" => {
+                    let steps = match value {
+                        Yaml::Array(steps) => steps.as_slice(),
+                        Yaml::Null => &[],
+                        _ => return Err(invalid("`steps` must be a list".to_string())),
+                    };
+                    for (k, value) in steps.iter().enumerate() {
+                        let fields = yaml_step(value).ok_or_else(|| invalid(format!("step {} is not a single name and its arguments", k + 1)))?;
+                        let fields: Vec<_> = fields.split_whitespace().collect();
+                        res.pipeline.steps.push(step(&fields).ok_or_else(|| invalid(format!("unknown step `{}`", fields.join(" "))))?);
+                    }
+                    has_steps = true;
+                },
+                "output" => res.output = Some(path()?),
+                "out-dir" => res.out_dir = Some(path()?),
+                "in-place" => res.in_place = value.as_bool().ok_or_else(|| invalid("`in-place` must be true or false".to_string()))?,
+                "manifest" => res.manifest = Some(path()?),
+                "jobs" => res.jobs = value.as_i64().and_then(|jobs| usize::try_from(jobs).ok()).filter(|&jobs| jobs > 0)
+                    .ok_or_else(|| invalid("`jobs` must be a positive number".to_string()))?,
+                _ => return Err(invalid(format!("unknown key `{key}`"))),
+            }
+        }
+
+        if res.inputs.is_empty() {
+            return Err(invalid("`inputs` is missing or empty".to_string()));
+        }
+        if !has_steps {
+            return Err(invalid("`steps` is missing".to_string()));
+        }
+        if [res.output.is_some(), res.out_dir.is_some(), res.in_place].into_iter().filter(|&set| set).count() > 1 {
+            return Err(invalid("at most one of `output`, `out-dir`, and `in-place` can be given".to_string()));
+        }
+        Ok(res)
+    }
+}
+
+/// Name and arguments of a step, written as `name args` or as `name: args`.
+fn yaml_step(value: &Yaml) -> Option<String> {
+    let scalar = |value: &Yaml| match value {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Null => Some(String::new()),
+        _ => None,
+    };
+    match value {
+        Yaml::Hash(step) if step.len() == 1 => {
+            let (name, args) = step.front()?;
+            Some(format!("{} {}", name.as_str()?, scalar(args)?))
+        },
+        Yaml::Hash(_) => None,
+        _ => scalar(value),
+    }
+}
+
+/// Step of a plan from its name and arguments, see [`Pipeline::from_plan`].
+fn step(fields: &[&str]) -> Option<Box<dyn Transform>> {
+    Some(match *fields {
+        ["sort", order] => Box::new(Sort::new(SortOrder::from_str(order, true).ok()?)),
+        ["transpose"] => Box::new(Transpose),
+        ["dedup"] => Box::new(SumDuplicates),
+        ["threshold", value] => Box::new(Threshold(value.parse().ok()?)),
+        ["reorder", "rcm"] => Box::new(Reorder(Reordering::Rcm)),
+        ["reorder", "degree-asc"] => Box::new(Reorder(Reordering::DegreeAscending)),
+        ["reorder", "degree-desc"] => Box::new(Reorder(Reordering::DegreeDescending)),
+        ["reorder", "amd"] => Box::new(Reorder(Reordering::Amd)),
+        ["reorder", "nd"] => Box::new(Reorder(Reordering::NestedDissection)),
+        ["reorder", "random", seed] => Box::new(Reorder(Reordering::Random { seed: seed.parse().ok()? })),
+        ["drop-empty"] => Box::new(DropEmpty),
+        ["expand-symmetric"] => Box::new(ExpandSymmetric),
+        ["cast", data_type] => Box::new(Cast(DataType::from_str(data_type, true).ok()?)),
+        _ => return None,
+    })
+}
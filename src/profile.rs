@@ -0,0 +1,180 @@
+use std::fmt::{self, Write};
+
+use crate::{Gershgorin, Matrix};
+
+/// Numeric summary of the values of a matrix, computed over its stored entries, see [`Matrix::value_profile`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueProfile {
+    /// Number of values that are NaN, in either part for complex values.
+    pub nan: usize,
+    /// Number of values that are infinite and not NaN, in either part for complex values.
+    pub infinite: usize,
+    /// Number of stored entries whose value is zero.
+    pub explicit_zeros: usize,
+    /// Histogram of the finite values, or of their real parts for complex values.
+    pub values: Histogram,
+    /// Histogram of the finite, nonzero absolute values, with logarithmic bins.
+    pub abs_values: Histogram,
+    /// Smallest and largest absolute value on the diagonal of a square matrix,
+    /// where duplicate entries are summed and rows without a diagonal entry count as zero.
+    pub diagonal_abs: Option<(f64, f64)>,
+    /// For every row of a square matrix, the absolute value of its diagonal entry
+    /// divided by the sum of the absolute values of its other entries, or infinity if those are all zero.
+    /// Rows with a ratio above one are strictly diagonally dominant.
+    pub dominance: Vec<f64>,
+}
+
+/// Counts of values in equal-width bins between the smallest and the largest value,
+/// or between their logarithms for logarithmic bins.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub log: bool,
+    pub counts: Vec<usize>,
+}
+
+impl Matrix {
+    /// Computes the numeric summary of the values, with histograms of the given number of bins.
+    /// Compact symmetric forms have their mirrored entries included in the diagonal dominance ratios.
+    ///
+    /// Panics if the number of bins is zero.
+    pub fn value_profile(&self, bins: usize) -> ValueProfile {
+        assert!(bins > 0, "number of bins must be positive");
+
+        let values: Vec<_> = (0..self.nvals).map(|i| self.value_at(i)).collect();
+        let nan = values.iter().filter(|(x, y)| x.is_nan() || y.is_nan()).count();
+        let infinite = values.iter().filter(|(x, y)| !x.is_nan() && !y.is_nan() && (x.is_infinite() || y.is_infinite())).count();
+        let explicit_zeros = values.iter().filter(|&&(x, y)| x == 0.0 && y == 0.0).count();
+
+        let finite = values.iter().filter(|(x, y)| x.is_finite() && y.is_finite());
+        let abs_values: Vec<_> = finite.clone().map(|(x, y)| x.hypot(*y)).filter(|&a| a > 0.0).collect();
+
+        let (diagonal_abs, dominance) = match self.gershgorin() {
+            Ok(Gershgorin { centers, radii }) => {
+                let diagonal: Vec<f64> = centers.into_iter().map(|(x, y)| x.hypot(y)).collect();
+                let min = diagonal.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = diagonal.iter().copied().fold(0.0, f64::max);
+                let dominance = diagonal.iter().zip(&radii)
+                    .map(|(&d, &o)| if o == 0.0 { if d == 0.0 { 0.0 } else { f64::INFINITY } } else { d / o })
+                    .collect();
+                ((self.nrows > 0).then_some((min, max)), dominance)
+            },
+            Err(_) => (None, Vec::new()),
+        };
+
+        ValueProfile {
+            nan,
+            infinite,
+            explicit_zeros,
+            values: Histogram::new(finite.map(|&(x, _)| x), bins, false),
+            abs_values: Histogram::new(abs_values.into_iter(), bins, true),
+            diagonal_abs,
+            dominance,
+        }
+    }
+}
+
+impl Histogram {
+    /// Histogram of the given values; logarithmic bins require positive values.
+    fn new(xs: impl Iterator<Item = f64> + Clone, bins: usize, log: bool) -> Self {
+        let scale = |x: f64| if log { x.log10() } else { x };
+        let min = xs.clone().reduce(f64::min).unwrap_or(0.0);
+        let max = xs.clone().reduce(f64::max).unwrap_or(0.0);
+
+        let mut counts = vec![0; bins];
+        let (lo, width) = (scale(min), (scale(max) - scale(min)) / bins as f64);
+        for x in xs {
+            let bin = if width > 0.0 { ((scale(x) - lo) / width) as usize } else { 0 };
+            counts[bin.min(bins - 1)] += 1;
+        }
+
+        Histogram { min, max, log, counts }
+    }
+
+    /// Boundaries of the bins, one more than the number of bins, or none if there are no values.
+    pub fn edges(&self) -> Vec<f64> {
+        if self.total() == 0 {
+            return Vec::new();
+        }
+
+        let n = self.counts.len();
+        (0..=n).map(|k| {
+            let t = k as f64 / n as f64;
+            if self.log {
+                10f64.powf(self.min.log10() + t * (self.max.log10() - self.min.log10()))
+            } else {
+                self.min + t * (self.max - self.min)
+            }
+        }).collect()
+    }
+
+    /// Total number of values.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        let edges: Vec<_> = self.edges().into_iter().map(json_number).collect();
+        let counts: Vec<_> = self.counts.iter().map(usize::to_string).collect();
+        format!("{{\"log\":{},\"edges\":[{}],\"counts\":[{}]}}", self.log, edges.join(","), counts.join(","))
+    }
+}
+
+impl ValueProfile {
+    /// Number of strictly diagonally dominant rows.
+    pub fn dominant_rows(&self) -> usize {
+        self.dominance.iter().filter(|&&r| r > 1.0).count()
+    }
+
+    /// Formats the profile as a JSON object, for dashboards.
+    /// The dominance ratios are summarized by their minimum and the number of dominant rows,
+    /// and non-finite numbers are written as `null`.
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        write!(s, "{{\"nan\":{},\"infinite\":{},\"explicit_zeros\":{}", self.nan, self.infinite, self.explicit_zeros).unwrap();
+        write!(s, ",\"values\":{},\"abs_values\":{}", self.values.to_json(), self.abs_values.to_json()).unwrap();
+        match self.diagonal_abs {
+            Some((min, max)) => write!(s, ",\"diagonal_abs\":[{},{}]", json_number(min), json_number(max)).unwrap(),
+            None => s.push_str(",\"diagonal_abs\":null"),
+        }
+        let min_ratio = self.dominance.iter().copied().reduce(f64::min);
+        write!(s, ",\"dominant_rows\":{},\"min_dominance\":{}}}", self.dominant_rows(), min_ratio.map_or("null".to_string(), json_number)).unwrap();
+        s
+    }
+}
+
+pub(crate) fn json_number(x: f64) -> String {
+    if x.is_finite() { x.to_string() } else { "null".to_string() }
+}
+
+impl fmt::Display for ValueProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nan:        {}", self.nan)?;
+        writeln!(f, "infinite:   {}", self.infinite)?;
+        writeln!(f, "zeros:      {}", self.explicit_zeros)?;
+        if let Some((min, max)) = self.diagonal_abs {
+            writeln!(f, "|diagonal|: {min:.3e} to {max:.3e}")?;
+            let min_ratio = self.dominance.iter().copied().fold(f64::INFINITY, f64::min);
+            writeln!(f, "dominance:  {} of {} rows, min ratio {min_ratio:.3e}", self.dominant_rows(), self.dominance.len())?;
+        }
+        writeln!(f, "values:")?;
+        write!(f, "{}", self.values)?;
+        writeln!(f, "|values|:")?;
+        write!(f, "{}", self.abs_values)
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total() == 0 {
+            return writeln!(f, "  (none)");
+        }
+
+        let edges = self.edges();
+        for (k, count) in self.counts.iter().enumerate() {
+            writeln!(f, "  [{:>10.3e}, {:>10.3e}{} {count}", edges[k], edges[k + 1], if k + 1 == self.counts.len() { "]" } else { ")" })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+//! Raw dumps of the triplets as flat little-endian binary arrays, with a JSON descriptor of their layout,
+//! such that test harnesses of GPU kernels can read them straight into staging buffers.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{Error, Int, Matrix, MatrixData, Precision};
+
+/// Width of the indices of a raw dump.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum IndexType {
+    /// Signed 32-bit indices, as cuSPARSE and hipSPARSE take by default.
+    #[default]
+    Int32,
+    Int64,
+}
+
+/// Options for raw dumps, see [`Matrix::write_raw`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RawOptions {
+    index_type: IndexType,
+    one_based: bool,
+    precision: Precision,
+}
+
+impl Default for RawOptions {
+    fn default() -> Self {
+        Self { index_type: IndexType::Int32, one_based: false, precision: Precision::default() }
+    }
+}
+
+impl RawOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails if an index of the matrix does not fit in the index type.
+    pub(crate) fn check_indices(&self, m: &Matrix) -> io::Result<()> {
+        let base = usize::from(!self.one_based);
+        if self.index_type == IndexType::Int32 && m.nrows.max(m.ncols).saturating_sub(base) > i32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "indices do not fit in int32; pass `--index-type int64`"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn index_type_name(&self) -> &'static str {
+        match self.index_type {
+            IndexType::Int32 => "int32",
+            IndexType::Int64 => "int64",
+        }
+    }
+
+    /// Writes a (1-based) index in the index type, and 0-based unless the indices are kept 1-based.
+    pub(crate) fn write_index<W: Write>(&self, wtr: &mut W, idx: usize) -> io::Result<()> {
+        let idx = idx - usize::from(!self.one_based);
+        match self.index_type {
+            IndexType::Int32 => wtr.write_all(&(idx as i32).to_le_bytes()),
+            IndexType::Int64 => wtr.write_all(&(idx as i64).to_le_bytes()),
+        }
+    }
+
+    pub fn index_type(mut self, index_type: IndexType) -> Self {
+        self.index_type = index_type;
+        self
+    }
+
+    /// Keep the 1-based indices of the file, instead of making them 0-based.
+    pub fn one_based(mut self, one_based: bool) -> Self {
+        self.one_based = one_based;
+        self
+    }
+
+    /// Precision to write real and complex values at, which defaults to that of [`Real`](crate::Real).
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+}
+
+impl Matrix {
+    /// Writes the stored entries to the directory `dir` as flat arrays without headers, in little-endian byte order:
+    ///
+    /// - `rows.bin` and `cols.bin`, the indices as `int32` or `int64`,
+    /// - `vals.bin`, the values as `float32` or `float64`, with the real and imaginary parts of complex values
+    ///   interleaved, or as `int32` or `int64` as stored for integer values, and absent for pattern matrices,
+    /// - `matrix.json`, a descriptor of the dimensions, symmetry, index base, order, and arrays.
+    ///
+    /// Compact symmetric forms only have their lower triangle written, as the descriptor notes.
+    /// Fails if an index does not fit in the index type.
+    pub fn write_raw(&self, dir: &Path, options: &RawOptions) -> Result<(), Error> {
+        options.check_indices(self)?;
+        fs::create_dir_all(dir)?;
+        for (name, indices) in [("rows.bin", &self.rows), ("cols.bin", &self.cols)] {
+            let mut wtr = BufWriter::new(File::create(dir.join(name))?);
+            indices.iter().try_for_each(|&idx| options.write_index(&mut wtr, idx))?;
+            wtr.flush()?;
+        }
+        let value_type = self.raw_value_type(options);
+        if value_type.is_some() {
+            let mut wtr = BufWriter::new(File::create(dir.join("vals.bin"))?);
+            (0..self.nvals).try_for_each(|i| self.write_raw_value(&mut wtr, i, options))?;
+            wtr.flush()?;
+        }
+
+        let ordering = if self.is_row_major() {
+            "\"row-major\""
+        } else if self.is_col_major() {
+            "\"col-major\""
+        } else {
+            "null"
+        };
+        let array = |file: &str, dtype: &str, len: usize| format!("{{\"file\":\"{file}\",\"dtype\":\"{dtype}\",\"len\":{len}}}");
+        let index_type = options.index_type_name();
+        let mut arrays = vec![array("rows.bin", index_type, self.nvals), array("cols.bin", index_type, self.nvals)];
+        if let Some(dtype) = value_type {
+            let len = if matches!(self.vals, MatrixData::Complex(..)) { 2 * self.nvals } else { self.nvals };
+            arrays.push(array("vals.bin", dtype, len));
+        }
+        let descriptor = format!(
+            "{{\"nrows\":{},\"ncols\":{},\"nvals\":{},\"data_type\":\"{}\",\"symmetry\":\"{}\",\"index_base\":{},\"ordering\":{ordering},\"byte_order\":\"little\",\"complex\":{},\"arrays\":[{}]}}\n",
+            self.nrows, self.ncols, self.nvals, self.vals.field(), self.symmetry, usize::from(options.one_based),
+            if matches!(self.vals, MatrixData::Complex(..)) { "\"interleaved\"" } else { "null" },
+            arrays.join(","),
+        );
+        fs::write(dir.join("matrix.json"), descriptor)?;
+        Ok(())
+    }
+
+    /// Type of the values of a raw dump, or `None` for pattern matrices, whose values are not written.
+    /// The real and imaginary parts of complex values both have this type.
+    pub(crate) fn raw_value_type(&self, options: &RawOptions) -> Option<&'static str> {
+        match (&self.vals, options.precision) {
+            (MatrixData::Real(_) | MatrixData::Complex(..), Precision::F32) => Some("float32"),
+            (MatrixData::Real(_) | MatrixData::Complex(..), Precision::F64) => Some("float64"),
+            (MatrixData::Integer(_), _) => Some(if size_of::<Int>() == 4 { "int32" } else { "int64" }),
+            (MatrixData::Bool(), _) => None,
+        }
+    }
+
+    /// Writes the value of entry `i` in the layout of a raw dump, or nothing for pattern matrices.
+    pub(crate) fn write_raw_value<W: Write>(&self, wtr: &mut W, i: usize, options: &RawOptions) -> io::Result<()> {
+        let mut write_float = |x: f64| match options.precision {
+            Precision::F32 => wtr.write_all(&(x as f32).to_le_bytes()),
+            Precision::F64 => wtr.write_all(&x.to_le_bytes()),
+        };
+        match &self.vals {
+            MatrixData::Real(xs) => write_float(xs[i] as f64),
+            MatrixData::Complex(xs, ys) => {
+                write_float(xs[i] as f64)?;
+                write_float(ys[i] as f64)
+            },
+            MatrixData::Integer(xs) => wtr.write_all(&xs[i].to_le_bytes()),
+            MatrixData::Bool() => Ok(()),
+        }
+    }
+}
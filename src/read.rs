@@ -0,0 +1,260 @@
+use log::warn;
+use rayon::prelude::*;
+
+use crate::{Error, Int, Matrix, MatrixData, Real, Symmetry};
+
+/// What to do when the number of entries differs from the size line.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum CountPolicy {
+    /// Keep the entries that are actually present, and warn about the difference.
+    #[default]
+    Lenient,
+    /// Fail if the number of entries differs from the size line.
+    Strict,
+}
+
+/// What to do with values that parse to NaN or an infinity.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum NonFinitePolicy {
+    /// Keep the values as they are.
+    #[default]
+    Keep,
+    /// Fail with the line number of the first such value.
+    Error,
+    /// Drop the entries.
+    Drop,
+    /// Replace the values with zero.
+    Zero,
+}
+
+/// Precision of real and complex values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum Precision {
+    /// 32-bit values, the default unless the `x64` feature is enabled.
+    F32,
+    /// 64-bit values, the default with the `x64` feature.
+    F64,
+}
+
+impl Default for Precision {
+    /// The precision of [`Real`].
+    fn default() -> Self {
+        if size_of::<Real>() == size_of::<f32>() { Precision::F32 } else { Precision::F64 }
+    }
+}
+
+/// Width to read integer values at.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum IntWidth {
+    /// 32-bit integers, the default unless the `x64` feature is enabled.
+    #[value(name = "32")]
+    I32,
+    /// 64-bit integers, which need the `x64` feature, as integer values are stored at 32 bits otherwise.
+    #[value(name = "64")]
+    I64,
+}
+
+impl Default for IntWidth {
+    /// The width of [`Int`].
+    fn default() -> Self {
+        if Int::BITS == 32 { IntWidth::I32 } else { IntWidth::I64 }
+    }
+}
+
+/// Options for reading Matrix-Market files.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadOptions {
+    count_policy: CountPolicy,
+    nonfinite_policy: NonFinitePolicy,
+    lenient_numbers: bool,
+    round_to_f32: bool,
+    int_width: IntWidth,
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count_policy(mut self, count_policy: CountPolicy) -> Self {
+        self.count_policy = count_policy;
+        self
+    }
+
+    /// What to do with values that parse to NaN or an infinity, in either part for complex values.
+    pub fn nonfinite_policy(mut self, nonfinite_policy: NonFinitePolicy) -> Self {
+        self.nonfinite_policy = nonfinite_policy;
+        self
+    }
+
+    /// Also accept real values with decimal commas (`1,5`), Fortran exponents (`1.0D+03`),
+    /// and Fortran exponents without a letter (`1.0-300`), as legacy exporters write them.
+    pub fn lenient_numbers(mut self, lenient_numbers: bool) -> Self {
+        self.lenient_numbers = lenient_numbers;
+        self
+    }
+
+    /// Rounds real and complex values to the nearest `f32` after parsing them, such that a build with the `x64` feature
+    /// reads the same values as a build without it. This only rounds: values are still stored as [`Real`],
+    /// so they take the same memory.
+    pub fn round_to_f32(mut self, round_to_f32: bool) -> Self {
+        self.round_to_f32 = round_to_f32;
+        self
+    }
+
+    /// Fails on integer values that do not fit in this width, naming their line, such that a build with the `x64` feature
+    /// refuses the same files as a build without it. Values are still stored as [`Int`],
+    /// and values that do not fit in [`Int`] are refused regardless.
+    pub fn int_width(mut self, int_width: IntWidth) -> Self {
+        self.int_width = int_width;
+        self
+    }
+
+    pub(crate) fn is_lenient(&self) -> bool {
+        self.lenient_numbers
+    }
+
+    /// Whether reading needs the line numbers of the entries, to report them in errors.
+    pub(crate) fn needs_lines(&self) -> bool {
+        self.nonfinite_policy == NonFinitePolicy::Error || self.int_width != IntWidth::default()
+    }
+
+    /// Reconciles the number of entries in the size line with the number that was found.
+    pub(crate) fn check_count(&self, expected: usize, found: usize) -> Result<(), Error> {
+        if expected != found {
+            match self.count_policy {
+                CountPolicy::Lenient => warn!("size line declares {expected} entries, found {found}"),
+                CountPolicy::Strict => return Err(Error::EntryCount { expected, found }),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Matrix {
+    /// Rounds the real and complex values to `f32` if the options ask for it.
+    pub(crate) fn round_values(&mut self, options: &ReadOptions) {
+        // Values are no more precise than `f32` without the `x64` feature
+        if !options.round_to_f32 || Precision::default() == Precision::F32 {
+            return;
+        }
+
+        #[allow(clippy::unnecessary_cast)]
+        let round = |x: &mut Real| *x = *x as f32 as Real;
+        match &mut self.vals {
+            MatrixData::Real(xs) => xs.par_iter_mut().for_each(round),
+            MatrixData::Complex(xs, ys) => {
+                xs.par_iter_mut().for_each(round);
+                ys.par_iter_mut().for_each(round);
+            },
+            MatrixData::Integer(_) | MatrixData::Bool() => {
+                /* nothing to do */
+            },
+        }
+    }
+
+    /// Checks that the integer values fit in the width of the options,
+    /// where `line_of` gives the line number of the entry at an index.
+    pub(crate) fn check_int_width<F>(&self, options: &ReadOptions, line_of: F) -> Result<(), Error>
+    where
+        F: FnOnce(usize) -> usize,
+    {
+        let MatrixData::Integer(xs) = &self.vals else {
+            return Ok(());
+        };
+        if options.int_width == IntWidth::I64 || IntWidth::default() == IntWidth::I32 {
+            return Ok(());
+        }
+
+        #[allow(clippy::useless_conversion)]
+        let overflows = |&x: &Int| i32::try_from(x).is_err();
+        match xs.par_iter().position_first(overflows) {
+            Some(i) => Err(Error::IntegerOverflow { line: line_of(i), bits: 32 }),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies the policy for non-finite values of the options,
+    /// where `line_of` gives the line number of the entry at an index.
+    pub(crate) fn scrub_nonfinite<F>(&mut self, options: &ReadOptions, line_of: F) -> Result<(), Error>
+    where
+        F: FnOnce(usize) -> usize,
+    {
+        let nonfinite = |i: usize| {
+            let (x, y) = self.value_at(i);
+            !x.is_finite() || !y.is_finite()
+        };
+
+        match (options.nonfinite_policy, &self.vals) {
+            (NonFinitePolicy::Keep, _) | (_, MatrixData::Integer(_) | MatrixData::Bool()) => {
+                /* nothing to do */
+            },
+            (NonFinitePolicy::Error, _) => {
+                if let Some(i) = (0..self.nvals).find(|&i| nonfinite(i)) {
+                    return Err(Error::NonFinite { line: line_of(i) });
+                }
+            },
+            (NonFinitePolicy::Drop, _) => {
+                let keep: Vec<_> = (0..self.nvals).filter(|&i| !nonfinite(i)).collect();
+                if keep.len() < self.nvals {
+                    warn!("dropped {} non-finite entries", self.nvals - keep.len());
+                    *self = self.gather(&keep);
+                }
+            },
+            (NonFinitePolicy::Zero, _) => {
+                let zero: Vec<_> = (0..self.nvals).filter(|&i| nonfinite(i)).collect();
+                if !zero.is_empty() {
+                    warn!("replaced {} non-finite values with zero", zero.len());
+                }
+                for i in zero {
+                    match &mut self.vals {
+                        MatrixData::Real(xs) => xs[i] = 0.0,
+                        MatrixData::Complex(xs, ys) => {
+                            xs[i] = 0.0;
+                            ys[i] = 0.0;
+                        },
+                        _ => unreachable!("integer and pattern values are finite"),
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+    /// Checks that every entry lies within the dimensions, and that matrices with a symmetric form are square,
+    /// where `line_of` gives the line number of the entry at an index.
+    pub(crate) fn check_bounds<F>(&self, line_of: F) -> Result<(), Error>
+    where
+        F: FnOnce(usize) -> usize,
+    {
+        check_shape(self.symmetry, self.nrows, self.ncols)?;
+        let outside = (0..self.nvals).into_par_iter()
+            .find_first(|&i| !(1..=self.nrows).contains(&self.rows[i]) || !(1..=self.ncols).contains(&self.cols[i]));
+        match outside {
+            Some(i) => check_index(self.rows[i], self.cols[i], self.nrows, self.ncols, || line_of(i)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that a matrix with a symmetric form is square, for readers that check their entries as they parse them.
+pub(crate) fn check_shape(symmetry: Symmetry, nrows: usize, ncols: usize) -> Result<(), Error> {
+    if symmetry != Symmetry::General && nrows != ncols {
+        let n = nrows.max(ncols);
+        return Err(Error::DimensionMismatch { expected: (n, n), found: (nrows, ncols) });
+    }
+    Ok(())
+}
+
+/// Checks that an entry lies within the dimensions, where `line_number` gives the line of the entry.
+pub(crate) fn check_index(row: usize, col: usize, nrows: usize, ncols: usize, line_number: impl FnOnce() -> usize) -> Result<(), Error> {
+    if !(1..=nrows).contains(&row) || !(1..=ncols).contains(&col) {
+        return Err(Error::OutOfBounds { line: line_number(), row, col, nrows, ncols });
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! Relabeling of rows and columns through a map from old to new indices,
+//! such as between the global numbering of a distributed mesh and the local numbering of one of its parts.
+
+use std::io::{BufRead, BufReader, Read};
+
+use rayon::prelude::*;
+
+use crate::{is_data, split_fields, Error, Matrix};
+
+/// Map from old to new (1-based) indices, where each old and each new index occurs at most once.
+/// Old indices without a new index are unmapped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexMap {
+    /// New index of every old index, or zero if it is unmapped.
+    new: Vec<usize>,
+    len: usize,
+}
+
+impl IndexMap {
+    /// Map from `old new` pairs of (1-based) indices.
+    pub fn from_pairs<I: IntoIterator<Item = (usize, usize)>>(pairs: I) -> Result<Self, Error> {
+        let mut new = Vec::new();
+        let mut len = 0;
+        for (old, index) in pairs {
+            if old == 0 || index == 0 {
+                return Err(Error::InvalidIndexMap { index: 0 });
+            }
+            if new.len() < old {
+                new.resize(old, 0);
+            }
+            if new[old - 1] != 0 {
+                return Err(Error::InvalidIndexMap { index: old });
+            }
+            new[old - 1] = index;
+            len = len.max(index);
+        }
+        Self::check_new(&new, len)?;
+        Ok(Self { new, len })
+    }
+
+    /// Map from the new (1-based) index of every old index in turn, a dense relabeling.
+    pub fn from_list(new: Vec<usize>) -> Result<Self, Error> {
+        if new.contains(&0) {
+            return Err(Error::InvalidIndexMap { index: 0 });
+        }
+        let len = new.iter().copied().max().unwrap_or(0);
+        Self::check_new(&new, len)?;
+        Ok(Self { new, len })
+    }
+
+    /// Reads a map of one `old new` pair per line, or of one new index per line for every old index in turn,
+    /// where lines starting with `%` are comments.
+    pub fn load<R: Read>(rdr: R) -> Result<Self, Error> {
+        let mut pairs = Vec::new();
+        let mut list = Vec::new();
+        for (k, line) in BufReader::new(rdr).split(b'\n').enumerate() {
+            let line = line?;
+            if !is_data(&line) {
+                continue;
+            }
+            let parse = |part: &[u8]| str::from_utf8(part).ok()
+                .and_then(|part| part.parse::<usize>().ok())
+                .ok_or_else(|| Error::InvalidField { line: k + 1, field: String::from_utf8_lossy(part).into_owned() });
+            let fields: Vec<_> = split_fields(&line).map(parse).collect::<Result<_, _>>()?;
+            match fields[..] {
+                [index] if pairs.is_empty() => list.push(index),
+                [old, index] if list.is_empty() => pairs.push((old, index)),
+                // Every line has the same form
+                _ => return Err(Error::InvalidField { line: k + 1, field: String::from_utf8_lossy(line.trim_ascii()).into_owned() }),
+            }
+        }
+        if list.is_empty() { Self::from_pairs(pairs) } else { Self::from_list(list) }
+    }
+
+    /// New index of (1-based) old index `old`, if it is mapped.
+    pub fn get(&self, old: usize) -> Option<usize> {
+        self.new.get(old.wrapping_sub(1)).copied().filter(|&index| index != 0)
+    }
+
+    /// Number of new indices, the largest of them.
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Fails on the first new index that occurs more than once.
+    fn check_new(new: &[usize], len: usize) -> Result<(), Error> {
+        let mut seen = vec![false; len];
+        for &index in new.iter().filter(|&&index| index != 0) {
+            if seen[index - 1] {
+                return Err(Error::InvalidIndexMap { index });
+            }
+            seen[index - 1] = true;
+        }
+        Ok(())
+    }
+}
+
+impl Matrix {
+    /// Relabels the rows and columns through the given maps, or keeps their indices without one,
+    /// and drops the entries in unmapped rows or columns.
+    /// The dimensions become the number of new indices of each map.
+    ///
+    /// Compact symmetric forms need the same map for their rows and columns,
+    /// and entries that are relabeled to the upper triangle move to their mirrored positions.
+    pub fn relabel(&mut self, rows: Option<&IndexMap>, cols: Option<&IndexMap>) -> Result<(), Error> {
+        if rows != cols {
+            self.check_general()?;
+        }
+
+        let indices: Vec<_> = (0..self.nvals).into_par_iter()
+            .filter(|&i| rows.is_none_or(|map| map.get(self.rows[i]).is_some())
+                && cols.is_none_or(|map| map.get(self.cols[i]).is_some()))
+            .collect();
+        if indices.len() < self.nvals {
+            *self = self.gather(&indices);
+        }
+
+        if let Some(map) = rows {
+            self.rows.par_iter_mut().for_each(|row| *row = map.get(*row).unwrap());
+            self.nrows = map.len();
+        }
+        if let Some(map) = cols {
+            self.cols.par_iter_mut().for_each(|col| *col = map.get(*col).unwrap());
+            self.ncols = map.len();
+        }
+        self.sortedness = None;
+
+        self.fold_to_lower();
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+//! Reading and writing matrices in object stores, addressed by `s3://`, `gs://`, and `az://` URLs.
+//! Credentials and other options are taken from the environment, such as `AWS_ACCESS_KEY_ID` and `AWS_REGION`,
+//! `GOOGLE_SERVICE_ACCOUNT`, or `AZURE_STORAGE_ACCOUNT_NAME` and `AZURE_STORAGE_ACCOUNT_KEY`.
+
+use std::io;
+use std::sync::Arc;
+
+use object_store::buffered::BufWriter;
+use object_store::path::Path;
+use object_store::{ObjectStore, ObjectStoreExt};
+use tokio_util::io::StreamReader;
+use url::Url;
+
+use crate::{DataType, Error, Matrix, ReadOptions, TextFormat};
+
+impl Matrix {
+    /// Downloads a Matrix-Market file from an object store, parsing it while it streams in.
+    pub async fn from_url(url: &str, data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        let (store, path) = parse_url(url)?;
+        let stream = store.get(&path).await.map_err(io::Error::from)?.into_stream();
+        Matrix::from_async_reader(StreamReader::new(stream), data_type, options).await
+    }
+
+    /// Uploads the matrix to an object store, in parts while it is being written.
+    pub async fn write_url(&self, url: &str) -> io::Result<()> {
+        self.write_url_with(url, &TextFormat::default()).await
+    }
+
+    /// Like [`Matrix::write_url`], but with the given text format, see [`Matrix::formatted`].
+    pub async fn write_url_with(&self, url: &str, format: &TextFormat) -> io::Result<()> {
+        let (store, path) = parse_url(url)?;
+        self.write_async_with(BufWriter::new(Arc::from(store), path), format).await
+    }
+}
+
+/// The object store of a URL, configured from the environment, and the path within it.
+fn parse_url(url: &str) -> io::Result<(Box<dyn ObjectStore>, Path)> {
+    let url = Url::parse(url).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid URL `{url}`: {e}")))?;
+    object_store::parse_url_opts(&url, std::env::vars()).map_err(io::Error::from)
+}
@@ -0,0 +1,425 @@
+use std::collections::BTreeSet;
+
+use rayon::prelude::*;
+
+use crate::generators::Rng;
+use crate::{Error, Matrix, MatrixData, Symmetry};
+
+/// Symmetric reorderings of the rows and columns of a square matrix.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Reordering {
+    /// Reverse Cuthill-McKee, which reduces the bandwidth and profile.
+    Rcm,
+    /// Rows of smallest degree first; rows of equal degree keep their relative order.
+    DegreeAscending,
+    /// Rows of largest degree first; rows of equal degree keep their relative order.
+    DegreeDescending,
+    /// Approximate minimum degree, after Amestoy, Davis, and Duff, which eliminates a row of smallest degree first
+    /// to reduce the fill of Cholesky and LU factorizations; ties go to the lowest row.
+    /// Unlike the AMD library, rows with the same structure are not merged into supervariables.
+    Amd,
+    /// Nested dissection, which orders a separator of the graph after the two halves it splits apart,
+    /// recursively, such that the halves can be factorized independently, as parallel direct solvers do.
+    /// Separators are middle levels of breadth-first searches from pseudo-peripheral rows,
+    /// and parts of at most 64 rows are ordered by approximate minimum degree.
+    NestedDissection,
+    /// Uniformly random permutation, deterministic for a given seed.
+    /// Useful for destroying accidental locality when benchmarking.
+    Random { seed: u64 },
+}
+
+impl Matrix {
+    /// Computes a permutation of the given kind, without applying it.
+    /// Entry `k` of the permutation is the (0-based) index of the row and column that moves to position `k`.
+    ///
+    /// Apart from random permutations, the permutation depends only on the structure of the matrix plus its transpose.
+    pub fn reordering(&self, method: Reordering) -> Result<Vec<usize>, Error> {
+        self.check_square()?;
+
+        Ok(match method {
+            Reordering::Rcm => self.reverse_cuthill_mckee(),
+            Reordering::DegreeAscending => {
+                let adjacency = self.adjacency();
+                let mut order: Vec<_> = (0..self.nrows).collect();
+                order.sort_by_key(|&v| adjacency[v].len());
+                order
+            },
+            Reordering::DegreeDescending => {
+                let adjacency = self.adjacency();
+                let mut order: Vec<_> = (0..self.nrows).collect();
+                order.sort_by_key(|&v| std::cmp::Reverse(adjacency[v].len()));
+                order
+            },
+            Reordering::Amd => approximate_minimum_degree(self.adjacency()),
+            Reordering::NestedDissection => self.nested_dissection(),
+            Reordering::Random { seed } => {
+                let mut rng = Rng::new(seed);
+                let mut order: Vec<_> = (0..self.nrows).collect();
+                for i in (1..order.len()).rev() {
+                    order.swap(i, rng.below(i + 1));
+                }
+                order
+            },
+        })
+    }
+
+    /// Computes and applies a permutation of the given kind, and returns it.
+    pub fn reorder(&mut self, method: Reordering) -> Result<Vec<usize>, Error> {
+        let permutation = self.reordering(method)?;
+        self.permute_symmetric(&permutation);
+        Ok(permutation)
+    }
+
+    /// Moves row and column `permutation[k]` to position `k`, for every `k`.
+    /// Matrices stored in a compact symmetric form keep only their lower triangle.
+    ///
+    /// Panics if `permutation` is not a permutation of the rows.
+    pub fn permute_symmetric(&mut self, permutation: &[usize]) {
+        assert_eq!(permutation.len(), self.nrows, "expected one index per row");
+        let inverse = inverse(permutation);
+
+        self.rows.par_iter_mut().for_each(|row| *row = inverse[*row - 1] + 1);
+        self.cols.par_iter_mut().for_each(|col| *col = inverse[*col - 1] + 1);
+        self.sortedness = None;
+
+        self.fold_to_lower();
+    }
+
+    /// Moves row `row_permutation[k]` and column `col_permutation[k]` to position `k`, for every `k`.
+    /// Unlike [`Matrix::permute_symmetric`], this requires a general matrix.
+    ///
+    /// Panics if either is not a permutation of the rows or columns respectively.
+    pub fn permute(&mut self, row_permutation: &[usize], col_permutation: &[usize]) -> Result<(), Error> {
+        self.check_general()?;
+        assert_eq!(row_permutation.len(), self.nrows, "expected one index per row");
+        assert_eq!(col_permutation.len(), self.ncols, "expected one index per column");
+        let (row_inverse, col_inverse) = (inverse(row_permutation), inverse(col_permutation));
+
+        self.rows.par_iter_mut().for_each(|row| *row = row_inverse[*row - 1] + 1);
+        self.cols.par_iter_mut().for_each(|col| *col = col_inverse[*col - 1] + 1);
+        self.sortedness = None;
+        Ok(())
+    }
+
+    /// Moves entry `i` to the other triangle, adjusting its value as the symmetry requires.
+    pub(crate) fn mirror_at(&mut self, i: usize) {
+        std::mem::swap(&mut self.rows[i], &mut self.cols[i]);
+        self.sortedness = None;
+        match (&mut self.vals, self.symmetry) {
+            (MatrixData::Real(xs), Symmetry::SkewSymmetric) => xs[i] = -xs[i],
+            (MatrixData::Integer(xs), Symmetry::SkewSymmetric) => xs[i] = -xs[i],
+            (MatrixData::Complex(xs, ys), Symmetry::SkewSymmetric) => {
+                xs[i] = -xs[i];
+                ys[i] = -ys[i];
+            },
+            (MatrixData::Complex(_, ys), Symmetry::Hermitian) => ys[i] = -ys[i],
+            _ => {
+                /* nothing to do */
+            },
+        }
+    }
+
+    pub(crate) fn check_square(&self) -> Result<(), Error> {
+        if self.nrows != self.ncols {
+            let n = self.nrows.max(self.ncols);
+            return Err(Error::DimensionMismatch { expected: (n, n), found: (self.nrows, self.ncols) });
+        }
+
+        Ok(())
+    }
+
+    /// Neighbours of every (0-based) row in the structure of the matrix plus its transpose,
+    /// sorted and without the diagonal.
+    pub(crate) fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.nrows.max(self.ncols)];
+        for i in 0..self.nvals {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            if row != col {
+                adjacency[row].push(col);
+                adjacency[col].push(row);
+            }
+        }
+
+        adjacency.par_iter_mut().for_each(|neighbours| {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        });
+        adjacency
+    }
+
+    fn nested_dissection(&self) -> Vec<usize> {
+        let adjacency = self.adjacency();
+        let n = adjacency.len();
+        let mut dissection = Dissection {
+            adjacency: &adjacency,
+            part: vec![0; n],
+            parts: 1,
+            levels: vec![usize::MAX; n],
+            local: vec![usize::MAX; n],
+            order: Vec::with_capacity(n),
+        };
+        dissection.dissect(0, (0..n).collect());
+        dissection.order
+    }
+
+    fn reverse_cuthill_mckee(&self) -> Vec<usize> {
+        let adjacency = self.adjacency();
+        let n = adjacency.len();
+
+        let mut by_degree: Vec<_> = (0..n).collect();
+        by_degree.sort_by_key(|&v| adjacency[v].len());
+
+        let mut order = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        let (mut levels, mut queue) = (vec![usize::MAX; n], Vec::new());
+        for &v in &by_degree {
+            if visited[v] {
+                continue;
+            }
+
+            let start = pseudo_peripheral(&adjacency, v, |_| true, &mut levels, &mut queue);
+            visited[start] = true;
+            order.push(start);
+
+            let mut head = order.len() - 1;
+            while head < order.len() {
+                let v = order[head];
+                head += 1;
+
+                let first = order.len();
+                for &u in &adjacency[v] {
+                    if !visited[u] {
+                        visited[u] = true;
+                        order.push(u);
+                    }
+                }
+                order[first..].sort_by_key(|&u| adjacency[u].len());
+            }
+        }
+
+        order.reverse();
+        order
+    }
+}
+
+/// Approximate minimum degree on the quotient graph, where the eliminated rows form elements
+/// whose rows are pairwise connected, and the degrees are bounded as in AMD.
+/// `variables` starts as the adjacency lists, and keeps the rows adjacent to every row besides through elements.
+fn approximate_minimum_degree(mut variables: Vec<Vec<usize>>) -> Vec<usize> {
+    let n = variables.len();
+    // Elements adjacent to every row, and the rows of every element, which is named after its pivot
+    let mut elements: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut absorbed = vec![false; n];
+
+    let mut degree: Vec<_> = variables.iter().map(Vec::len).collect();
+    let mut queue: BTreeSet<_> = degree.iter().copied().zip(0..n).collect();
+    let mut mark = vec![usize::MAX; n];
+    // Number of rows of every element outside the new element, valid for elements stamped with the pivot
+    let mut external = vec![0; n];
+    let mut stamp = vec![usize::MAX; n];
+
+    let mut order = Vec::with_capacity(n);
+    while let Some((_, p)) = queue.pop_first() {
+        order.push(p);
+
+        // The new element has the neighbours of the pivot, and the rows of the elements it absorbs
+        let mut pivot = Vec::new();
+        mark[p] = p;
+        for &i in variables[p].iter().chain(elements[p].iter().flat_map(|&e| &members[e])) {
+            if mark[i] != p {
+                mark[i] = p;
+                pivot.push(i);
+            }
+        }
+        for e in std::mem::take(&mut elements[p]) {
+            absorbed[e] = true;
+            members[e] = Vec::new();
+        }
+        variables[p] = Vec::new();
+
+        for &i in &pivot {
+            elements[i].retain(|&e| !absorbed[e]);
+            for &e in &elements[i] {
+                if stamp[e] != p {
+                    stamp[e] = p;
+                    external[e] = members[e].len();
+                }
+                external[e] -= 1;
+            }
+        }
+
+        for &i in &pivot {
+            // Rows of the new element are connected through it
+            variables[i].retain(|&j| mark[j] != p);
+            let bound = variables[i].len() + pivot.len() - 1 + elements[i].iter().map(|&e| external[e]).sum::<usize>();
+            queue.remove(&(degree[i], i));
+            degree[i] = bound.min(degree[i] + pivot.len() - 1).min(n - order.len() - 1);
+            queue.insert((degree[i], i));
+            elements[i].push(p);
+        }
+        members[p] = pivot;
+    }
+    order
+}
+
+/// Largest part that nested dissection orders by approximate minimum degree instead of splitting it.
+const LEAF_SIZE: usize = 64;
+
+/// Nested dissection in progress, where the rows of every part that remains to be ordered share a label.
+struct Dissection<'a> {
+    adjacency: &'a [Vec<usize>],
+    part: Vec<usize>,
+    parts: usize,
+    levels: Vec<usize>,
+    /// Index of every row within its part, for the parts ordered by approximate minimum degree.
+    local: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl Dissection<'_> {
+    /// Appends the rows of the part with the label `id` to the order, splitting it recursively.
+    fn dissect(&mut self, id: usize, rows: Vec<usize>) {
+        if rows.len() <= LEAF_SIZE {
+            return self.order_leaf(id, &rows);
+        }
+
+        // Components are ordered one after another, without a separator
+        let mut components = Vec::new();
+        for &row in &rows {
+            if self.part[row] == id {
+                let label = self.new_part();
+                self.part[row] = label;
+                let mut component = vec![row];
+                let mut head = 0;
+                while head < component.len() {
+                    let v = component[head];
+                    head += 1;
+                    for &u in &self.adjacency[v] {
+                        if self.part[u] == id {
+                            self.part[u] = label;
+                            component.push(u);
+                        }
+                    }
+                }
+                components.push((label, component));
+            }
+        }
+        if components.len() > 1 {
+            return components.into_iter().for_each(|(label, component)| self.dissect(label, component));
+        }
+        let (id, rows) = components.pop().unwrap();
+
+        // The separator is the part of the middle level of a long level structure that borders the next level
+        let part = &self.part;
+        let mut queue = Vec::with_capacity(rows.len());
+        let start = pseudo_peripheral(self.adjacency, rows[0], |v| part[v] == id, &mut self.levels, &mut queue);
+        let last = level_structure(self.adjacency, start, |v| part[v] == id, &mut self.levels, &mut queue);
+        if last < 2 {
+            queue.iter().for_each(|&v| self.levels[v] = usize::MAX);
+            return self.order_leaf(id, &rows);
+        }
+
+        let middle = self.levels[queue[queue.len() / 2]].clamp(1, last - 1);
+        let (lower_id, upper_id) = (self.new_part(), self.new_part());
+        let (mut lower, mut upper, mut separator) = (Vec::new(), Vec::new(), Vec::new());
+        for &v in &queue {
+            let level = self.levels[v];
+            if level > middle {
+                self.part[v] = upper_id;
+                upper.push(v);
+            } else if level == middle && self.adjacency[v].iter().any(|&u| self.levels[u] == middle + 1) {
+                self.part[v] = usize::MAX;
+                separator.push(v);
+            } else {
+                self.part[v] = lower_id;
+                lower.push(v);
+            }
+        }
+        queue.iter().for_each(|&v| self.levels[v] = usize::MAX);
+
+        self.dissect(lower_id, lower);
+        self.dissect(upper_id, upper);
+        self.order.extend(separator);
+    }
+
+    /// Appends the rows of a small part to the order, by approximate minimum degree on the graph of the part.
+    fn order_leaf(&mut self, id: usize, rows: &[usize]) {
+        rows.iter().enumerate().for_each(|(k, &row)| self.local[row] = k);
+        let adjacency = rows.iter()
+            .map(|&row| self.adjacency[row].iter().filter(|&&u| self.part[u] == id).map(|&u| self.local[u]).collect())
+            .collect();
+        self.order.extend(approximate_minimum_degree(adjacency).into_iter().map(|k| rows[k]));
+    }
+
+    fn new_part(&mut self) -> usize {
+        self.parts += 1;
+        self.parts - 1
+    }
+}
+
+/// Position of every index in the permutation.
+fn inverse(permutation: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![usize::MAX; permutation.len()];
+    for (new, &old) in permutation.iter().enumerate() {
+        assert!(inverse[old] == usize::MAX, "index {old} occurs more than once");
+        inverse[old] = new;
+    }
+    inverse
+}
+
+/// Finds a vertex of large eccentricity in the component of `start` among the rows for which `inside` holds,
+/// following George and Liu: repeatedly move to a vertex of smallest degree in the last level of a breadth-first search,
+/// for as long as the number of levels grows. `levels` holds `usize::MAX` for every row, before and after.
+fn pseudo_peripheral(
+    adjacency: &[Vec<usize>],
+    mut start: usize,
+    inside: impl Fn(usize) -> bool,
+    levels: &mut [usize],
+    queue: &mut Vec<usize>,
+) -> usize {
+    let mut depth = 0;
+    loop {
+        let last = level_structure(adjacency, start, &inside, levels, queue);
+        let candidate = queue.iter()
+            .copied()
+            .filter(|&v| levels[v] == last)
+            .min_by_key(|&v| adjacency[v].len())
+            .unwrap();
+
+        queue.iter().for_each(|&v| levels[v] = usize::MAX);
+        if last <= depth {
+            return start;
+        }
+
+        depth = last;
+        start = candidate;
+    }
+}
+
+/// Breadth-first search from `start` among the rows for which `inside` holds, which sets the level of every row it reaches,
+/// lists them in `queue` by level, and returns the last level.
+fn level_structure(
+    adjacency: &[Vec<usize>],
+    start: usize,
+    inside: impl Fn(usize) -> bool,
+    levels: &mut [usize],
+    queue: &mut Vec<usize>,
+) -> usize {
+    queue.clear();
+    queue.push(start);
+    levels[start] = 0;
+
+    let mut head = 0;
+    while head < queue.len() {
+        let v = queue[head];
+        head += 1;
+        for &u in &adjacency[v] {
+            if levels[u] == usize::MAX && inside(u) {
+                levels[u] = levels[v] + 1;
+                queue.push(u);
+            }
+        }
+    }
+    levels[*queue.last().unwrap()]
+}
@@ -0,0 +1,97 @@
+//! Views of the rows of a matrix in row-major order, which consume the sorted entries like CSR without building it,
+//! and lookups of single entries.
+
+use std::ops::Range;
+
+use rayon::prelude::*;
+
+use crate::{offsets, Int, Matrix, MatrixData, Real, SortOrder, Value};
+
+/// Borrowed values of consecutive entries, in the layout of the matrix.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ValueSlice<'a> {
+    Real(&'a [Real]),
+    /// Real and imaginary parts.
+    Complex(&'a [Real], &'a [Real]),
+    Integer(&'a [Int]),
+    /// Pattern matrices have no values.
+    Bool,
+}
+
+/// Entries of a single row, see [`Matrix::row`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RowView<'a> {
+    row: usize,
+    cols: &'a [usize],
+    vals: ValueSlice<'a>,
+}
+
+impl<'a> RowView<'a> {
+    /// (1-based) index of the row.
+    pub fn row(&self) -> usize { self.row }
+    /// (1-based) column indices of the entries, in increasing order.
+    pub fn cols(&self) -> &'a [usize] { self.cols }
+    pub fn values(&self) -> ValueSlice<'a> { self.vals }
+    pub fn len(&self) -> usize { self.cols.len() }
+    pub fn is_empty(&self) -> bool { self.cols.is_empty() }
+}
+
+impl Matrix {
+    /// Entries of (1-based) row `i`, without copying them.
+    ///
+    /// Requires the entries to be in row-major order, such as after [`Matrix::sort_row_major`].
+    /// Panics if `i` is not a row of the matrix.
+    pub fn row(&self, i: usize) -> RowView<'_> {
+        assert!((1..=self.nrows).contains(&i), "row {i} is out of range for {} rows", self.nrows);
+        debug_assert!(self.is_row_major(), "expected the entries in row-major order");
+
+        self.row_view(i, major_range(&self.rows, i))
+    }
+
+    /// Entries of every row in turn, including empty rows, without copying them.
+    ///
+    /// Requires the entries to be in row-major order, such as after [`Matrix::sort_row_major`].
+    pub fn rows_iter(&self) -> impl Iterator<Item = RowView<'_>> {
+        debug_assert!(self.is_row_major(), "expected the entries in row-major order");
+
+        let offsets = offsets(&self.rows, self.nrows);
+        (1..=self.nrows).map(move |i| self.row_view(i, offsets[i - 1]..offsets[i]))
+    }
+
+    /// Value of the entry at (1-based) row `row` and column `col`, or `None` if no entry is stored there
+    /// or the data type does not match, as for [`Matrix::values`].
+    /// If there are duplicate entries, the value of any of them is returned,
+    /// and compact symmetric forms only have entries in their lower triangle.
+    ///
+    /// Entries that are known to be in row-major or column-major order, such as after sorting them, are binary-searched,
+    /// and other entries are scanned.
+    pub fn get<N: Value>(&self, row: usize, col: usize) -> Option<N> {
+        let search = |major: &[usize], minor: &[usize], (i, j)| {
+            let range = major_range(major, i);
+            let start = range.start;
+            minor[range].binary_search(&j).ok().map(|k| start + k)
+        };
+        let index = match self.sortedness {
+            Some(SortOrder::RowMajor) => search(&self.rows, &self.cols, (row, col)),
+            Some(SortOrder::ColMajor) => search(&self.cols, &self.rows, (col, row)),
+            _ => (0..self.nvals).into_par_iter().find_any(|&i| self.rows[i] == row && self.cols[i] == col),
+        };
+        N::value_at(self, index?)
+    }
+
+    fn row_view(&self, row: usize, range: Range<usize>) -> RowView<'_> {
+        let vals = match &self.vals {
+            MatrixData::Real(xs) => ValueSlice::Real(&xs[range.clone()]),
+            MatrixData::Complex(xs, ys) => ValueSlice::Complex(&xs[range.clone()], &ys[range.clone()]),
+            MatrixData::Integer(xs) => ValueSlice::Integer(&xs[range.clone()]),
+            MatrixData::Bool() => ValueSlice::Bool,
+        };
+        RowView { row, cols: &self.cols[range], vals }
+    }
+}
+
+/// Range of the entries with major index `i`, given the major indices of entries sorted by them.
+fn major_range(major: &[usize], i: usize) -> Range<usize> {
+    let start = major.partition_point(|&k| k < i);
+    start..start + major[start..].partition_point(|&k| k == i)
+}
@@ -0,0 +1,97 @@
+use rayon::prelude::*;
+
+use crate::{Matrix, MatrixData, Real};
+
+impl Matrix {
+    /// Multiplies every entry in row `i` by `factors[i - 1]`.
+    /// Integer and pattern matrices are converted to real matrices first,
+    /// and compact symmetric forms are expanded, as scaled rows break the symmetry.
+    pub fn scale_rows(&mut self, factors: &[f64]) {
+        assert_eq!(factors.len(), self.nrows, "expected one factor per row");
+        self.expand_symmetric();
+        self.scale_by(|row, _| factors[row - 1]);
+    }
+
+    /// Multiplies every entry in column `j` by `factors[j - 1]`, like [`Matrix::scale_rows`].
+    pub fn scale_cols(&mut self, factors: &[f64]) {
+        assert_eq!(factors.len(), self.ncols, "expected one factor per column");
+        self.expand_symmetric();
+        self.scale_by(|_, col| factors[col - 1]);
+    }
+
+    /// Scales the rows, and then the columns, by the inverse of their infinity-norm,
+    /// such that the largest absolute value in every non-empty row and column is one.
+    ///
+    /// Returns the row and column factors that were applied; empty rows and columns get a factor of one.
+    /// Scaling by the reciprocals of these factors undoes the equilibration, up to rounding.
+    /// Compact symmetric forms are expanded first, such that the norms include the implied entries.
+    pub fn equilibrate(&mut self) -> (Vec<f64>, Vec<f64>) {
+        self.expand_symmetric();
+        let row_factors = inverse_norms(self.max_abs_per(&self.rows, self.nrows));
+        self.scale_rows(&row_factors);
+
+        let col_factors = inverse_norms(self.max_abs_per(&self.cols, self.ncols));
+        self.scale_cols(&col_factors);
+
+        (row_factors, col_factors)
+    }
+
+    /// Largest absolute value in each row or column, given the corresponding indices.
+    fn max_abs_per(&self, indices: &[usize], n: usize) -> Vec<f64> {
+        let mut norms = vec![0.0; n];
+        for (i, &idx) in indices.iter().enumerate() {
+            let norm = &mut norms[idx - 1];
+            *norm = self.abs_at(i).max(*norm);
+        }
+        norms
+    }
+
+    fn scale_by<F>(&mut self, factor: F)
+    where
+        F: Fn(usize, usize) -> f64 + Sync,
+    {
+        self.make_real();
+
+        let coords = self.rows.par_iter().zip(self.cols.par_iter());
+        match &mut self.vals {
+            MatrixData::Real(xs) => {
+                coords.zip(xs.par_iter_mut())
+                    .for_each(|((&row, &col), x)| {
+                        *x = (*x as f64 * factor(row, col)) as Real;
+                    });
+            },
+            MatrixData::Complex(xs, ys) => {
+                coords.zip(xs.par_iter_mut())
+                    .zip(ys.par_iter_mut())
+                    .for_each(|(((&row, &col), x), y)| {
+                        let f = factor(row, col);
+                        *x = (*x as f64 * f) as Real;
+                        *y = (*y as f64 * f) as Real;
+                    });
+            },
+            MatrixData::Integer(_) | MatrixData::Bool() => unreachable!(),
+        }
+    }
+
+    /// Converts integer values to reals, and gives pattern entries an explicit value of one.
+    fn make_real(&mut self) {
+        match &self.vals {
+            MatrixData::Integer(xs) => {
+                self.vals = MatrixData::Real(xs.par_iter().map(|&x| x as Real).collect());
+            },
+            MatrixData::Bool() => {
+                self.vals = MatrixData::Real(vec![1.0; self.nvals]);
+            },
+            MatrixData::Real(_) | MatrixData::Complex(_, _) => {
+                /* nothing to do */
+            },
+        }
+    }
+}
+
+#[inline]
+fn inverse_norms(norms: Vec<f64>) -> Vec<f64> {
+    norms.into_iter()
+        .map(|norm| if norm > 0.0 { 1.0 / norm } else { 1.0 })
+        .collect()
+}
@@ -0,0 +1,130 @@
+//! Serialization of matrices as `{nrows, ncols, data_type, symmetry, rows, cols, values}`,
+//! with 1-based indices and the data type named as in the Matrix-Market banner.
+//! Complex values are stored as `[re, im]` pairs, and pattern matrices have no values.
+
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::read::check_shape;
+use crate::{Int, Matrix, MatrixData, Real, Symmetry};
+
+impl Serialize for Matrix {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Matrix", 7)?;
+        s.serialize_field("nrows", &self.nrows)?;
+        s.serialize_field("ncols", &self.ncols)?;
+        s.serialize_field("data_type", self.vals.field())?;
+        s.serialize_field("symmetry", &self.symmetry.to_string())?;
+        s.serialize_field("rows", &self.rows)?;
+        s.serialize_field("cols", &self.cols)?;
+
+        match &self.vals {
+            MatrixData::Real(xs) => {
+                s.serialize_field("values", xs)?;
+            },
+            MatrixData::Complex(xs, ys) => {
+                s.serialize_field("values", &ComplexValues(xs, ys))?;
+            },
+            MatrixData::Integer(xs) => {
+                s.serialize_field("values", xs)?;
+            },
+            MatrixData::Bool() => {
+                s.skip_field("values")?;
+            },
+        }
+
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Matrix {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = Repr::deserialize(deserializer)?;
+        repr.try_into().map_err(de::Error::custom)
+    }
+}
+
+struct ComplexValues<'a>(&'a [Real], &'a [Real]);
+
+impl Serialize for ComplexValues<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().zip(self.1).map(|(&x, &y)| [x, y]))
+    }
+}
+
+#[derive(Deserialize)]
+struct Repr {
+    nrows: usize,
+    ncols: usize,
+    data_type: String,
+    #[serde(default)]
+    symmetry: Option<String>,
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    #[serde(default)]
+    values: Option<Values>,
+}
+
+/// Values as they appear in the input; integers are tried first,
+/// so real values that happen to be whole numbers end up here as well.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Values {
+    Integer(Vec<Int>),
+    Real(Vec<Real>),
+    Complex(Vec<[Real; 2]>),
+}
+
+impl TryFrom<Repr> for Matrix {
+    type Error = String;
+
+    fn try_from(repr: Repr) -> Result<Self, String> {
+        let nvals = repr.rows.len();
+        if repr.cols.len() != nvals {
+            return Err(format!("expected {nvals} column indices, found {}", repr.cols.len()));
+        }
+        if let Some(row) = repr.rows.iter().find(|&&row| row == 0 || row > repr.nrows) {
+            return Err(format!("row index {row} out of bounds for {} rows", repr.nrows));
+        }
+        if let Some(col) = repr.cols.iter().find(|&&col| col == 0 || col > repr.ncols) {
+            return Err(format!("column index {col} out of bounds for {} columns", repr.ncols));
+        }
+
+        let vals = match (repr.data_type.as_str(), repr.values) {
+            ("real", Some(Values::Real(xs))) => MatrixData::Real(xs),
+            ("real", Some(Values::Integer(xs))) => MatrixData::Real(xs.into_iter().map(|x| x as Real).collect()),
+            ("integer", Some(Values::Integer(xs))) => MatrixData::Integer(xs),
+            ("complex", Some(Values::Complex(zs))) => MatrixData::Complex(zs.iter().map(|z| z[0]).collect(), zs.iter().map(|z| z[1]).collect()),
+            // An empty list is indistinguishable between the value types.
+            ("complex", Some(Values::Integer(xs))) if xs.is_empty() => MatrixData::Complex(Vec::new(), Vec::new()),
+            ("pattern", None) => MatrixData::Bool(),
+            (data_type @ ("real" | "integer" | "complex"), _) => {
+                return Err(format!("expected a list of {data_type} values"));
+            },
+            ("pattern", Some(_)) => return Err("expected no values for a pattern matrix".to_string()),
+            (data_type, _) => return Err(format!("unknown data type `{data_type}`")),
+        };
+
+        let len = match &vals {
+            MatrixData::Real(xs) => xs.len(),
+            MatrixData::Complex(xs, _) => xs.len(),
+            MatrixData::Integer(xs) => xs.len(),
+            MatrixData::Bool() => nvals,
+        };
+        if len != nvals {
+            return Err(format!("expected {nvals} values, found {len}"));
+        }
+
+        let mut m = Matrix::new(repr.nrows, repr.ncols, repr.rows, repr.cols, vals);
+        if let Some(symmetry) = repr.symmetry {
+            m.symmetry = symmetry.parse::<Symmetry>()?;
+        }
+
+        // The same checks as for a Matrix-Market file, and that compact forms only store their lower triangle
+        check_shape(m.symmetry, m.nrows, m.ncols).map_err(|e| e.to_string())?;
+        m.check_skew_diagonal().map_err(|e| e.to_string())?;
+        m.check_lower_storage().map_err(|e| e.to_string())?;
+        Ok(m)
+    }
+}
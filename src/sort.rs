@@ -0,0 +1,342 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io;
+
+use log::warn;
+use rayon::prelude::*;
+
+use crate::memory::available_memory;
+use crate::{offsets, Matrix, MatrixData};
+
+/// Number of entries below which the automatic strategy always sorts tuples.
+const AUTO_ZIP_THRESHOLD: usize = 1 << 16;
+
+/// Orders of the entries of a matrix.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum SortOrder {
+    RowMajor,
+    ColMajor,
+    /// Along a Z-order curve, by interleaving the bits of the row and column.
+    Morton,
+    /// Along a Hilbert curve, which unlike a Z-order curve only moves between adjacent tiles.
+    Hilbert,
+}
+
+/// How to order entries with the same coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum TieBreak {
+    /// Any order, or their original order with a stable sort.
+    #[default]
+    None,
+    /// Ascending by value; complex values by their real part, and then by their imaginary part.
+    Value,
+    /// Descending by absolute value, or by magnitude for complex values.
+    AbsDesc,
+}
+
+/// How to sort entries into row- or column-major order, trading speed for memory.
+/// Other orders, and tie-breaking by value, always sort a permutation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum)]
+pub enum SortStrategy {
+    /// Sort tuples if they fit in the available memory, otherwise a permutation,
+    /// and otherwise sort externally.
+    #[default]
+    Auto,
+    /// Copy the entries into tuples and sort those, which is fastest but roughly doubles the memory.
+    /// Sorts a permutation instead if the sort must be stable.
+    Zip,
+    /// Sort a permutation and apply it in place, which allocates one index per entry.
+    Permute,
+    /// Stable counting sort by the minor and then by the major index,
+    /// in time linear in the number of entries and the dimensions, but not in parallel.
+    Counting,
+    /// Stable sort of chunks of entries that are spilled to temporary files and merged back,
+    /// which allocates one chunk beyond the entries.
+    External,
+}
+
+/// Options for sorting entries, see [`Matrix::sort_with`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    stable: bool,
+    pub(crate) tie_break: TieBreak,
+    strategy: SortStrategy,
+    pub(crate) chunk_size: Option<usize>,
+}
+
+impl SortOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep entries that compare equal in their original order.
+    pub fn stable(mut self, stable: bool) -> Self {
+        self.stable = stable;
+        self
+    }
+
+    /// How to order entries with the same coordinates.
+    /// Summing duplicate entries after sorting then adds their values in a fixed order, regardless of the thread count.
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// How to sort into row- or column-major order.
+    pub fn strategy(mut self, strategy: SortStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Number of entries per chunk of the external sort.
+    ///
+    /// Panics when sorting externally if the chunk size is zero.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+}
+
+impl Matrix {
+    /// Sorts the entries into the given order,
+    /// picking a strategy from the number of entries and the available memory.
+    /// Fails as [`Matrix::sort_with`] does.
+    pub fn sort(&mut self, order: SortOrder) -> io::Result<()> {
+        self.sort_with(order, &SortOptions::default())
+    }
+
+    /// Sorts the entries into the given order, with the given sort options.
+    /// Entries that are known to be in that order are left as they are, unless ties are broken by value or magnitude.
+    ///
+    /// Only the external strategy can fail, if its temporary files cannot be read back after the entries are freed,
+    /// which leaves the matrix without entries. If they cannot be written, the entries are sorted in memory instead.
+    #[tracing::instrument(name = "sort", skip_all, fields(%order))]
+    pub fn sort_with(&mut self, order: SortOrder, options: &SortOptions) -> io::Result<()> {
+        if self.is_sorted(order) && options.tie_break == TieBreak::None {
+            return Ok(());
+        }
+
+        let strategy = match options.strategy {
+            SortStrategy::Auto => self.auto_strategy(),
+            strategy => strategy,
+        };
+        let untied = options.tie_break == TieBreak::None;
+        let major = matches!(order, SortOrder::RowMajor | SortOrder::ColMajor);
+        let col_major = order == SortOrder::ColMajor;
+
+        match strategy {
+            SortStrategy::Zip if major && untied && !options.stable => {
+                if col_major { self.sort_col_major() } else { self.sort_row_major() }
+                return Ok(());
+            },
+            SortStrategy::Counting if major && untied => {
+                self.counting_sort(col_major);
+                self.sortedness = Some(order);
+                return Ok(());
+            },
+            SortStrategy::External if major && untied => {
+                let chunk_size = options.chunk_size.unwrap_or(crate::external::CHUNK_SIZE);
+                match self.spill_runs(col_major, chunk_size) {
+                    Ok(runs) => {
+                        runs.merge_into(self)?;
+                        self.sortedness = Some(order);
+                        return Ok(());
+                    },
+                    Err(e) => warn!("could not sort externally, sorting a permutation instead: {e}"),
+                }
+            },
+            _ => {
+                /* sort a permutation */
+            },
+        }
+
+        match order {
+            SortOrder::RowMajor => self.sort_by_key_with(|m, i| (m.rows[i], m.cols[i]), options),
+            SortOrder::ColMajor => self.sort_by_key_with(|m, i| (m.cols[i], m.rows[i]), options),
+            SortOrder::Morton => {
+                let keys: Vec<_> = (0..self.nvals).into_par_iter()
+                    .map(|i| morton(self.rows[i] - 1, self.cols[i] - 1))
+                    .collect();
+                self.sort_by_key_with(|_, i| keys[i], options);
+            },
+            SortOrder::Hilbert => {
+                let bits = usize::BITS - self.nrows.max(self.ncols).saturating_sub(1).leading_zeros();
+                let keys: Vec<_> = (0..self.nvals).into_par_iter()
+                    .map(|i| hilbert(bits, self.rows[i] - 1, self.cols[i] - 1))
+                    .collect();
+                self.sort_by_key_with(|_, i| keys[i], options);
+            },
+        }
+        self.sortedness = Some(order);
+        Ok(())
+    }
+
+    /// Whether the entries are known to be in the given order, because they were sorted into it
+    /// or were read in it, and not moved since.
+    /// Unlike [`Matrix::is_row_major`] and [`Matrix::is_col_major`], this does not inspect the entries.
+    pub fn is_sorted(&self, order: SortOrder) -> bool {
+        self.sortedness == Some(order)
+    }
+
+    /// Records whether the entries are in row-major or column-major order, such as after reading them.
+    pub(crate) fn detect_sortedness(&mut self) {
+        self.sortedness = None;
+        self.sortedness = if self.is_row_major() {
+            Some(SortOrder::RowMajor)
+        } else if self.is_col_major() {
+            Some(SortOrder::ColMajor)
+        } else {
+            None
+        };
+    }
+
+    /// Sorts the entries by tiles of `block_rows` by `block_cols`, in row-major order of the tiles,
+    /// and in row-major order within every tile, as blocked kernels and formats expect.
+    ///
+    /// Panics if either block dimension is zero.
+    pub fn sort_blocked(&mut self, block_rows: usize, block_cols: usize) {
+        self.sort_blocked_with(block_rows, block_cols, &SortOptions::default());
+    }
+
+    /// Sorts the entries by tiles, see [`Matrix::sort_blocked`], with the given sort options.
+    #[tracing::instrument(name = "sort", skip_all, fields(block_rows = block_rows, block_cols = block_cols))]
+    pub fn sort_blocked_with(&mut self, block_rows: usize, block_cols: usize, options: &SortOptions) {
+        assert!(block_rows > 0 && block_cols > 0, "block dimensions must be positive");
+        self.sort_by_key_with(|m, i| {
+            let (row, col) = (m.rows[i], m.cols[i]);
+            ((row - 1) / block_rows, (col - 1) / block_cols, row, col)
+        }, options);
+        self.sortedness = None;
+    }
+
+    /// Picks the fastest strategy whose additional memory fits in the available memory.
+    fn auto_strategy(&self) -> SortStrategy {
+        if self.nvals < AUTO_ZIP_THRESHOLD {
+            return SortStrategy::Zip;
+        }
+        let Some(available) = available_memory() else {
+            return SortStrategy::Zip;
+        };
+
+        let data_type = self.vals.data_type();
+        let base = Matrix::estimated_bytes(self.nvals, data_type);
+        [SortStrategy::Zip, SortStrategy::Permute].into_iter()
+            .find(|&strategy| Matrix::estimated_sort_bytes(self.nvals, data_type, strategy) - base <= available)
+            .unwrap_or(SortStrategy::External)
+    }
+
+    /// Stable counting sort by the minor index, and then by the major index.
+    fn counting_sort(&mut self, col_major: bool) {
+        let (major, minor, nmajor, nminor) = if col_major {
+            (&self.cols, &self.rows, self.ncols, self.nrows)
+        } else {
+            (&self.rows, &self.cols, self.nrows, self.ncols)
+        };
+
+        let by_minor = counting_permutation(minor, nminor, 0..self.nvals);
+        let permutation = counting_permutation(major, nmajor, by_minor.into_iter());
+        self.apply_permutation(permutation);
+    }
+
+    /// Sorts the entries by the key of their index, breaking ties as the options require.
+    fn sort_by_key_with<K, F>(&mut self, key: F, options: &SortOptions)
+    where
+        K: Ord,
+        F: Fn(&Matrix, usize) -> K + Sync,
+    {
+        let compare = |&a: &usize, &b: &usize| {
+            key(self, a).cmp(&key(self, b)).then_with(|| match options.tie_break {
+                TieBreak::None => Ordering::Equal,
+                TieBreak::Value => self.vals.compare(a, b),
+                TieBreak::AbsDesc => self.abs_at(b).total_cmp(&self.abs_at(a)),
+            })
+        };
+
+        let mut permutation: Vec<_> = (0..self.nvals).collect();
+        if options.stable {
+            permutation.par_sort_by(compare);
+        } else {
+            permutation.par_sort_unstable_by(compare);
+        }
+
+        self.apply_permutation(permutation);
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SortOrder::*;
+        match self {
+            RowMajor => write!(f, "row-major"),
+            ColMajor => write!(f, "col-major"),
+            Morton => write!(f, "morton"),
+            Hilbert => write!(f, "hilbert"),
+        }
+    }
+}
+
+/// Stable order of the entries, visited in the given order, by their (1-based) key up to `n`.
+fn counting_permutation(keys: &[usize], n: usize, order: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut starts = offsets(keys, n);
+    let mut permutation = vec![0; keys.len()];
+    for i in order {
+        let start = &mut starts[keys[i] - 1];
+        permutation[*start] = i;
+        *start += 1;
+    }
+    permutation
+}
+
+/// Position of a (0-based) coordinate along a Z-order curve, with the row bits above the column bits.
+fn morton(row: usize, col: usize) -> u128 {
+    spread(row as u64) << 1 | spread(col as u64)
+}
+
+/// Spreads the bits of `x` to the even bit positions.
+fn spread(x: u64) -> u128 {
+    let mut x = x as u128;
+    x = (x | x << 32) & 0x0000_0000_FFFF_FFFF_0000_0000_FFFF_FFFF;
+    x = (x | x << 16) & 0x0000_FFFF_0000_FFFF_0000_FFFF_0000_FFFF;
+    x = (x | x << 8) & 0x00FF_00FF_00FF_00FF_00FF_00FF_00FF_00FF;
+    x = (x | x << 4) & 0x0F0F_0F0F_0F0F_0F0F_0F0F_0F0F_0F0F_0F0F;
+    x = (x | x << 2) & 0x3333_3333_3333_3333_3333_3333_3333_3333;
+    x = (x | x << 1) & 0x5555_5555_5555_5555_5555_5555_5555_5555;
+    x
+}
+
+/// Position of a (0-based) coordinate along a Hilbert curve through a square of side `2^bits`,
+/// starting at the top left and ending at the top right.
+fn hilbert(bits: u32, row: usize, col: usize) -> u128 {
+    let (mut x, mut y) = (col as u128, row as u128);
+    let mut d = 0;
+    for level in (0..bits).rev() {
+        let s = 1u128 << level;
+        let (rx, ry) = (x & s != 0, y & s != 0);
+        d += s * s * ((3 * rx as u128) ^ ry as u128);
+
+        // Rotate the quadrant, such that the curve through it starts at the top left
+        if !ry {
+            if rx {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+    }
+    d
+}
+
+impl MatrixData {
+    /// Total order of the values at `a` and `b`.
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        match self {
+            MatrixData::Real(xs) => xs[a].total_cmp(&xs[b]),
+            MatrixData::Complex(xs, ys) => xs[a].total_cmp(&xs[b]).then(ys[a].total_cmp(&ys[b])),
+            MatrixData::Integer(xs) => xs[a].cmp(&xs[b]),
+            MatrixData::Bool() => Ordering::Equal,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::ops::{Add, Mul, Neg};
+
+use crate::{Complex, Matrix, MatrixData, Symmetry};
+
+impl Matrix {
+    /// Sparse matrix-vector product `A x`, visiting the entries in their stored order.
+    /// Pattern entries are ones, and matrices stored in a compact symmetric form include their mirrored entries.
+    ///
+    /// Panics if `x` does not have one value per column, or if the matrix is complex; use [`Matrix::spmv_complex`] instead.
+    pub fn spmv(&self, x: &[f64]) -> Vec<f64> {
+        assert!(!matches!(self.vals, MatrixData::Complex(..)), "use `spmv_complex` for complex matrices");
+        self.spmv_by(x, |i| self.value_at(i).0, |v| v)
+    }
+
+    /// Sparse matrix-vector product `A x` for complex matrices, or real matrices with complex vectors.
+    ///
+    /// Panics if `x` does not have one value per column.
+    pub fn spmv_complex(&self, x: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        self.spmv_by(x, |i| {
+            let (re, im) = self.value_at(i);
+            Complex::new(re, im)
+        }, |v| v.conj())
+    }
+
+    fn spmv_by<T, V, C>(&self, x: &[T], value: V, conjugate: C) -> Vec<T>
+    where
+        T: Copy + Default + Add<Output = T> + Mul<Output = T> + Neg<Output = T>,
+        V: Fn(usize) -> T,
+        C: Fn(T) -> T,
+    {
+        assert_eq!(x.len(), self.ncols, "expected one value per column");
+        let mut y = vec![T::default(); self.nrows];
+
+        match self.symmetry {
+            Symmetry::General => {
+                for i in 0..self.nvals {
+                    let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+                    y[row] = y[row] + value(i) * x[col];
+                }
+            },
+            symmetry => {
+                let mirror = |v: T| match symmetry {
+                    Symmetry::SkewSymmetric => -v,
+                    Symmetry::Hermitian => conjugate(v),
+                    _ => v,
+                };
+
+                for i in 0..self.nvals {
+                    let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+                    let v = value(i);
+                    y[row] = y[row] + v * x[col];
+                    if row != col {
+                        y[col] = y[col] + mirror(v) * x[row];
+                    }
+                }
+            },
+        }
+
+        y
+    }
+}
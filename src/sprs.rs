@@ -0,0 +1,67 @@
+//! Conversions to and from [`sprs`] matrices, which use 0-based indices.
+
+use std::ops::Add;
+
+use sprs::{CsMat, TriMat};
+
+use crate::{offsets, Error, Matrix, Value};
+
+impl Matrix {
+    /// Converts a general matrix into a `sprs` triplet matrix, keeping the order of the entries.
+    pub fn to_trimat<N: Value>(&self) -> Result<TriMat<N>, Error> {
+        self.check_general()?;
+        let data = self.values_as::<N>()?;
+        let rows = self.rows.iter().map(|&row| row - 1).collect();
+        let cols = self.cols.iter().map(|&col| col - 1).collect();
+        Ok(TriMat::from_triplets((self.nrows, self.ncols), rows, cols, data))
+    }
+
+    /// Converts a general matrix into a `sprs` compressed matrix.
+    ///
+    /// Matrices sorted in row-major order become CSR matrices, and matrices sorted in column-major
+    /// order become CSC matrices, without sorting them again. Anything else, including sorted
+    /// matrices with duplicate entries, goes through a triplet matrix and becomes a CSR matrix.
+    pub fn to_csmat<N>(&self) -> Result<CsMat<N>, Error>
+    where
+        N: Value + Add<Output = N>,
+    {
+        self.check_general()?;
+        let shape = (self.nrows, self.ncols);
+
+        if self.is_row_major() {
+            let indptr = offsets(&self.rows, self.nrows);
+            let indices = self.cols.iter().map(|&col| col - 1).collect();
+            if let Ok(m) = CsMat::try_new(shape, indptr, indices, self.values_as::<N>()?) {
+                return Ok(m);
+            }
+        } else if self.is_col_major() {
+            let indptr = offsets(&self.cols, self.ncols);
+            let indices = self.rows.iter().map(|&row| row - 1).collect();
+            if let Ok(m) = CsMat::try_new_csc(shape, indptr, indices, self.values_as::<N>()?) {
+                return Ok(m);
+            }
+        }
+
+        Ok(self.to_trimat::<N>()?.to_csr())
+    }
+
+    /// Creates a general matrix from a `sprs` triplet matrix, keeping the order of the entries.
+    pub fn from_trimat<N: Value>(m: &TriMat<N>) -> Self {
+        let rows = m.row_inds().iter().map(|&row| row + 1).collect();
+        let cols = m.col_inds().iter().map(|&col| col + 1).collect();
+        N::into_matrix(m.rows(), m.cols(), rows, cols, m.data().to_vec())
+    }
+
+    /// Creates a general matrix from a `sprs` compressed matrix.
+    /// CSR matrices result in row-major order, and CSC matrices in column-major order.
+    pub fn from_csmat<N: Value>(m: &CsMat<N>) -> Self {
+        let mut outer = Vec::with_capacity(m.nnz());
+        for (i, range) in m.indptr().iter_outer_sz().enumerate() {
+            outer.extend(range.map(|_| i + 1));
+        }
+        let inner = m.indices().iter().map(|&idx| idx + 1).collect();
+
+        let (rows, cols) = if m.is_csr() { (outer, inner) } else { (inner, outer) };
+        N::into_matrix(m.rows(), m.cols(), rows, cols, m.data().to_vec())
+    }
+}
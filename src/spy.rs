@@ -0,0 +1,96 @@
+use std::io::{self, Write};
+
+use rayon::prelude::*;
+
+use crate::{Matrix, Symmetry};
+
+/// Nonzero pattern of a matrix, rasterized onto a grid of pixels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spy {
+    width: usize,
+    height: usize,
+    /// Number of entries per pixel, in row-major order.
+    counts: Vec<u32>,
+}
+
+impl Matrix {
+    /// Rasterizes the nonzero pattern onto a `width` by `height` grid, counting the entries that fall into each pixel.
+    /// For matrices stored in a compact symmetric form, the implied upper triangle is included.
+    pub fn spy(&self, width: usize, height: usize) -> Spy {
+        assert!(width > 0 && height > 0, "spy plot must have at least one pixel");
+
+        let pixel = |row: usize, col: usize| {
+            let x = (col - 1) * width / self.ncols;
+            let y = (row - 1) * height / self.nrows;
+            y * width + x
+        };
+
+        let mirrored = self.symmetry != Symmetry::General;
+        let counts = (0..self.nvals).into_par_iter()
+            .fold(|| vec![0u32; width * height], |mut counts, i| {
+                let (row, col) = (self.rows[i], self.cols[i]);
+                counts[pixel(row, col)] += 1;
+                if mirrored && row != col {
+                    counts[pixel(col, row)] += 1;
+                }
+                counts
+            })
+            .reduce(|| vec![0u32; width * height], |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                a
+            });
+
+        Spy { width, height, counts }
+    }
+}
+
+impl Spy {
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+
+    /// Number of entries per pixel, in row-major order.
+    pub fn counts(&self) -> &[u32] { &self.counts }
+
+    /// Density of each pixel between zero (empty) and one (densest pixel), on a logarithmic scale.
+    /// The logarithm keeps sparse regions visible next to much denser ones.
+    pub fn densities(&self) -> Vec<f64> {
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        let scale = (max as f64).ln_1p();
+        self.counts.iter()
+            .map(|&c| if c == 0 { 0.0 } else { (c as f64).ln_1p() / scale })
+            .collect()
+    }
+
+    /// Grayscale intensity of each pixel, where empty pixels are white and the densest pixels black.
+    pub fn to_grayscale(&self) -> Vec<u8> {
+        self.densities().into_iter()
+            .map(|d| (255.0 * (1.0 - d)).round() as u8)
+            .collect()
+    }
+
+    /// Writes the image as an 8-bit grayscale PNG.
+    pub fn write_png<W: Write>(&self, wtr: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(wtr, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut wtr = encoder.write_header()?;
+        wtr.write_image_data(&self.to_grayscale())?;
+        wtr.finish()?;
+        Ok(())
+    }
+
+    /// Writes the image as a binary portable graymap (PGM).
+    pub fn write_pgm<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        write!(wtr, "P5\n{} {}\n255\n", self.width, self.height)?;
+        wtr.write_all(&self.to_grayscale())
+    }
+
+    /// Writes the image as a binary portable pixmap (PPM), with equal color channels.
+    pub fn write_ppm<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        write!(wtr, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let rgb: Vec<u8> = self.to_grayscale().into_iter()
+            .flat_map(|g| [g, g, g])
+            .collect();
+        wtr.write_all(&rgb)
+    }
+}
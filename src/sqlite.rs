@@ -0,0 +1,73 @@
+//! Export of the triplets to a table of a SQLite database, with 1-based `row` and `col` columns
+//! and the values in a `value` column, or in `re` and `im` columns for complex values, as for the `arrow` feature.
+//! Pattern matrices have no value columns. The table has an index on `row` and one on `col`.
+//!
+//! The dimensions, data type, and symmetry are kept in a `matrices` table, with a row per exported table
+//! under the columns `name`, `nrows`, `ncols`, `nvals`, `data_type`, and `symmetry`.
+
+use std::io;
+use std::path::Path;
+
+use rusqlite::{Connection, params};
+
+use crate::{Error, Matrix, MatrixData};
+
+impl Matrix {
+    /// Writes the triplets to the table `table` of the SQLite database at `path`, which is created if needed.
+    /// An existing table of the same name is replaced, while other tables of the database are kept.
+    /// The entries are inserted in a single transaction, and the indices are built after them.
+    pub fn write_sqlite(&self, path: &Path, table: &str) -> Result<(), Error> {
+        self.try_write_sqlite(path, table).map_err(|e| io::Error::other(e).into())
+    }
+
+    fn try_write_sqlite(&self, path: &Path, table: &str) -> rusqlite::Result<()> {
+        let mut conn = Connection::open(path)?;
+        let tx = conn.transaction()?;
+        let name = quote(table);
+        let columns = match self.vals {
+            MatrixData::Real(_) => ", value REAL",
+            MatrixData::Complex(..) => ", re REAL, im REAL",
+            MatrixData::Integer(_) => ", value INTEGER",
+            MatrixData::Bool() => "",
+        };
+        tx.execute_batch(&format!(
+            "DROP TABLE IF EXISTS {name};
+             CREATE TABLE {name} (row INTEGER NOT NULL, col INTEGER NOT NULL{columns});
+             CREATE TABLE IF NOT EXISTS matrices (name TEXT PRIMARY KEY, nrows INTEGER, ncols INTEGER, nvals INTEGER, data_type TEXT, symmetry TEXT);"
+        ))?;
+
+        {
+            let insert = match self.vals {
+                MatrixData::Real(_) | MatrixData::Integer(_) => "(row, col, value) VALUES (?1, ?2, ?3)",
+                MatrixData::Complex(..) => "(row, col, re, im) VALUES (?1, ?2, ?3, ?4)",
+                MatrixData::Bool() => "(row, col) VALUES (?1, ?2)",
+            };
+            let mut stmt = tx.prepare(&format!("INSERT INTO {name} {insert}"))?;
+            for i in 0..self.nvals {
+                let (row, col) = (self.rows[i] as i64, self.cols[i] as i64);
+                match &self.vals {
+                    MatrixData::Real(xs) => stmt.execute(params![row, col, xs[i] as f64])?,
+                    MatrixData::Complex(xs, ys) => stmt.execute(params![row, col, xs[i] as f64, ys[i] as f64])?,
+                    MatrixData::Integer(xs) => stmt.execute(params![row, col, xs[i] as i64])?,
+                    MatrixData::Bool() => stmt.execute(params![row, col])?,
+                };
+            }
+        }
+
+        tx.execute_batch(&format!(
+            "CREATE INDEX {} ON {name} (row);
+             CREATE INDEX {} ON {name} (col);",
+            quote(&format!("{table}_row")), quote(&format!("{table}_col")),
+        ))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO matrices VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![table, self.nrows as i64, self.ncols as i64, self.nvals as i64, self.vals.field().to_string(), self.symmetry.to_string()],
+        )?;
+        tx.commit()
+    }
+}
+
+/// Quotes an identifier, such that any name can be used as a table name.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
@@ -0,0 +1,315 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::profile::json_number;
+use crate::{Error, Matrix, MatrixData, Symmetry};
+
+/// Structural summary of a matrix, computed over its stored entries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub nrows: usize,
+    pub ncols: usize,
+    pub nvals: usize,
+    pub data_type: &'static str,
+    pub symmetry: Symmetry,
+    /// Fraction of the entries that are present, where compact symmetric forms have their mirrored entries included.
+    pub density: f64,
+    /// Number of entries on the main diagonal.
+    pub diagonal: usize,
+    /// Number of rows without entries, where compact symmetric forms have their mirrored entries included,
+    /// as for the other counts of rows and columns.
+    pub empty_rows: usize,
+    pub empty_cols: usize,
+    /// Smallest and largest number of entries in a row.
+    pub row_nvals: (usize, usize),
+    /// Smallest and largest number of entries in a column.
+    pub col_nvals: (usize, usize),
+    /// Number of rows and columns in each connected component, largest first.
+    pub components: Vec<(usize, usize)>,
+    /// Fraction of the entries that exactly equal their transposed counterpart, see [`Matrix::symmetry_score`].
+    pub symmetry_score: f64,
+    /// Smallest `k` such that every entry is within `k` of the diagonal, see [`Matrix::bandwidth`].
+    pub bandwidth: usize,
+    /// Fraction of the entries within each band width, see [`Matrix::band_fractions`]; empty unless filled in.
+    pub bands: Vec<(usize, f64)>,
+    /// Row, column, and absolute value of the largest entries, see [`Matrix::largest_entries`]; empty unless filled in.
+    pub largest: Vec<(usize, usize, f64)>,
+}
+
+/// Structural metrics of every row or every column, see [`Matrix::row_metrics`] and [`Matrix::col_metrics`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Name of the index column of the CSV file, `row` or `col`.
+    label: &'static str,
+    /// Number of entries in every row or column.
+    pub nvals: Vec<usize>,
+    /// Smallest and largest value in every row or column, or `None` if it has no entries.
+    /// Complex values compare by their absolute values, and pattern entries are one.
+    pub min_max: Vec<Option<(f64, f64)>>,
+    /// Largest distance of an entry from the diagonal in every row or column.
+    pub bandwidth: Vec<usize>,
+}
+
+impl LineMetrics {
+    /// Writes the metrics of every row or column as CSV, with their (1-based) indices
+    /// and empty values for those without entries.
+    pub fn write_csv<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        writeln!(wtr, "{},nnz,min,max,bandwidth", self.label)?;
+        for (i, ((nvals, min_max), bandwidth)) in self.nvals.iter().zip(&self.min_max).zip(&self.bandwidth).enumerate() {
+            match min_max {
+                Some((min, max)) => writeln!(wtr, "{},{nvals},{min},{max},{bandwidth}", i + 1)?,
+                None => writeln!(wtr, "{},{nvals},,,{bandwidth}", i + 1)?,
+            }
+        }
+        wtr.flush()
+    }
+}
+
+/// Gershgorin discs of the rows of a square matrix, see [`Matrix::gershgorin`].
+/// Every eigenvalue lies in the union of the discs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gershgorin {
+    /// Center of every disc, the real and imaginary part of the diagonal entry of its row.
+    pub centers: Vec<(f64, f64)>,
+    /// Radius of every disc, the sum of the absolute values of the other entries of its row.
+    pub radii: Vec<f64>,
+}
+
+impl Gershgorin {
+    /// Number of strictly diagonally dominant rows, whose discs exclude zero.
+    pub fn dominant_rows(&self) -> usize {
+        self.centers.iter().zip(&self.radii).filter(|&(&(x, y), &r)| x.hypot(y) > r).count()
+    }
+
+    /// Smallest and largest real part within the discs, which bound the real parts of the eigenvalues.
+    pub fn real_bounds(&self) -> (f64, f64) {
+        self.centers.iter().zip(&self.radii)
+            .map(|(&(x, _), &r)| (x - r, x + r))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (a, b)| (lo.min(a), hi.max(b)))
+    }
+
+    /// Largest absolute value within the discs, which bounds the spectral radius.
+    pub fn abs_bound(&self) -> f64 {
+        self.centers.iter().zip(&self.radii).map(|(&(x, y), &r)| x.hypot(y) + r).fold(0.0, f64::max)
+    }
+
+    /// Writes one line per row with its (1-based) index, center, radius, and whether it is strictly diagonally dominant.
+    pub fn write_csv<W: Write>(&self, mut wtr: W) -> io::Result<()> {
+        writeln!(wtr, "row,center_re,center_im,radius,dominant")?;
+        for (i, (&(x, y), &r)) in self.centers.iter().zip(&self.radii).enumerate() {
+            writeln!(wtr, "{},{x},{y},{r},{}", i + 1, x.hypot(y) > r)?;
+        }
+        wtr.flush()
+    }
+
+    /// Formats the summary of the discs as a JSON object.
+    pub fn to_json(&self) -> String {
+        let (lo, hi) = self.real_bounds();
+        format!(
+            "{{\"real_bounds\":[{},{}],\"abs_bound\":{},\"dominant_rows\":{}}}",
+            json_number(lo), json_number(hi), json_number(self.abs_bound()), self.dominant_rows(),
+        )
+    }
+}
+
+impl fmt::Display for Gershgorin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (lo, hi) = self.real_bounds();
+        writeln!(f, "eigenvalues: real part in [{lo:.6e}, {hi:.6e}], absolute value at most {:.6e}", self.abs_bound())?;
+        writeln!(f, "dominant:    {} of {} rows", self.dominant_rows(), self.radii.len())
+    }
+}
+
+impl Matrix {
+    /// Gershgorin discs of the rows of a square matrix, where duplicate entries are summed.
+    /// Compact symmetric forms have their mirrored entries included in the radii.
+    pub fn gershgorin(&self) -> Result<Gershgorin, Error> {
+        self.check_square()?;
+
+        let mut centers = vec![(0.0, 0.0); self.nrows];
+        let mut radii = vec![0.0; self.nrows];
+        for i in 0..self.nvals {
+            let (row, col) = (self.rows[i] - 1, self.cols[i] - 1);
+            let (x, y) = self.value_at(i);
+            if row == col {
+                centers[row].0 += x;
+                centers[row].1 += y;
+            } else {
+                radii[row] += x.hypot(y);
+                if self.symmetry != Symmetry::General {
+                    radii[col] += x.hypot(y);
+                }
+            }
+        }
+
+        Ok(Gershgorin { centers, radii })
+    }
+
+    /// Metrics of every row, where compact symmetric forms have their mirrored entries included.
+    pub fn row_metrics(&self) -> LineMetrics {
+        self.line_metrics("row", self.nrows, |i| (self.rows[i], self.cols[i]))
+    }
+
+    /// Metrics of every column, where compact symmetric forms have their mirrored entries included.
+    pub fn col_metrics(&self) -> LineMetrics {
+        self.line_metrics("col", self.ncols, |i| (self.cols[i], self.rows[i]))
+    }
+
+    /// Metrics of the lines given by the (1-based) major and minor index of every entry.
+    fn line_metrics<F>(&self, label: &'static str, n: usize, coords: F) -> LineMetrics
+    where
+        F: Fn(usize) -> (usize, usize),
+    {
+        let mut metrics = LineMetrics { label, nvals: vec![0; n], min_max: vec![None; n], bandwidth: vec![0; n] };
+        let mut add = |line: usize, x: f64, distance: usize| {
+            metrics.nvals[line] += 1;
+            metrics.bandwidth[line] = metrics.bandwidth[line].max(distance);
+            let (min, max) = metrics.min_max[line].get_or_insert((x, x));
+            *min = min.min(x);
+            *max = max.max(x);
+        };
+
+        let complex = matches!(self.vals, MatrixData::Complex(..));
+        for i in 0..self.nvals {
+            let (major, minor) = coords(i);
+            let x = if complex { self.abs_at(i) } else { self.value_at(i).0 };
+            add(major - 1, x, major.abs_diff(minor));
+            if self.symmetry != Symmetry::General && major != minor {
+                let mirrored = if self.symmetry == Symmetry::SkewSymmetric && !complex { -x } else { x };
+                add(minor - 1, mirrored, major.abs_diff(minor));
+            }
+        }
+        metrics
+    }
+
+    pub fn stats(&self) -> Stats {
+        let mirrored = self.symmetry != Symmetry::General;
+        let row_counts = counts(&self.rows, &self.cols, self.nrows, mirrored);
+        let col_counts = counts(&self.cols, &self.rows, self.ncols, mirrored);
+        let present: usize = row_counts.iter().sum();
+        let mut components = self.connected_components().sizes();
+        components.sort_by(|a, b| b.cmp(a));
+
+        Stats {
+            nrows: self.nrows,
+            ncols: self.ncols,
+            nvals: self.nvals,
+            data_type: self.vals.field(),
+            symmetry: self.symmetry,
+            density: present as f64 / (self.nrows as f64 * self.ncols as f64).max(1.0),
+            diagonal: (0..self.nvals).filter(|&i| self.rows[i] == self.cols[i]).count(),
+            empty_rows: row_counts.iter().filter(|&&c| c == 0).count(),
+            empty_cols: col_counts.iter().filter(|&&c| c == 0).count(),
+            row_nvals: min_max(&row_counts),
+            col_nvals: min_max(&col_counts),
+            components,
+            symmetry_score: self.symmetry_score(0.0),
+            bandwidth: self.bandwidth(),
+            bands: Vec::new(),
+            largest: Vec::new(),
+        }
+    }
+
+    /// The `k` entries of largest absolute value, largest first, as their (1-based) row, column, and absolute value,
+    /// such as to spot a rogue value from a faulty assembly. NaNs count as larger than any value.
+    pub fn largest_entries(&self, k: usize) -> Vec<(usize, usize, f64)> {
+        let cmp = |&a: &usize, &b: &usize| self.abs_at(b).total_cmp(&self.abs_at(a)).then(a.cmp(&b));
+        let mut indices: Vec<_> = (0..self.nvals).collect();
+        if k < indices.len() {
+            indices.select_nth_unstable_by(k, cmp);
+            indices.truncate(k);
+        }
+        indices.sort_unstable_by(cmp);
+        indices.into_iter().map(|i| (self.rows[i], self.cols[i], self.abs_at(i))).collect()
+    }
+
+    /// Renders the nonzero pattern as block characters, `cols` characters wide.
+    /// Each character covers roughly twice as many rows as columns, to compensate for the shape of terminal cells.
+    pub fn spy_ascii(&self, cols: usize) -> String {
+        const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+        let height = (cols * self.nrows).div_ceil(2 * self.ncols.max(1)).max(1);
+        let spy = self.spy(cols, height);
+
+        let mut res = String::with_capacity((cols + 1) * height * 3);
+        for line in spy.densities().chunks(cols) {
+            for &d in line {
+                let shade = if d == 0.0 { 0 } else { 1 + (d * 3.0).round() as usize };
+                res.push(SHADES[shade]);
+            }
+            res.push('\n');
+        }
+        res
+    }
+}
+
+impl Stats {
+    /// Formats the summary as a JSON object, for dashboards.
+    pub fn to_json(&self) -> String {
+        let pair = |(a, b): (usize, usize)| format!("[{a},{b}]");
+        let components: Vec<_> = self.components.iter().map(|&size| pair(size)).collect();
+        let bands: Vec<_> = self.bands.iter().map(|&(k, fraction)| format!("[{k},{}]", json_number(fraction))).collect();
+        let largest: Vec<_> = self.largest.iter().map(|&(row, col, abs)| format!("[{row},{col},{}]", json_number(abs))).collect();
+        format!(
+            "{{\"nrows\":{},\"ncols\":{},\"nvals\":{},\"data_type\":\"{}\",\"symmetry\":\"{}\",\"density\":{},\"diagonal\":{},\
+            \"empty_rows\":{},\"empty_cols\":{},\"row_nvals\":{},\"col_nvals\":{},\"components\":[{}],\"symmetry_score\":{},\
+            \"bandwidth\":{},\"bands\":[{}],\"largest\":[{}]}}",
+            self.nrows, self.ncols, self.nvals, self.data_type, self.symmetry, json_number(self.density), self.diagonal,
+            self.empty_rows, self.empty_cols, pair(self.row_nvals), pair(self.col_nvals), components.join(","),
+            json_number(self.symmetry_score), self.bandwidth, bands.join(","), largest.join(","),
+        )
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size:       {} x {}", self.nrows, self.ncols)?;
+        writeln!(f, "entries:    {}", self.nvals)?;
+        writeln!(f, "type:       {} {}", self.data_type, self.symmetry)?;
+        writeln!(f, "density:    {:.3e}", self.density)?;
+        writeln!(f, "diagonal:   {}", self.diagonal)?;
+        writeln!(f, "empty rows: {}", self.empty_rows)?;
+        writeln!(f, "empty cols: {}", self.empty_cols)?;
+        writeln!(f, "row nnz:    {} to {}", self.row_nvals.0, self.row_nvals.1)?;
+        writeln!(f, "col nnz:    {} to {}", self.col_nvals.0, self.col_nvals.1)?;
+        writeln!(f, "symmetric:  {:.2}% of entries", 100.0 * self.symmetry_score)?;
+        writeln!(f, "bandwidth:  {}", self.bandwidth)?;
+        for (k, fraction) in &self.bands {
+            let label = format!("within {k}:");
+            writeln!(f, "{label:<12}{:.2}% of entries", 100.0 * fraction)?;
+        }
+        for (k, (row, col, abs)) in self.largest.iter().enumerate() {
+            writeln!(f, "{:<12}{abs:.6e} at ({row}, {col})", if k == 0 { "largest:" } else { "" })?;
+        }
+
+        const SHOWN: usize = 8;
+        write!(f, "components: {}", self.components.len())?;
+        for (k, (nrows, ncols)) in self.components.iter().take(SHOWN).enumerate() {
+            write!(f, "{} {nrows} x {ncols}", if k == 0 { " of" } else { "," })?;
+        }
+        if self.components.len() > SHOWN {
+            write!(f, ", ...")?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Number of occurrences of each (1-based) index.
+/// Number of entries in every line given by the (1-based) major indices,
+/// counting off-diagonal entries in the line of their minor index as well if they are `mirrored`.
+fn counts(major: &[usize], minor: &[usize], n: usize, mirrored: bool) -> Vec<usize> {
+    let mut counts = vec![0; n];
+    for (&i, &j) in major.iter().zip(minor) {
+        counts[i - 1] += 1;
+        if mirrored && i != j {
+            counts[j - 1] += 1;
+        }
+    }
+    counts
+}
+
+fn min_max(counts: &[usize]) -> (usize, usize) {
+    let min = counts.iter().copied().min().unwrap_or(0);
+    let max = counts.iter().copied().max().unwrap_or(0);
+    (min, max)
+}
@@ -0,0 +1,205 @@
+//! Parsing a Matrix-Market file while it streams in from an asynchronous reader, such as a download,
+//! and writing one to an asynchronous writer, such as an upload.
+//! The stream is cut into chunks of whole lines, which are parsed on the thread pool while the next chunks are read,
+//! such that reading, any decompression within the reader, and parsing overlap.
+
+use std::collections::VecDeque;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::oneshot;
+
+use crate::chunks::split_header;
+use crate::read::{check_index, check_shape};
+use crate::{entry_fields, is_data, line_of, parse_decimal, parse_field, parse_int, parse_real, parse_size, DataType, Error, Matrix, MatrixData, ReadOptions, Symmetry, TextFormat};
+
+/// Number of bytes per chunk of lines.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Number of bytes per read.
+const READ_SIZE: usize = 1 << 16;
+
+impl Matrix {
+    /// Parses a Matrix-Market file from an asynchronous reader, without buffering all of it first.
+    /// Compressed streams can be decompressed by wrapping the reader, such as with `async-compression`.
+    #[tracing::instrument(name = "parse", skip_all, fields(%data_type))]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(mut rdr: R, data_type: DataType, options: &ReadOptions) -> Result<Self, Error> {
+        let mut buf = Vec::with_capacity(CHUNK_SIZE);
+        let mut eof = false;
+
+        let header = loop {
+            if let Some((header, rest)) = split_header(&buf) && (!rest.is_empty() || buf.ends_with(b"\n") || eof) {
+                let size = parse_size(header, || line_of(&buf, header))?;
+                break Some((size, buf.len() - rest.len()));
+            }
+            if eof {
+                break None;
+            }
+            eof = read_more(&mut rdr, &mut buf).await?;
+        };
+
+        let banner = buf.split(|&b| b == b'\n').next().unwrap_or_default();
+        let symmetry = Symmetry::from_banner(&String::from_utf8_lossy(banner));
+
+        let Some(((nrows, ncols, expected), consumed)) = header else {
+            // File is empty or contains only comments, return empty matrix
+            let mut m = Matrix::new(0, 0, Vec::new(), Vec::new(), MatrixData::new(data_type));
+            m.symmetry = symmetry;
+            return Ok(m);
+        };
+        check_shape(symmetry, nrows, ncols)?;
+
+        let mut first_line = buf[..consumed].iter().filter(|&&b| b == b'\n').count() + 1;
+        buf.drain(..consumed);
+
+        let lenient = options.is_lenient();
+        let needs_lines = options.needs_lines();
+        let max_pending = 2 * rayon::current_num_threads();
+        let mut pending = VecDeque::new();
+        let mut parts = Vec::new();
+
+        while !(eof && buf.is_empty()) {
+            while buf.len() < CHUNK_SIZE && !eof {
+                eof = read_more(&mut rdr, &mut buf).await?;
+            }
+
+            let end = if eof {
+                buf.len()
+            } else if let Some(end) = buf.iter().rposition(|&b| b == b'\n') {
+                end + 1
+            } else {
+                // A single line spans the whole chunk, so we read on until it ends
+                eof = read_more(&mut rdr, &mut buf).await?;
+                continue;
+            };
+
+            let rest = buf.split_off(end);
+            let chunk = std::mem::replace(&mut buf, rest);
+            let line = first_line;
+            first_line += chunk.iter().filter(|&&b| b == b'\n').count();
+
+            let (tx, rx) = oneshot::channel();
+            rayon::spawn(move || {
+                let part = panic::catch_unwind(AssertUnwindSafe(|| parse_chunk(&chunk, data_type, (nrows, ncols), lenient, line, needs_lines)));
+                let _ = tx.send(part);
+            });
+            pending.push_back(rx);
+
+            // Limit the number of chunks in memory if reading outpaces parsing
+            if pending.len() >= max_pending {
+                parts.push(receive(pending.pop_front().unwrap()).await?);
+            }
+        }
+
+        while let Some(rx) = pending.pop_front() {
+            parts.push(receive(rx).await?);
+        }
+
+        let nvals = parts.iter().map(|(part, _)| part.nvals).sum();
+        options.check_count(expected, nvals)?;
+
+        let mut m = Matrix::new(nrows, ncols, Vec::with_capacity(nvals), Vec::with_capacity(nvals), MatrixData::with_capacity(data_type, nvals));
+        m.symmetry = symmetry;
+        let mut line_numbers = Vec::new();
+        for (part, lines) in parts {
+            m.append(&part);
+            line_numbers.extend(lines);
+        }
+
+        m.round_values(options);
+        m.check_int_width(options, |i| line_numbers[i])?;
+        m.scrub_nonfinite(options, |i| line_numbers[i])?;
+        m.check_skew_diagonal()?;
+        m.detect_sortedness();
+        Ok(m)
+    }
+
+    /// Writes the matrix to an asynchronous writer, a chunk of lines at a time, and shuts the writer down.
+    pub async fn write_async<W: AsyncWrite + Unpin>(&self, wtr: W) -> io::Result<()> {
+        self.write_async_with(wtr, &TextFormat::default()).await
+    }
+
+    /// Like [`Matrix::write_async`], but with the given text format, see [`Matrix::formatted`].
+    #[tracing::instrument(name = "write", skip_all)]
+    pub async fn write_async_with<W: AsyncWrite + Unpin>(&self, mut wtr: W, format: &TextFormat) -> io::Result<()> {
+        let widths = self.widths(format);
+        let mut buf = String::with_capacity(CHUNK_SIZE);
+        buf.push_str(&self.header());
+
+        for i in 0..self.nvals {
+            self.write_entry(&mut buf, i, format, widths.as_ref()).map_err(io::Error::other)?;
+
+            if buf.len() >= CHUNK_SIZE {
+                wtr.write_all(buf.as_bytes()).await?;
+                buf.clear();
+            }
+        }
+
+        wtr.write_all(buf.as_bytes()).await?;
+        wtr.shutdown().await
+    }
+}
+
+/// Appends the next bytes of the reader, returning whether it has ended.
+async fn read_more<R: AsyncRead + Unpin>(rdr: &mut R, buf: &mut Vec<u8>) -> io::Result<bool> {
+    let len = buf.len();
+    buf.resize(len + READ_SIZE, 0);
+    let n = rdr.read(&mut buf[len..]).await?;
+    buf.truncate(len + n);
+    Ok(n == 0)
+}
+
+/// Waits for a parsed chunk, and continues any panic of its parser.
+async fn receive<T>(rx: oneshot::Receiver<std::thread::Result<T>>) -> T {
+    rx.await
+        .expect("parsing a chunk stopped without a result")
+        .unwrap_or_else(|e| panic::resume_unwind(e))
+}
+
+/// Parses the entries of a chunk of lines, whose first line has the given (1-based) line number,
+/// and returns them with their line numbers if needed.
+fn parse_chunk(chunk: &[u8], data_type: DataType, (nrows, ncols): (usize, usize), lenient: bool, first_line: usize, needs_lines: bool) -> Result<(Matrix, Vec<usize>), Error> {
+    let mut m = Matrix::new(0, 0, Vec::new(), Vec::new(), MatrixData::new(data_type));
+    let mut line_numbers = Vec::new();
+
+    for (n, line) in chunk.split(|&b| b == b'\n').enumerate() {
+        if !is_data(line) {
+            continue;
+        }
+        if needs_lines {
+            line_numbers.push(first_line + n);
+        }
+
+        let line_number = || first_line + n;
+        let (r, c) = match &mut m.vals {
+            MatrixData::Real(xs) => {
+                let [r, c, v] = entry_fields(line, data_type, line_number)?;
+                xs.push(parse_field(v, |v| parse_real(v, lenient), line_number)?);
+                (r, c)
+            },
+            MatrixData::Complex(xs, ys) => {
+                let [r, c, re, im] = entry_fields(line, data_type, line_number)?;
+                xs.push(parse_field(re, |re| parse_real(re, lenient), line_number)?);
+                ys.push(parse_field(im, |im| parse_real(im, lenient), line_number)?);
+                (r, c)
+            },
+            MatrixData::Integer(xs) => {
+                let [r, c, v] = entry_fields(line, data_type, line_number)?;
+                xs.push(parse_int(v, line_number)?);
+                (r, c)
+            },
+            MatrixData::Bool() => {
+                let [r, c] = entry_fields(line, data_type, line_number)?;
+                (r, c)
+            },
+        };
+        let (row, col) = (parse_field(r, parse_decimal, line_number)?, parse_field(c, parse_decimal, line_number)?);
+        check_index(row, col, nrows, ncols, line_number)?;
+        m.rows.push(row);
+        m.cols.push(col);
+    }
+
+    m.nvals = m.rows.len();
+    Ok((m, line_numbers))
+}
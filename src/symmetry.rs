@@ -0,0 +1,341 @@
+use std::{fmt, str::FromStr};
+
+use rayon::prelude::*;
+
+use crate::{Error, Matrix, MatrixData};
+
+/// Symmetry qualifier of the Matrix-Market banner.
+/// Matrices with a symmetry other than `General` only store their lower triangle.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    General,
+    Symmetric,
+    SkewSymmetric,
+    Hermitian,
+}
+
+/// Result of [`Matrix::detect_symmetry`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SymmetryReport {
+    /// Every entry (i, j) has a stored counterpart (j, i).
+    pub structural: bool,
+    /// Every entry satisfies a(i, j) = a(j, i).
+    pub symmetric: bool,
+    /// Every entry satisfies a(i, j) = -a(j, i), which implies a zero diagonal.
+    pub skew: bool,
+    /// Every entry satisfies a(i, j) = conj(a(j, i)); only set for complex matrices.
+    pub hermitian: bool,
+}
+
+impl SymmetryReport {
+    /// The most compact symmetry qualifier that this matrix can be stored with.
+    pub fn symmetry(&self) -> Symmetry {
+        if self.symmetric {
+            Symmetry::Symmetric
+        } else if self.skew {
+            Symmetry::SkewSymmetric
+        } else if self.hermitian {
+            Symmetry::Hermitian
+        } else {
+            Symmetry::General
+        }
+    }
+}
+
+impl Matrix {
+    /// Checks whether this matrix is symmetric, skew-symmetric, or hermitian.
+    ///
+    /// Two values `a` and `b` are considered equal if `|a - b| <= tol * max(|a|, |b|)`.
+    /// An entry without a stored counterpart is compared against zero,
+    /// so explicit zeros do not break numerical symmetry, only structural symmetry.
+    ///
+    /// Fails if the matrix is not square.
+    pub fn detect_symmetry(&self, tol: f64) -> Result<SymmetryReport, Error> {
+        self.check_square()?;
+        if self.symmetry != Symmetry::General {
+            // Only the lower triangle is stored, the rest is implied by the qualifier
+            return Ok(SymmetryReport {
+                structural: true,
+                symmetric: self.symmetry == Symmetry::Symmetric,
+                skew: self.symmetry == Symmetry::SkewSymmetric,
+                hermitian: self.symmetry == Symmetry::Hermitian,
+            });
+        }
+
+        let order = self.row_major_order();
+        let is_complex = self.is_complex();
+
+        let report = (0..self.nvals).into_par_iter()
+            .map(|i| {
+                let mirror = self.mirror(&order, i).map(|j| self.value_at(j));
+
+                let (re, im) = self.value_at(i);
+                let (mre, mim) = mirror.unwrap_or((0.0, 0.0));
+                let symmetric = approx_eq(re, mre, tol) && approx_eq(im, mim, tol);
+                let skew = approx_eq(re, -mre, tol) && approx_eq(im, -mim, tol);
+                let hermitian = if self.rows[i] == self.cols[i] {
+                    // Relative to the entry itself, as its own mirror would only allow an imaginary part of zero
+                    im.abs() <= tol * re.hypot(im)
+                } else {
+                    approx_eq(re, mre, tol) && approx_eq(im, -mim, tol)
+                };
+
+                SymmetryReport {
+                    structural: mirror.is_some(),
+                    symmetric,
+                    skew,
+                    hermitian: hermitian && is_complex,
+                }
+            })
+            .reduce(|| SymmetryReport { structural: true, symmetric: true, skew: true, hermitian: is_complex },
+                |a, b| SymmetryReport {
+                    structural: a.structural && b.structural,
+                    symmetric: a.symmetric && b.symmetric,
+                    skew: a.skew && b.skew,
+                    hermitian: a.hermitian && b.hermitian,
+                });
+        Ok(report)
+    }
+
+    /// Fraction of the entries `a(i, j)` whose counterpart `a(j, i)` is stored and equal within the tolerance,
+    /// compared as in [`Matrix::detect_symmetry`], which is one for a symmetric matrix.
+    /// Entries that the symmetry qualifier implies count as stored.
+    pub fn symmetry_score(&self, tol: f64) -> f64 {
+        let (matching, total) = if self.symmetry != Symmetry::General {
+            // Every entry off the diagonal stands for itself and its implied mirror
+            (0..self.nvals).into_par_iter()
+                .map(|i| {
+                    if self.rows[i] == self.cols[i] {
+                        return (1, 1);
+                    }
+                    let (re, im) = self.value_at(i);
+                    let matches = match self.symmetry {
+                        Symmetry::Symmetric => true,
+                        Symmetry::SkewSymmetric => approx_eq(re, -re, tol) && approx_eq(im, -im, tol),
+                        Symmetry::Hermitian => approx_eq(im, -im, tol),
+                        Symmetry::General => unreachable!(),
+                    };
+                    (if matches { 2 } else { 0 }, 2)
+                })
+                .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
+        } else {
+            let order = self.row_major_order();
+            let matching = (0..self.nvals).into_par_iter()
+                .filter(|&i| self.mirror(&order, i).is_some_and(|j| {
+                    let ((re, im), (mre, mim)) = (self.value_at(i), self.value_at(j));
+                    approx_eq(re, mre, tol) && approx_eq(im, mim, tol)
+                }))
+                .count();
+            (matching, self.nvals)
+        };
+
+        if total == 0 { 1.0 } else { matching as f64 / total as f64 }
+    }
+
+    /// Drops the strict upper triangle and marks the matrix as symmetric,
+    /// such that it is written in the compact form.
+    ///
+    /// This does not check whether the matrix is actually symmetric;
+    /// use [`Matrix::detect_symmetry`] for that. Fails if the matrix is not square,
+    /// or if it is already stored in another compact form.
+    pub fn compress_symmetric(&mut self) -> Result<(), Error> {
+        self.check_square()?;
+        if self.symmetry != Symmetry::Symmetric {
+            self.check_general()?;
+        }
+        *self = self.lower_triangle(false);
+        self.symmetry = Symmetry::Symmetric;
+        Ok(())
+    }
+
+    /// Drops the strict upper triangle of a complex matrix and marks it as hermitian,
+    /// such that it is written in the compact form, like [`Matrix::compress_symmetric`].
+    ///
+    /// The imaginary parts of the diagonal must be zero within the relative tolerance `tol`
+    /// of the absolute values of their entries, and are set to zero.
+    pub fn compress_hermitian(&mut self, tol: f64) -> Result<(), Error> {
+        self.check_square()?;
+        if self.symmetry != Symmetry::Hermitian {
+            self.check_general()?;
+        }
+        let MatrixData::Complex(xs, ys) = &mut self.vals else {
+            return Err(Error::TypeMismatch { expected: "complex", found: self.vals.field() });
+        };
+
+        let (rows, cols) = (&self.rows, &self.cols);
+        let violation = (0..self.nvals).into_par_iter().find_first(|&i| {
+            let im = ys[i] as f64;
+            rows[i] == cols[i] && (im.abs() > tol * (xs[i] as f64).hypot(im) || im.is_nan())
+        });
+        if let Some(i) = violation {
+            return Err(Error::SymmetryViolation { row: rows[i], col: cols[i], symmetry: Symmetry::Hermitian });
+        }
+        ys.par_iter_mut().enumerate()
+            .filter(|&(i, _)| rows[i] == cols[i])
+            .for_each(|(_, y)| *y = 0.0);
+
+        *self = self.lower_triangle(false);
+        self.symmetry = Symmetry::Hermitian;
+        Ok(())
+    }
+
+    /// Drops the upper triangle and the diagonal, which must be zero, and marks the matrix as skew-symmetric,
+    /// such that it is written in the compact form, like [`Matrix::compress_symmetric`].
+    ///
+    /// This does not check whether the entries off the diagonal are actually skew-symmetric;
+    /// use [`Matrix::detect_symmetry`] for that.
+    pub fn compress_skew(&mut self) -> Result<(), Error> {
+        self.check_square()?;
+        if self.symmetry != Symmetry::SkewSymmetric {
+            self.check_general()?;
+        }
+        if matches!(self.vals, MatrixData::Bool()) {
+            return Err(Error::TypeMismatch { expected: "real", found: self.vals.field() });
+        }
+        if let Some(i) = self.nonzero_diagonal() {
+            return Err(Error::SymmetryViolation { row: self.rows[i], col: self.cols[i], symmetry: Symmetry::SkewSymmetric });
+        }
+
+        *self = self.lower_triangle(true);
+        self.symmetry = Symmetry::SkewSymmetric;
+        Ok(())
+    }
+
+    /// Checks that a skew-symmetric matrix has no nonzero entries on its diagonal,
+    /// which its compact form leaves out as they must equal their own negation.
+    pub(crate) fn check_skew_diagonal(&self) -> Result<(), Error> {
+        if self.symmetry != Symmetry::SkewSymmetric {
+            return Ok(());
+        }
+
+        match self.nonzero_diagonal() {
+            Some(i) => Err(Error::SymmetryViolation { row: self.rows[i], col: self.cols[i], symmetry: self.symmetry }),
+            None => Ok(()),
+        }
+    }
+
+    /// Index of the first entry on the diagonal that is not zero.
+    fn nonzero_diagonal(&self) -> Option<usize> {
+        (0..self.nvals).into_par_iter().find_first(|&i| self.rows[i] == self.cols[i] && self.value_at(i) != (0.0, 0.0))
+    }
+
+    /// Stores the implied upper triangle explicitly and marks the matrix as general,
+    /// undoing [`Matrix::compress_symmetric`] and its skew-symmetric and hermitian counterparts.
+    /// Entries in row-major or column-major order stay in that order.
+    pub fn expand_symmetric(&mut self) {
+        if self.symmetry == Symmetry::General {
+            return;
+        }
+        let (row_major, col_major) = (self.is_row_major(), self.is_col_major());
+
+        let off_diagonal: Vec<_> = (0..self.nvals).into_par_iter()
+            .filter(|&i| self.rows[i] != self.cols[i])
+            .collect();
+        let mut mirrored = self.gather(&off_diagonal);
+        (0..mirrored.nvals).for_each(|i| mirrored.mirror_at(i));
+
+        self.append(&mirrored);
+        self.symmetry = Symmetry::General;
+        if row_major {
+            self.sort_row_major();
+        } else if col_major {
+            self.sort_col_major();
+        }
+    }
+
+    /// Checks that a matrix with a symmetry other than `General` stores no entries above the diagonal,
+    /// as the Matrix-Market format requires, such that it is written as a valid file.
+    pub fn check_lower_storage(&self) -> Result<(), Error> {
+        if self.symmetry == Symmetry::General {
+            return Ok(());
+        }
+
+        match (0..self.nvals).into_par_iter().find_first(|&i| self.rows[i] < self.cols[i]) {
+            Some(i) => Err(Error::UpperEntry { row: self.rows[i], col: self.cols[i], symmetry: self.symmetry }),
+            None => Ok(()),
+        }
+    }
+
+    /// Moves the entries above the diagonal of a matrix that only stores its lower triangle
+    /// to their mirrored positions, adjusting their values as the symmetry requires.
+    /// An entry whose mirror is also stored becomes a duplicate of it.
+    pub fn fold_to_lower(&mut self) {
+        if self.symmetry == Symmetry::General {
+            return;
+        }
+
+        for i in 0..self.nvals {
+            if self.rows[i] < self.cols[i] {
+                self.mirror_at(i);
+            }
+        }
+    }
+
+    /// Indices of the entries in row-major order.
+    fn row_major_order(&self) -> Vec<usize> {
+        let mut order: Vec<_> = (0..self.nvals).collect();
+        order.par_sort_unstable_by_key(|&i| (self.rows[i], self.cols[i]));
+        order
+    }
+
+    /// Index of an entry at the transposed position of entry `i`, given the row-major order of the entries.
+    fn mirror(&self, order: &[usize], i: usize) -> Option<usize> {
+        let key = (self.cols[i], self.rows[i]);
+        order.binary_search_by_key(&key, |&j| (self.rows[j], self.cols[j]))
+            .ok()
+            .map(|j| order[j])
+    }
+
+    #[inline]
+    pub(crate) fn is_complex(&self) -> bool {
+        matches!(self.vals, MatrixData::Complex(_, _))
+    }
+}
+
+impl Symmetry {
+    /// Reads the symmetry qualifier from a `%%MatrixMarket` banner line.
+    /// Anything that is not a banner is treated as a general matrix.
+    pub(crate) fn from_banner(banner: &str) -> Self {
+        if !banner.starts_with("%%MatrixMarket") {
+            return Symmetry::General;
+        }
+
+        banner.split_ascii_whitespace()
+            .last()
+            .and_then(|qualifier| qualifier.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Symmetry::*;
+        match s.to_ascii_lowercase().as_str() {
+            "general" => Ok(General),
+            "symmetric" => Ok(Symmetric),
+            "skew-symmetric" => Ok(SkewSymmetric),
+            "hermitian" => Ok(Hermitian),
+            _ => Err(format!("unknown symmetry qualifier `{s}`")),
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Symmetry::*;
+        match self {
+            General => write!(f, "general"),
+            Symmetric => write!(f, "symmetric"),
+            SkewSymmetric => write!(f, "skew-symmetric"),
+            Hermitian => write!(f, "hermitian"),
+        }
+    }
+}
+
+#[inline]
+fn approx_eq(a: f64, b: f64, tol: f64) -> bool {
+    a == b || (a - b).abs() <= tol * a.abs().max(b.abs())
+}
@@ -0,0 +1,150 @@
+//! Sparse tensors in the FROSTT `.tns` format: one entry per line, with a (1-based) index for every mode,
+//! followed by the value. There is no size line; the dimensions are the largest index of every mode.
+//! Lines starting with `#` or `%` are comments.
+
+use std::cmp::Ordering;
+use std::fmt;
+#[cfg(feature = "mmap")]
+use std::fs;
+
+#[cfg(feature = "mmap")]
+use memmap2::MmapOptions;
+use rayon::prelude::*;
+
+use crate::{parse_field, parse_int, split_fields, try_parse_utf8, DataType, Error, MatrixData};
+
+/// Sparse tensor with any number of modes, in coordinate format.
+#[derive(Clone, PartialEq)]
+pub struct Tensor {
+    dims: Vec<usize>,
+    /// (1-based) indices of the entries, by mode.
+    indices: Vec<Vec<usize>>,
+    vals: MatrixData,
+    nvals: usize,
+}
+
+impl Tensor {
+    /// Number of modes.
+    pub fn ndims(&self) -> usize { self.dims.len() }
+    pub fn dims(&self) -> &[usize] { &self.dims }
+    pub fn nvals(&self) -> usize { self.nvals }
+
+    /// Indices of the entries along the given (0-based) mode, starting at one.
+    pub fn indices(&self, mode: usize) -> &[usize] { &self.indices[mode] }
+
+    /// Memory-maps a `.tns` file and parses it in parallel.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap(file: fs::File, data_type: DataType) -> Result<Self, Error> {
+        let mmap = unsafe { MmapOptions::new().map(&file)? };
+        Self::from_bytes(&mmap, data_type)
+    }
+
+    /// Parses the contents of a `.tns` file in parallel.
+    /// The number of modes follows from the number of columns of the first entry and the data type,
+    /// and every other entry must have as many columns.
+    pub fn from_bytes(bytes: &[u8], data_type: DataType) -> Result<Self, Error> {
+        let lines: Vec<_> = bytes.split(|&b| b == b'\n')
+            .enumerate()
+            .filter(|(_, line)| line.trim_ascii().first().is_some_and(|&b| b != b'#' && b != b'%'))
+            .map(|(i, line)| (i + 1, line))
+            .collect();
+        let nvals = lines.len();
+
+        let nvalues = match data_type {
+            DataType::Real | DataType::Integer => 1,
+            DataType::Complex => 2,
+            DataType::Bool => 0,
+        };
+        let nfields = lines.first().map_or(nvalues, |(_, line)| split_fields(line).count().max(nvalues));
+        let ndims = nfields - nvalues;
+
+        let entries: Vec<_> = lines.into_par_iter()
+            .map(|(line, bytes)| (line, split_fields(bytes).collect::<Vec<_>>()))
+            .collect();
+        if let Some((line, parts)) = entries.par_iter().find_first(|(_, parts)| parts.len() != nfields) {
+            return Err(Error::RaggedEntry { line: *line, expected: nfields, found: parts.len() });
+        }
+
+        let indices: Vec<Vec<usize>> = (0..ndims).into_par_iter()
+            .map(|mode| entries.iter().map(|(line, parts)| parse_field(parts[mode], try_parse_utf8, || *line)).collect())
+            .collect::<Result<_, _>>()?;
+        let dims = indices.iter().map(|idxs| idxs.par_iter().copied().max().unwrap_or(0)).collect();
+
+        let value = |k: usize| entries.par_iter().map(move |(line, parts)| parse_field(parts[ndims + k], try_parse_utf8, || *line));
+        let vals = match data_type {
+            DataType::Real => MatrixData::Real(value(0).collect::<Result<_, _>>()?),
+            DataType::Complex => MatrixData::Complex(value(0).collect::<Result<_, _>>()?, value(1).collect::<Result<_, _>>()?),
+            DataType::Integer => MatrixData::Integer(
+                entries.par_iter().map(|(line, parts)| parse_int(parts[ndims], || *line)).collect::<Result<_, _>>()?,
+            ),
+            DataType::Bool => MatrixData::Bool(),
+        };
+
+        Ok(Self { dims, indices, vals, nvals })
+    }
+
+    /// Sorts the entries lexicographically by their indices, comparing the (0-based) modes in the given order.
+    /// Fails if `modes` is not a permutation of the modes.
+    pub fn sort(&mut self, modes: &[usize]) -> Result<(), Error> {
+        let mut sorted = modes.to_vec();
+        sorted.sort_unstable();
+        if !sorted.into_iter().eq(0..self.ndims()) {
+            return Err(Error::InvalidModes { modes: modes.to_vec(), ndims: self.ndims() });
+        }
+
+        let mut permutation: Vec<_> = (0..self.nvals).collect();
+        permutation.par_sort_unstable_by(|&a, &b| {
+            modes.iter()
+                .map(|&mode| self.indices[mode][a].cmp(&self.indices[mode][b]))
+                .find(|&ord| ord != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        self.indices = self.indices.par_iter()
+            .map(|idxs| permutation.iter().map(|&i| idxs[i]).collect())
+            .collect();
+        self.vals = self.vals.gather(&permutation);
+        Ok(())
+    }
+
+    /// Whether the entries are sorted lexicographically by the (0-based) modes in the given order.
+    pub fn is_sorted(&self, modes: &[usize]) -> bool {
+        (1..self.nvals).into_par_iter().all(|i| {
+            modes.iter()
+                .map(|&mode| self.indices[mode][i - 1].cmp(&self.indices[mode][i]))
+                .find(|&ord| ord != Ordering::Equal)
+                != Some(Ordering::Greater)
+        })
+    }
+}
+
+impl fmt::Display for Tensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (0..self.nvals).try_for_each(|i| {
+            for (mode, idxs) in self.indices.iter().enumerate() {
+                if mode > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", idxs[i])?;
+            }
+
+            use MatrixData::*;
+            match &self.vals {
+                Real(xs) => writeln!(f, " {}", xs[i]),
+                Complex(xs, ys) => writeln!(f, " {} {}", xs[i], ys[i]),
+                Integer(xs) => writeln!(f, " {}", xs[i]),
+                Bool() => writeln!(f),
+            }
+        })
+    }
+}
+
+impl fmt::Debug for Tensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tensor")
+            .field("dims", &self.dims)
+            .field("nvals", &self.nvals)
+            .field("data_type", &self.vals.field())
+            .finish()
+    }
+}
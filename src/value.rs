@@ -0,0 +1,125 @@
+use std::io::{self, Write};
+
+use crate::{Complex, Int, Matrix, MatrixData, Real};
+
+/// Scalar types that the values of a matrix can be extracted as, or constructed from.
+pub trait Value: Copy + Send + Sync + 'static {
+    /// Name of the corresponding data type in the Matrix-Market banner.
+    const FIELD: &'static str;
+
+    /// Copies the values out of `m`, or returns `None` if its data type does not match.
+    /// Pattern matrices match any value type, with every entry being one.
+    fn values(m: &Matrix) -> Option<Vec<Self>>;
+
+    /// Copies the value of entry `i` out of `m`, or returns `None` if its data type does not match.
+    fn value_at(m: &Matrix, i: usize) -> Option<Self>;
+
+    /// Creates a general matrix from 1-based coordinates and values of this type.
+    fn into_matrix(nrows: usize, ncols: usize, rows: Vec<usize>, cols: Vec<usize>, values: Vec<Self>) -> Matrix;
+
+    /// Writes this value as it appears in an entry line, including the leading space.
+    fn write_value<W: Write>(&self, wtr: &mut W) -> io::Result<()>;
+}
+
+impl Value for Real {
+    const FIELD: &'static str = "real";
+
+    fn values(m: &Matrix) -> Option<Vec<Self>> {
+        match &m.vals {
+            MatrixData::Real(xs) => Some(xs.clone()),
+            MatrixData::Bool() => Some(vec![1.0; m.nvals]),
+            _ => None,
+        }
+    }
+
+    fn value_at(m: &Matrix, i: usize) -> Option<Self> {
+        match &m.vals {
+            MatrixData::Real(xs) => Some(xs[i]),
+            MatrixData::Bool() => Some(1.0),
+            _ => None,
+        }
+    }
+
+    fn into_matrix(nrows: usize, ncols: usize, rows: Vec<usize>, cols: Vec<usize>, values: Vec<Self>) -> Matrix {
+        Matrix::new(nrows, ncols, rows, cols, MatrixData::Real(values))
+    }
+
+    fn write_value<W: Write>(&self, wtr: &mut W) -> io::Result<()> {
+        write!(wtr, " {self}")
+    }
+}
+
+impl Value for Int {
+    const FIELD: &'static str = "integer";
+
+    fn values(m: &Matrix) -> Option<Vec<Self>> {
+        match &m.vals {
+            MatrixData::Integer(xs) => Some(xs.clone()),
+            MatrixData::Bool() => Some(vec![1; m.nvals]),
+            _ => None,
+        }
+    }
+
+    fn value_at(m: &Matrix, i: usize) -> Option<Self> {
+        match &m.vals {
+            MatrixData::Integer(xs) => Some(xs[i]),
+            MatrixData::Bool() => Some(1),
+            _ => None,
+        }
+    }
+
+    fn into_matrix(nrows: usize, ncols: usize, rows: Vec<usize>, cols: Vec<usize>, values: Vec<Self>) -> Matrix {
+        Matrix::new(nrows, ncols, rows, cols, MatrixData::Integer(values))
+    }
+
+    fn write_value<W: Write>(&self, wtr: &mut W) -> io::Result<()> {
+        write!(wtr, " {self}")
+    }
+}
+
+impl Value for Complex<Real> {
+    const FIELD: &'static str = "complex";
+
+    fn values(m: &Matrix) -> Option<Vec<Self>> {
+        match &m.vals {
+            MatrixData::Complex(xs, ys) => Some(xs.iter().zip(ys).map(|(&x, &y)| Self::new(x, y)).collect()),
+            MatrixData::Bool() => Some(vec![Self::new(1.0, 0.0); m.nvals]),
+            _ => None,
+        }
+    }
+
+    fn value_at(m: &Matrix, i: usize) -> Option<Self> {
+        match &m.vals {
+            MatrixData::Complex(xs, ys) => Some(Self::new(xs[i], ys[i])),
+            MatrixData::Bool() => Some(Self::new(1.0, 0.0)),
+            _ => None,
+        }
+    }
+
+    fn into_matrix(nrows: usize, ncols: usize, rows: Vec<usize>, cols: Vec<usize>, values: Vec<Self>) -> Matrix {
+        let (xs, ys) = values.into_iter().map(|z| (z.re, z.im)).unzip();
+        Matrix::new(nrows, ncols, rows, cols, MatrixData::Complex(xs, ys))
+    }
+
+    fn write_value<W: Write>(&self, wtr: &mut W) -> io::Result<()> {
+        write!(wtr, " {} {}", self.re, self.im)
+    }
+}
+
+impl Matrix {
+    /// Copies the values out of this matrix as the given type,
+    /// or returns `None` if the data type does not match.
+    /// Pattern matrices match any value type, with every entry being one.
+    pub fn values<N: Value>(&self) -> Option<Vec<N>> {
+        N::values(self)
+    }
+
+    /// Like [`Matrix::values`], but with an error that describes the mismatch.
+    #[cfg(any(feature = "sprs", feature = "faer"))]
+    pub(crate) fn values_as<N: Value>(&self) -> Result<Vec<N>, crate::Error> {
+        N::values(self).ok_or(crate::Error::TypeMismatch {
+            expected: N::FIELD,
+            found: self.vals.field(),
+        })
+    }
+}
@@ -0,0 +1,183 @@
+//! Dense vectors in the Matrix-Market array format, such as right-hand sides and solutions.
+
+use std::{fmt, io::{self, BufReader, Read}};
+
+use rayon::prelude::*;
+
+use crate::{entry_fields, fields, is_data, line_of, parse_field, parse_int, parse_real, parse_utf8, DataType, Error, Matrix, MatrixData, Real};
+
+/// Dense vector, stored as an `n` by 1 matrix in the array format.
+#[derive(Clone, PartialEq)]
+pub struct Vector {
+    vals: MatrixData,
+}
+
+impl Vector {
+    pub fn len(&self) -> usize {
+        match &self.vals {
+            MatrixData::Real(xs) => xs.len(),
+            MatrixData::Complex(xs, _) => xs.len(),
+            MatrixData::Integer(xs) => xs.len(),
+            MatrixData::Bool() => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn data_type(&self) -> DataType { self.vals.data_type() }
+
+    /// Reads a Matrix-Market file in the array format, with a single column.
+    pub fn from_reader<R: Read>(mut rdr: BufReader<R>, data_type: DataType) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        rdr.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes, data_type)
+    }
+
+    /// Parses the contents of a Matrix-Market file in the array format, with a single column.
+    pub fn from_bytes(bytes: &[u8], data_type: DataType) -> Result<Self, Error> {
+        let (_, vals) = parse_array(bytes, data_type, Some(1))?;
+        Ok(Self { vals })
+    }
+
+    /// Copies the entries into a `Vec<f64>`, such as to use them as the factors of [`Matrix::scale_rows`].
+    /// Integer entries are converted, and complex vectors are refused.
+    pub fn to_vec(&self) -> Result<Vec<f64>, Error> {
+        match &self.vals {
+            MatrixData::Real(xs) => Ok(xs.iter().map(|&x| x as f64).collect()),
+            MatrixData::Integer(xs) => Ok(xs.iter().map(|&x| x as f64).collect()),
+            vals => Err(Error::TypeMismatch { expected: "real", found: vals.field() }),
+        }
+    }
+
+    /// Moves entry `permutation[k]` to position `k`, for every `k`,
+    /// as [`Matrix::permute_symmetric`] does for the rows.
+    ///
+    /// Panics if `permutation` is not a permutation of the entries.
+    pub fn permute(&mut self, permutation: &[usize]) {
+        assert_eq!(permutation.len(), self.len(), "expected one index per entry");
+        self.vals = self.vals.gather(permutation);
+    }
+
+    /// Multiplies every entry by `factor`.
+    /// Integer vectors are converted to real vectors first.
+    pub fn scale(&mut self, factor: f64) {
+        self.scale_by(|_| factor);
+    }
+
+    /// Multiplies entry `i` by `factors[i]`, such as by the row factors of [`Matrix::equilibrate`].
+    /// Integer vectors are converted to real vectors first.
+    pub fn scale_entries(&mut self, factors: &[f64]) {
+        assert_eq!(factors.len(), self.len(), "expected one factor per entry");
+        self.scale_by(|i| factors[i]);
+    }
+
+    fn scale_by<F>(&mut self, factor: F)
+    where
+        F: Fn(usize) -> f64 + Sync,
+    {
+        if let MatrixData::Integer(xs) = &self.vals {
+            self.vals = MatrixData::Real(xs.par_iter().map(|&x| x as Real).collect());
+        }
+
+        match &mut self.vals {
+            MatrixData::Real(xs) => {
+                xs.par_iter_mut().enumerate().for_each(|(i, x)| *x = (*x as f64 * factor(i)) as Real);
+            },
+            MatrixData::Complex(xs, ys) => {
+                xs.par_iter_mut().zip(ys.par_iter_mut()).enumerate().for_each(|(i, (x, y))| {
+                    let f = factor(i);
+                    *x = (*x as f64 * f) as Real;
+                    *y = (*y as f64 * f) as Real;
+                });
+            },
+            MatrixData::Integer(_) | MatrixData::Bool() => unreachable!(),
+        }
+    }
+}
+
+impl From<Vec<Real>> for Vector {
+    fn from(xs: Vec<Real>) -> Self {
+        Self { vals: MatrixData::Real(xs) }
+    }
+}
+
+impl From<Vector> for Matrix {
+    /// Stores every entry of the vector explicitly, as an `n` by 1 matrix in the coordinate format.
+    fn from(v: Vector) -> Self {
+        let n = v.len();
+        Matrix::new(n, 1, (1..=n).collect(), vec![1; n], v.vals)
+    }
+}
+
+impl fmt::Debug for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Vector {{ len: {}, field: {} }}", self.len(), self.vals.field())
+    }
+}
+
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "%%MatrixMarket matrix array {} general", self.vals.field())?;
+        writeln!(f, "{} 1", self.len())?;
+        (0..self.len()).try_for_each(|i| {
+            use MatrixData::*;
+            match &self.vals {
+                Real(xs) => writeln!(f, "{}", xs[i]),
+                Complex(xs, ys) => writeln!(f, "{} {}", xs[i], ys[i]),
+                Integer(xs) => writeln!(f, "{}", xs[i]),
+                Bool() => Ok(()),
+            }
+        })
+    }
+}
+
+/// Parses the contents of a Matrix-Market file in the array format, returning the number of columns and the values,
+/// which are stored column by column. Fails if the number of columns differs from `ncols`, if given.
+pub(crate) fn parse_array(bytes: &[u8], data_type: DataType, ncols: Option<usize>) -> Result<(usize, MatrixData), Error> {
+    if let DataType::Bool = data_type {
+        return Err(Error::TypeMismatch { expected: "real", found: "pattern" });
+    }
+
+    let banner = bytes.split(|&b| b == b'\n').next().unwrap_or_default();
+    if banner.to_ascii_lowercase().windows(10).any(|w| w == b"coordinate") {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "expected the array format, found the coordinate format").into());
+    }
+
+    let mut lines = bytes.split(|&b| b == b'\n').filter(|line| is_data(line));
+    let Some(header) = lines.next() else {
+        // File is empty or contains only comments, return no values
+        return Ok((ncols.unwrap_or(0), MatrixData::new(data_type)));
+    };
+
+    let [nrows, found] = fields(header);
+    let (nrows, found): (usize, usize) = (parse_utf8(nrows), parse_utf8(found));
+    if let Some(ncols) = ncols && found != ncols {
+        return Err(Error::DimensionMismatch { expected: (nrows, ncols), found: (nrows, found) });
+    }
+
+    let mut vals = MatrixData::with_capacity(data_type, nrows * found);
+    for line in lines {
+        let line_number = || line_of(bytes, line);
+        match &mut vals {
+            MatrixData::Real(xs) => {
+                let [v] = entry_fields(line, data_type, line_number)?;
+                xs.push(parse_field(v, |v| parse_real(v, false), line_number)?);
+            },
+            MatrixData::Complex(xs, ys) => {
+                let [re, im] = entry_fields(line, data_type, line_number)?;
+                xs.push(parse_field(re, |re| parse_real(re, false), line_number)?);
+                ys.push(parse_field(im, |im| parse_real(im, false), line_number)?);
+            },
+            MatrixData::Integer(xs) => {
+                let [v] = entry_fields(line, data_type, line_number)?;
+                xs.push(parse_int(v, line_number)?);
+            },
+            MatrixData::Bool() => unreachable!(),
+        }
+    }
+
+    let v = Vector { vals };
+    if v.len() != nrows * found {
+        return Err(Error::EntryCount { expected: nrows * found, found: v.len() });
+    }
+    Ok((found, v.vals))
+}
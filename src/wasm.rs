@@ -0,0 +1,56 @@
+//! JavaScript bindings, for inspecting matrices in the browser.
+//!
+//! On `wasm32-unknown-unknown` threads are unavailable, and rayon runs everything on the current thread.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{DataType, Matrix, MatrixData, ReadOptions};
+
+/// A parsed matrix with 0-based coordinates.
+/// Complex values are interleaved as real and imaginary parts, and pattern matrices have no values.
+#[wasm_bindgen(getter_with_clone)]
+pub struct ParsedMatrix {
+    pub nrows: usize,
+    pub ncols: usize,
+    /// Data type as named in the Matrix-Market banner.
+    pub field: String,
+    /// Symmetry as named in the Matrix-Market banner.
+    pub symmetry: String,
+    pub rows: Vec<u32>,
+    pub cols: Vec<u32>,
+    pub vals: Vec<f64>,
+}
+
+/// Parses the contents of a Matrix-Market file, using the data type from its banner.
+/// Malformed contents are reported as an error, as a panic would trap the whole instance.
+#[wasm_bindgen]
+pub fn parse(bytes: &[u8]) -> Result<ParsedMatrix, JsError> {
+    let banner = bytes.split(|&b| b == b'\n').next().unwrap_or_default();
+    let banner = String::from_utf8_lossy(banner).to_ascii_lowercase();
+    let data_type = match banner.split_ascii_whitespace().nth(3) {
+        Some("real" | "double") => DataType::Real,
+        Some("complex") => DataType::Complex,
+        Some("integer") => DataType::Integer,
+        Some("pattern") => DataType::Bool,
+        Some(field) => return Err(JsError::new(&format!("unsupported data type `{field}`"))),
+        None => return Err(JsError::new("missing Matrix-Market banner")),
+    };
+
+    let m = Matrix::try_parse_bytes(bytes, data_type, &ReadOptions::default()).map_err(|e| JsError::new(&e.to_string()))?;
+    let vals = match &m.vals {
+        MatrixData::Real(xs) => xs.iter().map(|&x| x as f64).collect(),
+        MatrixData::Complex(xs, ys) => xs.iter().zip(ys).flat_map(|(&x, &y)| [x as f64, y as f64]).collect(),
+        MatrixData::Integer(xs) => xs.iter().map(|&x| x as f64).collect(),
+        MatrixData::Bool() => Vec::new(),
+    };
+
+    Ok(ParsedMatrix {
+        nrows: m.nrows,
+        ncols: m.ncols,
+        field: m.vals.field().to_string(),
+        symmetry: m.symmetry.to_string(),
+        rows: m.rows.iter().map(|&row| (row - 1) as u32).collect(),
+        cols: m.cols.iter().map(|&col| (col - 1) as u32).collect(),
+        vals,
+    })
+}
@@ -0,0 +1,88 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+use crate::{DataType, Error, MatrixData, Symmetry, Value};
+
+/// Width reserved for the number of entries in the size line, enough for any `u64`.
+const NVALS_WIDTH: usize = 20;
+
+/// Writes entries one at a time, for streams whose number of entries is not known up front.
+///
+/// The size line is written with room for the number of entries, which is patched in by [`MatrixWriter::finish`].
+/// Without finishing, the size line is left blank where the number of entries should be.
+/// Writers with a symmetry other than `General` refuse entries above the diagonal.
+pub struct MatrixWriter<W: Write + Seek> {
+    wtr: W,
+    data_type: DataType,
+    symmetry: Symmetry,
+    /// Position of the number of entries in the size line.
+    nvals_pos: u64,
+    nvals: u64,
+}
+
+impl<W: Write + Seek> MatrixWriter<W> {
+    /// Writes the banner and a placeholder size line.
+    pub fn new(mut wtr: W, nrows: usize, ncols: usize, data_type: DataType, symmetry: Symmetry) -> io::Result<Self> {
+        writeln!(wtr, "%%MatrixMarket matrix coordinate {} {symmetry}", field(data_type))?;
+        write!(wtr, "{nrows} {ncols} ")?;
+        let nvals_pos = wtr.stream_position()?;
+        writeln!(wtr, "{:NVALS_WIDTH$}", "")?;
+
+        Ok(Self { wtr, data_type, symmetry, nvals_pos, nvals: 0 })
+    }
+
+    /// Writes an entry with 1-based coordinates; the value type must match the data type of this writer.
+    pub fn write_entry<N: Value>(&mut self, row: usize, col: usize, value: N) -> io::Result<()> {
+        if N::FIELD != field(self.data_type) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("expected a {} value, found {}", field(self.data_type), N::FIELD)));
+        }
+        self.check_lower(row, col)?;
+
+        write!(self.wtr, "{row} {col}")?;
+        value.write_value(&mut self.wtr)?;
+        writeln!(self.wtr)?;
+        self.nvals += 1;
+        Ok(())
+    }
+
+    /// Writes an entry of a pattern matrix, with 1-based coordinates.
+    pub fn write_pattern_entry(&mut self, row: usize, col: usize) -> io::Result<()> {
+        if !matches!(self.data_type, DataType::Bool) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("expected a {} value, found none", field(self.data_type))));
+        }
+        self.check_lower(row, col)?;
+
+        writeln!(self.wtr, "{row} {col}")?;
+        self.nvals += 1;
+        Ok(())
+    }
+
+    fn check_lower(&self, row: usize, col: usize) -> io::Result<()> {
+        if self.symmetry != Symmetry::General && row < col {
+            let e = Error::UpperEntry { row, col, symmetry: self.symmetry };
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, e.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Number of entries written so far.
+    pub fn nvals(&self) -> u64 {
+        self.nvals
+    }
+
+    /// Patches the number of entries into the size line, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let end = self.wtr.stream_position()?;
+        self.wtr.seek(SeekFrom::Start(self.nvals_pos))?;
+        write!(self.wtr, "{:<NVALS_WIDTH$}", self.nvals)?;
+        self.wtr.seek(SeekFrom::Start(end))?;
+        self.wtr.flush()?;
+        Ok(self.wtr)
+    }
+}
+
+/// Name of a data type in the Matrix-Market banner.
+fn field(data_type: DataType) -> &'static str {
+    MatrixData::new(data_type).field()
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "arrow")]
+
+use std::fs::File;
+use std::io::Cursor;
+
+use arrow_array::{Float64Array, UInt64Array};
+use matrix_market_transform::*;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+#[test]
+fn record_batch() {
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n3 3 2\n2 1 0.5\n3 3 2\n", DataType::Real);
+    let batch = m.to_record_batch();
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.schema().metadata()["symmetry"], "symmetric");
+
+    let rows = batch.column_by_name("row").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!(rows.values(), &[2, 3]);
+    let values = batch.column_by_name("value").unwrap().as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(values.values(), &[0.5, 2.0]);
+
+    let complex = generators::erdos_renyi(4, 5, 0.5, DataType::Complex, 1).to_record_batch();
+    assert!(complex.column_by_name("re").is_some() && complex.column_by_name("im").is_some());
+}
+
+#[test]
+fn ipc_and_parquet() {
+    let m = generators::erdos_renyi(20, 30, 0.1, DataType::Integer, 2);
+
+    let mut bytes = Vec::new();
+    m.write_arrow_ipc(&mut bytes).unwrap();
+    let reader = arrow_ipc::reader::FileReader::try_new(Cursor::new(bytes), None).unwrap();
+    let batches: Vec<_> = reader.map(Result::unwrap).collect();
+    assert_eq!(batches, [m.to_record_batch()]);
+
+    let path = std::env::temp_dir().join(format!("mmt-test-{}.parquet", std::process::id()));
+    m.write_parquet(File::create(&path).unwrap()).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+    let nrows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+    assert_eq!(nrows, m.nvals());
+    std::fs::remove_file(path).unwrap();
+}
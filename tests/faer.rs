@@ -0,0 +1,36 @@
+#![cfg(feature = "faer")]
+
+use matrix_market_transform::*;
+
+#[test]
+fn sparse_col_and_row_mat() {
+    let mut m = generators::erdos_renyi(40, 30, 0.1, DataType::Real, 3);
+    m.shuffle(3);
+
+    let csc = m.to_sparse_col_mat::<Real>().unwrap();
+    let csr = m.to_sparse_row_mat::<Real>().unwrap();
+    assert_eq!(csc.compute_nnz(), m.nvals());
+    assert_eq!(csr.compute_nnz(), m.nvals());
+
+    m.sort_col_major();
+    let values = m.values::<Real>().unwrap();
+    let mut k = 0;
+    for j in 0..m.ncols() {
+        for (&i, &x) in csc.row_idx_of_col_raw(j).iter().zip(csc.val_of_col(j)) {
+            assert_eq!((i + 1, j + 1, x), (m.rows()[k], m.cols()[k], values[k]));
+            k += 1;
+        }
+    }
+
+    m.sort_row_major();
+    let values = m.values::<Real>().unwrap();
+    let mut k = 0;
+    for i in 0..m.nrows() {
+        for (&j, &x) in csr.col_idx_of_row_raw(i).iter().zip(csr.val_of_row(i)) {
+            assert_eq!((i + 1, j + 1, x), (m.rows()[k], m.cols()[k], values[k]));
+            k += 1;
+        }
+    }
+
+    assert!(matches!(m.to_sparse_col_mat::<Int>(), Err(Error::TypeMismatch { .. })));
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "capi")]
+
+use std::{ffi::CString, ptr, slice};
+
+use matrix_market_transform::ffi::*;
+use matrix_market_transform::Real;
+
+const DATA: &str = "%%MatrixMarket matrix coordinate real general
+3 3 4
+3 1 4.0
+1 1 1.0
+2 3 3.0
+1 2 2.0
+";
+
+#[test]
+fn csr_pointers() {
+    let path = std::env::temp_dir().join("mmt_ffi_csr_pointers.mtx");
+    std::fs::write(&path, DATA).unwrap();
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+
+    unsafe {
+        let m = mmt_read_file(path.as_ptr(), MMT_REAL);
+        assert!(!m.is_null());
+
+        let (mut nrows, mut nvals) = (0, 0);
+        let (mut row_ptr, mut col_idx, mut values) = (ptr::null(), ptr::null(), ptr::null());
+        assert_eq!(mmt_get_csr_pointers(m, &mut nrows, ptr::null_mut(), &mut nvals, &mut row_ptr, &mut col_idx, &mut values), 0);
+        assert_eq!((nrows, nvals), (3, 4));
+        assert_eq!(slice::from_raw_parts(row_ptr, nrows + 1), [0, 2, 3, 4]);
+        assert_eq!(slice::from_raw_parts(col_idx, nvals), [0, 1, 2, 0]);
+        assert_eq!(slice::from_raw_parts(values as *const Real, nvals), [1.0, 2.0, 3.0, 4.0]);
+
+        assert_eq!(mmt_sort(m, MMT_COL_MAJOR), 0);
+        assert_eq!(mmt_sort(m, 7), -1);
+        mmt_free(m);
+
+        assert!(mmt_read_file(c"does-not-exist.mtx".as_ptr(), MMT_REAL).is_null());
+    }
+}
+
+#[test]
+fn malformed_files() {
+    // Files that cannot be parsed give null instead of aborting the host
+    for (name, contents) in [("bounds", "3 3 1\n4 1 1.0\n"), ("field", "3 3 1\n1 1 x\n"), ("shape", "%%MatrixMarket matrix coordinate real symmetric\n2 3 0\n")] {
+        let path = std::env::temp_dir().join(format!("mmt_ffi_malformed_{name}.mtx"));
+        std::fs::write(&path, contents).unwrap();
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        assert!(unsafe { mmt_read_file(path.as_ptr(), MMT_REAL) }.is_null(), "{name}");
+    }
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "hdf5")]
+
+use matrix_market_transform::*;
+
+#[test]
+fn round_trip() {
+    let dir = std::env::temp_dir();
+    for (k, data_type) in [DataType::Real, DataType::Complex, DataType::Integer, DataType::Bool].into_iter().enumerate() {
+        let m = generators::erdos_renyi(30, 20, 0.1, data_type, k as u64);
+        let path = dir.join(format!("mmt-test-{}-{k}.h5", std::process::id()));
+        m.write_hdf5(&path).unwrap();
+        assert_eq!(Matrix::from_hdf5(&path, data_type).unwrap(), m);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let empty = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real general\n4 5 0\n", DataType::Real);
+    let path = dir.join(format!("mmt-test-{}-empty.h5", std::process::id()));
+    empty.write_hdf5(&path).unwrap();
+    assert_eq!(Matrix::from_hdf5(&path, DataType::Real).unwrap(), empty);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn pattern_values() {
+    let m = generators::erdos_renyi(10, 10, 0.2, DataType::Bool, 3);
+    let path = std::env::temp_dir().join(format!("mmt-test-{}-pattern.h5", std::process::id()));
+    m.write_hdf5(&path).unwrap();
+    assert!(matches!(Matrix::from_hdf5(&path, DataType::Real), Err(Error::TypeMismatch { .. })));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn mat() {
+    let m = generators::erdos_renyi(30, 20, 0.1, DataType::Complex, 4);
+    let path = std::env::temp_dir().join(format!("mmt-test-{}.mat", std::process::id()));
+    m.write_mat(&path, "A").unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(bytes.starts_with(b"MATLAB 7.3 MAT-file"));
+    assert_eq!(&bytes[124..128], &[0x00, 0x02, b'I', b'M']);
+    assert_eq!(&bytes[512..520], b"\x89HDF\r\n\x1a\n");
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "serde")]
+
+use matrix_market_transform::*;
+
+#[test]
+fn json_round_trip() {
+    for data_type in [DataType::Real, DataType::Complex, DataType::Integer, DataType::Bool] {
+        let m = generators::erdos_renyi(20, 30, 0.1, data_type, 4);
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<Matrix>(&json).unwrap(), m);
+    }
+
+    let json = r#"{"nrows": 3, "ncols": 3, "data_type": "real", "symmetry": "symmetric",
+                   "rows": [1, 2, 3], "cols": [1, 1, 3], "values": [1, 0.5, 2]}"#;
+    let m: Matrix = serde_json::from_str(json).unwrap();
+    assert_eq!(m.symmetry(), Symmetry::Symmetric);
+    assert_eq!(m.values::<Real>().unwrap(), vec![1.0, 0.5, 2.0]);
+
+    let json = r#"{"nrows": 2, "ncols": 2, "data_type": "real", "rows": [1, 3], "cols": [1, 1], "values": [1, 2]}"#;
+    assert!(serde_json::from_str::<Matrix>(json).is_err());
+    let json = r#"{"nrows": 2, "ncols": 2, "data_type": "integer", "rows": [1], "cols": [1], "values": [1.5]}"#;
+    assert!(serde_json::from_str::<Matrix>(json).is_err());
+
+    // Compact forms are checked as when reading a file
+    let invalid = [
+        r#"{"nrows": 2, "ncols": 3, "data_type": "real", "symmetry": "symmetric", "rows": [1], "cols": [1], "values": [1]}"#,
+        r#"{"nrows": 2, "ncols": 2, "data_type": "real", "symmetry": "skew-symmetric", "rows": [1], "cols": [1], "values": [1]}"#,
+        r#"{"nrows": 2, "ncols": 2, "data_type": "real", "symmetry": "symmetric", "rows": [1], "cols": [2], "values": [1]}"#,
+    ];
+    for json in invalid {
+        assert!(serde_json::from_str::<Matrix>(json).is_err(), "{json}");
+    }
+}
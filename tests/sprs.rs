@@ -0,0 +1,37 @@
+#![cfg(feature = "sprs")]
+
+use matrix_market_transform::*;
+
+#[test]
+fn csmat_round_trip() {
+    let mut m = generators::erdos_renyi(40, 30, 0.1, DataType::Real, 1);
+    m.shuffle(1);
+
+    let tri = m.to_trimat::<Real>().unwrap();
+    assert_eq!(Matrix::from_trimat(&tri), m);
+
+    m.sort_row_major();
+    let csr = m.to_csmat::<Real>().unwrap();
+    assert!(csr.is_csr());
+    assert_eq!(Matrix::from_csmat(&csr), m);
+
+    m.sort_col_major();
+    let csc = m.to_csmat::<Real>().unwrap();
+    assert!(csc.is_csc());
+    assert_eq!(Matrix::from_csmat(&csc), m);
+
+    assert!(matches!(m.to_csmat::<Int>(), Err(Error::TypeMismatch { .. })));
+}
+
+#[test]
+fn complex_and_pattern() {
+    let mut m = generators::erdos_renyi(20, 20, 0.2, DataType::Complex, 2);
+    m.sort_row_major();
+    let csr = m.to_csmat::<Complex<Real>>().unwrap();
+    assert_eq!(Matrix::from_csmat(&csr), m);
+
+    let p = generators::erdos_renyi(20, 20, 0.2, DataType::Bool, 2);
+    let csr = p.to_csmat::<Int>().unwrap();
+    assert_eq!(csr.nnz(), p.nvals());
+    assert!(csr.data().iter().all(|&x| x == 1));
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "async")]
+
+use std::io::BufReader;
+
+use matrix_market_transform::*;
+
+fn block_on<F: Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(f)
+}
+
+#[test]
+fn async_reader() {
+    let mut data = "%%MatrixMarket matrix coordinate complex hermitian\n% comment\n\n700 700 200000\n".to_string();
+    for i in 0..200000usize {
+        data += &format!("{} {} {i}.5 -{i}\n", i * 7919 % 700 + 1, i % 700 + 1);
+        if i % 1000 == 0 {
+            data += "% comment\n\n";
+        }
+    }
+
+    let m = block_on(Matrix::from_async_reader(data.as_bytes(), DataType::Complex, &ReadOptions::default())).unwrap();
+    assert_eq!(m, Matrix::from_reader(BufReader::new(data.as_bytes()), DataType::Complex));
+    assert_eq!(m.symmetry(), Symmetry::Hermitian);
+
+    let empty = block_on(Matrix::from_async_reader("%%MatrixMarket matrix coordinate real general\n".as_bytes(), DataType::Real, &ReadOptions::default())).unwrap();
+    assert_eq!(empty.nvals(), 0);
+}
+
+#[test]
+fn async_reader_nonfinite() {
+    let data = "%%MatrixMarket matrix coordinate real general\n3 3 3\n1 1 1.0\n% comment\n2 2 NaN\n3 3 3.0";
+    let options = ReadOptions::new().nonfinite_policy(NonFinitePolicy::Error);
+    let e = block_on(Matrix::from_async_reader(data.as_bytes(), DataType::Real, &options)).unwrap_err();
+    assert_eq!(e.to_string(), "non-finite value on line 5");
+}
+
+#[test]
+fn async_writer() {
+    let m = generators::erdos_renyi(300, 200, 0.05, DataType::Complex, 3);
+    let mut bytes = Vec::new();
+    block_on(m.write_async(&mut bytes)).unwrap();
+    assert_eq!(bytes, m.to_string().as_bytes());
+
+    // The text format applies as it does to files
+    for format in [TextFormat::new().align(true), TextFormat::new().hex_floats(true), TextFormat::new().align(true).hex_floats(true)] {
+        let mut bytes = Vec::new();
+        block_on(m.write_async_with(&mut bytes, &format)).unwrap();
+        assert_eq!(bytes, m.formatted(format).to_string().as_bytes());
+    }
+}
@@ -9,6 +9,7 @@ fn test() {
     assert_eq!(m.nrows(), 9);
     assert_eq!(m.ncols(), 9);
     assert_eq!(m.nvals(), 49);
+    assert_eq!(Matrix::from_bytes(DATA.get_ref().as_bytes(), DataType::Real), m);
 
     let mut m2 = m.clone();
     m2.sort_row_major();
@@ -16,7 +17,672 @@ fn test() {
     assert_eq!(m, m2);
 }
 
+#[test]
+fn equilibrate() {
+    let r = BufReader::new(DATA);
+    let mut m = Matrix::from_reader(r, DataType::Real);
+
+    let (row_factors, col_factors) = m.equilibrate();
+    assert_eq!(row_factors.len(), 9);
+    assert_eq!(col_factors.len(), 9);
+    assert!((row_factors[0] - 1.0 / 0.75).abs() < 1e-6);
+    assert!(col_factors.iter().all(|&c| c >= 1.0));
+}
+
+#[test]
+fn triangles() {
+    let r = BufReader::new(DATA);
+    let m = Matrix::from_reader(r, DataType::Real);
+
+    let diag = m.diagonal();
+    let lower = m.lower_triangle(true);
+    let upper = m.upper_triangle(true);
+    assert_eq!(diag.nvals(), 9);
+    assert_eq!(diag.nvals() + lower.nvals() + upper.nvals(), m.nvals());
+    assert_eq!(m.lower_triangle(false).nvals(), diag.nvals() + lower.nvals());
+}
+
+#[test]
+fn compact_transforms() {
+    let compact = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n3 3 4\n1 1 4\n2 1 2\n3 1 -1\n3 3 2\n", DataType::Real);
+    let mut general = compact.clone();
+    general.expand_symmetric();
+
+    // Parts of a compact form include its implied upper triangle, and are general
+    let upper = compact.upper_triangle(true);
+    assert_eq!((upper.symmetry(), upper.rows(), upper.cols()), (Symmetry::General, &[1, 1][..], &[2, 3][..]));
+    for (part, expected) in [(compact.lower_triangle(true), general.lower_triangle(true)), (compact.diagonal(), general.diagonal())] {
+        assert_eq!(part.symmetry(), Symmetry::General);
+        assert_eq!(part.canonical_hash(None).unwrap(), expected.canonical_hash(None).unwrap());
+    }
+
+    // Scaling breaks the symmetry, so it applies to the expanded matrix
+    let (mut scaled, mut expected) = (compact.clone(), general.clone());
+    assert_eq!(scaled.equilibrate(), expected.equilibrate());
+    assert_eq!((scaled.symmetry(), scaled.nvals()), (Symmetry::General, 6));
+    assert_eq!(scaled.canonical_hash(None).unwrap(), expected.canonical_hash(None).unwrap());
+    let mut scaled = compact.clone();
+    scaled.scale_rows(&[1.0, 2.0, 3.0]);
+    assert_eq!(scaled.get::<Real>(1, 2), Some(2.0));
+    assert_eq!(scaled.get::<Real>(2, 1), Some(4.0));
+}
+
+#[test]
+fn symmetry() {
+    let r = BufReader::new(DATA);
+    let m = Matrix::from_reader(r, DataType::Real);
+    assert_eq!(m.detect_symmetry(0.0).unwrap().symmetry(), Symmetry::General);
+
+    let r = BufReader::new(SYMMETRIC);
+    let mut m = Matrix::from_reader(r, DataType::Real);
+    let report = m.detect_symmetry(0.0).unwrap();
+    assert!(report.structural && report.symmetric && !report.skew);
+
+    m.compress_symmetric().unwrap();
+    assert_eq!(m.symmetry(), Symmetry::Symmetric);
+    assert_eq!(m.nvals(), 4);
+    // Compressing again changes nothing, but another compact form is not relabeled
+    let mut again = m.clone();
+    again.compress_symmetric().unwrap();
+    assert_eq!(again.canonical_hash(None).unwrap(), m.canonical_hash(None).unwrap());
+    let mut skew = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n2 1 3\n", DataType::Real);
+    assert!(matches!(skew.compress_symmetric(), Err(Error::SymmetryMismatch { found: Symmetry::SkewSymmetric, .. })));
+    assert_eq!(skew.symmetry(), Symmetry::SkewSymmetric);
+
+    let s = m.to_string();
+    assert!(s.starts_with("%%MatrixMarket matrix coordinate real symmetric\n"));
+    let m2 = Matrix::from_reader(BufReader::new(s.as_bytes()), DataType::Real);
+    assert_eq!(m, m2);
+
+    // Only square matrices have a compact symmetric form
+    let mut m = Matrix::from_bytes(b"2 3 2\n1 1 1\n2 2 1\n", DataType::Real);
+    assert!(matches!(m.detect_symmetry(0.0), Err(Error::DimensionMismatch { .. })));
+    assert!(matches!(m.compress_symmetric(), Err(Error::DimensionMismatch { .. })));
+    assert!(matches!(m.compress_skew(), Err(Error::DimensionMismatch { .. })));
+    assert_eq!((m.symmetry(), m.nvals()), (Symmetry::General, 2));
+}
+
+#[test]
+fn merge() {
+    let r = BufReader::new(DATA);
+    let m = Matrix::from_reader(r, DataType::Real);
+
+    let mut m2 = m.clone();
+    assert!(matches!(m2.merge(&m, DuplicatePolicy::Error), Err(Error::DuplicateEntry { .. })));
+    assert_eq!(m, m2);
+
+    m2.merge(&m, DuplicatePolicy::Sum).unwrap();
+    assert_eq!(m2.nvals(), m.nvals());
+    m2.scale_rows(&[0.5; 9]);
+    let mut m3 = m.clone();
+    m3.sort_row_major();
+    assert_eq!(m2, m3);
+
+    let lower = m.lower_triangle(false);
+    let mut upper = m.upper_triangle(true);
+    upper.merge(&lower, DuplicatePolicy::Error).unwrap();
+    assert_eq!(upper, m3);
+
+    // Only entries that occur in both matrices are refused, duplicates within one of them are summed
+    let mut a = Matrix::from_bytes(b"2 2 2\n1 1 1\n1 1 2\n", DataType::Integer);
+    let b = Matrix::from_bytes(b"2 2 2\n2 2 4\n2 2 8\n", DataType::Integer);
+    a.merge(&b, DuplicatePolicy::Error).unwrap();
+    assert_eq!(a, Matrix::from_bytes(b"2 2 2\n1 1 3\n2 2 12\n", DataType::Integer));
+    let c = Matrix::from_bytes(b"2 2 1\n2 2 1\n", DataType::Integer);
+    assert!(matches!(a.merge(&c, DuplicatePolicy::Error), Err(Error::DuplicateEntry { row: 2, col: 2 })));
+
+    // Integer sums that overflow are an error, not a wrapped value
+    let max = Matrix::from_bytes(format!("2 2 1\n2 2 {}\n", Int::MAX).as_bytes(), DataType::Integer);
+    let before = a.clone();
+    assert!(matches!(a.merge(&max, DuplicatePolicy::Sum), Err(Error::SumOverflow { row: 2, col: 2 })));
+    assert_eq!(a, before);
+    let mut sum = Matrix::from_bytes(format!("2 2 2\n1 1 {}\n1 1 1\n", Int::MAX).as_bytes(), DataType::Integer);
+    assert!(matches!(sum.sum_duplicates(), Err(Error::SumOverflow { row: 1, col: 1 })));
+    assert_eq!(sum.nvals(), 2);
+    assert!(matches!(sum.norms(), Err(Error::SumOverflow { .. })));
+}
+
+#[test]
+fn sort_options() {
+    let bytes = b"2 2 5\n2 1 1\n1 1 -3\n2 1 -2\n1 1 2\n2 1 0\n";
+
+    let mut m = Matrix::from_bytes(bytes, DataType::Real);
+    m.sort_with(SortOrder::RowMajor, &SortOptions::new().stable(true)).unwrap();
+    assert_eq!(m.values::<Real>().unwrap(), [-3.0, 2.0, 1.0, -2.0, 0.0]);
+
+    let mut m = Matrix::from_bytes(bytes, DataType::Real);
+    m.sort_with(SortOrder::RowMajor, &SortOptions::new().tie_break(TieBreak::Value)).unwrap();
+    assert_eq!(m.values::<Real>().unwrap(), [-3.0, 2.0, -2.0, 0.0, 1.0]);
+
+    let mut m = Matrix::from_bytes(bytes, DataType::Real);
+    m.sort_with(SortOrder::ColMajor, &SortOptions::new().tie_break(TieBreak::AbsDesc)).unwrap();
+    assert_eq!(m.values::<Real>().unwrap(), [-3.0, 2.0, -2.0, 1.0, 0.0]);
+    assert!(m.is_col_major());
+}
+
+#[test]
+fn curve_orders() {
+    let bytes = b"4 4 6\n1 1 1\n4 4 2\n2 1 3\n1 3 4\n3 2 5\n1 2 6\n";
+
+    let mut m = Matrix::from_bytes(bytes, DataType::Real);
+    m.sort(SortOrder::Morton).unwrap();
+    assert_eq!(m.values::<Real>().unwrap(), [1.0, 6.0, 3.0, 4.0, 5.0, 2.0]);
+
+    // The Hilbert curve goes through the top left quadrant, then the bottom quadrants, and ends in the top right one
+    let mut m = Matrix::from_bytes(bytes, DataType::Real);
+    m.sort(SortOrder::Hilbert).unwrap();
+    assert_eq!(m.values::<Real>().unwrap(), [1.0, 6.0, 3.0, 5.0, 2.0, 4.0]);
+
+    // Tiles of two rows by three columns: the top left tile in row-major order comes first
+    let mut m = Matrix::from_bytes(bytes, DataType::Real);
+    m.sort_blocked(2, 3);
+    assert_eq!(m.values::<Real>().unwrap(), [1.0, 6.0, 4.0, 3.0, 5.0, 2.0]);
+}
+
+#[test]
+fn elementwise() {
+    let a = Matrix::from_bytes(b"2 2 3\n1 1 1\n2 2 2\n2 1 3\n", DataType::Real);
+    let b = Matrix::from_bytes(b"2 2 3\n2 2 2\n1 2 4\n1 1 5\n", DataType::Real);
+
+    let sum = a.add(&b).unwrap();
+    assert_eq!(sum.rows(), [1, 1, 2, 2]);
+    assert_eq!(sum.cols(), [1, 2, 1, 2]);
+    assert_eq!(sum.values::<Real>().unwrap(), [6.0, 4.0, 3.0, 4.0]);
+
+    // Entries that cancel out stay as explicit zeros
+    let diff = a.sub(&b).unwrap();
+    assert_eq!(diff.values::<Real>().unwrap(), [-4.0, -4.0, 3.0, 0.0]);
+
+    let product = a.hadamard(&b).unwrap();
+    assert_eq!(product.rows(), [1, 2]);
+    assert_eq!(product.cols(), [1, 2]);
+    assert_eq!(product.values::<Real>().unwrap(), [5.0, 4.0]);
+
+    // Integer products that overflow are an error, not a wrapped value
+    let max = Matrix::from_bytes(format!("2 2 2\n1 1 2\n2 2 {}\n", Int::MAX).as_bytes(), DataType::Integer);
+    let two = Matrix::from_bytes(b"2 2 2\n1 1 3\n2 2 2\n", DataType::Integer);
+    assert!(matches!(max.hadamard(&two), Err(Error::ProductOverflow { row: 2, col: 2 })));
+
+    let c = Matrix::from_bytes(b"3 2 0\n", DataType::Real);
+    assert!(a.add(&c).is_err());
+}
+
+#[test]
+fn concat() {
+    let r = BufReader::new(DATA);
+    let m = Matrix::from_reader(r, DataType::Real);
+    let ms = [m.clone(), m.clone(), m.clone()];
+
+    let h = Matrix::hstack(&ms).unwrap();
+    assert_eq!((h.nrows(), h.ncols(), h.nvals()), (9, 27, 3 * 49));
+
+    let v = Matrix::vstack(&ms).unwrap();
+    assert_eq!((v.nrows(), v.ncols(), v.nvals()), (27, 9, 3 * 49));
+
+    let d = Matrix::block_diag(&ms).unwrap();
+    assert_eq!((d.nrows(), d.ncols(), d.nvals()), (27, 27, 3 * 49));
+    assert_eq!(d.lower_triangle(false).nvals() + d.upper_triangle(true).nvals(), d.nvals());
+
+    assert!(matches!(Matrix::hstack(&[m.clone(), d]), Err(Error::DimensionMismatch { .. })));
+}
+
+#[test]
+fn generators() {
+    let m = generators::erdos_renyi(100, 50, 0.1, DataType::Complex, 42);
+    assert_eq!(m, generators::erdos_renyi(100, 50, 0.1, DataType::Complex, 42));
+    assert_ne!(m, generators::erdos_renyi(100, 50, 0.1, DataType::Complex, 43));
+
+    let m = generators::banded(10, 2, 1, DataType::Integer, 0);
+    assert_eq!(m.nvals(), 10 + 9 + 9 + 8);
+
+    let m = generators::diagonally_dominant(50, 0.2, 0);
+    assert!(m.detect_symmetry(0.0).unwrap().symmetric);
+
+    let mut m = generators::laplacian_3d(4, 3, 2);
+    assert_eq!(m.nrows(), 24);
+    assert_eq!(m.nvals(), 24 + 2 * (3 * 3 * 2 + 4 * 2 * 2 + 4 * 3));
+    assert!(m.detect_symmetry(0.0).unwrap().symmetric);
+
+    let m2 = m.clone();
+    m.shuffle(7);
+    assert_ne!(m, m2);
+    m.sort_row_major();
+    assert_eq!(m, m2);
+}
+
+#[test]
+fn spy() {
+    let r = BufReader::new(DATA);
+    let m = Matrix::from_reader(r, DataType::Real);
+
+    let spy = m.spy(3, 3);
+    assert_eq!(spy.counts().iter().sum::<u32>() as usize, m.nvals());
+    assert_eq!(m.spy(9, 9).counts().iter().filter(|&&c| c > 0).count(), m.nvals());
+
+    let ascii = m.spy_ascii(8);
+    assert_eq!(ascii.lines().count(), 4);
+    assert!(ascii.lines().all(|line| line.chars().count() == 8));
+
+    let r = BufReader::new(SYMMETRIC);
+    let mut m = Matrix::from_reader(r, DataType::Real);
+    let spy = m.spy(3, 3);
+    m.compress_symmetric().unwrap();
+    assert_eq!(m.spy(3, 3), spy);
+}
+
+const SYMMETRIC: Cursor<&'static str> = Cursor::new(r#"%%MatrixMarket matrix coordinate real general
+3 3 6
+1 1 4
+2 1 -1
+1 2 -1
+2 2 4
+3 2 -1
+2 3 -1
+"#);
+
 /// http://www.cise.ufl.edu/research/sparse/matrices/vanHeukelum/cage4
+#[test]
+fn entry_count() {
+    let fewer = "%%MatrixMarket matrix coordinate real general\n3 3 3\n1 1 1.0\n2 2 2.0\n\n\n";
+    let more = "%%MatrixMarket matrix coordinate real general\n3 3 1\n1 1 1.0\n2 2 2.0\n";
+    let strict = ReadOptions::new().count_policy(CountPolicy::Strict);
+
+    for data in [fewer, more] {
+        let m = Matrix::from_bytes(data.as_bytes(), DataType::Real);
+        assert_eq!(m.nvals(), 2);
+        assert_eq!(m.rows(), [1, 2]);
+        assert_eq!(Matrix::from_reader(BufReader::new(data.as_bytes()), DataType::Real), m);
+
+        let e = Matrix::from_bytes_with(data.as_bytes(), DataType::Real, &strict);
+        assert!(matches!(e, Err(Error::EntryCount { found: 2, .. })));
+        let e = Matrix::from_reader_with(BufReader::new(data.as_bytes()), DataType::Real, &strict);
+        assert!(matches!(e, Err(Error::EntryCount { found: 2, .. })));
+    }
+}
+
+#[test]
+fn messy_lines() {
+    let clean = "%%MatrixMarket matrix coordinate complex general\n3 3 2\n1 1 1.0 2.0\n3 2 3.0 4.0\n";
+    let crlf = clean.replace('\n', "\r\n");
+    let blank = "\n%%MatrixMarket matrix coordinate complex general\n\n3 3 2\n\n1 1 1.0 2.0\n   \n3 2 3.0 4.0\n\n";
+    let comments = "%%MatrixMarket matrix coordinate complex general\n% a\n3 3 2\n% b\n1 1 1.0 2.0\n  % c\n3 2 3.0 4.0\n%";
+
+    let expected = Matrix::from_bytes(clean.as_bytes(), DataType::Complex);
+    assert_eq!(expected.nvals(), 2);
+    for data in [crlf.as_str(), blank, comments] {
+        assert_eq!(Matrix::from_bytes(data.as_bytes(), DataType::Complex), expected);
+        assert_eq!(Matrix::from_reader(BufReader::new(data.as_bytes()), DataType::Complex), expected);
+    }
+}
+
+#[test]
+fn messy_whitespace() {
+    let clean = "%%MatrixMarket matrix coordinate real general\n3 3 3\n1 1 1.5\n3 2 -2\n2 3 1e3\n";
+    let spaces = "%%MatrixMarket   matrix coordinate real general\n  3  3   3\n 1 1 1.5\n3    2 -2  \n\t2\t3\t1e3\n";
+    let mixed = "%%MatrixMarket matrix coordinate real general\r\n3 \t3 3\r\n1\t 1 \t1.5\t\r\n  3 2  -2\r\n2 3 1e3 % trailing\r\n";
+
+    let expected = Matrix::from_bytes(clean.as_bytes(), DataType::Real);
+    for data in [spaces, mixed] {
+        for data_type in [DataType::Real, DataType::Bool] {
+            let expected = Matrix::from_bytes(clean.as_bytes(), data_type);
+            assert_eq!(Matrix::from_bytes(data.as_bytes(), data_type), expected);
+            assert_eq!(Matrix::from_reader(BufReader::new(data.as_bytes()), data_type), expected);
+        }
+    }
+    assert_eq!(expected.values::<Real>().unwrap(), [1.5, -2.0, 1e3]);
+}
+
+#[test]
+fn complex() {
+    let data = "%%MatrixMarket matrix coordinate complex general\n2 3 2\n1 2 3.0 4.0\n2 3 1.0 -1.0\n";
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Complex);
+
+    let mut h = m.clone();
+    h.conjugate_transpose();
+    assert_eq!((h.nrows(), h.ncols()), (3, 2));
+    assert_eq!((h.rows(), h.cols()), (&[2, 3][..], &[1, 2][..]));
+    assert_eq!(h.values::<Complex<Real>>().unwrap(), [Complex::new(3.0, -4.0), Complex::new(1.0, 1.0)]);
+
+    assert_eq!(m.abs().values::<Real>().unwrap()[0], 5.0);
+    assert_eq!(m.real_part().unwrap().values::<Real>().unwrap(), [3.0, 1.0]);
+    assert_eq!(m.imag_part().unwrap().values::<Real>().unwrap(), [4.0, -1.0]);
+    assert!(matches!(m.abs().real_part(), Err(Error::TypeMismatch { .. })));
+
+    let data = "%%MatrixMarket matrix coordinate complex hermitian\n2 2 2\n1 1 1.0 0.0\n2 1 3.0 4.0\n";
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Complex);
+    let mut h = m.clone();
+    h.conjugate_transpose();
+    assert_eq!(h, m);
+    assert_eq!(m.real_part().unwrap().symmetry(), Symmetry::Symmetric);
+    assert_eq!(m.imag_part().unwrap().symmetry(), Symmetry::SkewSymmetric);
+}
+
+#[test]
+fn streaming_writer() {
+    let m = generators::erdos_renyi(30, 20, 0.2, DataType::Real, 5);
+    let values = m.values::<Real>().unwrap();
+
+    let mut wtr = MatrixWriter::new(Cursor::new(Vec::new()), 30, 20, DataType::Real, Symmetry::General).unwrap();
+    for i in (0..m.nvals()).filter(|&i| values[i] > 0.5) {
+        wtr.write_entry(m.rows()[i], m.cols()[i], values[i]).unwrap();
+    }
+    assert!(wtr.write_entry(1, 1, 1 as Int).is_err());
+    let bytes = wtr.finish().unwrap().into_inner();
+
+    let strict = ReadOptions::new().count_policy(CountPolicy::Strict);
+    let filtered = Matrix::from_bytes_with(&bytes, DataType::Real, &strict).unwrap();
+    assert_eq!(filtered.nvals(), values.iter().filter(|&&x| x > 0.5).count());
+    assert_eq!((filtered.nrows(), filtered.ncols()), (30, 20));
+}
+
+#[test]
+fn canonical_hash() {
+    let m = generators::erdos_renyi(40, 40, 0.1, DataType::Real, 6);
+    let mut shuffled = m.clone();
+    shuffled.shuffle(6);
+    assert_eq!(shuffled.canonical_hash(None).unwrap(), m.canonical_hash(None).unwrap());
+
+    // Splitting an entry into two duplicates gives the same canonical form
+    let split = Matrix::from_bytes(b"2 2 3\n1 1 0.5\n2 2 2.0\n1 1 0.5\n", DataType::Real);
+    let whole = Matrix::from_bytes(b"2 2 2\n2 2 2.0\n1 1 1.0\n", DataType::Real);
+    assert_eq!(split.canonical_hash(None).unwrap(), whole.canonical_hash(None).unwrap());
+
+    let perturbed = Matrix::from_bytes(b"2 2 2\n2 2 2.0001\n1 1 1.0\n", DataType::Real);
+    assert_ne!(perturbed.canonical_hash(None).unwrap(), whole.canonical_hash(None).unwrap());
+    assert_eq!(perturbed.canonical_hash(Some(1e-2)).unwrap(), whole.canonical_hash(Some(1e-2)).unwrap());
+
+    // Summing these duplicates in a different order gives a different float
+    let a = Matrix::from_bytes(b"1 1 3\n1 1 1e20\n1 1 -1e20\n1 1 1.0\n", DataType::Real);
+    let b = Matrix::from_bytes(b"1 1 3\n1 1 1.0\n1 1 1e20\n1 1 -1e20\n", DataType::Real);
+    let c = Matrix::from_bytes(b"1 1 3\n1 1 -1e20\n1 1 1.0\n1 1 1e20\n", DataType::Real);
+    assert_eq!(a.canonical_hash(None).unwrap(), b.canonical_hash(None).unwrap());
+    assert_eq!(a.canonical_hash(None).unwrap(), c.canonical_hash(None).unwrap());
+    let mut shuffled = Matrix::from_bytes(b"2 2 6\n1 1 1e20\n2 1 0.1\n1 1 -1e20\n2 1 0.2\n1 1 1.0\n2 1 0.3\n", DataType::Real);
+    let hash = shuffled.canonical_hash(None).unwrap();
+    for seed in 0..8 {
+        shuffled.shuffle(seed);
+        assert_eq!(shuffled.canonical_hash(None).unwrap(), hash);
+    }
+}
+
+#[test]
+fn reorder() {
+    let m = Matrix::from_bytes(b"4 4 5\n1 1 1\n2 1 1\n4 1 1\n3 3 1\n4 4 1\n", DataType::Real);
+    assert_eq!(m.bandwidth(), 3);
+    assert_eq!(m.profile(), 1 + 3);
+    assert!((m.rms_wavefront() - 4.5f64.sqrt()).abs() < 1e-12);
+
+    // Scramble a grid, which reverse Cuthill-McKee should bring back to a narrow band
+    let mut m = generators::laplacian_2d(10, 10);
+    let scrambled: Vec<usize> = (0..100).map(|i| i * 37 % 100).collect();
+    m.permute_symmetric(&scrambled);
+    let before = (m.bandwidth(), m.profile());
+    let permutation = m.reorder(Reordering::Rcm).unwrap();
+    assert_eq!(permutation.len(), 100);
+    assert!(m.bandwidth() <= 11 && m.bandwidth() < before.0);
+    assert!(m.profile() < before.1);
+
+    // Compact symmetric matrices stay in the lower triangle
+    let mut m = Matrix::from_reader(BufReader::new(SYMMETRIC), DataType::Real);
+    m.compress_symmetric().unwrap();
+    m.reorder(Reordering::Rcm).unwrap();
+    assert!(m.rows().iter().zip(m.cols()).all(|(r, c)| r >= c));
+
+    assert!(generators::erdos_renyi(3, 4, 0.5, DataType::Real, 1).reorder(Reordering::Rcm).is_err());
+
+    // Degrees in the example are 2, 1, 0, 1
+    let m = Matrix::from_bytes(b"4 4 5\n1 1 1\n2 1 1\n4 1 1\n3 3 1\n4 4 1\n", DataType::Real);
+    assert_eq!(m.reordering(Reordering::DegreeAscending).unwrap(), [2, 1, 3, 0]);
+    assert_eq!(m.reordering(Reordering::DegreeDescending).unwrap(), [0, 1, 3, 2]);
+
+    let random = m.reordering(Reordering::Random { seed: 7 }).unwrap();
+    assert_eq!(random, m.reordering(Reordering::Random { seed: 7 }).unwrap());
+    let mut sorted = random.clone();
+    sorted.sort();
+    assert_eq!(sorted, [0, 1, 2, 3]);
+}
+
+#[test]
+fn components() {
+    // Two blocks, {1, 3} and {2, 4}, and an empty fifth row and column
+    let m = Matrix::from_bytes(b"5 5 5\n1 1 1\n3 1 2\n2 4 3\n4 4 4\n2 2 5\n", DataType::Real);
+    let components = m.connected_components();
+    assert_eq!(components.count, 3);
+    assert_eq!(components.rows, [0, 1, 0, 1, 2]);
+    assert_eq!(components.sizes(), [(2, 2), (2, 2), (1, 1)]);
+    assert_eq!(m.stats().components, [(2, 2), (2, 2), (1, 1)]);
+
+    let parts = m.split_components();
+    assert_eq!(parts.len(), 3);
+    assert_eq!((parts[1].nrows(), parts[1].ncols()), (2, 2));
+    assert_eq!(parts[1].rows(), [1, 2, 1]);
+    assert_eq!(parts[1].cols(), [2, 2, 1]);
+    assert_eq!(parts[2].nvals(), 0);
+
+    // Rectangular matrices use separate vertices for rows and columns
+    let m = Matrix::from_bytes(b"2 3 2\n1 3 1\n2 1 1\n", DataType::Real);
+    let components = m.connected_components();
+    assert_eq!(components.count, 3);
+    assert_eq!(components.cols, [1, 2, 0]);
+}
+
+#[test]
+fn btf() {
+    // Upper triangular after swapping the first two rows, with three blocks of size one
+    let mut m = Matrix::from_bytes(b"3 3 5\n2 1 1\n2 2 1\n1 2 1\n1 3 1\n3 3 1\n", DataType::Real);
+    let btf = m.btf();
+    assert_eq!(btf.structural_rank, 3);
+    assert_eq!(btf.coarse, [(0, 0), (3, 3), (0, 0)]);
+    assert_eq!(btf.blocks, [1, 1, 1]);
+    m.permute(&btf.row_permutation, &btf.col_permutation).unwrap();
+    assert!(m.rows().iter().zip(m.cols()).all(|(r, c)| r <= c));
+
+    // Rows 1 and 2 only have entries in column 1, so column 2 or 3 stays unmatched
+    let m = Matrix::from_bytes(b"3 3 4\n1 1 1\n2 1 1\n3 2 1\n3 3 1\n", DataType::Real);
+    assert_eq!(m.maximum_matching().iter().flatten().count(), 2);
+    let btf = m.btf();
+    assert_eq!(btf.structural_rank, 2);
+    assert_eq!(btf.coarse, [(1, 2), (0, 0), (2, 1)]);
+    assert_eq!(btf.row_permutation, [2, 0, 1]);
+    assert_eq!(btf.col_permutation, [1, 2, 0]);
+
+    // A cycle through all rows is a single irreducible block
+    let m = Matrix::from_bytes(b"3 3 6\n1 1 1\n2 2 1\n3 3 1\n1 2 1\n2 3 1\n3 1 1\n", DataType::Real);
+    assert_eq!(m.btf().blocks, [3]);
+}
+
+#[test]
+fn spmv() {
+    let m = Matrix::from_bytes(b"2 3 3\n1 1 2\n2 3 -1\n1 2 1\n", DataType::Real);
+    assert_eq!(m.spmv(&[1.0, 2.0, 3.0]), [4.0, -3.0]);
+
+    // Compact forms include their mirrored entries
+    let mut m = Matrix::from_reader(BufReader::new(SYMMETRIC), DataType::Real);
+    let x: Vec<f64> = (1..=m.ncols()).map(|i| i as f64).collect();
+    let expected = m.spmv(&x);
+    m.compress_symmetric().unwrap();
+    assert_eq!(m.spmv(&x), expected);
+
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate complex hermitian\n2 2 2\n1 1 1 0\n2 1 0 1\n", DataType::Complex);
+    let y = m.spmv_complex(&[Complex::new(1.0, 0.0), Complex::new(0.0, 1.0)]);
+    assert_eq!(y, [Complex::new(2.0, 0.0), Complex::new(0.0, 1.0)]);
+}
+
+#[test]
+fn bsr() {
+    // Two by two blocks, with one missing entry in the bottom right block
+    let m = Matrix::from_bytes(b"4 4 11\n1 1 1\n1 2 2\n2 1 3\n2 2 4\n3 1 5\n3 2 6\n4 1 7\n4 2 8\n3 3 9\n4 4 10\n4 3 11\n", DataType::Real);
+    assert_eq!(m.block_fill(2), 11.0 / 12.0);
+    assert_eq!(m.detect_block_size(4, 1.0), 1);
+    assert_eq!(m.detect_block_size(4, 0.9), 2);
+
+    let bsr = m.to_bsr(2).unwrap();
+    assert_eq!(bsr.row_offsets, [0, 1, 3]);
+    assert_eq!(bsr.block_cols, [0, 0, 1]);
+    assert_eq!(bsr.values[8..], [9.0, 0.0, 11.0, 10.0]);
+
+    let mut bytes = Vec::new();
+    bsr.write(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 4 + 8 * (4 + 3 + 3) + 8 * 12);
+    assert_eq!(&bytes[..4], b"BSR1");
+}
+
+#[test]
+fn ell() {
+    // Rows of length 1, 3, 0, and 2
+    let m = Matrix::from_bytes(b"4 4 6\n2 4 4\n1 1 1\n2 1 2\n4 2 6\n2 2 3\n4 4 5\n", DataType::Real);
+
+    let ell = m.to_ell().unwrap();
+    assert_eq!(ell.width, 3);
+    assert_eq!(ell.cols, [0, 0, 0, 1, 0, 1, 0, 3, 0, 3, 0, 0]);
+    assert_eq!(ell.values, [1.0, 2.0, 0.0, 6.0, 0.0, 3.0, 0.0, 5.0, 0.0, 4.0, 0.0, 0.0]);
+
+    let sell = m.to_sell(2, 4).unwrap();
+    assert_eq!(sell.permutation, [1, 3, 0, 2]);
+    assert_eq!(sell.chunk_offsets, [0, 6, 8]);
+    assert_eq!(sell.cols[..6], [0, 1, 1, 3, 3, 0]);
+    assert_eq!(sell.values[6..], [1.0, 0.0]);
+
+    let mut bytes = Vec::new();
+    sell.write(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 4 + 8 * (6 + 4 + 3 + 8) + 8 * 8);
+}
+
+#[test]
+fn graphblas() {
+    let m = Matrix::from_bytes(b"2 3 3\n2 3 1\n1 2 2\n2 3 3\n", DataType::Integer);
+    let u64s = |bytes: &[u8]| bytes.chunks(8).map(|b| u64::from_le_bytes(b.try_into().unwrap())).collect::<Vec<_>>();
+
+    let mut bytes = Vec::new();
+    m.write_graphblas(&mut bytes, GraphBlasFormat::Csr).unwrap();
+    assert_eq!(&bytes[..4], b"GrBI");
+    assert_eq!(u64s(&bytes[4..]), [0, 1, 2, 3, 3, 2, 2, 0, 1, 2, 1, 2, 2, 4]);
+
+    let mut bytes = Vec::new();
+    m.write_graphblas(&mut bytes, GraphBlasFormat::Csc).unwrap();
+    assert_eq!(u64s(&bytes[4..]), [1, 1, 2, 3, 4, 2, 2, 0, 0, 1, 2, 0, 1, 2, 4]);
+}
+
+#[test]
+fn parallel_config() {
+    let threads = ParallelConfig::new().threads(2).install(rayon::current_num_threads).unwrap();
+    assert_eq!(threads, 2);
+
+    let threads = ParallelConfig::new().install(rayon::current_num_threads).unwrap();
+    assert_eq!(threads, rayon::current_num_threads());
+}
+
+#[test]
+fn tensor() {
+    let mut t = Tensor::from_bytes(b"# comment\n2 1 3 1.5\n1 2 1 2.5\n1 1 2 -1\n\n2 1 1 4\n", DataType::Real).unwrap();
+    assert_eq!((t.ndims(), t.dims(), t.nvals()), (3, &[2, 2, 3][..], 4));
+
+    t.sort(&[0, 1, 2]).unwrap();
+    assert!(t.is_sorted(&[0, 1, 2]));
+    assert_eq!(t.to_string(), "1 1 2 -1\n1 2 1 2.5\n2 1 1 4\n2 1 3 1.5\n");
+
+    t.sort(&[2, 0, 1]).unwrap();
+    assert_eq!(t.indices(2), [1, 1, 2, 3]);
+    assert_eq!(t.indices(0), [1, 2, 1, 2]);
+    assert_eq!(Tensor::from_bytes(t.to_string().as_bytes(), DataType::Real).unwrap(), t);
+
+    let pattern = Tensor::from_bytes(b"1 2 3 4\n", DataType::Bool).unwrap();
+    assert_eq!(pattern.dims(), [1, 2, 3, 4]);
+
+    // Modes that are out of range, repeated, or missing
+    for modes in [&[0, 1, 3][..], &[0, 0, 1], &[1, 0]] {
+        assert!(matches!(t.sort(modes), Err(Error::InvalidModes { ndims: 3, .. })));
+    }
+
+    // Entries with a different number of fields than the first, or fields that are not numbers
+    let ragged = Tensor::from_bytes(b"% comment\n1 1 1 1.5\n2 2 2.5\n", DataType::Real);
+    assert!(matches!(ragged, Err(Error::RaggedEntry { line: 3, expected: 4, found: 3 })));
+    let invalid = Tensor::from_bytes(b"1 1 1 1.5\n2 x 2 2.5\n", DataType::Real);
+    assert!(matches!(invalid, Err(Error::InvalidField { line: 2, .. })));
+    let overflow = Tensor::from_bytes(b"1 1 99999999999999999999\n", DataType::Integer);
+    assert!(matches!(overflow, Err(Error::IntegerOverflow { line: 1, .. })));
+}
+
+#[test]
+fn graph() {
+    let dimacs = b"c road network\np sp 3 3\na 1 2 7\na 2 3 4\na 3 1 -2\n";
+    let m = Matrix::from_graph(dimacs, GraphFormat::Dimacs, DataType::Integer).unwrap();
+    assert_eq!((m.nrows(), m.nvals(), m.cols()), (3, 3, &[2, 3, 1][..]));
+
+    let mut bytes = Vec::new();
+    m.write_graph(&mut bytes, GraphFormat::Dimacs).unwrap();
+    assert_eq!(Matrix::from_graph(&bytes, GraphFormat::Dimacs, DataType::Integer).unwrap(), m);
+    assert!(matches!(Matrix::from_graph(b"p sp 3 4\na 1 2 7\n", GraphFormat::Dimacs, DataType::Integer), Err(Error::EntryCount { .. })));
+
+    let snap = b"# Directed graph\n# FromNodeId\tToNodeId\n0\t4\n4\t1\n";
+    let m = Matrix::from_graph(snap, GraphFormat::Snap, DataType::Bool).unwrap();
+    assert_eq!((m.nrows(), m.rows(), m.cols()), (5, &[1, 5][..], &[5, 2][..]));
+
+    let mut bytes = Vec::new();
+    Matrix::from_bytes(b"%%MatrixMarket matrix coordinate pattern symmetric\n3 3 2\n2 1\n3 3\n", DataType::Bool)
+        .write_graph(&mut bytes, GraphFormat::Snap).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), "# Nodes: 3 Edges: 3\n# FromNodeId\tToNodeId\n1\t0\n0\t1\n2\t2\n");
+}
+
+#[test]
+fn value_profile() {
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n3 3 5\n1 1 4\n2 1 -1\n2 2 0\n3 2 inf\n3 3 NaN\n", DataType::Real);
+    let profile = m.value_profile(4);
+    assert_eq!((profile.nan, profile.infinite, profile.explicit_zeros), (1, 1, 1));
+    assert_eq!((profile.values.min, profile.values.max, &profile.values.counts[..]), (-1.0, 4.0, &[2, 0, 0, 1][..]));
+    assert_eq!(profile.abs_values.edges().len(), 5);
+    assert!((profile.abs_values.edges()[2] - 2.0).abs() < 1e-12);
+    assert_eq!(profile.diagonal_abs, Some((0.0, 4.0)));
+    assert_eq!(&profile.dominance[..2], [4.0, 0.0]);
+    assert_eq!(profile.dominant_rows(), 1);
+    assert!(profile.to_json().starts_with("{\"nan\":1,\"infinite\":1,\"explicit_zeros\":1,"));
+
+    let empty = generators::erdos_renyi(3, 4, 0.0, DataType::Real, 1).value_profile(3);
+    assert_eq!((empty.values.total(), empty.diagonal_abs), (0, None));
+    assert!(empty.values.edges().is_empty());
+}
+
+#[test]
+fn diagonal_checks() {
+    let mut m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real general\n3 4 5\n1 1 2\n1 1 -2\n2 1 5\n3 3 0\n3 4 1\n", DataType::Real);
+    assert_eq!(m.missing_diagonal(), [2]);
+    assert_eq!(m.zero_diagonal(), [1, 3]);
+
+    assert_eq!(m.ensure_diagonal(7.0).unwrap(), 1);
+    assert!(m.missing_diagonal().is_empty());
+    assert_eq!((m.nvals(), m.rows()[5], m.cols()[5]), (6, 2, 2));
+    assert_eq!(m.ensure_diagonal(7.0).unwrap(), 0);
+
+    // The diagonal of a skew-symmetric matrix can only get explicit zeros, which are written and read back
+    let mut m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n2 1 3\n", DataType::Real);
+    assert!(matches!(m.ensure_diagonal(1.0), Err(Error::SymmetryViolation { row: 1, col: 1, symmetry: Symmetry::SkewSymmetric })));
+    assert_eq!(m.nvals(), 1);
+    assert_eq!(m.ensure_diagonal(0.0).unwrap(), 2);
+    assert_eq!(Matrix::try_parse_bytes(m.to_string().as_bytes(), DataType::Real, &ReadOptions::default()).unwrap(), m);
+}
+
+#[test]
+fn nonfinite() {
+    let data = b"%%MatrixMarket matrix coordinate real general\n% exported\n3 3 3\n1 1 1.5\n2 2 NaN\n3 3 -Infinity\n";
+    let read = |policy| Matrix::from_bytes_with(data, DataType::Real, &ReadOptions::new().nonfinite_policy(policy));
+    let from_reader = |policy| Matrix::from_reader_with(BufReader::new(&data[..]), DataType::Real, &ReadOptions::new().nonfinite_policy(policy));
+
+    assert_eq!(read(NonFinitePolicy::Keep).unwrap().nvals(), 3);
+    assert!(matches!(read(NonFinitePolicy::Error), Err(Error::NonFinite { line: 5 })));
+    assert!(matches!(from_reader(NonFinitePolicy::Error), Err(Error::NonFinite { line: 5 })));
+    assert_eq!(read(NonFinitePolicy::Drop).unwrap().rows(), [1]);
+    assert_eq!(read(NonFinitePolicy::Zero).unwrap().to_string(), "%%MatrixMarket matrix coordinate real general\n3 3 3\n1 1 1.5\n2 2 0\n3 3 0\n");
+}
+
+#[test]
+fn lenient_numbers() {
+    let data = b"%%MatrixMarket matrix coordinate complex general\n2 2 3\n1 1 1,5 +2\n1 2 1.0D+03 -2.5d-1\n2 2 1.0-3 4.\n";
+    let options = ReadOptions::new().lenient_numbers(true);
+    let expected = "%%MatrixMarket matrix coordinate complex general\n2 2 3\n1 1 1.5 2\n1 2 1000 -0.25\n2 2 0.001 4\n";
+    assert_eq!(Matrix::from_bytes_with(data, DataType::Complex, &options).unwrap().to_string(), expected);
+    assert_eq!(Matrix::from_reader_with(BufReader::new(&data[..]), DataType::Complex, &options).unwrap().to_string(), expected);
+}
+
 const DATA: Cursor<&'static str> = Cursor::new(r#"9 9 49
     1 1 .75
     2 1 .075027667114587
@@ -68,3 +734,1494 @@ const DATA: Cursor<&'static str> = Cursor::new(r#"9 9 49
     8 9 .25
     9 9 .166666666666667
 "#);
+
+#[test]
+fn memory_estimate() {
+    let data = "%%MatrixMarket matrix coordinate real general\n% comment\n 4 5  3\n1 1 1.0\n2 2 2.0\n3 3 3.0\n";
+    assert_eq!(Matrix::read_size(data.as_bytes()).unwrap(), Some((4, 5, 3)));
+    assert_eq!(Matrix::read_size("%%MatrixMarket matrix coordinate real general\n".as_bytes()).unwrap(), None);
+    assert!(Matrix::read_size("x y z\n".as_bytes()).is_err());
+
+    let index = 2 * std::mem::size_of::<usize>();
+    assert_eq!(Matrix::estimated_bytes(10, DataType::Bool), 10 * index);
+    assert_eq!(Matrix::estimated_bytes(10, DataType::Complex), 10 * (index + 2 * std::mem::size_of::<Real>()));
+    for data_type in [DataType::Real, DataType::Complex, DataType::Integer, DataType::Bool] {
+        let permute = Matrix::estimated_sort_bytes(1000, data_type, SortStrategy::Permute);
+        let zip = Matrix::estimated_sort_bytes(1000, data_type, SortStrategy::Zip);
+        assert!(Matrix::estimated_bytes(1000, data_type) < permute && permute < zip);
+    }
+}
+
+#[test]
+fn large_size_line() {
+    // Sizes beyond 32 bits, with a leading `+` and unusual whitespace between the fields
+    let data = b"%%MatrixMarket matrix coordinate real general\n5000000000\t 6000000000 \x0b+8589934592\r\n5000000000 1 1.5\n";
+    assert_eq!(Matrix::read_size(&data[..]).unwrap(), Some((5_000_000_000, 6_000_000_000, 1 << 33)));
+    let m = Matrix::from_bytes(data, DataType::Real);
+    assert_eq!((m.nrows(), m.ncols(), m.nvals()), (5_000_000_000, 6_000_000_000, 1));
+    assert_eq!(m.rows(), [5_000_000_000]);
+
+    // Sizes that do not fit in 64 bits are not sizes, and entries that do not fit in memory are refused before allocating them
+    assert!(matches!(Matrix::read_size(&b"1 1 18446744073709551616\n"[..]), Err(Error::SizeLine { line: 1 })));
+    let err = Matrix::from_reader_with(BufReader::new(&b"2 2 4611686018427387904\n1 1 1\n"[..]), DataType::Real, &ReadOptions::default()).unwrap_err();
+    assert_eq!(err.to_string(), "a 2x2 matrix with 4611686018427387904 entries does not fit in memory");
+
+    assert_eq!(Matrix::estimated_bytes(usize::MAX, DataType::Real), usize::MAX);
+    assert!(Matrix::check_addressable(10, 10, 1 << 40, DataType::Real, Some(SortStrategy::External)).is_ok());
+    assert!(Matrix::check_addressable(usize::MAX, 10, 10, DataType::Real, Some(SortStrategy::Counting)).is_err());
+}
+
+#[test]
+fn round_to_f32() {
+    assert_eq!(Precision::default() == Precision::F64, cfg!(feature = "x64"));
+
+    // Rounding to `f32` gives the same values with or without the `x64` feature
+    let data = b"2 2 2\n1 1 0.1\n2 2 1e300\n";
+    let options = ReadOptions::new().round_to_f32(true);
+    let m = Matrix::from_bytes_with(data, DataType::Real, &options).unwrap();
+    assert_eq!(m.values::<Real>().unwrap(), [0.1f32 as Real, Real::INFINITY]);
+    let err = Matrix::from_reader_with(BufReader::new(&data[..]), DataType::Real, &options.nonfinite_policy(NonFinitePolicy::Error)).unwrap_err();
+    assert!(matches!(err, Error::NonFinite { line: 3 }));
+}
+
+#[test]
+fn int_width() {
+    // Values that overflow are reported with their line, and reading them at 32 bits gives the same error with or without the `x64` feature
+    let data = b"%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 1 7\n2 2 -3000000000\n";
+    let options = ReadOptions::new().int_width(IntWidth::I32);
+    let err = Matrix::from_bytes_with(data, DataType::Integer, &options).unwrap_err();
+    assert_eq!(err.to_string(), "integer value on line 4 does not fit in 32 bits");
+    let err = Matrix::from_reader_with(BufReader::new(&data[..]), DataType::Integer, &options).unwrap_err();
+    assert!(matches!(err, Error::IntegerOverflow { line: 4, bits: 32 }));
+
+    let data = b"1 1 1\n1 1 99999999999999999999\n";
+    let err = Matrix::from_bytes_with(data, DataType::Integer, &ReadOptions::default()).unwrap_err();
+    assert!(matches!(err, Error::IntegerOverflow { line: 2, bits: 64 }) || !cfg!(feature = "x64"));
+}
+
+#[test]
+fn sort_strategies() {
+    // Entries with duplicate coordinates, whose values record their original order
+    let mut data = "%%MatrixMarket matrix coordinate integer general\n300 200 4000\n".to_string();
+    for i in 0..4000 {
+        data += &format!("{} {} {i}\n", i * 7919 % 300 + 1, i * 104729 % 50 + 1);
+    }
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Integer);
+    for order in [SortOrder::RowMajor, SortOrder::ColMajor] {
+        let mut expected = m.clone();
+        expected.sort_with(order, &SortOptions::new().stable(true).strategy(SortStrategy::Permute)).unwrap();
+
+        for strategy in [SortStrategy::Auto, SortStrategy::Zip, SortStrategy::Counting, SortStrategy::External] {
+            let mut sorted = m.clone();
+            sorted.sort_with(order, &SortOptions::new().stable(true).strategy(strategy).chunk_size(999)).unwrap();
+            assert_eq!(sorted, expected, "{strategy:?}");
+
+            let mut sorted = m.clone();
+            sorted.sort_with(order, &SortOptions::new().strategy(strategy).chunk_size(999)).unwrap();
+            assert!(if order == SortOrder::RowMajor { sorted.is_row_major() } else { sorted.is_col_major() });
+            assert_eq!(sorted.nvals(), m.nvals());
+        }
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn external_sort_temp_dir() {
+    // Links planted where the runs could go are neither followed nor replaced
+    let victim = std::env::temp_dir().join(format!("mmt-test-victim-{}", std::process::id()));
+    std::fs::create_dir_all(&victim).unwrap();
+    std::fs::write(victim.join("0.run"), "keep").unwrap();
+    let links: Vec<_> = (0..64).map(|k| std::env::temp_dir().join(format!("mmt-{}-{k}", std::process::id()))).collect();
+    links.iter().for_each(|link| std::os::unix::fs::symlink(&victim, link).unwrap());
+
+    let mut m = generators::erdos_renyi(50, 50, 0.2, DataType::Real, 1);
+    m.sort_with(SortOrder::ColMajor, &SortOptions::new().strategy(SortStrategy::External).chunk_size(100)).unwrap();
+    assert!(m.is_col_major());
+    assert_eq!(std::fs::read_to_string(victim.join("0.run")).unwrap(), "keep");
+    assert_eq!(std::fs::read_dir(&victim).unwrap().count(), 1);
+
+    links.iter().for_each(|link| std::fs::remove_file(link).unwrap());
+    std::fs::remove_dir_all(&victim).unwrap();
+}
+
+#[test]
+fn pattern() {
+    let data = "%%MatrixMarket matrix coordinate real symmetric\n% comment\n4 4 4\n1 1 1.0\n\t3  2 -2.0\r\n\n% c\n4 1 3.0\n4 4 4.0";
+    let pattern = Pattern::from_bytes_with(data.as_bytes(), &ReadOptions::default()).unwrap();
+    assert_eq!((pattern.nrows(), pattern.ncols(), pattern.nvals()), (4, 4, 4));
+    assert_eq!((pattern.rows(), pattern.cols()), (&[1, 3, 4, 4][..], &[1, 2, 1, 4][..]));
+    assert_eq!(Matrix::from(pattern), Matrix::from_bytes(data.as_bytes(), DataType::Bool));
+
+    // Several chunks of lines
+    let mut data = "%%MatrixMarket matrix coordinate pattern general\n1000 1000 300000\n".to_string();
+    for i in 0..300000usize {
+        data += &format!("{} {}\n", i * 7919 % 1000 + 1, i % 1000 + 1);
+    }
+    let pattern = Pattern::from_bytes_with(data.as_bytes(), &ReadOptions::default()).unwrap();
+    assert_eq!(Matrix::from(pattern), Matrix::from_bytes(data.as_bytes(), DataType::Bool));
+
+    let overflow = "%%MatrixMarket matrix coordinate pattern general\n5000000000 1 1\n5000000000 1\n";
+    assert!(Pattern::from_bytes_with(overflow.as_bytes(), &ReadOptions::default()).is_err());
+    let strict = ReadOptions::new().count_policy(CountPolicy::Strict);
+    assert!(Pattern::from_bytes_with("%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 1\n".as_bytes(), &strict).is_err());
+}
+
+#[test]
+fn index_widths() {
+    let indices: Vec<usize> = (0..16).map(|k| 10usize.pow(k) + k as usize).chain([9, 99_999_999, 123_456_789_012_345]).collect();
+    let mut data = format!("%%MatrixMarket matrix coordinate pattern general\n{0} {0} {1}\n", usize::MAX / 2, indices.len());
+    for &i in &indices {
+        data += &format!("{i} 0{i}\n");
+    }
+
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Bool);
+    assert_eq!(m.rows(), indices);
+    assert_eq!(m.cols(), indices);
+    assert_eq!(Matrix::from_reader(BufReader::new(data.as_bytes()), DataType::Bool), m);
+}
+
+#[test]
+fn chunked() {
+    // Several chunks of lines, with comments and blank lines between the entries
+    let mut data = "%%MatrixMarket matrix coordinate complex general\n% comment\n500 400 100000\n".to_string();
+    for i in 0..100000usize {
+        data += &format!("{} {} {i}.5 -{i}\n", i * 7919 % 500 + 1, i % 400 + 1);
+        if i % 1000 == 0 {
+            data += "% comment\n\n";
+        }
+    }
+
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Complex);
+    assert_eq!(m.nvals(), 100000);
+    assert_eq!(m, Matrix::from_reader(BufReader::new(data.as_bytes()), DataType::Complex));
+}
+
+#[test]
+fn partition_rows() {
+    let m = Matrix::from_bytes(b"5 3 5\n5 1 1\n1 2 2\n3 3 3\n2 1 4\n4 2 5\n", DataType::Real);
+    let blocks = m.row_blocks(2);
+    assert_eq!(blocks, [0..2, 2..5]);
+
+    let parts = m.partition_rows(&blocks, false).unwrap();
+    assert_eq!((parts[1].nrows(), parts[1].ncols()), (5, 3));
+    assert_eq!(parts[1].rows(), [5, 3, 4]);
+
+    let parts = m.partition_rows(&blocks, true).unwrap();
+    assert_eq!(parts[0].rows(), [1, 2]);
+    assert_eq!((parts[1].nrows(), parts[1].ncols()), (3, 3));
+    assert_eq!(parts[1].rows(), [3, 1, 2]);
+    assert_eq!(parts[1].cols(), [1, 3, 2]);
+
+    let mut stacked = Matrix::vstack(&parts).unwrap();
+    let mut m = m;
+    stacked.sort_row_major();
+    m.sort_row_major();
+    assert_eq!(stacked, m);
+}
+
+#[test]
+fn partition_balanced() {
+    // Row 1 holds half of the entries
+    let m = Matrix::from_bytes(b"4 4 8\n1 1 1\n1 2 1\n1 3 1\n1 4 1\n2 2 1\n3 3 1\n4 4 1\n4 1 1\n", DataType::Real);
+    let blocks = m.balanced_row_blocks(2);
+    assert_eq!(blocks, [0..1, 1..4]);
+    let counts: Vec<_> = m.partition_rows(&blocks, false).unwrap().iter().map(|part| part.nvals()).collect();
+    assert_eq!(counts, [4, 4]);
+    assert_eq!(Imbalance::new(&counts).ratio, 1.0);
+
+    // A grid of two by two blocks, ordered by block of rows
+    let parts = m.partition_blocks(&m.row_blocks(2), &m.col_blocks(2), true).unwrap();
+    let counts: Vec<_> = parts.iter().map(|part| part.nvals()).collect();
+    assert_eq!(counts, [3, 2, 1, 2]);
+    assert_eq!((parts[3].nrows(), parts[3].ncols()), (2, 2));
+    assert_eq!(parts[3].rows(), [1, 2]);
+    assert_eq!(parts[3].cols(), [1, 2]);
+    assert_eq!(Imbalance::new(&counts), Imbalance { min: 1, max: 3, mean: 2.0, ratio: 1.5 });
+}
+
+#[test]
+fn permutation() {
+    let p = Permutation::new(vec![2, 0, 1]).unwrap();
+    assert!(p.then(&p.inverse()).unwrap().is_identity());
+    assert!(matches!(Permutation::new(vec![0, 0, 1]), Err(Error::InvalidPermutation { index: 0, len: 3 })));
+
+    // Applying two permutations one by one is the same as applying their composition
+    let m = Matrix::from_bytes(b"3 3 4\n1 1 1\n2 1 2\n3 2 3\n3 3 4\n", DataType::Real);
+    let q = Permutation::new(vec![1, 2, 0]).unwrap();
+    let mut a = m.clone();
+    a.permute_symmetric(p.indices());
+    a.permute_symmetric(q.indices());
+    let mut b = m;
+    b.permute_symmetric(p.then(&q).unwrap().indices());
+    assert_eq!(a, b);
+
+    for format in [PermutationFormat::Text, PermutationFormat::Binary] {
+        let mut bytes = Vec::new();
+        p.save(&mut bytes, format).unwrap();
+        assert_eq!(Permutation::load(bytes.as_slice(), format).unwrap(), p);
+    }
+    assert_eq!(Permutation::load(&b"% comment\n3\n1\n2\n"[..], PermutationFormat::Text).unwrap(), p);
+    assert!(Permutation::load(&b"3\n0\n"[..], PermutationFormat::Text).is_err());
+}
+
+#[test]
+fn vector() {
+    let data = b"%%MatrixMarket matrix array real general\n% comment\n3 1\n1.5\n-2\n3\n";
+    let mut v = Vector::from_bytes(data, DataType::Real).unwrap();
+    assert_eq!(v.len(), 3);
+    assert_eq!(Vector::from_bytes(v.to_string().as_bytes(), DataType::Real).unwrap(), v);
+
+    // Entries move with the rows of a symmetric permutation
+    v.permute(&[2, 0, 1]);
+    assert_eq!(v.to_string(), "%%MatrixMarket matrix array real general\n3 1\n3\n1.5\n-2\n");
+
+    assert!(matches!(Vector::from_bytes(b"3 1\n1\n2\n", DataType::Real), Err(Error::EntryCount { expected: 3, found: 2 })));
+    assert!(matches!(Vector::from_bytes(b"2 2\n1\n2\n3\n4\n", DataType::Real), Err(Error::DimensionMismatch { .. })));
+}
+
+#[test]
+fn vector_transforms() {
+    // Integer vectors become real vectors when scaled
+    let mut v = Vector::from_bytes(b"%%MatrixMarket matrix array integer general\n2 1\n3\n-4\n", DataType::Integer).unwrap();
+    v.scale_entries(&[0.5, 2.0]);
+    assert_eq!(v.to_string(), "%%MatrixMarket matrix array real general\n2 1\n1.5\n-8\n");
+
+    assert_eq!(v.to_vec().unwrap(), [1.5, -8.0]);
+
+    let mut v = Vector::from_bytes(b"%%MatrixMarket matrix array complex general\n2 1\n1 2\n3 -4\n", DataType::Complex).unwrap();
+    v.scale(-1.0);
+    assert_eq!(v.to_string(), "%%MatrixMarket matrix array complex general\n2 1\n-1 -2\n-3 4\n");
+    assert!(matches!(v.to_vec(), Err(Error::TypeMismatch { expected: "real", found: "complex" })));
+
+    let m = Matrix::from(Vector::from(vec![1.0, 0.0, 2.0]));
+    assert_eq!((m.nrows(), m.ncols(), m.nvals()), (3, 1, 3));
+    assert_eq!(m.rows(), [1, 2, 3]);
+}
+
+#[test]
+fn symmetry_score() {
+    // (2, 1) matches (1, 2) only within a tolerance, and (3, 1) has no counterpart
+    let m = Matrix::from_bytes(b"3 3 5\n1 1 1\n2 1 2\n1 2 2.001\n3 1 4\n3 3 5\n", DataType::Real);
+    assert_eq!(m.symmetry_score(0.0), 0.4);
+    assert_eq!(m.symmetry_score(1e-3), 0.8);
+    assert_eq!(m.stats().symmetry_score, 0.4);
+
+    // Entries of a symmetric matrix imply their counterparts
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n2 2 2\n1 1 1\n2 1 2\n", DataType::Real);
+    assert_eq!(m.symmetry_score(0.0), 1.0);
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n2 1 2\n", DataType::Real);
+    assert_eq!(m.symmetry_score(0.0), 0.0);
+}
+
+#[test]
+fn gershgorin() {
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n3 3 4\n1 1 4\n2 1 -1\n2 2 1\n3 3 -2\n", DataType::Real);
+    let discs = m.gershgorin().unwrap();
+    assert_eq!(discs.centers, [(4.0, 0.0), (1.0, 0.0), (-2.0, 0.0)]);
+    assert_eq!(discs.radii, [1.0, 1.0, 0.0]);
+    assert_eq!(discs.dominant_rows(), 2);
+    assert_eq!(discs.real_bounds(), (-2.0, 5.0));
+    assert_eq!(discs.abs_bound(), 5.0);
+
+    let mut csv = Vec::new();
+    discs.write_csv(&mut csv).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap().lines().nth(2), Some("2,1,0,1,false"));
+
+    assert!(Matrix::from_bytes(b"2 3 0\n", DataType::Real).gershgorin().is_err());
+}
+
+#[test]
+fn norms() {
+    // The duplicate entries at (1, 2) sum to -2
+    let m = Matrix::from_bytes(b"2 3 4\n1 1 3\n1 2 -1\n1 2 -1\n2 3 4\n", DataType::Real);
+    let norms = m.norms().unwrap();
+    assert_eq!(norms, Norms { fro: 29f64.sqrt(), inf: 5.0, one: 4.0, max_abs: 4.0 });
+    assert_eq!(m.norm_inf().unwrap(), 5.0);
+
+    // Mirrored entries of compact forms count as well
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n2 2 2\n1 1 1\n2 1 -2\n", DataType::Real);
+    assert_eq!(m.norm_fro().unwrap(), 3.0);
+    assert_eq!((m.norm_inf().unwrap(), m.norm_one().unwrap(), m.max_abs().unwrap()), (3.0, 3.0, 2.0));
+}
+
+#[test]
+fn check_equal() {
+    let m = Matrix::from_bytes(b"2 2 2\n1 1 1.5\n2 2 -3\n", DataType::Real);
+    let written = Matrix::from_bytes(m.to_string().as_bytes(), DataType::Real);
+    assert!(m.check_equal(&written, Tolerance::new()).is_ok());
+
+    // The next representable value, which differs by one unit in the last place
+    let other = format!("2 2 2\n1 1 1.5\n2 2 {}\n", (-3.0 as Real).next_down());
+    let other = Matrix::from_bytes(other.as_bytes(), DataType::Real);
+    assert!(matches!(m.check_equal(&other, Tolerance::new()), Err(Error::ValueMismatch { row: 2, col: 2 })));
+    assert!(m.check_equal(&other, Tolerance::new().ulps(1)).is_ok());
+    assert!(m.check_equal(&other, Tolerance::new().abs(1e-6)).is_ok());
+
+    let swapped = Matrix::from_bytes(b"2 2 2\n2 2 -3\n1 1 1.5\n", DataType::Real);
+    assert!(m.check_equal(&swapped, Tolerance::new().abs(10.0)).is_err());
+    assert!(matches!(m.check_equal(&Matrix::from_bytes(b"2 2 1\n1 1 1.5\n", DataType::Real), Tolerance::new()), Err(Error::EntryCount { .. })));
+}
+
+#[test]
+fn lossless_round_trip() {
+    let values: Vec<Real> = vec![
+        0.1, 1.0 / 3.0, -0.0, std::f64::consts::PI as Real, 1e-30, -2.5e30, Real::EPSILON, 1.0 + Real::EPSILON,
+        Real::MAX, Real::MIN, Real::MIN_POSITIVE, Real::MIN_POSITIVE / 8.0, Real::from_bits(1), Real::INFINITY, Real::NEG_INFINITY,
+    ];
+    let mut data = format!("{} 1 {}\n", values.len(), values.len());
+    for (i, x) in values.iter().enumerate() {
+        data += &format!("{} 1 {x}\n", i + 1);
+    }
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Real);
+
+    // Both the shortest decimal and the hexadecimal values read back bit for bit
+    for written in [m.to_string(), m.hex_floats().to_string()] {
+        let read = Matrix::from_bytes(written.as_bytes(), DataType::Real);
+        let bits = |m: &Matrix| m.values::<Real>().unwrap().iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&read), bits(&m));
+    }
+
+    assert!(m.hex_floats().to_string().contains("\n3 1 -0x0p+0\n"));
+    let m = Matrix::from_bytes(b"1 1 1\n1 1 -0x1.8p+1 0X.8P-1\n", DataType::Complex);
+    assert_eq!(m.values::<Complex<Real>>().unwrap(), [Complex::new(-3.0, 0.25)]);
+}
+
+#[test]
+fn field_count() {
+    let data = b"%%MatrixMarket matrix coordinate pattern general\n% comment\n3 3 3\n1 1\n2 2\n3 3\n";
+    let err = Matrix::from_bytes_with(data, DataType::Real, &ReadOptions::default()).unwrap_err();
+    assert!(matches!(err, Error::FieldCount { line: 4, expected: 3, found: 2, .. }));
+    assert_eq!(err.to_string(), "expected 3 fields for real, found 2 on line 4; is this a pattern matrix?");
+    assert!(matches!(err.suggested_type(), Some(DataType::Bool)));
+
+    let err = Matrix::from_reader_with(BufReader::new(Cursor::new(b"2 2 2\n1 1 1.5\n2 2 2.5\n")), DataType::Complex, &ReadOptions::default()).unwrap_err();
+    assert!(matches!(err, Error::FieldCount { line: 2, expected: 4, found: 3, .. }));
+    assert!(matches!(err.suggested_type(), Some(DataType::Real)));
+
+    assert_eq!(Matrix::from_bytes(data, DataType::Bool).nvals(), 3);
+}
+
+#[test]
+fn try_parse_bytes() {
+    let parse = |data: &[u8]| Matrix::try_parse_bytes(data, DataType::Real, &ReadOptions::default());
+
+    assert!(matches!(parse(b"3 3\n1 1 1\n"), Err(Error::SizeLine { line: 1 })));
+    assert!(matches!(parse(b"% comment\n3 x 1\n1 1 1\n"), Err(Error::SizeLine { line: 2 })));
+    assert!(matches!(parse(b"3 3 1\n1 -1 1\n"), Err(Error::InvalidField { line: 2, .. })));
+    assert!(matches!(parse(b"3 3 1\n1 1 \xff\n"), Err(Error::InvalidField { line: 2, .. })));
+    let err = parse(b"3 3 2\n1 1 1\n\n4 1 1\n").unwrap_err();
+    assert_eq!(err.to_string(), "entry at (4, 1) on line 4 lies outside the 3x3 matrix");
+    assert!(matches!(parse(b"3 3 1\n0 1 1\n"), Err(Error::OutOfBounds { row: 0, .. })));
+    assert!(matches!(parse(b"%%MatrixMarket matrix coordinate real symmetric\n3 4 1\n1 1 1\n"), Err(Error::DimensionMismatch { .. })));
+
+    // Bounds are checked before non-finite entries are dropped, such that the lines still match
+    let options = ReadOptions::new().nonfinite_policy(NonFinitePolicy::Drop);
+    let err = Matrix::try_parse_bytes(b"3 3 2\n1 1 nan\n5 1 1\n", DataType::Real, &options).unwrap_err();
+    assert!(matches!(err, Error::OutOfBounds { line: 3, .. }));
+    let m = Matrix::try_parse_bytes(b"3 3 2\n1 1 nan\n2 1 1\n", DataType::Real, &options).unwrap();
+    assert_eq!(m.nvals(), 1);
+    assert!(m.is_sorted(SortOrder::RowMajor));
+}
+
+#[test]
+fn symmetric_storage() {
+    let mut m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n3 3 2\n2 1 1.5\n3 2 -2\n", DataType::Real);
+    assert!(m.check_lower_storage().is_ok());
+
+    // Transposing keeps the stored triangle, and expanding mirrors it with negated values
+    m.transpose();
+    let mut general = m.clone();
+    general.expand_symmetric();
+    assert_eq!(general.symmetry(), Symmetry::General);
+    assert_eq!(general.to_string(), "%%MatrixMarket matrix coordinate real general\n3 3 4\n1 2 1.5\n2 1 -1.5\n2 3 -2\n3 2 2\n");
+
+    let mut upper = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n2 2 2\n1 1 1\n1 2 2\n", DataType::Real);
+    assert!(matches!(upper.check_lower_storage(), Err(Error::UpperEntry { row: 1, col: 2, symmetry: Symmetry::Symmetric })));
+    upper.fold_to_lower();
+    assert!(upper.check_lower_storage().is_ok());
+    assert_eq!((upper.rows(), upper.cols()), (&[1, 2][..], &[1, 1][..]));
+
+    let mut wtr = MatrixWriter::new(Cursor::new(Vec::new()), 2, 2, DataType::Bool, Symmetry::Symmetric).unwrap();
+    assert!(wtr.write_pattern_entry(2, 1).is_ok());
+    assert!(wtr.write_pattern_entry(1, 2).is_err());
+}
+
+#[test]
+fn row_views() {
+    let mut m = Matrix::from_bytes(b"4 3 4\n3 2 3\n1 3 2\n1 1 1\n3 1 4\n", DataType::Real);
+    m.sort_row_major();
+
+    let row = m.row(3);
+    assert_eq!((row.row(), row.cols()), (3, &[1, 2][..]));
+    assert_eq!(row.values(), ValueSlice::Real(&[4.0, 3.0]));
+    assert!(m.row(2).is_empty() && m.row(4).is_empty());
+
+    let lens: Vec<_> = m.rows_iter().map(|row| row.len()).collect();
+    assert_eq!(lens, [2, 0, 2, 0]);
+    assert!(m.rows_iter().all(|row| row == m.row(row.row())));
+}
+
+#[test]
+fn get() {
+    let mut m = Matrix::from_bytes(b"3 3 4\n3 2 3\n1 3 2\n1 1 1\n3 1 4\n", DataType::Real);
+    let lookups = |m: &Matrix| [(1, 1), (1, 3), (3, 1), (3, 2), (2, 2)].map(|(i, j)| m.get::<Real>(i, j));
+    let expected = [Some(1.0), Some(2.0), Some(4.0), Some(3.0), None];
+
+    assert_eq!(lookups(&m), expected);
+    m.sort_row_major();
+    assert_eq!(lookups(&m), expected);
+    m.sort(SortOrder::ColMajor).unwrap();
+    assert_eq!(lookups(&m), expected);
+    assert_eq!(m.get::<Int>(1, 1), None);
+
+    // Appending entries leaves them out of order
+    m.ensure_diagonal(5.0).unwrap();
+    assert_eq!(m.get::<Real>(2, 2), Some(5.0));
+    m.transpose();
+    assert_eq!(m.get::<Real>(1, 3), Some(4.0));
+}
+
+#[test]
+fn sortedness() {
+    let sorted = Matrix::from_bytes(b"3 3 3\n1 1 1\n2 1 2\n3 3 3\n", DataType::Real);
+    assert!(sorted.is_sorted(SortOrder::RowMajor));
+    let unsorted = Matrix::from_bytes(b"3 3 3\n2 1 2\n1 1 1\n3 3 3\n", DataType::Real);
+    assert!(!unsorted.is_sorted(SortOrder::RowMajor) && !unsorted.is_sorted(SortOrder::ColMajor));
+
+    // Sorting entries that are known to be in order leaves them as they are
+    let mut m = sorted.clone();
+    m.sort_with(SortOrder::RowMajor, &SortOptions::new().strategy(SortStrategy::External)).unwrap();
+    assert_eq!(m, sorted);
+
+    m.transpose();
+    assert!(m.is_sorted(SortOrder::ColMajor));
+    m.permute_symmetric(&[2, 1, 0]);
+    assert!(!m.is_sorted(SortOrder::ColMajor) && !m.is_sorted(SortOrder::RowMajor));
+    m.sort(SortOrder::Morton).unwrap();
+    assert!(m.is_sorted(SortOrder::Morton));
+    m.sort_blocked(2, 2);
+    assert!(!m.is_sorted(SortOrder::Morton));
+}
+
+#[test]
+fn deterministic_sort() {
+    let data = b"3 3 6\n2 2 1\n1 1 2\n2 2 3\n1 1 4\n3 1 5\n1 1 6\n";
+    let m = Matrix::from_bytes(data, DataType::Real);
+    let expected = "%%MatrixMarket matrix coordinate real general\n3 3 6\n1 1 2\n1 1 4\n1 1 6\n2 2 1\n2 2 3\n3 1 5\n";
+
+    // Stable sorts keep duplicate entries in their order, whatever the strategy and thread count
+    for strategy in [SortStrategy::Zip, SortStrategy::Permute, SortStrategy::Counting, SortStrategy::External] {
+        for threads in [1, 3] {
+            let mut m = m.clone();
+            let options = SortOptions::new().stable(true).strategy(strategy).chunk_size(2);
+            ParallelConfig::new().threads(threads).install(|| m.sort_with(SortOrder::RowMajor, &options)).unwrap().unwrap();
+            assert_eq!(m.to_string(), expected);
+        }
+    }
+}
+
+#[test]
+fn phase_spans() {
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+
+    /// Collects the names of the spans that are created.
+    struct Names(Arc<Mutex<Vec<&'static str>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Names {
+        fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: Context<'_, S>) {
+            self.0.lock().unwrap().push(attrs.metadata().name());
+        }
+    }
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(Names(names.clone()));
+    tracing::subscriber::with_default(subscriber, || {
+        let mut m = Matrix::from_bytes(b"2 2 3\n2 2 1\n1 1 2\n2 2 3\n", DataType::Real);
+        m.sum_duplicates().unwrap();
+    });
+    assert_eq!(*names.lock().unwrap(), ["parse", "sort", "coalesce"]);
+}
+
+#[test]
+fn dense() {
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate integer skew-symmetric\n3 3 4\n2 1 4\n3 2 -1\n3 1 2\n3 1 1\n", DataType::Integer);
+    assert_eq!(m.to_dense_vec(9).unwrap(), [0.0, -4.0, -3.0, 4.0, 0.0, 1.0, 3.0, -1.0, 0.0]);
+    assert!(matches!(m.to_dense_vec(8), Err(Error::DenseLimit { nrows: 3, ncols: 3, max_elements: 8 })));
+    assert_eq!(m.dense_len(9).unwrap(), 9);
+
+    let mut text = Vec::new();
+    m.write_dense(&mut text, 9).unwrap();
+    assert_eq!(text, b"0 -4 -3\n4 0 1\n3 -1 0\n");
+}
+
+#[test]
+fn graph_transforms() {
+    // Edges in both directions, in one direction, and a self-loop, and an isolated node
+    let m = Matrix::from_bytes(b"4 4 5\n1 2 3\n2 1 3\n3 1 1\n3 3 5\n1 3 2\n", DataType::Real);
+    let a = m.to_adjacency().unwrap();
+    assert_eq!(a.to_string(), "%%MatrixMarket matrix coordinate pattern symmetric\n4 4 2\n2 1\n3 1\n");
+
+    let l = m.to_laplacian().unwrap();
+    let dense = l.to_dense_vec(16).unwrap();
+    assert_eq!(dense, [2.0, -1.0, -1.0, 0.0, -1.0, 1.0, 0.0, 0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    assert!(dense.chunks(4).all(|row| row.iter().sum::<f64>() == 0.0));
+
+    let n = m.to_normalized_laplacian().unwrap().to_dense_vec(16).unwrap();
+    assert_eq!((n[0], n[5], n[15]), (1.0, 1.0, 0.0));
+    assert!((n[1] + 0.5f64.sqrt()).abs() < 1e-6);
+
+    assert!(matches!(Matrix::from_bytes(b"2 3 1\n1 2 1\n", DataType::Real).to_adjacency(), Err(Error::DimensionMismatch { .. })));
+}
+
+#[test]
+fn compress_empty() {
+    let mut m = Matrix::from_bytes(b"5 6 3\n2 1 1\n2 4 2\n4 6 3\n", DataType::Real);
+    let (rows, cols) = m.compress_empty();
+    assert_eq!((rows, cols), (vec![2, 4], vec![1, 4, 6]));
+    assert_eq!(m.to_string(), "%%MatrixMarket matrix coordinate real general\n2 3 3\n1 1 1\n1 2 2\n2 3 3\n");
+    assert!(m.is_row_major());
+
+    // Symmetric forms keep an index if its row or its column has entries
+    let mut s = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n4 4 2\n3 1 1\n3 3 2\n", DataType::Real);
+    let (rows, cols) = s.compress_empty();
+    assert_eq!((rows, cols), (vec![1, 3], vec![1, 3]));
+    assert_eq!(s.to_string(), "%%MatrixMarket matrix coordinate real symmetric\n2 2 2\n2 1 1\n2 2 2\n");
+}
+
+#[test]
+fn relabel() {
+    // Global rows 3 and 5 become local rows 1 and 2, and the other rows are dropped
+    let rows = IndexMap::load(&b"% global local\n3 1\n5 2\n"[..]).unwrap();
+    let cols = IndexMap::load(&b"2\n1\n3\n"[..]).unwrap();
+    let mut m = Matrix::from_bytes(b"5 3 4\n3 1 1\n4 2 2\n5 2 3\n5 3 4\n", DataType::Real);
+    m.relabel(Some(&rows), Some(&cols)).unwrap();
+    assert_eq!(m.to_string(), "%%MatrixMarket matrix coordinate real general\n2 3 3\n1 2 1\n2 1 3\n2 3 4\n");
+
+    // Symmetric entries that land in the upper triangle are mirrored
+    let map = IndexMap::from_list(vec![2, 1]).unwrap();
+    let mut s = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n2 1 5\n", DataType::Real);
+    s.relabel(Some(&map), Some(&map)).unwrap();
+    assert_eq!(s.to_string(), "%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n2 1 -5\n");
+    assert!(matches!(s.relabel(Some(&map), None), Err(Error::SymmetryMismatch { .. })));
+
+    assert!(matches!(IndexMap::from_pairs([(1, 2), (2, 2)]), Err(Error::InvalidIndexMap { index: 2 })));
+    assert!(matches!(IndexMap::load(&b"1 2\n3\n"[..]), Err(Error::InvalidField { line: 2, .. })));
+}
+
+#[test]
+fn retain() {
+    let mut m = Matrix::from_bytes(b"3 3 5\n1 1 4\n1 3 -1\n2 2 3\n3 1 2\n3 3 -5\n", DataType::Real);
+    m.retain(|row, col, x: Real| row.abs_diff(col) <= 1 || x > 0.0).unwrap();
+    assert_eq!(m.to_string(), "%%MatrixMarket matrix coordinate real general\n3 3 4\n1 1 4\n2 2 3\n3 1 2\n3 3 -5\n");
+    assert!(m.is_row_major());
+
+    // Pattern matrices match any value type
+    let mut p = Matrix::from_bytes(b"2 2 2\n1 1\n2 1\n", DataType::Bool);
+    p.retain(|row, _, x: Int| row == 2 && x == 1).unwrap();
+    assert_eq!(p.nvals(), 1);
+
+    // Compact forms pass their mirrored entries as well
+    let mut s = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n3 3 3\n1 1 4\n2 1 2\n3 1 -1\n", DataType::Real);
+    s.retain(|row, _, _: Real| row == 1).unwrap();
+    assert_eq!(s.symmetry(), Symmetry::General);
+    assert_eq!(s.nvals(), 3);
+    assert_eq!(s.get::<Real>(1, 3), Some(-1.0));
+
+    assert!(matches!(m.retain(|_, _, _: Int| true), Err(Error::TypeMismatch { expected: "integer", found: "real" })));
+}
+
+#[test]
+fn band_fractions() {
+    let m = Matrix::from_bytes(b"4 4 4\n1 1 1\n2 1 1\n4 1 1\n3 4 1\n", DataType::Real);
+    assert_eq!(m.stats().bandwidth, 3);
+    assert_eq!(m.band_fractions(&[0, 1, 3]), [0.25, 0.75, 1.0]);
+
+    // Mirrored entries count as well
+    let s = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n3 3 2\n1 1 1\n3 1 1\n", DataType::Real);
+    assert_eq!(s.band_fractions(&[1]), [1.0 / 3.0]);
+}
+
+#[test]
+fn largest_entries() {
+    let m = Matrix::from_bytes(b"3 3 5\n1 1 2\n1 3 -1e6\n2 2 3\n3 1 -3\n3 3 nan\n", DataType::Real);
+    let largest = m.largest_entries(3);
+    assert_eq!((largest[0].0, largest[0].1), (3, 3));
+    assert!(largest[0].2.is_nan());
+    assert_eq!(&largest[1..], [(1, 3, 1e6), (2, 2, 3.0)]);
+    assert_eq!(m.largest_entries(10).len(), 5);
+}
+
+#[test]
+fn line_metrics() {
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n3 3 2\n2 1 2\n3 1 -1\n", DataType::Real);
+    let rows = m.row_metrics();
+    assert_eq!(rows.nvals, [2, 1, 1]);
+    assert_eq!(rows.min_max, [Some((-2.0, 1.0)), Some((2.0, 2.0)), Some((-1.0, -1.0))]);
+    assert_eq!(rows.bandwidth, [2, 1, 2]);
+
+    // The summary counts the mirrored entries as well
+    let stats = m.stats();
+    assert_eq!((stats.nvals, stats.density), (2, 4.0 / 9.0));
+    assert_eq!((stats.empty_rows, stats.empty_cols), (0, 0));
+    assert_eq!((stats.row_nvals, stats.col_nvals), ((1, 2), (1, 2)));
+
+    let cols = Matrix::from_bytes(b"2 3 2\n1 1 4\n2 1 -3\n", DataType::Real).col_metrics();
+    let mut csv = Vec::new();
+    cols.write_csv(&mut csv).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "col,nnz,min,max,bandwidth\n1,2,-3,4,1\n2,0,,,0\n3,0,,,0\n");
+}
+
+#[test]
+fn error_metrics() {
+    let golden = Matrix::from_bytes(b"2 2 3\n1 1 2\n2 1 4\n2 2 1\n", DataType::Real);
+    let m = Matrix::from_bytes(b"2 2 3\n2 1 5\n1 1 1\n1 1 1\n", DataType::Real);
+    let metrics = m.error_metrics(&golden).unwrap();
+    assert_eq!((metrics.max_abs, metrics.max_rel, metrics.worst), (1.0, 1.0, Some((2, 2))));
+    assert_eq!(metrics.mean_rel, 1.25 / 3.0);
+    assert_eq!(metrics.fro, 2.0f64.sqrt());
+    assert!((metrics.rel_fro - (2.0f64 / 21.0).sqrt()).abs() < 1e-12);
+
+    // Entries that differ where the reference is zero are infinitely wrong
+    let extra = Matrix::from_bytes(b"2 2 4\n1 1 2\n2 1 4\n2 2 1\n1 2 1e-9\n", DataType::Real);
+    let metrics = extra.error_metrics(&golden).unwrap();
+    assert_eq!((metrics.max_rel, metrics.worst), (f64::INFINITY, Some((1, 2))));
+    assert_eq!(golden.error_metrics(&golden).unwrap().worst, None);
+}
+
+#[test]
+fn compress_hermitian() {
+    let data = b"%%MatrixMarket matrix coordinate complex general\n2 2 4\n1 1 2 1e-9\n2 1 3 4\n1 2 3 -4\n2 2 1 0\n";
+    let mut m = Matrix::from_bytes(data, DataType::Complex);
+    assert!(m.detect_symmetry(1e-6).unwrap().hermitian);
+    assert!(!m.detect_symmetry(0.0).unwrap().hermitian);
+
+    assert!(matches!(m.clone().compress_hermitian(0.0), Err(Error::SymmetryViolation { row: 1, col: 1, symmetry: Symmetry::Hermitian })));
+    m.compress_hermitian(1e-6).unwrap();
+    assert_eq!(m.to_string(), "%%MatrixMarket matrix coordinate complex hermitian\n2 2 3\n1 1 2 0\n2 1 3 4\n2 2 1 0\n");
+
+    let mut real = Matrix::from_bytes(b"1 1 1\n1 1 1\n", DataType::Real);
+    assert!(matches!(real.compress_hermitian(0.0), Err(Error::TypeMismatch { .. })));
+}
+
+#[test]
+fn skew_symmetric() {
+    // Explicit zeros on the diagonal are allowed, other values are not
+    let data = b"%%MatrixMarket matrix coordinate real skew-symmetric\n3 3 3\n2 1 2\n3 3 0\n3 2 -1\n";
+    let mut m = Matrix::from_bytes(data, DataType::Real);
+    m.expand_symmetric();
+    let expanded = m.to_string();
+    assert_eq!(expanded, "%%MatrixMarket matrix coordinate real general\n3 3 5\n2 1 2\n3 3 0\n3 2 -1\n1 2 -2\n2 3 1\n");
+
+    let bad = b"%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 2\n2 1 2\n2 2 1\n";
+    assert!(matches!(Matrix::from_bytes_with(bad, DataType::Real, &ReadOptions::new()),
+        Err(Error::SymmetryViolation { row: 2, col: 2, symmetry: Symmetry::SkewSymmetric })));
+    assert!(matches!(Matrix::from_reader_with(BufReader::new(&bad[..]), DataType::Real, &ReadOptions::new()),
+        Err(Error::SymmetryViolation { .. })));
+
+    assert!(m.detect_symmetry(0.0).unwrap().skew);
+    m.compress_skew().unwrap();
+    assert_eq!(m.to_string(), "%%MatrixMarket matrix coordinate real skew-symmetric\n3 3 2\n2 1 2\n3 2 -1\n");
+
+    let mut diagonal = Matrix::from_bytes(b"2 2 1\n1 1 3\n", DataType::Real);
+    assert!(matches!(diagonal.compress_skew(), Err(Error::SymmetryViolation { row: 1, col: 1, .. })));
+}
+
+#[test]
+fn pipeline() {
+    use matrix_market_transform::pipeline::*;
+
+    let data = b"3 3 5\n3 1 1e-20\n2 2 1\n1 3 2\n2 2 3\n1 1 -4\n";
+    let plan = "% clean up\ndedup\nthreshold 1e-12\n\ntranspose\nsort row-major\n";
+    let pipeline = Pipeline::from_plan(plan).unwrap();
+    assert_eq!(pipeline.names().collect::<Vec<_>>(), ["dedup", "threshold", "transpose", "sort"]);
+
+    let m = pipeline.run(Matrix::from_bytes(data, DataType::Real)).unwrap();
+    assert!(m.is_row_major());
+    assert_eq!(m.to_string(), "%%MatrixMarket matrix coordinate real general\n3 3 3\n1 1 -4\n2 2 4\n3 1 2\n");
+
+    let mut steps = Vec::new();
+    let negate = from_fn("negate", |mut m: Matrix| { m.scale_rows(&[-1.0; 3]); Ok(m) });
+    Pipeline::new().then(negate).then(Sort::new(SortOrder::ColMajor))
+        .run_with(Matrix::from_bytes(data, DataType::Real), |step, m| { steps.push(step.name()); step.apply(m) })
+        .unwrap();
+    assert_eq!(steps, ["negate", "sort"]);
+
+    assert!(matches!(Pipeline::from_plan("sort\n"), Err(Error::InvalidField { line: 1, .. })));
+    assert!(matches!(Pipeline::from_plan("dedup\nthreshold x\n"), Err(Error::InvalidField { line: 2, .. })));
+    let rectangular = Matrix::from_bytes(b"2 3 1\n1 3 1\n", DataType::Real);
+    assert!(Pipeline::new().then(Reorder(Reordering::Rcm)).run(rectangular).is_err());
+}
+
+#[test]
+fn cast() {
+    use matrix_market_transform::pipeline::*;
+
+    // Values only widen, and patterns become ones
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 1 3\n2 1 -2\n", DataType::Integer);
+    let pipeline = Pipeline::from_plan("cast real\ncast complex\n").unwrap();
+    let complex = pipeline.run(m.clone()).unwrap();
+    assert_eq!(complex.values::<Complex<Real>>().unwrap(), [Complex::new(3.0, 0.0), Complex::new(-2.0, 0.0)]);
+    assert!(matches!(complex.cast(DataType::Real), Err(Error::TypeMismatch { expected: "real", found: "complex" })));
+    assert!(matches!(m.cast(DataType::Real).unwrap().cast(DataType::Integer), Err(Error::TypeMismatch { expected: "integer", found: "real" })));
+
+    let skew = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n2 1 5\n", DataType::Real);
+    let pattern = skew.cast(DataType::Bool).unwrap();
+    assert_eq!(pattern.to_string(), "%%MatrixMarket matrix coordinate pattern symmetric\n2 2 1\n2 1\n");
+    assert_eq!(pattern.cast(DataType::Integer).unwrap().values::<Int>().unwrap(), [1]);
+}
+
+#[test]
+fn plan() {
+    use matrix_market_transform::pipeline::*;
+
+    let yaml = "inputs: [a.mtx, 'data/*.mtx']\ntype: integer\nsteps:\n  - dedup\n  - threshold: 1e-12\n  - reorder: random 7\n  - sort row-major\nout-dir: out/\njobs: 2\n";
+    let plan = Plan::from_yaml(yaml).unwrap();
+    assert_eq!(plan.inputs, [std::path::Path::new("a.mtx"), std::path::Path::new("data/*.mtx")]);
+    assert!(matches!(plan.data_type, DataType::Integer));
+    assert_eq!(plan.pipeline.names().collect::<Vec<_>>(), ["dedup", "threshold", "reorder", "sort"]);
+    assert_eq!(plan.out_dir.as_deref(), Some(std::path::Path::new("out/")));
+    assert_eq!((plan.output, plan.in_place, plan.jobs), (None, false, 2));
+
+    let plan = Plan::from_yaml("inputs: a.mtx\nsteps: [transpose]\noutput: b.mtx\n").unwrap();
+    assert_eq!(plan.inputs.len(), 1);
+    assert_eq!(plan.data_type.to_string(), "real");
+
+    for bad in ["inputs: a.mtx\n", "steps: [dedup]\n", "inputs: a.mtx\nsteps: [sort]\n", "inputs: a.mtx\nsteps: [dedup]\nfoo: 1\n",
+        "inputs: a.mtx\nsteps: [dedup]\noutput: b.mtx\nin-place: true\n", "inputs: [a.mtx\n", "- a.mtx\n"] {
+        assert!(matches!(Plan::from_yaml(bad), Err(Error::InvalidPlan { .. })), "{bad}");
+    }
+}
+
+#[test]
+fn sort_checkpointed() {
+    let mut data = "%%MatrixMarket matrix coordinate integer general\n300 200 4000\n".to_string();
+    for i in 0..4000 {
+        data += &format!("{} {} {i}\n", i * 7919 % 300 + 1, i * 104729 % 50 + 1);
+    }
+    let m = Matrix::from_bytes(data.as_bytes(), DataType::Integer);
+    let options = SortOptions::new().chunk_size(999);
+    let mut expected = m.clone();
+    expected.sort_with(SortOrder::RowMajor, &options.stable(true)).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("mmt-test-checkpoint-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // A directory in the place of the second run interrupts the sort after the first run
+    std::fs::create_dir_all(dir.join("1.run")).unwrap();
+    let mut sorted = m.clone();
+    assert!(sorted.sort_checkpointed(SortOrder::RowMajor, &options, &dir, false).is_err());
+    assert_eq!(sorted, m);
+    let checkpoint = std::fs::read_to_string(dir.join("checkpoint")).unwrap();
+    assert_eq!(checkpoint.lines().filter(|line| line.starts_with("run ")).count(), 1);
+
+    std::fs::remove_dir(dir.join("1.run")).unwrap();
+    sorted.sort_checkpointed(SortOrder::RowMajor, &options, &dir, true).unwrap();
+    assert_eq!(sorted, expected);
+    assert!(sorted.is_sorted(SortOrder::RowMajor));
+    assert!(!dir.join("checkpoint").exists() && !dir.join("0.run").exists());
+
+    // Runs of other entries are written again
+    let mut other = m.clone();
+    other.transpose();
+    let mut expected = other.clone();
+    expected.sort_with(SortOrder::ColMajor, &options.stable(true)).unwrap();
+    std::fs::create_dir_all(dir.join("1.run")).unwrap();
+    assert!(m.clone().sort_checkpointed(SortOrder::ColMajor, &options, &dir, false).is_err());
+    std::fs::remove_dir(dir.join("1.run")).unwrap();
+    other.sort_checkpointed(SortOrder::ColMajor, &options, &dir, true).unwrap();
+    assert_eq!(other, expected);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn write_mmap() {
+    let path = std::env::temp_dir().join(format!("mmt-test-mmap-{}.mtx", std::process::id()));
+    let mut m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate complex general\n4 4 3\n1 1 1.5 -2\n4 2 0.1 0\n3 3 -1e-7 3\n", DataType::Complex);
+    for hex_floats in [false, true] {
+        m.write_mmap(&path, &TextFormat::new().hex_floats(hex_floats)).unwrap();
+        let expected = if hex_floats { m.hex_floats().to_string() } else { m.to_string() };
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+    }
+
+    // More entries than fit in a single chunk
+    m = generators::erdos_renyi(1000, 1000, 0.1, DataType::Integer, 3);
+    for format in [TextFormat::new(), TextFormat::new().align(true)] {
+        m.write_mmap(&path, &format).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), m.formatted(format).to_string());
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn aligned() {
+    let data = b"%%MatrixMarket matrix coordinate real general\n12 3 3\n1 1 1.5\n12 2 -1e-7\n3 3 0\n";
+    let m = Matrix::from_bytes(data, DataType::Real);
+    let text = m.formatted(TextFormat::new().align(true)).to_string();
+    let lines: Vec<_> = text.lines().skip(2).collect();
+    assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    assert!(lines[1].starts_with("12 2 "));
+    assert!(lines[0].starts_with(" 1 1 ") && lines[0].ends_with("1.50000000e0") || cfg!(feature = "x64"));
+    assert_eq!(Matrix::from_bytes(text.as_bytes(), DataType::Real), m);
+
+    let hex = m.formatted(TextFormat::new().align(true).hex_floats(true)).to_string();
+    assert_eq!(Matrix::from_bytes(hex.as_bytes(), DataType::Real), m);
+    assert_eq!(m.formatted(TextFormat::new()).to_string(), m.to_string());
+    assert_eq!(m.formatted(TextFormat::new().hex_floats(true)).to_string(), m.hex_floats().to_string());
+
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 1 -12\n2 2 7\n", DataType::Integer);
+    assert_eq!(m.formatted(TextFormat::new().align(true)).to_string(), "%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 1 -12\n2 2   7\n");
+}
+
+#[test]
+fn write_raw() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-raw-{}", std::process::id()));
+    let data = b"%%MatrixMarket matrix coordinate complex general\n3 4 2\n1 2 1.5 -2\n3 4 0.5 0\n";
+    let m = Matrix::from_bytes(data, DataType::Complex);
+    m.write_raw(&dir, &RawOptions::new().precision(Precision::F64)).unwrap();
+
+    let i32s = |name: &str| std::fs::read(dir.join(name)).unwrap().chunks(4).map(|b| i32::from_le_bytes(b.try_into().unwrap())).collect::<Vec<_>>();
+    assert_eq!(i32s("rows.bin"), [0, 2]);
+    assert_eq!(i32s("cols.bin"), [1, 3]);
+    let vals: Vec<_> = std::fs::read(dir.join("vals.bin")).unwrap().chunks(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+    assert_eq!(vals, [1.5, -2.0, 0.5, 0.0]);
+
+    let descriptor: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("matrix.json")).unwrap()).unwrap();
+    assert_eq!(descriptor["index_base"], 0);
+    assert_eq!(descriptor["complex"], "interleaved");
+    assert_eq!(descriptor["ordering"], "row-major");
+    assert_eq!(descriptor["arrays"][2]["dtype"], "float64");
+    assert_eq!(descriptor["arrays"][2]["len"], 4);
+
+    // Pattern matrices have no values
+    let pattern = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate pattern general\n2 2 1\n2 1\n", DataType::Bool);
+    std::fs::remove_dir_all(&dir).unwrap();
+    pattern.write_raw(&dir, &RawOptions::new().index_type(IndexType::Int64).one_based(true)).unwrap();
+    assert_eq!(std::fs::read(dir.join("rows.bin")).unwrap(), 2i64.to_le_bytes());
+    assert!(!dir.join("vals.bin").exists());
+    let descriptor: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("matrix.json")).unwrap()).unwrap();
+    assert_eq!(descriptor["arrays"].as_array().unwrap().len(), 2);
+    assert_eq!(descriptor["index_base"], 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn write_npy() {
+    // Header text and data of a version 1.0 `.npy` file
+    fn parse(bytes: &[u8]) -> (String, &[u8]) {
+        assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+        let len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + len) % 64, 0);
+        (String::from_utf8(bytes[10..10 + len].to_vec()).unwrap(), &bytes[10 + len..])
+    }
+
+    let dir = std::env::temp_dir().join(format!("mmt-test-npy-{}", std::process::id()));
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate complex general\n3 4 2\n1 2 1.5 -2\n3 4 0.5 0\n", DataType::Complex);
+    m.write_npy(&dir, &RawOptions::new().precision(Precision::F64)).unwrap();
+
+    let rows = std::fs::read(dir.join("rows.npy")).unwrap();
+    let (header, data) = parse(&rows);
+    assert!(header.starts_with("{'descr': '<i4', 'fortran_order': False, 'shape': (2,), }") && header.ends_with(" \n"));
+    assert_eq!(data, [0i32.to_le_bytes(), 2i32.to_le_bytes()].concat());
+    let vals = std::fs::read(dir.join("vals.npy")).unwrap();
+    let (header, data) = parse(&vals);
+    assert!(header.starts_with("{'descr': '<c16',"));
+    assert_eq!(data, [1.5f64, -2.0, 0.5, 0.0].map(f64::to_le_bytes).concat());
+    let shape = std::fs::read(dir.join("shape.npy")).unwrap();
+    assert_eq!(parse(&shape).1, [3i64.to_le_bytes(), 4i64.to_le_bytes()].concat());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let mut records = Vec::new();
+    m.write_npy_records(&mut records, &RawOptions::new().index_type(IndexType::Int64).precision(Precision::F32)).unwrap();
+    let (header, data) = parse(&records);
+    assert!(header.starts_with("{'descr': [('row', '<i8'), ('col', '<i8'), ('val', '<c8')], 'fortran_order': False, 'shape': (2,), }"));
+    assert_eq!(data.len(), 2 * (8 + 8 + 8));
+    assert_eq!(&data[24..40], [2i64.to_le_bytes(), 3i64.to_le_bytes()].concat());
+}
+
+#[test]
+fn write_json() {
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate complex symmetric\n3 3 2\n1 1 1.5 -2\n3 2 inf 0\n", DataType::Complex);
+    let mut lines = Vec::new();
+    m.write_jsonl(&mut lines).unwrap();
+    assert_eq!(String::from_utf8(lines).unwrap(), "{\"r\":1,\"c\":1,\"v\":[1.5,-2.0]}\n{\"r\":3,\"c\":2,\"v\":[null,0.0]}\n");
+
+    let pattern = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate pattern general\n2 2 1\n2 1\n", DataType::Bool);
+    let mut lines = Vec::new();
+    pattern.write_jsonl(&mut lines).unwrap();
+    assert_eq!(lines, b"{\"r\":2,\"c\":1}\n");
+
+    let mut json = Vec::new();
+    m.write_json(&mut json).unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(value, serde_json::json!({
+        "nrows": 3, "ncols": 3, "data_type": "complex", "symmetry": "symmetric",
+        "rows": [1, 3], "cols": [1, 2], "values": [[1.5, -2.0], [null, 0.0]],
+    }));
+
+    // The same layout as the `serde` feature
+    #[cfg(feature = "serde")]
+    {
+        let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate integer general\n2 3 2\n1 3 -4\n2 1 7\n", DataType::Integer);
+        let mut json = Vec::new();
+        m.write_json(&mut json).unwrap();
+        assert_eq!(serde_json::from_slice::<Matrix>(&json).unwrap(), m);
+        assert_eq!(String::from_utf8(json).unwrap().trim_end(), serde_json::to_string(&m).unwrap());
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn write_sqlite() {
+    let path = std::env::temp_dir().join(format!("mmt-test-sqlite-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate integer symmetric\n3 3 3\n1 1 4\n3 1 -2\n3 3 5\n", DataType::Integer);
+    m.write_sqlite(&path, "a").unwrap();
+    // Writing again replaces the table
+    m.write_sqlite(&path, "a").unwrap();
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let entries: Vec<(i64, i64, i64)> = conn.prepare("SELECT row, col, value FROM a ORDER BY col, row").unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(entries, [(1, 1, 4), (3, 1, -2), (3, 3, 5)]);
+    let indices: i64 = conn.query_row("SELECT count(*) FROM sqlite_master WHERE type = 'index' AND tbl_name = 'a'", [], |r| r.get(0)).unwrap();
+    assert_eq!(indices, 2);
+    let info: (i64, i64, String, String) = conn.query_row("SELECT nrows, nvals, data_type, symmetry FROM matrices WHERE name = 'a'", [], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))).unwrap();
+    assert_eq!(info, (3, 3, "integer".to_string(), "symmetric".to_string()));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn from_archive() {
+    use std::io::Write;
+
+    let matrix = b"%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.5\n2 1 -2\n";
+    let rhs = b"%%MatrixMarket matrix array real general\n2 1\n1\n2\n";
+    let dir = std::env::temp_dir().join(format!("mmt-test-archive-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // The right-hand side comes first, and the primary matrix is named after its directory
+    let tar_path = dir.join("bcs.tar.gz");
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(std::fs::File::create(&tar_path).unwrap(), flate2::Compression::default()));
+    for (name, data) in [("bcs/bcs_b.mtx", &rhs[..]), ("bcs/bcs.mtx", &matrix[..])] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, name, data).unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap();
+
+    let options = ReadOptions::default();
+    let expected = Matrix::from_bytes(matrix, DataType::Real);
+    assert!(is_archive(&tar_path));
+    assert_eq!(archive_stem(&tar_path), Some("bcs"));
+    assert_eq!(Matrix::from_archive(&tar_path, None, DataType::Real, &options).unwrap(), expected);
+    let b = Matrix::from_archive(&tar_path, Some("bcs_b.mtx"), DataType::Real, &options);
+    assert!(b.is_err(), "right-hand sides are dense arrays");
+    assert!(Matrix::from_archive(&tar_path, Some("x.mtx"), DataType::Real, &options).is_err());
+
+    // Without a matrix named after its directory, the only one that is not a right-hand side is read
+    let zip_path = dir.join("m.zip");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+    for (name, data) in [("m_b.mtx", &rhs[..]), ("matrix.mtx", &matrix[..])] {
+        zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap();
+    assert_eq!(Matrix::from_archive(&zip_path, None, DataType::Real, &options).unwrap(), expected);
+    assert_eq!(Matrix::from_archive(&zip_path, Some("matrix.mtx"), DataType::Real, &options).unwrap(), expected);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dataset() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-dataset-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("A.mtx");
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n3 3 4\n1 1 2\n2 2 4\n3 3 8\n3 1 1\n").unwrap();
+    std::fs::write(dir.join("A_b.mtx"), "%%MatrixMarket matrix array real general\n3 1\n2\n4\n9\n").unwrap();
+    std::fs::write(dir.join("A_coord.mtx"), "%%MatrixMarket matrix array real general\n3 2\n0\n1\n2\n10\n11\n12\n").unwrap();
+
+    let mut dataset = Dataset::load(&path, DataType::Real, &ReadOptions::default()).unwrap();
+    let coords = dataset.coords.as_ref().unwrap();
+    assert_eq!((coords.len(), coords.dim(), coords.get(2, 1)), (3, 2, 12.0));
+
+    // The members follow the rows of the matrix
+    dataset.permute_symmetric(&[2, 0, 1]);
+    assert_eq!(dataset.rhs, Some(Vector::from(vec![9.0, 2.0, 4.0])));
+    let coords = dataset.coords.as_ref().unwrap();
+    assert_eq!((0..3).map(|i| (coords.get(i, 0), coords.get(i, 1))).collect::<Vec<_>>(), [(2.0, 12.0), (0.0, 10.0), (1.0, 11.0)]);
+
+    // The right-hand side is scaled by the row factors
+    let (row_factors, _) = dataset.equilibrate();
+    let scaled = [9.0, 2.0, 4.0].iter().zip(&row_factors).map(|(&b, &f)| (b * f) as Real).collect::<Vec<_>>();
+    assert_eq!(dataset.rhs, Some(Vector::from(scaled)));
+
+    let out = dir.join("B.mtx");
+    dataset.save(&out).unwrap();
+    let saved = Dataset::load(&out, DataType::Real, &ReadOptions::default()).unwrap();
+    assert_eq!((saved.rhs, saved.coords), (dataset.rhs, dataset.coords));
+
+    // Members must have an entry per row
+    std::fs::write(dir.join("A_b.mtx"), "%%MatrixMarket matrix array real general\n2 1\n1\n2\n").unwrap();
+    assert!(matches!(Dataset::load(&path, DataType::Real, &ReadOptions::default()), Err(Error::DimensionMismatch { .. })));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn symbolic_fill() {
+    // Eliminating the first row of an arrow matrix fills in the rest of the matrix at level 1
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate pattern symmetric\n4 4 7\n1 1\n2 1\n3 1\n4 1\n2 2\n3 3\n4 4\n", DataType::Bool);
+    let fill = m.ilu_fill(2).unwrap();
+    assert_eq!(fill.levels, [10, 6, 0]);
+    assert_eq!((fill.nnz(0), fill.nnz(1), fill.nnz(5)), (10, 16, 16));
+    assert_eq!(fill.growth(1), 1.6);
+    assert_eq!(m.ic_fill(1).unwrap().levels, [7, 3]);
+
+    // Missing diagonal entries are part of ILU(0), and a tridiagonal matrix has no fill
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real general\n3 3 4\n1 2 1\n2 1 1\n2 3 1\n3 2 1\n", DataType::Real);
+    assert_eq!(m.ilu_fill(3).unwrap().levels, [7, 0, 0, 0]);
+
+    assert!(matches!(Matrix::from_bytes(b"2 3 0\n", DataType::Real).ilu_fill(0), Err(Error::DimensionMismatch { .. })));
+}
+
+#[test]
+fn elimination_tree() {
+    // The hub of an arrow matrix fills in everything after it, unless it is eliminated last
+    let mut m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate pattern symmetric\n4 4 7\n1 1\n2 1\n3 1\n4 1\n2 2\n3 3\n4 4\n", DataType::Bool);
+    assert_eq!(m.elimination_tree().unwrap(), [Some(1), Some(2), Some(3), None]);
+    assert_eq!(m.cholesky_counts().unwrap(), [4, 3, 2, 1]);
+    m.reorder(Reordering::Amd).unwrap();
+    assert_eq!(m.cholesky_counts().unwrap().iter().sum::<usize>(), 7);
+
+    // The Cholesky factor is that of incomplete Cholesky with every level of fill
+    let mut m = generators::laplacian_2d(8, 8);
+    let banded: usize = m.cholesky_counts().unwrap().iter().sum();
+    m.reorder(Reordering::Random { seed: 3 }).unwrap();
+    let random: usize = m.cholesky_counts().unwrap().iter().sum();
+    assert_eq!(random, m.ic_fill(64).unwrap().nnz(64));
+    m.reorder(Reordering::Amd).unwrap();
+    let amd: usize = m.cholesky_counts().unwrap().iter().sum();
+    assert!(amd < banded && banded < random, "{amd} {banded} {random}");
+}
+
+#[test]
+fn nested_dissection() {
+    // Separators are ordered last, which fills in less than a banded ordering of a grid
+    let mut m = generators::laplacian_2d(40, 40);
+    let mut banded = m.clone();
+    banded.reorder(Reordering::Rcm).unwrap();
+    let mut permutation = m.reorder(Reordering::NestedDissection).unwrap();
+    permutation.sort();
+    assert_eq!(permutation, (0..1600).collect::<Vec<_>>());
+    let nd: usize = m.cholesky_counts().unwrap().iter().sum();
+    let rcm: usize = banded.cholesky_counts().unwrap().iter().sum();
+    assert!(nd < rcm, "{nd} {rcm}");
+
+    // Disconnected parts, and dense parts without a separator, are ordered as well
+    for (density, seed) in [(0.002, 1), (0.9, 2)] {
+        let m = generators::erdos_renyi(300, 300, density, DataType::Real, seed);
+        let mut permutation = m.reordering(Reordering::NestedDissection).unwrap();
+        permutation.sort();
+        assert_eq!(permutation, (0..300).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn transversal() {
+    // Only one matching puts entries on the whole diagonal
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real general\n3 3 5\n1 2 1\n2 3 5\n3 1 2\n1 3 0.5\n3 2 9\n", DataType::Real);
+    assert_eq!(m.transversal().unwrap(), [2, 0, 1]);
+    assert_eq!(m.weighted_transversal().unwrap(), [2, 0, 1]);
+
+    // The weighted matching prefers the larger entries off the diagonal
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real symmetric\n2 2 3\n1 1 1\n2 1 -10\n2 2 1\n", DataType::Real);
+    assert_eq!(m.transversal().unwrap(), [0, 1]);
+    assert_eq!(m.weighted_transversal().unwrap(), [1, 0]);
+
+    // Structurally singular matrices put the unmatched rows in the remaining positions
+    let m = Matrix::from_bytes(b"%%MatrixMarket matrix coordinate real general\n3 3 2\n1 1 1\n2 1 1\n", DataType::Real);
+    assert_eq!(m.transversal().unwrap(), [0, 1, 2]);
+    assert_eq!(m.weighted_transversal().unwrap(), [0, 1, 2]);
+
+    // The product of the diagonal is the largest of all permutations
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![Vec::new()];
+        }
+        permutations(n - 1).into_iter().flat_map(|p| (0..n).map(move |k| {
+            let mut p = p.clone();
+            p.insert(k, n - 1);
+            p
+        })).collect()
+    }
+    for seed in 0..20 {
+        let m = generators::erdos_renyi(5, 5, 0.5, DataType::Real, seed);
+        let dense = m.to_dense_vec(25).unwrap();
+        let product = |p: &[usize]| (0..5).map(|k| dense[p[k] * 5 + k].abs()).product::<f64>();
+        let best = permutations(5).iter().map(|p| product(p)).fold(0.0, f64::max);
+        let found = product(&m.weighted_transversal().unwrap());
+        assert!((found - best).abs() <= 1e-9 * best, "seed {seed}: {found} < {best}");
+        assert_eq!(product(&m.transversal().unwrap()) > 0.0, best > 0.0);
+    }
+}
+
+/// Runs the command-line tool with the given arguments.
+#[cfg(feature = "mmap")]
+fn cli(args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_matrix_market_transform")).args(args).output().unwrap()
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_out_of_bounds() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-bounds-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("oob.mtx");
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n5 1 2.0\n").unwrap();
+    let out = dir.join("out.mtx");
+    let (input, output) = (path.to_str().unwrap(), out.to_str().unwrap());
+
+    // Every subcommand reports the entry instead of panicking, and writes nothing
+    let commands: [&[&str]; 5] = [
+        &["stats", input],
+        &["stats", input, "-t", "bool"],
+        &["scale", input, "--equilibrate", "-o", output],
+        &["reorder", input, "-o", output],
+        &["sort", input, "-o", output],
+    ];
+    for args in commands {
+        let output = cli(args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success(), "{args:?}");
+        assert!(stderr.contains("entry at (5, 1) on line 4 lies outside the 2x2 matrix") && !stderr.contains("panicked"), "{stderr}");
+    }
+    assert!(!out.exists());
+
+    // Every reader of the library checks the bounds
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(matches!(Matrix::from_bytes_with(&bytes, DataType::Real, &ReadOptions::default()), Err(Error::OutOfBounds { line: 4, .. })));
+    assert!(matches!(Matrix::from_reader_with(BufReader::new(&bytes[..]), DataType::Real, &ReadOptions::default()), Err(Error::OutOfBounds { line: 4, .. })));
+    assert!(matches!(Pattern::from_bytes_with(&bytes, &ReadOptions::default()), Err(Error::OutOfBounds { line: 4, .. })));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_several_inputs() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-inputs-{}", std::process::id()));
+    for sub in ["a", "b", "c"] {
+        std::fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+    let unsorted = "%%MatrixMarket matrix coordinate real general\n2 2 2\n2 2 2.0\n1 1 1.0\n";
+    for path in ["a/m.mtx", "b/m.mtx", "c/n.mtx"] {
+        std::fs::write(dir.join(path), unsorted).unwrap();
+    }
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    // Globs and paths mix, and every result is written under the name of its input
+    let output = cli(&["sort", &path("c/*.mtx"), &path("a/m.mtx"), "--out-dir", &path("out"), "-j", "2"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    for name in ["out/m.mtx", "out/n.mtx"] {
+        let m = Matrix::from_bytes(&std::fs::read(dir.join(name)).unwrap(), DataType::Real);
+        assert!(m.is_row_major() && m.nvals() == 2, "{name}");
+    }
+
+    // Inputs with the same file name are refused before anything is written
+    let output = cli(&["sort", &path("a/m.mtx"), &path("b/m.mtx"), "--out-dir", &path("clash"), "-j", "2"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would both be written to"));
+    assert!(!dir.join("clash/m.mtx").exists());
+
+    assert!(!cli(&["sort", &path("a/m.mtx"), &path("c/n.mtx"), "-o", &path("single.mtx")]).status.success());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_tensor_errors() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-tensor-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (valid, ragged) = (dir.join("valid.tns"), dir.join("ragged.tns"));
+    std::fs::write(&valid, "1 2 3 1.5\n2 1 1 2.5\n").unwrap();
+    std::fs::write(&ragged, "1 2 3 1.5\n2 1 2.5\n").unwrap();
+
+    let cases = [
+        (&valid, "3,0,1", "modes [3, 0, 1] are not a permutation of the 3 modes"),
+        (&valid, "0,1", "modes [0, 1] are not a permutation of the 3 modes"),
+        (&ragged, "0,1,2", "expected 4 fields like the first entry, found 3 on line 2"),
+    ];
+    for (path, modes, message) in cases {
+        let output = cli(&["tensor", path.to_str().unwrap(), "--modes", modes]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success() && stderr.contains(message) && !stderr.contains("panicked"), "{stderr}");
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_zero_sizes() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-zero-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("m.mtx");
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n2 2 2.0\n").unwrap();
+    let input = path.to_str().unwrap();
+
+    // Sizes of zero are refused by the argument parser instead of panicking
+    let png = dir.join("spy.png");
+    let commands: [&[&str]; 5] = [
+        &["stats", input, "--histogram", "0"],
+        &["bench-spmv", input, "-n", "0"],
+        &["stats", input, "--spy", "0"],
+        &["spy", input, "-o", png.to_str().unwrap(), "--width", "0"],
+        &["spy", input, "-o", png.to_str().unwrap(), "--height", "0"],
+    ];
+    for args in commands {
+        let output = cli(args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success() && stderr.contains("invalid value '0'") && !stderr.contains("panicked"), "{stderr}");
+    }
+    assert!(!png.exists());
+    assert!(cli(&["stats", input, "--histogram", "1"]).status.success());
+    let output = cli(&["stats", input, "--spy", "1"]);
+    assert!(output.status.success() && String::from_utf8_lossy(&output.stdout).contains("█\n"));
+    let output = cli(&["bench-spmv", input, "-n", "1"]);
+    assert!(output.status.success() && !String::from_utf8_lossy(&output.stdout).contains("NaN"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_scale_factors() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-scale-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path, rows, cols, out) = (dir.join("m.mtx"), dir.join("rows.mtx"), dir.join("cols.mtx"), dir.join("out.mtx"));
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n2 3 3\n1 1 1\n2 2 2\n1 3 4\n").unwrap();
+    std::fs::write(&rows, "%%MatrixMarket matrix array real general\n2 1\n2\n0.5\n").unwrap();
+    std::fs::write(&cols, "%%MatrixMarket matrix array real general\n3 1\n1\n3\n-1\n").unwrap();
+    let (input, output) = (path.to_str().unwrap(), out.to_str().unwrap());
+    let (rows, cols) = (rows.to_str().unwrap(), cols.to_str().unwrap());
+
+    assert!(cli(&["scale", input, "--row-factors", rows, "--col-factors", cols, "-o", output]).status.success());
+    let m = Matrix::from_bytes(&std::fs::read(&out).unwrap(), DataType::Real);
+    assert_eq!(m.values::<Real>().unwrap(), [2.0, 3.0, -8.0]);
+
+    // The factors must match the matrix, and one way to scale must be given, but not both
+    let commands: [&[&str]; 4] = [
+        &["scale", input, "--row-factors", cols, "-o", output],
+        &["scale", input, "-o", output],
+        &["scale", input, "--equilibrate", "--row-factors", rows, "-o", output],
+        &["scale", input, "--row-factors", rows, "--col-factors", rows, "-o", output],
+    ];
+    for args in commands {
+        let output = cli(args);
+        assert!(!output.status.success() && !String::from_utf8_lossy(&output.stderr).contains("panicked"));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_generate_density() {
+    // Densities outside of [0, 1] are refused by the argument parser instead of panicking
+    let commands: [&[&str]; 4] = [
+        &["generate", "erdos-renyi", "--rows", "3", "--cols", "3", "-d", "2"],
+        &["generate", "erdos-renyi", "--rows", "3", "--cols", "3", "-d=-0.5"],
+        &["generate", "spd", "-n", "3", "-d", "1.5"],
+        &["generate", "spd", "-n", "3", "-d", "NaN"],
+    ];
+    for args in commands {
+        let output = cli(args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success() && stderr.contains("expected a number between 0 and 1") && !stderr.contains("panicked"), "{stderr}");
+    }
+    let dir = std::env::temp_dir().join(format!("mmt-test-density-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("spd.mtx");
+    assert!(cli(&["generate", "spd", "-n", "3", "-d", "1", "-o", out.to_str().unwrap()]).status.success());
+    assert_eq!(Matrix::from_bytes(&std::fs::read(&out).unwrap(), DataType::Real).nvals(), 9);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_dense_limit() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-dense-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("m.mtx");
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n3 3 1\n1 1 1.0\n").unwrap();
+    let (new, old) = (dir.join("new.txt"), dir.join("old.txt"));
+    std::fs::write(&old, "previous contents\n").unwrap();
+
+    // The limit is checked before the output file is created or truncated
+    for out in [&new, &old] {
+        let output = cli(&["convert", path.to_str().unwrap(), "--to", "dense", "--max-elements", "8", "-o", out.to_str().unwrap()]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("a dense 3x3 matrix has more than 8 elements"));
+    }
+    assert!(!new.exists());
+    assert_eq!(std::fs::read_to_string(&old).unwrap(), "previous contents\n");
+
+    assert!(cli(&["convert", path.to_str().unwrap(), "--to", "dense", "--max-elements", "9", "-o", new.to_str().unwrap()]).status.success());
+    assert_eq!(std::fs::read_to_string(&new).unwrap(), "1 0 0\n0 0 0\n0 0 0\n");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_logging() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-logging-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path, out) = (dir.join("m.mtx"), dir.join("out.mtx"));
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n2 2 2\n2 2 2.0\n1 1 1.0\n").unwrap();
+    let sort = ["sort", path.to_str().unwrap(), "-o", out.to_str().unwrap(), "--timing-json"];
+
+    // The timings are a single JSON object on stdout, and the log goes to stderr
+    let output = cli(&sort);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.starts_with("{\"read\":") && stdout.contains(",\"sort\":") && stdout.contains(",\"write\":") && stdout.ends_with("}\n"), "{stdout}");
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[INFO ] sort: "));
+
+    // Quiet runs only log errors, and verbose runs also dump the matrix
+    let output = cli(&[&sort[..], &["-q"]].concat());
+    assert!(output.status.success() && output.stderr.is_empty());
+    let output = cli(&["sort", path.to_str().unwrap(), "-v"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[DEBUG] Matrix {"));
+    let output = cli(&["sort", dir.join("missing.mtx").to_str().unwrap(), "-q"]);
+    assert!(!output.status.success() && String::from_utf8_lossy(&output.stderr).contains("[ERROR] "));
+    assert!(!cli(&["sort", path.to_str().unwrap(), "-q", "-v"]).status.success());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_reorder_methods() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-reorder-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path, out, perm) = (dir.join("m.mtx"), dir.join("out.mtx"), dir.join("perm.txt"));
+    let m = generators::erdos_renyi(30, 30, 0.1, DataType::Real, 4);
+    std::fs::write(&path, m.to_string()).unwrap();
+
+    // Every method, and the seed of the random one, reaches the library
+    let cases = [
+        ("degree-asc", "0", Reordering::DegreeAscending),
+        ("degree-desc", "0", Reordering::DegreeDescending),
+        ("random", "7", Reordering::Random { seed: 7 }),
+        ("random", "8", Reordering::Random { seed: 8 }),
+    ];
+    for (method, seed, reordering) in cases {
+        let args = ["reorder", path.to_str().unwrap(), "-m", method, "--seed", seed, "--save-permutation", perm.to_str().unwrap(), "-o", out.to_str().unwrap()];
+        assert!(cli(&args).status.success(), "{method}");
+        let saved = Permutation::load(std::fs::File::open(&perm).unwrap(), PermutationFormat::Text).unwrap();
+        assert_eq!(saved.indices(), m.reordering(reordering).unwrap(), "{method} {seed}");
+
+        let mut expected = m.clone();
+        expected.reorder(reordering).unwrap();
+        let written = Matrix::from_bytes(&std::fs::read(&out).unwrap(), DataType::Real);
+        assert_eq!(written.canonical_hash(None).unwrap(), expected.canonical_hash(None).unwrap(), "{method} {seed}");
+    }
+    assert_ne!(m.reordering(Reordering::Random { seed: 7 }).unwrap(), m.reordering(Reordering::Random { seed: 8 }).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sort_blocked() {
+    // Tiles of two by two that do not divide the five rows and columns, so the last ones are partial
+    let mut m = generators::erdos_renyi(5, 5, 0.8, DataType::Real, 2);
+    m.sort_blocked(2, 2);
+    let tiles: Vec<_> = m.rows().iter().zip(m.cols()).map(|(r, c)| ((r - 1) / 2, (c - 1) / 2, *r, *c)).collect();
+    assert!(tiles.is_sorted() && tiles.len() == m.nvals());
+
+    // Stable sorting keeps duplicate entries in the order they are stored
+    let mut m = Matrix::from_bytes(b"4 4 4\n3 3 1\n1 1 2\n3 3 3\n1 2 4\n", DataType::Real);
+    m.sort_blocked_with(2, 2, &SortOptions::new().stable(true));
+    assert_eq!(m.values::<Real>().unwrap(), [2.0, 4.0, 1.0, 3.0]);
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_sort_blocked() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-blocked-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path, out) = (dir.join("m.mtx"), dir.join("out.mtx"));
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n4 4 6\n1 1 1\n4 4 2\n2 1 3\n1 3 4\n3 2 5\n1 2 6\n").unwrap();
+    let (input, output) = (path.to_str().unwrap(), out.to_str().unwrap());
+
+    assert!(cli(&["sort", input, "--block-rows", "2", "--block-cols", "3", "-o", output]).status.success());
+    let m = Matrix::from_bytes(&std::fs::read(&out).unwrap(), DataType::Real);
+    assert_eq!(m.values::<Real>().unwrap(), [1.0, 6.0, 4.0, 3.0, 5.0, 2.0]);
+
+    // Both block dimensions are needed, and must be positive
+    let output = cli(&["sort", input, "--block-rows", "0", "--block-cols", "3"]);
+    assert!(!output.status.success() && String::from_utf8_lossy(&output.stderr).contains("block dimensions must be positive"));
+    assert!(!cli(&["sort", input, "--block-rows", "2"]).status.success());
+    assert!(!cli(&["sort", input, "--block-rows", "2", "--block-cols", "2", "-s", "col-major"]).status.success());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mmap")]
+fn cli_manifests() {
+    let dir = std::env::temp_dir().join(format!("mmt-test-manifests-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("m.mtx");
+    // Two components of two rows and columns each
+    std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n4 4 6\n1 1 1\n2 1 2\n1 2 3\n3 3 4\n4 3 5\n4 4 6\n").unwrap();
+    let input = path.to_str().unwrap();
+
+    let read_json = |path: &std::path::Path| serde_json::from_slice::<serde_json::Value>(&std::fs::read(path).unwrap()).unwrap();
+    let hex = |m: &Matrix| m.canonical_hash(None).unwrap().iter().map(|b| format!("{b:02x}")).collect::<String>();
+    // Every part describes the file it names
+    let check_part = |part: &serde_json::Value, path: &std::path::Path| {
+        let m = Matrix::from_bytes(&std::fs::read(path).unwrap(), DataType::Real);
+        assert_eq!((part["nrows"].as_u64(), part["ncols"].as_u64()), (Some(m.nrows() as u64), Some(m.ncols() as u64)));
+        assert_eq!(part["nvals"].as_u64(), Some(m.nvals() as u64));
+        assert_eq!((part["data_type"].as_str(), part["symmetry"].as_str(), part["index_base"].as_u64()), (Some("real"), Some("general"), Some(1)));
+        assert_eq!(part["sha256"].as_str(), Some(hex(&m).as_str()));
+    };
+
+    let manifest = dir.join("files.json");
+    let out = dir.join("sorted");
+    assert!(cli(&["sort", input, "--out-dir", out.to_str().unwrap(), "--manifest", manifest.to_str().unwrap()]).status.success());
+    let files = read_json(&manifest)["files"].as_array().unwrap().clone();
+    assert_eq!(files.len(), 1);
+    assert_eq!((files[0]["file"].as_str(), files[0]["ordering"].as_str()), (Some(out.join("m.mtx").to_str().unwrap()), Some("row-major")));
+    check_part(&files[0], &out.join("m.mtx"));
+
+    let out = dir.join("partition");
+    assert!(cli(&["partition", input, "--parts", "2", "--out-dir", out.to_str().unwrap()]).status.success());
+    let manifest = read_json(&out.join("m.json"));
+    assert_eq!((manifest["nrows"].as_u64(), manifest["nvals"].as_u64(), manifest["indices"].as_str()), (Some(4), Some(6), Some("global")));
+    assert_eq!(manifest["grid"], serde_json::json!([2, 1]));
+    assert_eq!(manifest["imbalance"]["ratio"].as_f64(), Some(1.0));
+    let parts = manifest["parts"].as_array().unwrap();
+    assert_eq!(parts.len(), 2);
+    for (k, part) in parts.iter().enumerate() {
+        assert_eq!(part["file"].as_str(), Some(format!("m.{k}.mtx").as_str()));
+        assert_eq!((part["first_row"].as_u64(), part["first_col"].as_u64()), (Some(1 + 2 * k as u64), Some(1)));
+        // The size is that of the block, while its file keeps the global indices and dimensions
+        assert_eq!((part["nrows"].as_u64(), part["ncols"].as_u64(), part["nvals"].as_u64()), (Some(2), Some(4), Some(3)));
+        let m = Matrix::from_bytes(&std::fs::read(out.join(format!("m.{k}.mtx"))).unwrap(), DataType::Real);
+        assert_eq!(part["sha256"].as_str(), Some(hex(&m).as_str()));
+    }
+
+    let out = dir.join("split");
+    assert!(cli(&["split", input, "--by", "component", "--out-dir", out.to_str().unwrap()]).status.success());
+    let manifest = read_json(&out.join("m.json"));
+    assert_eq!((manifest["nrows"].as_u64(), manifest["ncols"].as_u64(), manifest["nvals"].as_u64()), (Some(4), Some(4), Some(6)));
+    let parts = manifest["parts"].as_array().unwrap();
+    assert_eq!(parts.len(), 2);
+    for (k, part) in parts.iter().enumerate() {
+        assert_eq!(part["file"].as_str(), Some(format!("m.{k}.mtx").as_str()));
+        check_part(part, &out.join(format!("m.{k}.mtx")));
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
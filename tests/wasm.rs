@@ -0,0 +1,21 @@
+#![cfg(feature = "wasm")]
+
+use matrix_market_transform::wasm::parse;
+
+// Errors are only tested in the browser, as creating a `JsError` needs a JavaScript host
+
+#[test]
+fn parse_banner_types() {
+    let m = parse(b"%%MatrixMarket matrix coordinate real general\n2 3 2\n2 3 1.5\n1 1 -2\n").unwrap();
+    assert_eq!((m.nrows, m.ncols, m.field.as_str(), m.symmetry.as_str()), (2, 3, "real", "general"));
+    assert_eq!((m.rows, m.cols, m.vals), (vec![1, 0], vec![2, 0], vec![1.5, -2.0]));
+
+    let m = parse(b"%%MatrixMarket matrix coordinate complex hermitian\n2 2 1\n2 1 1.0 -2.0\n").unwrap();
+    assert_eq!((m.field.as_str(), m.symmetry.as_str(), m.vals), ("complex", "hermitian", vec![1.0, -2.0]));
+
+    let m = parse(b"%%MatrixMarket matrix coordinate pattern general\n3 3 2\n1 2\n3 3\n").unwrap();
+    assert_eq!((m.field.as_str(), m.rows, m.cols, m.vals), ("pattern", vec![0, 2], vec![1, 2], vec![]));
+
+    let m = parse(b"%%MatrixMarket matrix coordinate integer general\n1 1 1\n1 1 7\n").unwrap();
+    assert_eq!((m.field.as_str(), m.vals), ("integer", vec![7.0]));
+}